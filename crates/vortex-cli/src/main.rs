@@ -0,0 +1,228 @@
+//! Vortex Config command-line tools.
+
+mod render;
+mod report;
+mod validate;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use vortex_git::DuplicateKeyPolicy;
+use vortex_server::ValidationMode;
+use vortex_server::crypto::KeyRing;
+
+use crate::render::{RenderError, RenderOptions};
+
+/// Command-line tools for Vortex Config repositories.
+#[derive(Debug, Parser)]
+#[command(name = "vortex", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Check every configuration file in a repository.
+    ///
+    /// Exits with status 1 when errors are found (or warnings, with
+    /// `--deny-warnings`) and 2 when the repository cannot be read.
+    Validate {
+        /// Path to a checkout of the configuration repository.
+        repo: PathBuf,
+
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        /// Comma-separated directories to search, as in GIT_SEARCH_PATHS.
+        #[arg(long, value_delimiter = ',')]
+        search_paths: Vec<String>,
+
+        /// Fail on warnings as well as errors.
+        #[arg(long)]
+        deny_warnings: bool,
+
+        /// age identity file to decrypt SOPS files, as in SOPS_AGE_KEY_FILE.
+        #[arg(long)]
+        sops_age_key_file: Option<PathBuf>,
+    },
+
+    /// Print the configuration the server would return for an application.
+    ///
+    /// The output is the server's response body for the same repository state
+    /// and settings, except that `${secret:...}` references are printed as
+    /// written instead of resolved. Without `--label` the working tree is
+    /// rendered, including uncommitted changes.
+    Render {
+        /// Path to the configuration repository.
+        #[arg(long)]
+        repo: PathBuf,
+
+        /// Application name.
+        #[arg(long)]
+        app: String,
+
+        /// Comma-separated profiles, as in the request path.
+        #[arg(long)]
+        profile: String,
+
+        /// Branch, tag or commit to render instead of the working tree.
+        #[arg(long)]
+        label: Option<String>,
+
+        /// Label reported for the working tree, as in GIT_DEFAULT_LABEL.
+        #[arg(long, default_value = "main")]
+        default_label: String,
+
+        /// Output format name or extension, as in `?format=`.
+        #[arg(long, default_value = "json")]
+        format: String,
+
+        /// Comma-separated directories to search, as in GIT_SEARCH_PATHS.
+        #[arg(long, value_delimiter = ',')]
+        search_paths: Vec<String>,
+
+        /// Schema validation mode, as in VORTEX_VALIDATION_MODE.
+        #[arg(long, default_value = "annotate")]
+        validation: ValidationMode,
+
+        /// Duplicate key handling (ignore, warn or error), as in
+        /// VORTEX_DUPLICATE_KEYS.
+        #[arg(long, default_value = "warn")]
+        duplicate_keys: DuplicateKeyPolicy,
+
+        /// Comma-separated built-in YAML tag handlers to enable (env), as
+        /// in VORTEX_YAML_TAGS.
+        #[arg(long, value_delimiter = ',')]
+        yaml_tags: Vec<String>,
+
+        /// age identity file to decrypt SOPS files, as in SOPS_AGE_KEY_FILE.
+        #[arg(long)]
+        sops_age_key_file: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    Sarif,
+}
+
+fn main() -> ExitCode {
+    match Cli::parse().command {
+        Command::Validate {
+            repo,
+            format,
+            search_paths,
+            deny_warnings,
+            sops_age_key_file,
+        } => {
+            let search_paths = split_list(search_paths);
+
+            let report =
+                match validate::validate_repo(&repo, search_paths, sops_age_key_file.as_deref()) {
+                    Ok(report) => report,
+                    Err(e) => {
+                        eprintln!("error: {}: {}", repo.display(), e);
+                        return ExitCode::from(2);
+                    },
+                };
+
+            let output = match format {
+                OutputFormat::Text => report.to_text(),
+                OutputFormat::Json => report.to_json() + "\n",
+                OutputFormat::Sarif => report.to_sarif() + "\n",
+            };
+            print!("{}", output);
+
+            if report.is_failure(deny_warnings) {
+                ExitCode::FAILURE
+            } else {
+                ExitCode::SUCCESS
+            }
+        },
+        Command::Render {
+            repo,
+            app,
+            profile,
+            label,
+            default_label,
+            format,
+            search_paths,
+            validation,
+            duplicate_keys,
+            yaml_tags,
+            sops_age_key_file,
+        } => {
+            let keys = match KeyRing::from_env() {
+                Ok(keys) => keys,
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    return ExitCode::from(2);
+                },
+            };
+            let options = RenderOptions {
+                repo,
+                app,
+                profiles: split_list(vec![profile]),
+                label,
+                default_label,
+                search_paths: split_list(search_paths),
+                format,
+                validation,
+                duplicate_keys,
+                yaml_tags: split_list(yaml_tags),
+                keys,
+                sops_age_key_file,
+            };
+
+            let runtime = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    return ExitCode::from(2);
+                },
+            };
+
+            match runtime.block_on(render::render(&options)) {
+                Ok(rendered) => {
+                    for unresolved in &rendered.unresolved {
+                        eprintln!(
+                            "warning: placeholder '${{{}}}' in '{}' does not resolve",
+                            unresolved.key, unresolved.path
+                        );
+                    }
+                    print!("{}", rendered.body);
+                    ExitCode::SUCCESS
+                },
+                Err(RenderError::Rejected(report)) => {
+                    eprintln!("error: configuration does not match its schema");
+                    for violation in report.violations() {
+                        eprintln!("  {}: {}", violation.path, violation.message);
+                    }
+                    ExitCode::FAILURE
+                },
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    ExitCode::FAILURE
+                },
+            }
+        },
+    }
+}
+
+/// Splits comma-separated values and drops empty entries.
+fn split_list(values: Vec<String>) -> Vec<String> {
+    values
+        .iter()
+        .flat_map(|v| v.split(','))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
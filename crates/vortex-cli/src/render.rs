@@ -0,0 +1,501 @@
+//! `vortex render`: resolves a configuration locally, as the server would.
+//!
+//! The configuration goes through the server's own pipeline
+//! ([`fetch_config_with_result`] and [`to_body`]), so the output is the body
+//! the server returns for the same repository state and settings, with one
+//! exception: `${secret:...}` references are never resolved. The server
+//! resolves them after caching, against its own secret providers, so they
+//! are rendered as written.
+//!
+//! With a label, the repository is cloned into a temporary directory and the
+//! label is checked out by a [`GitBackend`], exactly as on the server.
+//! Without one, the working tree is read as-is (uncommitted changes
+//! included) and reported under the default label.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use vortex_core::format::FormatRegistry;
+use vortex_core::format::yaml::TaggedYamlFormat;
+use vortex_core::placeholder::{self, UnresolvedPlaceholder};
+use vortex_core::validation::{SchemaValidator, ValidationReport};
+use vortex_git::{
+    AgeIdentityFile, ConfigFileResolver, ConfigQuery, ConfigResult, ConfigSource,
+    ConfigSourceError, DuplicateKeyPolicy, GitBackend, GitBackendConfig, SopsDecryptor,
+};
+use vortex_server::CacheError;
+use vortex_server::crypto::KeyRing;
+use vortex_server::extractors::accept::OutputFormat;
+use vortex_server::handlers::config::fetch_config_with_result;
+use vortex_server::response::{response_formats, to_body};
+use vortex_server::{ValidationMode, ValidationPolicy};
+
+/// Errors that prevent rendering.
+#[derive(Debug, thiserror::Error)]
+pub enum RenderError {
+    /// The requested output format is not registered or cannot serialize.
+    #[error("unsupported output format '{0}'")]
+    UnsupportedFormat(String),
+
+    /// No profile was given.
+    #[error("at least one profile is required")]
+    NoProfiles,
+
+    /// A YAML tag handler name is not a built-in handler.
+    #[error("{0}")]
+    YamlTags(String),
+
+    /// The repository could not be read or the label checked out.
+    #[error(transparent)]
+    Source(#[from] ConfigSourceError),
+
+    /// The server would fail the request.
+    #[error("{0}")]
+    Fetch(String),
+
+    /// The configuration does not match its schema (`reject` mode).
+    #[error("configuration does not match its schema")]
+    Rejected(ValidationReport),
+
+    /// The response could not be serialized.
+    #[error("serialization failed: {0}")]
+    Serialize(String),
+}
+
+/// What to render and how.
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Path to the configuration repository.
+    pub repo: PathBuf,
+    /// Application name.
+    pub app: String,
+    /// Active profiles, in request order.
+    pub profiles: Vec<String>,
+    /// Label (branch, tag or commit); `None` renders the working tree.
+    pub label: Option<String>,
+    /// Label reported when rendering the working tree (`GIT_DEFAULT_LABEL`).
+    pub default_label: String,
+    /// Directories to search (`GIT_SEARCH_PATHS`).
+    pub search_paths: Vec<String>,
+    /// Output format name or extension, as in `?format=`.
+    pub format: String,
+    /// Schema validation mode (`VORTEX_VALIDATION_MODE`).
+    pub validation: ValidationMode,
+    /// Duplicate key handling (`VORTEX_DUPLICATE_KEYS`).
+    pub duplicate_keys: DuplicateKeyPolicy,
+    /// Built-in YAML tag handlers to enable (`VORTEX_YAML_TAGS`).
+    pub yaml_tags: Vec<String>,
+    /// Keys for `{cipher}` values (`ENCRYPT_KEY`, `ENCRYPT_KEY_DIR`).
+    pub keys: KeyRing,
+    /// age identity file for SOPS-encrypted files (`SOPS_AGE_KEY_FILE`).
+    pub sops_age_key_file: Option<PathBuf>,
+}
+
+/// A rendered configuration.
+#[derive(Debug, Clone)]
+pub struct Rendered {
+    /// Response body, as the server would send it.
+    pub body: String,
+    /// Placeholders the client will not be able to resolve.
+    pub unresolved: Vec<UnresolvedPlaceholder>,
+}
+
+/// Renders the configuration described by `options`.
+pub async fn render(options: &RenderOptions) -> Result<Rendered, RenderError> {
+    let format = OutputFormat::from_param(
+        &options.format,
+        &response_formats(&FormatRegistry::builtin()),
+    )
+    .ok_or_else(|| RenderError::UnsupportedFormat(options.format.clone()))?;
+    if options.profiles.is_empty() {
+        return Err(RenderError::NoProfiles);
+    }
+
+    let policy = ValidationPolicy::new(options.validation);
+    let formats = format_registry(options)?;
+
+    match &options.label {
+        Some(label) => {
+            // Keep the clone alive until rendering is done.
+            let checkout = tempfile::tempdir().map_err(ConfigSourceError::from)?;
+            let source = clone_repository(options, label, checkout.path())
+                .await?
+                .with_format_registry(formats);
+            render_from(&source, &policy, options, label, &format).await
+        },
+        None => {
+            let source = WorkingTree::new(options, formats);
+            render_from(&source, &policy, options, &options.default_label, &format).await
+        },
+    }
+}
+
+async fn render_from(
+    source: &dyn ConfigSource,
+    policy: &ValidationPolicy,
+    options: &RenderOptions,
+    label: &str,
+    format: &OutputFormat,
+) -> Result<Rendered, RenderError> {
+    let (response, result) = fetch_config_with_result(
+        source,
+        policy,
+        &options.keys,
+        &options.app,
+        options.profiles.clone(),
+        label,
+    )
+    .await
+    .map_err(|e| match e {
+        CacheError::Validation(report) => RenderError::Rejected(report),
+        CacheError::FetchError(message) => RenderError::Fetch(message),
+    })?;
+    let body =
+        to_body(&response, format).map_err(|e| RenderError::Serialize(format!("{:?}", e)))?;
+    let unresolved = placeholder::find_unresolved(&result.merged());
+
+    Ok(Rendered { body, unresolved })
+}
+
+/// Builds the registry used to parse files, with the requested YAML tag
+/// handlers.
+fn format_registry(options: &RenderOptions) -> Result<Arc<FormatRegistry>, RenderError> {
+    let mut formats = FormatRegistry::builtin();
+    if !options.yaml_tags.is_empty() {
+        let yaml = TaggedYamlFormat::builtin(&options.yaml_tags)
+            .map_err(|e| RenderError::YamlTags(e.to_string()))?;
+        if let Some(entry) = formats.by_name("yaml").cloned() {
+            formats.register(entry.parser(yaml));
+        }
+    }
+    Ok(Arc::new(formats))
+}
+
+/// Clones the repository into `into` and checks out `label`.
+async fn clone_repository(
+    options: &RenderOptions,
+    label: &str,
+    into: &Path,
+) -> Result<GitBackend, ConfigSourceError> {
+    let repo = options.repo.canonicalize()?;
+    let mut builder = GitBackendConfig::builder()
+        .uri(repo.to_string_lossy())
+        .local_path(into.join("repo"))
+        .default_label(label)
+        .search_paths(options.search_paths.clone())
+        .duplicate_keys(options.duplicate_keys);
+    if let Some(identity) = &options.sops_age_key_file {
+        builder = builder.sops_age_key_file(identity);
+    }
+    let config = builder.build().map_err(ConfigSourceError::git)?;
+
+    GitBackend::new(config).await
+}
+
+/// A source reading the repository's working tree without Git.
+struct WorkingTree {
+    repo: PathBuf,
+    resolver: ConfigFileResolver,
+    default_label: String,
+}
+
+impl WorkingTree {
+    fn new(options: &RenderOptions, formats: Arc<FormatRegistry>) -> Self {
+        let mut resolver = ConfigFileResolver::new(&options.repo, options.search_paths.clone())
+            .with_format_registry(formats)
+            .with_duplicate_keys(options.duplicate_keys);
+        if let Some(identity) = &options.sops_age_key_file {
+            resolver =
+                resolver.with_sops(Arc::new(SopsDecryptor::new(AgeIdentityFile::new(identity))));
+        }
+
+        Self {
+            repo: options.repo.clone(),
+            resolver,
+            default_label: options.default_label.clone(),
+        }
+    }
+
+    /// Returns the commit checked out in the working tree, if it is a Git
+    /// repository.
+    fn head_commit(&self) -> Option<String> {
+        let output = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(&self.repo)
+            .output()
+            .ok()?;
+
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+#[async_trait]
+impl ConfigSource for WorkingTree {
+    async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+        let label = query.effective_label(&self.default_label);
+        let (sources, warnings) = self.resolver.resolve_with_warnings(query, label)?;
+
+        let mut result = ConfigResult::new(query.application(), query.profiles().to_vec(), label);
+        if let Some(commit) = self.head_commit() {
+            result.set_version(commit);
+        }
+        result.add_property_sources(sources);
+        result.add_warnings(warnings);
+        Ok(result)
+    }
+
+    async fn schema(
+        &self,
+        query: &ConfigQuery,
+    ) -> Result<Option<Arc<SchemaValidator>>, ConfigSourceError> {
+        Ok(self
+            .resolver
+            .load_schema(query.application())?
+            .map(Arc::new))
+    }
+
+    async fn health_check(&self) -> Result<(), ConfigSourceError> {
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "working-tree"
+    }
+
+    fn default_label(&self) -> &str {
+        &self.default_label
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use vortex_git::testing::repo;
+    use vortex_server::crypto::{AesGcmEncryptor, DEFAULT_KEY_ID};
+
+    fn options(repo: &Path, profiles: &[&str], format: &str) -> RenderOptions {
+        RenderOptions {
+            repo: repo.to_path_buf(),
+            app: "payments".to_string(),
+            profiles: profiles.iter().map(|p| p.to_string()).collect(),
+            label: None,
+            default_label: "main".to_string(),
+            search_paths: Vec::new(),
+            format: format.to_string(),
+            validation: ValidationMode::Annotate,
+            duplicate_keys: DuplicateKeyPolicy::Warn,
+            yaml_tags: Vec::new(),
+            keys: KeyRing::default(),
+            sops_age_key_file: None,
+        }
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?}", args);
+    }
+
+    #[tokio::test]
+    async fn test_render_working_tree_as_server_json() {
+        let dir = repo(&[
+            ("application.yml", "server:\n  port: 8080\n"),
+            (
+                "payments-prod.yml",
+                "server:\n  port: 9090\nurl: ${db.url}\n",
+            ),
+        ]);
+
+        let rendered = render(&options(dir.path(), &["prod"], "json"))
+            .await
+            .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&rendered.body).unwrap();
+        assert_eq!(json["name"], "payments");
+        assert_eq!(json["label"], "main");
+        assert_eq!(json["version"], serde_json::Value::Null);
+        assert_eq!(
+            json["propertySources"][0]["name"],
+            "git:main:payments-prod.yml"
+        );
+        assert_eq!(json["propertySources"][0]["source"]["server"]["port"], 9090);
+        assert_eq!(rendered.unresolved.len(), 1);
+        assert_eq!(rendered.unresolved[0].key, "db.url");
+    }
+
+    #[tokio::test]
+    async fn test_render_annotates_and_rejects_with_schema() {
+        let dir = repo(&[
+            ("payments.yml", "server:\n  port: http\n"),
+            (
+                "schemas/payments.schema.json",
+                r#"{"properties": {"server": {"properties": {"port": {"type": "integer"}}}}}"#,
+            ),
+        ]);
+        let mut options = options(dir.path(), &["default"], "yaml");
+
+        let rendered = render(&options).await.unwrap();
+        assert!(rendered.body.contains("validation:\n  valid: false"));
+
+        options.validation = ValidationMode::Reject;
+        let err = render(&options).await.unwrap_err();
+        assert!(matches!(err, RenderError::Rejected(report) if report.violations().len() == 1));
+    }
+
+    #[tokio::test]
+    async fn test_render_duplicate_keys() {
+        let dir = repo(&[("payments.yml", "db:\n  url: a\ndb:\n  url: b\n")]);
+        let mut options = options(dir.path(), &["default"], "json");
+
+        let rendered = render(&options).await.unwrap();
+        let json: serde_json::Value = serde_json::from_str(&rendered.body).unwrap();
+        assert_eq!(json["warnings"][0]["line"], 3);
+
+        options.duplicate_keys = DuplicateKeyPolicy::Error;
+        let err = render(&options).await.unwrap_err();
+        assert!(err.to_string().contains("duplicate key 'db'"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_render_yaml_merge_keys_and_tags() {
+        let dir = repo(&[(
+            "payments.yml",
+            "base: &base\n  port: 80\nserver:\n  <<: *base\n  host: !env VORTEX_RENDER_TEST_UNSET:localhost\n",
+        )]);
+        let mut options = options(dir.path(), &["default"], "json");
+
+        let err = render(&options).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unsupported tag '!env' at 'server.host'"),
+            "{}",
+            err
+        );
+
+        options.yaml_tags = vec!["env".to_string()];
+        let rendered = render(&options).await.unwrap();
+        let json: serde_json::Value = serde_json::from_str(&rendered.body).unwrap();
+        let server = &json["propertySources"][0]["source"]["server"];
+        assert_eq!(server["port"], 80);
+        assert_eq!(server["host"], "localhost");
+
+        options.yaml_tags = vec!["vault".to_string()];
+        let err = render(&options).await.unwrap_err();
+        assert!(matches!(err, RenderError::YamlTags(_)));
+    }
+
+    #[tokio::test]
+    async fn test_render_includes_with_origins() {
+        let dir = repo(&[
+            ("shared/kafka.json", r#"{"acks": "all", "retries": 3}"#),
+            (
+                "payments.yml",
+                "kafka:\n  $ref: shared/kafka.json\n  retries: 5\n",
+            ),
+        ]);
+
+        let rendered = render(&options(dir.path(), &["default"], "json"))
+            .await
+            .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&rendered.body).unwrap();
+        let source = &json["propertySources"][0];
+        assert_eq!(source["source"]["kafka"]["acks"], "all");
+        assert_eq!(source["source"]["kafka"]["retries"], 5);
+        assert_eq!(
+            source["origins"],
+            serde_json::json!({"kafka.acks": "shared/kafka.json"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_render_decrypts_cipher_values() {
+        let keys = KeyRing::new().with_key(DEFAULT_KEY_ID, AesGcmEncryptor::from_key(&[7; 32]));
+        let secret = keys.encrypt("hunter2").unwrap();
+        let dir = repo(&[(
+            "payments.yml",
+            &format!("db:\n  password: '{{cipher}}{}'\n", secret),
+        )]);
+
+        let rendered = render(&RenderOptions {
+            keys,
+            ..options(dir.path(), &["default"], "json")
+        })
+        .await
+        .unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&rendered.body).unwrap();
+        assert_eq!(
+            json["propertySources"][0]["source"]["db"]["password"],
+            "hunter2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_render_rejects_bad_arguments() {
+        let dir = repo(&[("payments.yml", "a: 1\n")]);
+
+        let err = render(&options(dir.path(), &["prod"], "xml"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RenderError::UnsupportedFormat(f) if f == "xml"));
+
+        let err = render(&options(dir.path(), &[], "json")).await.unwrap_err();
+        assert!(matches!(err, RenderError::NoProfiles));
+    }
+
+    #[tokio::test]
+    async fn test_render_label_from_git_history() {
+        let dir = repo(&[("payments.yml", "server:\n  port: 8080\n")]);
+        git(dir.path(), &["init", "-q", "-b", "main"]);
+        git(dir.path(), &["add", "-A"]);
+        git(dir.path(), &["commit", "-q", "-m", "v1"]);
+        git(dir.path(), &["tag", "v1"]);
+        fs::write(dir.path().join("payments.yml"), "server:\n  port: 9090\n").unwrap();
+        git(dir.path(), &["commit", "-q", "-am", "v2"]);
+
+        let mut options = options(dir.path(), &["default"], "properties");
+        options.label = Some("v1".to_string());
+        let tagged = render(&options).await.unwrap();
+
+        assert!(tagged.body.contains("# Label: v1\n"));
+        assert!(tagged.body.contains("server={\"port\":8080}\n"));
+
+        options.label = None;
+        let working = render(&options).await.unwrap();
+        assert!(working.body.contains("# Label: main\n"));
+        assert!(working.body.contains("server={\"port\":9090}\n"));
+    }
+
+    #[tokio::test]
+    async fn test_render_label_with_broken_schema() {
+        let dir = repo(&[
+            ("payments.yml", "server:\n  port: 8080\n"),
+            ("schemas/payments.schema.json", "{not json"),
+        ]);
+        git(dir.path(), &["init", "-q", "-b", "main"]);
+        git(dir.path(), &["add", "-A"]);
+        git(dir.path(), &["commit", "-q", "-m", "v1"]);
+
+        let mut options = options(dir.path(), &["default"], "json");
+        options.label = Some("main".to_string());
+        let rendered = render(&options).await.unwrap();
+        let json: serde_json::Value = serde_json::from_str(&rendered.body).unwrap();
+        assert_eq!(json["propertySources"][0]["source"]["server"]["port"], 8080);
+
+        options.validation = ValidationMode::Reject;
+        let err = render(&options).await.unwrap_err();
+        assert!(matches!(err, RenderError::Fetch(_)), "{}", err);
+    }
+}
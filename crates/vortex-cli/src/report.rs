@@ -0,0 +1,321 @@
+//! Findings produced by `vortex validate` and their output formats.
+
+use std::fmt::Write as _;
+
+use serde::Serialize;
+use serde_json::json;
+
+/// A check performed on the repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// A file could not be parsed.
+    ParseError,
+    /// A key is defined more than once in the same mapping.
+    DuplicateKey,
+    /// A schema file is not a valid JSON Schema.
+    InvalidSchema,
+    /// The merged configuration does not match the application's schema.
+    SchemaViolation,
+    /// A `${...}` placeholder references a missing property.
+    UnresolvedPlaceholder,
+    /// A file name does not follow `{application}[-{profile}]` conventions.
+    ProfileNaming,
+}
+
+impl Rule {
+    /// All rules, in reporting order.
+    pub const ALL: [Rule; 6] = [
+        Rule::ParseError,
+        Rule::DuplicateKey,
+        Rule::InvalidSchema,
+        Rule::SchemaViolation,
+        Rule::UnresolvedPlaceholder,
+        Rule::ProfileNaming,
+    ];
+
+    /// Stable identifier used in JSON and SARIF output.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::ParseError => "parse-error",
+            Rule::DuplicateKey => "duplicate-key",
+            Rule::InvalidSchema => "invalid-schema",
+            Rule::SchemaViolation => "schema-violation",
+            Rule::UnresolvedPlaceholder => "unresolved-placeholder",
+            Rule::ProfileNaming => "profile-naming",
+        }
+    }
+
+    /// One-line description of the rule.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::ParseError => "Configuration file cannot be parsed",
+            Rule::DuplicateKey => "Key is defined more than once in the same mapping",
+            Rule::InvalidSchema => "Schema file is not a valid JSON Schema",
+            Rule::SchemaViolation => "Resolved configuration does not match its JSON Schema",
+            Rule::UnresolvedPlaceholder => "Placeholder references a property that does not exist",
+            Rule::ProfileNaming => "File name does not follow {application}[-{profile}] naming",
+        }
+    }
+
+    /// Severity of findings for this rule.
+    ///
+    /// Placeholders may still be resolved by the client (e.g. from the
+    /// environment) and naming issues do not break resolution, so both are
+    /// warnings.
+    pub fn level(&self) -> Level {
+        match self {
+            Rule::UnresolvedPlaceholder | Rule::ProfileNaming => Level::Warning,
+            _ => Level::Error,
+        }
+    }
+}
+
+/// Severity of a finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    /// Must be fixed before merging.
+    Error,
+    /// Suspicious, but does not break resolution.
+    Warning,
+}
+
+impl Level {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        }
+    }
+}
+
+/// A problem found in the repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    /// Rule that produced the finding.
+    pub rule: Rule,
+    /// Severity of the finding.
+    pub level: Level,
+    /// File path relative to the repository root, with `/` separators.
+    pub file: String,
+    /// Line (1-based), when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Human-readable description.
+    pub message: String,
+}
+
+impl Finding {
+    /// Creates a finding with the rule's default level.
+    pub fn new(rule: Rule, file: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            rule,
+            level: rule.level(),
+            file: file.into(),
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    /// Sets the line of the finding.
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+}
+
+/// Result of validating a repository.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+    /// Number of configuration files checked.
+    pub files_checked: usize,
+    /// Findings, sorted by file, line and rule.
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    /// Adds a finding.
+    pub fn push(&mut self, finding: Finding) {
+        self.findings.push(finding);
+    }
+
+    /// Sorts findings and drops exact duplicates.
+    pub fn finish(&mut self) {
+        self.findings
+            .sort_by(|a, b| (&a.file, a.line, a.rule).cmp(&(&b.file, b.line, b.rule)));
+        self.findings.dedup();
+    }
+
+    /// Returns the number of findings with the given level.
+    pub fn count(&self, level: Level) -> usize {
+        self.findings.iter().filter(|f| f.level == level).count()
+    }
+
+    /// Returns true if the report should fail the build.
+    pub fn is_failure(&self, deny_warnings: bool) -> bool {
+        self.count(Level::Error) > 0 || (deny_warnings && self.count(Level::Warning) > 0)
+    }
+
+    /// Renders one line per finding followed by a summary.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+
+        for f in &self.findings {
+            let location = match f.line {
+                Some(line) => format!("{}:{}", f.file, line),
+                None => f.file.clone(),
+            };
+            let _ = writeln!(
+                out,
+                "{}: {}[{}]: {}",
+                location,
+                f.level.as_str(),
+                f.rule.id(),
+                f.message
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "{} files checked: {} errors, {} warnings",
+            self.files_checked,
+            self.count(Level::Error),
+            self.count(Level::Warning)
+        );
+        out
+    }
+
+    /// Renders the report as JSON.
+    pub fn to_json(&self) -> String {
+        let value = json!({
+            "filesChecked": self.files_checked,
+            "errors": self.count(Level::Error),
+            "warnings": self.count(Level::Warning),
+            "findings": self.findings,
+        });
+        serde_json::to_string_pretty(&value).unwrap_or_default()
+    }
+
+    /// Renders the report as a SARIF 2.1.0 log, for code scanning tools.
+    pub fn to_sarif(&self) -> String {
+        let rules: Vec<_> = Rule::ALL
+            .iter()
+            .map(|rule| {
+                json!({
+                    "id": rule.id(),
+                    "shortDescription": { "text": rule.description() },
+                    "defaultConfiguration": { "level": rule.level().as_str() },
+                })
+            })
+            .collect();
+
+        let results: Vec<_> = self
+            .findings
+            .iter()
+            .map(|f| {
+                let mut location = json!({
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": f.file },
+                    }
+                });
+                if let Some(line) = f.line {
+                    location["physicalLocation"]["region"] = json!({ "startLine": line });
+                }
+                json!({
+                    "ruleId": f.rule.id(),
+                    "level": f.level.as_str(),
+                    "message": { "text": f.message },
+                    "locations": [location],
+                })
+            })
+            .collect();
+
+        let value = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "vortex",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    }
+                },
+                "results": results,
+            }],
+        });
+        serde_json::to_string_pretty(&value).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> Report {
+        let mut report = Report {
+            files_checked: 2,
+            findings: Vec::new(),
+        };
+        report.push(Finding::new(Rule::UnresolvedPlaceholder, "b.yml", "missing 'x'").at_line(3));
+        report.push(Finding::new(Rule::DuplicateKey, "a.yml", "duplicate 'db'").at_line(7));
+        report.push(Finding::new(Rule::DuplicateKey, "a.yml", "duplicate 'db'").at_line(7));
+        report.finish();
+        report
+    }
+
+    #[test]
+    fn test_finish_sorts_and_dedups() {
+        let report = report();
+
+        assert_eq!(report.findings.len(), 2);
+        assert_eq!(report.findings[0].file, "a.yml");
+        assert_eq!(report.count(Level::Error), 1);
+        assert_eq!(report.count(Level::Warning), 1);
+        assert!(report.is_failure(false));
+    }
+
+    #[test]
+    fn test_warnings_fail_only_when_denied() {
+        let mut report = Report::default();
+        report.push(Finding::new(Rule::ProfileNaming, "App.yml", "uppercase"));
+
+        assert!(!report.is_failure(false));
+        assert!(report.is_failure(true));
+    }
+
+    #[test]
+    fn test_text_output() {
+        assert_eq!(
+            report().to_text(),
+            "a.yml:7: error[duplicate-key]: duplicate 'db'\n\
+             b.yml:3: warning[unresolved-placeholder]: missing 'x'\n\
+             2 files checked: 1 errors, 1 warnings\n"
+        );
+    }
+
+    #[test]
+    fn test_json_output() {
+        let json: serde_json::Value = serde_json::from_str(&report().to_json()).unwrap();
+
+        assert_eq!(json["errors"], 1);
+        assert_eq!(json["findings"][0]["rule"], "duplicate-key");
+        assert_eq!(json["findings"][0]["line"], 7);
+    }
+
+    #[test]
+    fn test_sarif_output() {
+        let sarif: serde_json::Value = serde_json::from_str(&report().to_sarif()).unwrap();
+
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 6);
+        assert_eq!(run["results"][0]["ruleId"], "duplicate-key");
+        assert_eq!(
+            run["results"][0]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            7
+        );
+        assert_eq!(run["results"][1]["level"], "warning");
+    }
+}
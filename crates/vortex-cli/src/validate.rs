@@ -0,0 +1,500 @@
+//! `vortex validate`: repository-wide checks for a configuration repository.
+//!
+//! Every configuration file is parsed and scanned for duplicate keys. Then,
+//! for each application and profile found in the repository, the merged
+//! configuration is resolved exactly as the server would and checked against
+//! the application's JSON Schema (if any) and for unresolved placeholders.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use vortex_core::format::ConfigFormat;
+use vortex_core::format::duplicates::find_duplicate_keys;
+use vortex_core::validation::SchemaValidator;
+use vortex_core::{ConfigPath, PropertySource, placeholder};
+use vortex_git::reader::sops::SOPS_EXTENSION_MARKER;
+use vortex_git::{
+    AgeIdentityFile, ConfigFileResolver, ConfigQuery, ConfigResult, ConfigSourceError,
+    SopsDecryptor,
+};
+
+use crate::report::{Finding, Report, Rule};
+
+/// Label used for property source names when resolving locally.
+const LOCAL_LABEL: &str = "local";
+
+/// Base name of files shared by all applications.
+const BASE_APPLICATION: &str = "application";
+
+/// Profile used when no profile is requested.
+const DEFAULT_PROFILE: &str = "default";
+
+/// Validates the repository at `repo`.
+///
+/// `search_paths` has the same meaning as the server's `GIT_SEARCH_PATHS`;
+/// SOPS files are decrypted with the age identity in `sops_age_key_file`.
+///
+/// # Errors
+///
+/// Returns an error only if the repository cannot be listed; problems in
+/// individual files are reported as findings.
+pub fn validate_repo(
+    repo: &Path,
+    search_paths: Vec<String>,
+    sops_age_key_file: Option<&Path>,
+) -> Result<Report, ConfigSourceError> {
+    let mut resolver = ConfigFileResolver::new(repo, search_paths.clone());
+    if let Some(identity) = sops_age_key_file {
+        resolver = resolver.with_sops(Arc::new(SopsDecryptor::new(AgeIdentityFile::new(identity))));
+    }
+    let files = resolver.list_config_files()?;
+    let mut report = Report {
+        files_checked: files.len(),
+        ..Report::default()
+    };
+
+    for file in &files {
+        check_file(&resolver, repo, file, &mut report);
+    }
+
+    let bases = search_bases(repo, &search_paths);
+    let stems: BTreeSet<String> = files
+        .iter()
+        .filter(|file| {
+            file.parent()
+                .is_some_and(|dir| bases.iter().any(|b| b == dir))
+        })
+        .filter_map(|file| {
+            let stem = file.file_stem()?.to_str()?;
+            // `payments-prod.sops.yml` is resolved as `payments-prod`.
+            let stem = stem
+                .strip_suffix(&format!(".{}", SOPS_EXTENSION_MARKER))
+                .unwrap_or(stem)
+                .to_string();
+            check_naming(repo, file, &stem, &mut report);
+            Some(stem)
+        })
+        .collect();
+
+    let schema_apps = schema_applications(repo);
+    for (app, profiles) in applications(&stems, &schema_apps) {
+        let schema = match resolver.load_schema(&app) {
+            Ok(schema) => schema,
+            Err(e) => {
+                report.push(Finding::new(
+                    Rule::InvalidSchema,
+                    schema_file(&app),
+                    e.to_string(),
+                ));
+                None
+            },
+        };
+
+        for profile in profiles {
+            check_resolved(&resolver, &app, &profile, schema.as_ref(), &mut report);
+        }
+    }
+
+    report.finish();
+    Ok(report)
+}
+
+/// Parses a file, expanding its includes, and scans it for duplicate keys.
+fn check_file(resolver: &ConfigFileResolver, repo: &Path, file: &Path, report: &mut Report) {
+    let rel = relative(repo, file);
+
+    if let Err(e) = resolver.read_file(file) {
+        report.push(Finding::new(Rule::ParseError, rel, e.to_string()));
+        return;
+    }
+
+    let (Some(format), Ok(content)) =
+        (ConfigFormat::from_path(file), std::fs::read_to_string(file))
+    else {
+        return;
+    };
+
+    for duplicate in find_duplicate_keys(format, &content).unwrap_or_default() {
+        report.push(
+            Finding::new(
+                Rule::DuplicateKey,
+                &rel,
+                format!(
+                    "Duplicate key '{}' (first defined at line {}); the last value wins",
+                    duplicate.path, duplicate.first_line
+                ),
+            )
+            .at_line(duplicate.line),
+        );
+    }
+}
+
+/// Checks that a file name is `{application}[-{profile}]` in lowercase.
+fn check_naming(repo: &Path, file: &Path, stem: &str, report: &mut Report) {
+    let problem = if stem.split('-').any(str::is_empty) {
+        Some("has an empty application or profile segment")
+    } else if stem.chars().any(|c| c.is_ascii_uppercase()) {
+        Some("contains uppercase letters; names are case-sensitive on the server")
+    } else if !stem
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        Some("contains characters other than letters, digits, '-', '_' and '.'")
+    } else {
+        None
+    };
+
+    if let Some(problem) = problem {
+        report.push(Finding::new(
+            Rule::ProfileNaming,
+            relative(repo, file),
+            format!("File name '{}' {}", stem, problem),
+        ));
+    }
+}
+
+/// Resolves one application/profile and checks the merged configuration.
+fn check_resolved(
+    resolver: &ConfigFileResolver,
+    app: &str,
+    profile: &str,
+    schema: Option<&SchemaValidator>,
+    report: &mut Report,
+) {
+    let query = ConfigQuery::new(app, vec![profile]);
+    // Unparseable files were already reported by `check_file`.
+    let Ok(sources) = resolver.resolve(&query, LOCAL_LABEL) else {
+        return;
+    };
+    let result = ConfigResult::new(app, vec![profile.to_string()], LOCAL_LABEL)
+        .with_property_sources(sources);
+    let merged = result.merged();
+
+    if let Some(schema) = schema {
+        for violation in schema.validate(&merged).violations() {
+            let file = defining_file(result.property_sources(), &violation.path)
+                .unwrap_or_else(|| schema_file(app));
+            let path = if violation.path.is_root() {
+                "<root>".to_string()
+            } else {
+                violation.path.to_string()
+            };
+            report.push(Finding::new(
+                Rule::SchemaViolation,
+                file,
+                format!("{}/{}: {}: {}", app, profile, path, violation.message),
+            ));
+        }
+    }
+
+    for unresolved in placeholder::find_unresolved(&merged) {
+        let file = defining_file(result.property_sources(), &unresolved.path)
+            .unwrap_or_else(|| format!("{}.*", app));
+        report.push(Finding::new(
+            Rule::UnresolvedPlaceholder,
+            file,
+            format!(
+                "Placeholder '${{{}}}' in '{}' does not resolve for {}/{}",
+                unresolved.key, unresolved.path, app, profile
+            ),
+        ));
+    }
+}
+
+/// Groups file stems into applications and their profiles.
+///
+/// A stem `{a}-{b}` is application `a` with profile `b` when `a` is itself an
+/// application (a file stem, `application`, or a schema name); otherwise the
+/// whole stem is the application. The longest matching prefix wins, so
+/// `payment-service-prod` is `payment-service`/`prod` when
+/// `payment-service.yml` exists. The shared `application` files are only
+/// checked on their own when the repository has no other applications.
+fn applications(
+    stems: &BTreeSet<String>,
+    schema_apps: &BTreeSet<String>,
+) -> BTreeMap<String, BTreeSet<String>> {
+    let known: BTreeSet<&str> = stems
+        .iter()
+        .chain(schema_apps)
+        .map(String::as_str)
+        .chain([BASE_APPLICATION])
+        .collect();
+
+    let mut apps: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for app in schema_apps {
+        apps.entry(app.clone()).or_default();
+    }
+    for stem in stems {
+        let split = known
+            .iter()
+            .filter(|app| app.len() < stem.len())
+            .filter(|app| {
+                stem.strip_prefix(**app)
+                    .and_then(|rest| rest.strip_prefix('-'))
+                    .is_some_and(|profile| !profile.is_empty())
+            })
+            .max_by_key(|app| app.len());
+
+        match split {
+            Some(app) => {
+                let profile = &stem[app.len() + 1..];
+                apps.entry(app.to_string())
+                    .or_default()
+                    .insert(profile.to_string());
+            },
+            None => {
+                apps.entry(stem.clone()).or_default();
+            },
+        }
+    }
+
+    if apps.len() > 1 {
+        apps.remove(BASE_APPLICATION);
+    }
+    for profiles in apps.values_mut() {
+        profiles.insert(DEFAULT_PROFILE.to_string());
+    }
+    apps
+}
+
+/// Returns the applications with a `schemas/{app}.schema.json` file.
+fn schema_applications(repo: &Path) -> BTreeSet<String> {
+    let Ok(entries) = std::fs::read_dir(repo.join("schemas")) else {
+        return BTreeSet::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            name.strip_suffix(".schema.json").map(String::from)
+        })
+        .collect()
+}
+
+/// Returns the file of the highest-precedence source defining `path`.
+fn defining_file(sources: &[PropertySource], path: &ConfigPath) -> Option<String> {
+    if path.is_root() {
+        return None;
+    }
+    let prefix = format!("git:{}:", LOCAL_LABEL);
+    sources
+        .iter()
+        .find(|source| source.config.get_path(path).is_some())
+        .map(|source| match source.origins.get(&path.to_string()) {
+            // Included from a fragment: point at the fragment.
+            Some(fragment) => fragment.clone(),
+            None => source
+                .name
+                .strip_prefix(&prefix)
+                .unwrap_or(&source.name)
+                .replace('\\', "/"),
+        })
+}
+
+fn search_bases(repo: &Path, search_paths: &[String]) -> Vec<PathBuf> {
+    if search_paths.is_empty() {
+        vec![repo.to_path_buf()]
+    } else {
+        search_paths.iter().map(|p| repo.join(p)).collect()
+    }
+}
+
+fn schema_file(app: &str) -> String {
+    format!("schemas/{}.schema.json", app)
+}
+
+fn relative(repo: &Path, file: &Path) -> String {
+    file.strip_prefix(repo)
+        .unwrap_or(file)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vortex_git::testing::repo;
+
+    fn rules(report: &Report) -> Vec<(Rule, String)> {
+        report
+            .findings
+            .iter()
+            .map(|f| (f.rule, f.file.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_repository() {
+        let dir = repo(&[
+            ("application.yml", "server:\n  port: 8080\n"),
+            (
+                "payments.yml",
+                "db:\n  host: localhost\n  url: jdbc://${db.host}\n",
+            ),
+            ("payments-prod.yml", "db:\n  host: prod-db\n"),
+        ]);
+
+        let report = validate_repo(dir.path(), vec![], None).unwrap();
+
+        assert_eq!(report.files_checked, 3);
+        assert!(report.findings.is_empty(), "{:?}", report.findings);
+    }
+
+    #[test]
+    fn test_sops_files_need_an_identity() {
+        let dir = repo(&[(
+            "payments-prod.sops.yml",
+            "db:\n  password: ENC[AES256_GCM,data:AA==,iv:AA==,tag:AA==,type:str]\n\
+             sops:\n  mac: ENC[AES256_GCM,data:AA==,iv:AA==,tag:AA==,type:str]\n  version: 3.9.0\n",
+        )]);
+
+        let report = validate_repo(dir.path(), vec![], None).unwrap();
+
+        assert_eq!(
+            rules(&report),
+            vec![(Rule::ParseError, "payments-prod.sops.yml".to_string())]
+        );
+        assert!(report.findings[0].message.contains("no age identity"));
+    }
+
+    #[test]
+    fn test_parse_errors_and_duplicates() {
+        let dir = repo(&[
+            ("broken.json", "{"),
+            (
+                "orders.yml",
+                "datasource:\n  url: a\ndatasource:\n  url: b\n",
+            ),
+        ]);
+
+        let report = validate_repo(dir.path(), vec![], None).unwrap();
+
+        assert_eq!(
+            rules(&report),
+            vec![
+                (Rule::ParseError, "broken.json".to_string()),
+                (Rule::DuplicateKey, "orders.yml".to_string()),
+            ]
+        );
+        assert_eq!(report.findings[1].line, Some(3));
+        assert!(report.is_failure(false));
+    }
+
+    #[test]
+    fn test_schema_violations_per_profile() {
+        let dir = repo(&[
+            ("payments.yml", "server:\n  port: 8080\n"),
+            ("payments-prod.yml", "server:\n  port: http\n"),
+            (
+                "schemas/payments.schema.json",
+                r#"{"required": ["server"], "properties": {"server": {"properties": {"port": {"type": "integer"}}}}}"#,
+            ),
+            ("schemas/orders.schema.json", r#"{"required": ["server"]}"#),
+            ("schemas/broken.schema.json", r#"{"type": 1}"#),
+        ]);
+
+        let report = validate_repo(dir.path(), vec![], None).unwrap();
+
+        assert_eq!(
+            rules(&report),
+            vec![
+                (Rule::SchemaViolation, "payments-prod.yml".to_string()),
+                (
+                    Rule::InvalidSchema,
+                    "schemas/broken.schema.json".to_string()
+                ),
+                (
+                    Rule::SchemaViolation,
+                    "schemas/orders.schema.json".to_string()
+                ),
+            ]
+        );
+        assert!(
+            report.findings[0]
+                .message
+                .starts_with("payments/prod: server.port:")
+        );
+    }
+
+    #[test]
+    fn test_includes_are_checked_and_violations_point_at_fragments() {
+        let dir = repo(&[
+            ("shared/server.yml", "server:\n  port: http\n"),
+            ("payments.yml", "<<: !include shared/server.yml\n"),
+            ("orders.yml", "db: !include shared/missing.yml\n"),
+            (
+                "schemas/payments.schema.json",
+                r#"{"properties": {"server": {"properties": {"port": {"type": "integer"}}}}}"#,
+            ),
+        ]);
+
+        let report = validate_repo(dir.path(), vec![], None).unwrap();
+
+        assert_eq!(
+            rules(&report),
+            vec![
+                (Rule::ParseError, "orders.yml".to_string()),
+                (Rule::SchemaViolation, "shared/server.yml".to_string()),
+            ]
+        );
+        assert!(report.findings[0].message.contains("does not exist"));
+    }
+
+    #[test]
+    fn test_unresolved_placeholders_and_naming() {
+        let dir = repo(&[
+            ("application.yml", "log:\n  dir: /var/log\n"),
+            (
+                "billing.yml",
+                "file: ${log.dir}/billing.log\nurl: ${db.url}\n",
+            ),
+            ("Billing-PROD.yml", "a: 1\n"),
+            ("billing-.yml", "a: 1\n"),
+        ]);
+
+        let report = validate_repo(dir.path(), vec![], None).unwrap();
+
+        assert_eq!(
+            rules(&report),
+            vec![
+                (Rule::ProfileNaming, "Billing-PROD.yml".to_string()),
+                (Rule::ProfileNaming, "billing-.yml".to_string()),
+                (Rule::UnresolvedPlaceholder, "billing.yml".to_string()),
+            ]
+        );
+        assert!(report.findings[2].message.contains("${db.url}"));
+        assert!(!report.is_failure(false));
+        assert!(report.is_failure(true));
+    }
+
+    #[test]
+    fn test_applications_grouping() {
+        let stems: BTreeSet<String> = [
+            "application",
+            "application-prod",
+            "payment-service",
+            "payment-service-prod",
+            "orders-eu",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        let schemas: BTreeSet<String> = ["orders".to_string()].into();
+
+        let apps = applications(&stems, &schemas);
+
+        let summary: Vec<(&str, Vec<&str>)> = apps
+            .iter()
+            .map(|(app, profiles)| (app.as_str(), profiles.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("orders", vec!["default", "eu"]),
+                ("payment-service", vec!["default", "prod"]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,896 @@
+//! Serde deserialization from [`ConfigValue`] into user types.
+//!
+//! Configuration often arrives as text (properties, env files, INI), so the
+//! deserializer applies relaxed coercions on top of the value's own type:
+//!
+//! - numbers and booleans from strings (`"8080"`, `"true"`, `"on"`);
+//! - byte sizes into integers (`"10MB"` -> `10485760`, 1024-based like Spring);
+//! - [`Duration`] from strings (`"30s"`, `"500ms"`, `"2h"`) or integers (millis);
+//! - sequences from comma-separated strings (`"a,b,c"`);
+//! - strings from numbers and booleans.
+//!
+//! Errors carry the key path of the offending value (`servers[1].port`).
+
+use std::borrow::Cow;
+use std::fmt;
+use std::time::Duration;
+
+use indexmap::IndexMap;
+use serde::de::value::{SeqDeserializer, StringDeserializer};
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, Unexpected,
+    VariantAccess, Visitor,
+};
+use serde::de::{Deserializer as _, Error as _};
+use serde::forward_to_deserialize_any;
+
+use crate::config::path::write_key;
+use crate::config::value::ConfigValue;
+use crate::error::VortexError;
+
+/// Error produced while deserializing a config value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeError {
+    path: String,
+    message: String,
+}
+
+impl DeError {
+    /// Key path of the value that failed to deserialize (empty for the root).
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Description of the failure.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn at(mut self, path: &str) -> Self {
+        if self.path.is_empty() {
+            self.path = path.to_string();
+        }
+        self
+    }
+}
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            path: String::new(),
+            message: msg.to_string(),
+        }
+    }
+}
+
+impl From<DeError> for VortexError {
+    fn from(err: DeError) -> Self {
+        let field = if err.path.is_empty() {
+            "<root>".to_string()
+        } else {
+            err.path
+        };
+        VortexError::validation_error(field, err.message)
+    }
+}
+
+type DeResult<T> = std::result::Result<T, DeError>;
+
+/// Deserializer over a borrowed (or, for coerced values, owned) config value.
+pub(crate) struct ValueDeserializer<'de> {
+    value: Cow<'de, ConfigValue>,
+    path: String,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    pub(crate) fn new(value: &'de ConfigValue, path: impl Into<String>) -> Self {
+        Self {
+            value: Cow::Borrowed(value),
+            path: path.into(),
+        }
+    }
+
+    fn owned(value: ConfigValue, path: String) -> Self {
+        Self {
+            value: Cow::Owned(value),
+            path,
+        }
+    }
+
+    fn invalid_type(&self, expected: &dyn de::Expected) -> DeError {
+        de::Error::invalid_type(unexpected(&self.value), expected)
+    }
+
+    fn integer(&self, expected: &dyn de::Expected) -> DeResult<i128> {
+        match self.value.as_ref() {
+            ConfigValue::Integer(i) => Ok(i128::from(*i)),
+            ConfigValue::Float(f) if f.fract() == 0.0 => Ok(f.into_inner() as i128),
+            ConfigValue::String(s) => {
+                let s = s.trim();
+                s.parse::<i128>()
+                    .ok()
+                    .or_else(|| parse_byte_size(s).map(i128::from))
+                    .ok_or_else(|| de::Error::invalid_value(Unexpected::Str(s), expected))
+            },
+            _ => Err(self.invalid_type(expected)),
+        }
+    }
+
+    fn float(&self, expected: &dyn de::Expected) -> DeResult<f64> {
+        match self.value.as_ref() {
+            ConfigValue::Float(f) => Ok(f.into_inner()),
+            ConfigValue::Integer(i) => Ok(*i as f64),
+            ConfigValue::String(s) => s
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(s), expected)),
+            _ => Err(self.invalid_type(expected)),
+        }
+    }
+
+    fn deserialize_duration<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        let duration = match self.value.as_ref() {
+            ConfigValue::Integer(ms) if *ms >= 0 => Duration::from_millis(*ms as u64),
+            ConfigValue::String(s) => parse_duration(s).ok_or_else(|| {
+                de::Error::invalid_value(Unexpected::Str(s), &"a duration such as `30s`")
+            })?,
+            // Already in serde's `{secs, nanos}` shape.
+            ConfigValue::Object(_) => return self.deserialize_map(visitor),
+            _ => return Err(self.invalid_type(&"a duration")),
+        };
+
+        let parts = [duration.as_secs(), u64::from(duration.subsec_nanos())];
+        visitor.visit_seq(SeqDeserializer::new(parts.into_iter()))
+    }
+}
+
+macro_rules! deserialize_signed {
+    ($($method:ident => $ty:ty),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+                let path = self.path.clone();
+                let n = self.integer(&stringify!($ty)).map_err(at(&path))?;
+                let n = i64::try_from(n).map_err(|_| {
+                    DeError::custom(format!("integer `{}` out of range for {}", n, stringify!($ty)))
+                        .at(&path)
+                })?;
+                visitor.visit_i64(n).map_err(at(&path))
+            }
+        )*
+    };
+}
+
+macro_rules! deserialize_unsigned {
+    ($($method:ident => $ty:ty),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+                let path = self.path.clone();
+                let n = self.integer(&stringify!($ty)).map_err(at(&path))?;
+                let result = match u64::try_from(n) {
+                    Ok(n) => visitor.visit_u64(n),
+                    Err(_) => match i64::try_from(n) {
+                        Ok(n) => visitor.visit_i64(n),
+                        Err(_) => Err(DeError::custom(format!(
+                            "integer `{}` out of range for {}",
+                            n,
+                            stringify!($ty)
+                        ))),
+                    },
+                };
+                result.map_err(at(&path))
+            }
+        )*
+    };
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for ValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        let path = self.path.clone();
+        let result = match self.value {
+            Cow::Borrowed(value) => match value {
+                ConfigValue::Null => visitor.visit_unit(),
+                ConfigValue::Bool(b) => visitor.visit_bool(*b),
+                ConfigValue::Integer(i) => visitor.visit_i64(*i),
+                ConfigValue::Float(f) => visitor.visit_f64(f.into_inner()),
+                ConfigValue::String(s) | ConfigValue::DateTime(s) => visitor.visit_borrowed_str(s),
+                ConfigValue::Array(arr) => {
+                    visit_array(arr.iter().map(Cow::Borrowed), &path, visitor)
+                },
+                ConfigValue::Object(map) => visit_object(map, &path, visitor),
+            },
+            Cow::Owned(value) => match value {
+                ConfigValue::Null => visitor.visit_unit(),
+                ConfigValue::Bool(b) => visitor.visit_bool(b),
+                ConfigValue::Integer(i) => visitor.visit_i64(i),
+                ConfigValue::Float(f) => visitor.visit_f64(f.into_inner()),
+                ConfigValue::String(s) | ConfigValue::DateTime(s) => visitor.visit_string(s),
+                ConfigValue::Array(arr) => {
+                    visit_array(arr.into_iter().map(Cow::Owned), &path, visitor)
+                },
+                ConfigValue::Object(map) => {
+                    let entries = map.into_iter().map(|(key, value)| {
+                        let path = child_path(&path, &key);
+                        (key, ValueDeserializer::owned(value, path))
+                    });
+                    visitor.visit_map(de::value::MapDeserializer::new(entries))
+                },
+            },
+        };
+        result.map_err(at(&path))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        let b = match self.value.as_ref() {
+            ConfigValue::Bool(b) => *b,
+            ConfigValue::Integer(0) => false,
+            ConfigValue::Integer(1) => true,
+            ConfigValue::String(s) => parse_bool(s)
+                .ok_or_else(|| de::Error::invalid_value(Unexpected::Str(s), &"a boolean"))
+                .map_err(at(&self.path))?,
+            _ => return Err(self.invalid_type(&"a boolean").at(&self.path)),
+        };
+        visitor.visit_bool(b).map_err(at(&self.path))
+    }
+
+    deserialize_signed! {
+        deserialize_i8 => i8,
+        deserialize_i16 => i16,
+        deserialize_i32 => i32,
+        deserialize_i64 => i64,
+    }
+
+    deserialize_unsigned! {
+        deserialize_u8 => u8,
+        deserialize_u16 => u16,
+        deserialize_u32 => u32,
+        deserialize_u64 => u64,
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        let f = self.float(&"a number").map_err(at(&self.path))?;
+        visitor.visit_f64(f).map_err(at(&self.path))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        let path = self.path.clone();
+        let result = match self.value.as_ref() {
+            ConfigValue::Bool(b) => visitor.visit_string(b.to_string()),
+            ConfigValue::Integer(i) => visitor.visit_string(i.to_string()),
+            ConfigValue::Float(f) => visitor.visit_string(f.to_string()),
+            ConfigValue::String(_) | ConfigValue::DateTime(_) => {
+                return self.deserialize_any(visitor);
+            },
+            _ => Err(self.invalid_type(&"a string")),
+        };
+        result.map_err(at(&path))
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        match self.value.as_ref() {
+            ConfigValue::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        match self.value.as_ref() {
+            ConfigValue::Null => visitor.visit_unit(),
+            _ => Err(self.invalid_type(&"null").at(&self.path)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        let path = self.path.clone();
+        let result = match self.value.as_ref() {
+            ConfigValue::Array(_) => return self.deserialize_any(visitor),
+            // Relaxed: "a, b, c" binds to a list, as in Spring.
+            ConfigValue::String(s) => {
+                let items = s
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| Cow::Owned(ConfigValue::String(item.to_string())));
+                visit_array(items, &path, visitor)
+            },
+            ConfigValue::Null => visit_array(std::iter::empty(), &path, visitor),
+            _ => Err(self.invalid_type(&"a sequence")),
+        };
+        result.map_err(at(&path))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> DeResult<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        match self.value.as_ref() {
+            ConfigValue::Object(_) => self.deserialize_any(visitor),
+            ConfigValue::Null => visitor
+                .visit_map(ObjectAccess::empty(&self.path))
+                .map_err(at(&self.path)),
+            _ => Err(self.invalid_type(&"a map").at(&self.path)),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        if name == "Duration" && fields == ["secs", "nanos"] {
+            let path = self.path.clone();
+            return self.deserialize_duration(visitor).map_err(at(&path));
+        }
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        let path = self.path.clone();
+        let access = match self.value {
+            Cow::Borrowed(ConfigValue::String(s)) => EnumAccessor::unit(s, &path),
+            Cow::Owned(ConfigValue::String(ref s)) => EnumAccessor::unit(s, &path),
+            Cow::Borrowed(ConfigValue::Object(map)) if map.len() == 1 => {
+                let (variant, value) = map.iter().next().expect("map has one entry");
+                EnumAccessor::with_value(variant.clone(), Cow::Borrowed(value), &path)
+            },
+            Cow::Owned(ConfigValue::Object(map)) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().expect("map has one entry");
+                EnumAccessor::with_value(variant, Cow::Owned(value), &path)
+            },
+            value => {
+                return Err(
+                    DeError::invalid_type(unexpected(&value), &"an enum variant").at(&path),
+                );
+            },
+        };
+        visitor.visit_enum(access).map_err(at(&path))
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        visitor.visit_unit()
+    }
+}
+
+fn visit_array<'de, I, V>(items: I, path: &str, visitor: V) -> DeResult<V::Value>
+where
+    I: Iterator<Item = Cow<'de, ConfigValue>>,
+    V: Visitor<'de>,
+{
+    visitor.visit_seq(ArrayAccess {
+        items: items.enumerate(),
+        path,
+    })
+}
+
+fn visit_object<'de, V: Visitor<'de>>(
+    map: &'de IndexMap<String, ConfigValue>,
+    path: &str,
+    visitor: V,
+) -> DeResult<V::Value> {
+    visitor.visit_map(ObjectAccess {
+        entries: Some(map.iter()),
+        pending: None,
+        path,
+    })
+}
+
+struct ArrayAccess<'a, I> {
+    items: std::iter::Enumerate<I>,
+    path: &'a str,
+}
+
+impl<'de, I> SeqAccess<'de> for ArrayAccess<'_, I>
+where
+    I: Iterator<Item = Cow<'de, ConfigValue>>,
+{
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> DeResult<Option<T::Value>> {
+        match self.items.next() {
+            Some((index, value)) => {
+                let path = format!("{}[{}]", self.path, index);
+                seed.deserialize(ValueDeserializer { value, path })
+                    .map(Some)
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+struct ObjectAccess<'de, 'a> {
+    entries: Option<indexmap::map::Iter<'de, String, ConfigValue>>,
+    pending: Option<(&'de String, &'de ConfigValue)>,
+    path: &'a str,
+}
+
+impl<'a> ObjectAccess<'_, 'a> {
+    fn empty(path: &'a str) -> Self {
+        Self {
+            entries: None,
+            pending: None,
+            path,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for ObjectAccess<'de, '_> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> DeResult<Option<K::Value>> {
+        let Some((key, value)) = self.entries.as_mut().and_then(Iterator::next) else {
+            return Ok(None);
+        };
+        self.pending = Some((key, value));
+
+        // Keys go through the relaxed deserializer too, so `HashMap<u16, _>` works.
+        let path = child_path(self.path, key);
+        seed.deserialize(ValueDeserializer::owned(
+            ConfigValue::String(key.clone()),
+            path,
+        ))
+        .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> DeResult<V::Value> {
+        let (key, value) = self
+            .pending
+            .take()
+            .ok_or_else(|| DeError::custom("value requested before key"))?;
+        seed.deserialize(ValueDeserializer::new(value, child_path(self.path, key)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.entries.as_ref().map(ExactSizeIterator::len)
+    }
+}
+
+struct EnumAccessor<'de> {
+    variant: String,
+    value: Option<Cow<'de, ConfigValue>>,
+    path: String,
+}
+
+impl<'de> EnumAccess<'de> for EnumAccessor<'de> {
+    type Error = DeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> DeResult<(V::Value, Self)> {
+        let deserializer: StringDeserializer<DeError> = self.variant.clone().into_deserializer();
+        let variant = seed.deserialize(deserializer)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for EnumAccessor<'de> {
+    type Error = DeError;
+
+    fn unit_variant(self) -> DeResult<()> {
+        match self.value.as_deref() {
+            None | Some(ConfigValue::Null) => Ok(()),
+            Some(value) => Err(de::Error::invalid_type(unexpected(value), &"unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> DeResult<T::Value> {
+        seed.deserialize(self.into_value_deserializer()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> DeResult<V::Value> {
+        de::Deserializer::deserialize_seq(self.into_value_deserializer()?, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        de::Deserializer::deserialize_map(self.into_value_deserializer()?, visitor)
+    }
+}
+
+impl<'de> EnumAccessor<'de> {
+    fn unit(variant: &str, path: &str) -> Self {
+        Self {
+            variant: variant.trim().to_string(),
+            value: None,
+            path: path.to_string(),
+        }
+    }
+
+    fn with_value(variant: String, value: Cow<'de, ConfigValue>, path: &str) -> Self {
+        let path = child_path(path, &variant);
+        Self {
+            variant,
+            value: Some(value),
+            path,
+        }
+    }
+
+    fn into_value_deserializer(self) -> DeResult<ValueDeserializer<'de>> {
+        let value = self.value.ok_or_else(|| {
+            de::Error::invalid_type(Unexpected::UnitVariant, &"variant with data")
+        })?;
+        Ok(ValueDeserializer {
+            value,
+            path: self.path,
+        })
+    }
+}
+
+/// Deserializer for the root of a [`ConfigMap`](crate::ConfigMap).
+pub(crate) struct RootDeserializer<'de> {
+    map: &'de IndexMap<String, ConfigValue>,
+}
+
+impl<'de> RootDeserializer<'de> {
+    pub(crate) fn new(map: &'de IndexMap<String, ConfigValue>) -> Self {
+        Self { map }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for RootDeserializer<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        visit_object(self.map, "", visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> DeResult<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> DeResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+/// Attaches `path` to errors raised below it that have no path yet.
+fn at(path: &str) -> impl Fn(DeError) -> DeError + '_ {
+    move |err| err.at(path)
+}
+
+/// Renders the path of `key` under `parent` the way [`ConfigPath`] does.
+///
+/// [`ConfigPath`]: crate::config::ConfigPath
+fn child_path(parent: &str, key: &str) -> String {
+    let mut path = parent.to_string();
+    let _ = write_key(&mut path, key, parent.is_empty());
+    path
+}
+
+fn unexpected(value: &ConfigValue) -> Unexpected<'_> {
+    match value {
+        ConfigValue::Null => Unexpected::Unit,
+        ConfigValue::Bool(b) => Unexpected::Bool(*b),
+        ConfigValue::Integer(i) => Unexpected::Signed(*i),
+        ConfigValue::Float(f) => Unexpected::Float(f.into_inner()),
+        ConfigValue::String(s) | ConfigValue::DateTime(s) => Unexpected::Str(s),
+        ConfigValue::Array(_) => Unexpected::Seq,
+        ConfigValue::Object(_) => Unexpected::Map,
+    }
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses a duration such as `30s`, `500ms`, `5m`, `2h` or `1d`.
+///
+/// A bare number is taken as milliseconds, following Spring Boot.
+fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let Some(split) = s.find(|c: char| !c.is_ascii_digit() && c != '.') else {
+        return s.parse().ok().map(Duration::from_millis);
+    };
+    let (amount, unit) = s.split_at(split);
+    let amount: f64 = amount.parse().ok()?;
+
+    let seconds = match unit.trim().to_ascii_lowercase().as_str() {
+        "ns" => amount / 1e9,
+        "us" | "µs" => amount / 1e6,
+        "ms" => amount / 1e3,
+        "s" => amount,
+        "m" => amount * 60.0,
+        "h" => amount * 3600.0,
+        "d" => amount * 86400.0,
+        _ => return None,
+    };
+
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Parses a data size such as `10MB`, `512KB` or `1 GB` into bytes (1024-based).
+fn parse_byte_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = s.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "B" => 1,
+        "KB" | "K" => 1 << 10,
+        "MB" | "M" => 1 << 20,
+        "GB" | "G" => 1 << 30,
+        "TB" | "T" => 1 << 40,
+        _ => return None,
+    };
+
+    amount.checked_mul(multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConfigMap;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Mode {
+        Active,
+        Standby,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Server {
+        host: String,
+        port: u16,
+        timeout: Duration,
+        max_body: u64,
+        compression: bool,
+        tags: Vec<String>,
+        mode: Mode,
+        #[serde(default)]
+        ssl: Option<bool>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct AppConfig {
+        name: String,
+        server: Server,
+        servers: Vec<Backend>,
+        weights: HashMap<u16, f64>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Backend {
+        host: String,
+        port: u16,
+    }
+
+    fn sample() -> ConfigMap {
+        // Values as a properties or env source would provide them: all strings.
+        ConfigMap::from_json(
+            r#"{
+                "name": "payments",
+                "server": {
+                    "host": "0.0.0.0",
+                    "port": "8080",
+                    "timeout": "30s",
+                    "max_body": "10MB",
+                    "compression": "on",
+                    "tags": "blue, green",
+                    "mode": "active"
+                },
+                "servers": [
+                    {"host": "a", "port": 9000},
+                    {"host": "b", "port": "9001"}
+                ],
+                "weights": {"80": "0.5", "443": 1}
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_deserialize_with_relaxed_coercions() {
+        let config: AppConfig = sample().deserialize().unwrap();
+
+        assert_eq!(config.name, "payments");
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.server.timeout, Duration::from_secs(30));
+        assert_eq!(config.server.max_body, 10 * 1024 * 1024);
+        assert!(config.server.compression);
+        assert_eq!(config.server.tags, vec!["blue", "green"]);
+        assert_eq!(config.server.mode, Mode::Active);
+        assert_eq!(config.server.ssl, None);
+        assert_eq!(config.servers[1].host, "b");
+        assert_eq!(config.servers[1].port, 9001);
+        assert_eq!(config.weights[&80], 0.5);
+        assert_eq!(config.weights[&443], 1.0);
+    }
+
+    #[test]
+    fn test_get_as() {
+        let config = sample();
+
+        assert_eq!(config.get_as::<u16>("server.port").unwrap(), 8080);
+        assert_eq!(config.get_as::<String>("servers").ok(), None);
+        assert_eq!(
+            config.get_as::<Duration>("server.timeout").unwrap(),
+            Duration::from_secs(30)
+        );
+        assert_eq!(config.get_as::<Mode>("server.mode").unwrap(), Mode::Active);
+
+        let mut millis = ConfigMap::new();
+        millis.insert("timeout", 1500);
+        assert_eq!(
+            millis.get_as::<Duration>("timeout").unwrap(),
+            Duration::from_millis(1500)
+        );
+    }
+
+    #[test]
+    fn test_get_as_missing_key() {
+        let err = sample().get_as::<u16>("server.missing").unwrap_err();
+
+        assert!(matches!(err, VortexError::PropertyNotFound { .. }));
+    }
+
+    #[test]
+    fn test_errors_point_at_key_path() {
+        let mut config = sample();
+        config.as_inner_mut().insert(
+            "servers".into(),
+            ConfigValue::Array(vec![
+                ConfigValue::Object(
+                    [("host".into(), "a".into()), ("port".into(), 1.into())].into(),
+                ),
+                ConfigValue::Object(
+                    [("host".into(), "b".into()), ("port".into(), "http".into())].into(),
+                ),
+            ]),
+        );
+
+        let err = config.deserialize::<AppConfig>().unwrap_err();
+        match err {
+            VortexError::ValidationError { field, message } => {
+                assert_eq!(field, "servers[1].port");
+                assert!(message.contains("http"), "{}", message);
+            },
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let err = sample().get_as::<u8>("server.port").unwrap_err();
+        assert!(err.to_string().contains("server.port"), "{}", err);
+
+        let err = sample().get_as::<Server>("server.host").unwrap_err();
+        assert!(err.to_string().contains("server.host"), "{}", err);
+    }
+
+    #[test]
+    fn test_missing_field_reports_parent_path() {
+        let config = ConfigMap::from_json(r#"{"server": {"host": "x"}}"#).unwrap();
+
+        let err = config.get_as::<Backend>("server").unwrap_err();
+
+        assert!(err.to_string().contains("server"), "{}", err);
+        assert!(err.to_string().contains("port"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1.5h"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2 m"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_duration("10"), Some(Duration::from_millis(10)));
+        assert_eq!(parse_duration("s"), None);
+        assert_eq!(parse_duration("10 parsecs"), None);
+    }
+
+    #[test]
+    fn test_parse_byte_size() {
+        assert_eq!(parse_byte_size("10MB"), Some(10 * 1024 * 1024));
+        assert_eq!(parse_byte_size("512 kb"), Some(512 * 1024));
+        assert_eq!(parse_byte_size("100B"), Some(100));
+        assert_eq!(parse_byte_size("10XB"), None);
+        assert_eq!(parse_byte_size("MB"), None);
+    }
+
+    #[test]
+    fn test_error_display_includes_path() {
+        let err = DeError::custom("invalid value").at("server.port");
+
+        assert_eq!(err.to_string(), "server.port: invalid value");
+        let err: VortexError = err.into();
+        assert!(err.is_validation_error());
+        assert!(err.to_string().contains("server.port"));
+    }
+}
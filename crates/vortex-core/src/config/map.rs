@@ -1,6 +1,9 @@
+use crate::config::de::{RootDeserializer, ValueDeserializer};
+use crate::config::path::{ConfigPath, PathSegment};
 use crate::config::value::ConfigValue;
 use crate::error::{Result, VortexError};
 use indexmap::IndexMap;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 /// A configuration map that holds key-value pairs with support for nested structures.
@@ -55,14 +58,16 @@ impl ConfigMap {
         self.inner.insert(key.into(), value.into());
     }
 
-    /// Retrieves a value by key, supporting dot notation for nested access.
+    /// Retrieves a value by path expression (see [`ConfigPath`]).
+    ///
+    /// Supports dot notation, array indices and bracketed/quoted keys. If
+    /// `path` is not a valid expression it is looked up as a literal key.
     ///
     /// # Example
     /// ```
-    /// # use vortex_core::{ConfigMap, ConfigValue};
-    /// let mut map = ConfigMap::new();
-    /// // Assuming map has {"server": {"port": 8080}}
-    /// // map.get("server.port") returns Some(&ConfigValue::Integer(8080))
+    /// # use vortex_core::ConfigMap;
+    /// let map = ConfigMap::from_json(r#"{"servers": [{"host": "a"}, {"host": "b"}]}"#).unwrap();
+    /// assert_eq!(map.get("servers[1].host").unwrap().as_str(), Some("b"));
     /// ```
     pub fn get(&self, path: &str) -> Option<&ConfigValue> {
         if path.is_empty() {
@@ -70,24 +75,148 @@ impl ConfigMap {
         }
 
         // Fast path for simple keys
-        if !path.contains('.') {
+        if !path.contains(['.', '[', '"', '\'']) {
             return self.inner.get(path);
         }
 
-        // Recursive lookup for dot notation
-        let parts: Vec<&str> = path.split('.').collect();
-        let mut current_value = self.inner.get(parts[0])?;
+        match ConfigPath::parse(path) {
+            Ok(path) => self.get_path(&path),
+            Err(_) => self.inner.get(path),
+        }
+    }
+
+    /// Retrieves the value at `path`.
+    pub fn get_path(&self, path: &ConfigPath) -> Option<&ConfigValue> {
+        let (first, rest) = path.segments().split_first()?;
+        let PathSegment::Key(key) = first else {
+            return None;
+        };
+
+        rest.iter()
+            .try_fold(self.inner.get(key)?, |value, segment| {
+                match (value, segment) {
+                    (ConfigValue::Object(map), PathSegment::Key(key)) => map.get(key),
+                    (ConfigValue::Array(arr), PathSegment::Index(index)) => arr.get(*index),
+                    _ => None,
+                }
+            })
+    }
+
+    /// Retrieves a mutable reference to the value at `path`.
+    pub fn get_path_mut(&mut self, path: &ConfigPath) -> Option<&mut ConfigValue> {
+        let (first, rest) = path.segments().split_first()?;
+        let PathSegment::Key(key) = first else {
+            return None;
+        };
 
-        for part in &parts[1..] {
-            match current_value {
-                ConfigValue::Object(map) => {
-                    current_value = map.get(*part)?;
+        rest.iter()
+            .try_fold(self.inner.get_mut(key)?, |value, segment| {
+                match (value, segment) {
+                    (ConfigValue::Object(map), PathSegment::Key(key)) => map.get_mut(key),
+                    (ConfigValue::Array(arr), PathSegment::Index(index)) => arr.get_mut(*index),
+                    _ => None,
+                }
+            })
+    }
+
+    /// Returns a mutable reference to the value at `path`, creating it if needed.
+    ///
+    /// Missing intermediate objects and arrays are created; arrays are padded
+    /// with `Null` up to the requested index and a new leaf starts as `Null`.
+    /// An existing value of the wrong kind along the path is replaced, so the
+    /// last write wins as in properties files.
+    pub fn entry_path(&mut self, path: &ConfigPath) -> Result<&mut ConfigValue> {
+        let Some((PathSegment::Key(key), rest)) = path.segments().split_first() else {
+            return Err(VortexError::parse_error(
+                "config_path",
+                format!("Path '{}' must start with a key", path),
+            ));
+        };
+
+        let mut current = self.inner.entry(key.clone()).or_insert(ConfigValue::Null);
+
+        for segment in rest {
+            current = match segment {
+                PathSegment::Key(key) => object_mut(current)
+                    .entry(key.clone())
+                    .or_insert(ConfigValue::Null),
+                PathSegment::Index(index) => {
+                    let arr = array_mut(current);
+                    if arr.len() <= *index {
+                        arr.resize(*index + 1, ConfigValue::Null);
+                    }
+                    &mut arr[*index]
                 },
-                _ => return None,
-            }
+            };
         }
 
-        Some(current_value)
+        Ok(current)
+    }
+
+    /// Sets the value at `path`, returning the previous value if there was one.
+    ///
+    /// Intermediate containers are created as described in [`entry_path`].
+    ///
+    /// [`entry_path`]: ConfigMap::entry_path
+    pub fn set_path(
+        &mut self,
+        path: &ConfigPath,
+        value: impl Into<ConfigValue>,
+    ) -> Result<Option<ConfigValue>> {
+        let existed = self.get_path(path).is_some();
+        let previous = std::mem::replace(self.entry_path(path)?, value.into());
+        Ok(existed.then_some(previous))
+    }
+
+    /// Removes the value at `path`, returning it.
+    ///
+    /// Object keys keep the order of their siblings; removing an array element
+    /// shifts the following elements down.
+    pub fn remove_path(&mut self, path: &ConfigPath) -> Option<ConfigValue> {
+        let mut parent = path.clone();
+        let last = parent.pop()?;
+
+        if parent.is_root() {
+            return match last {
+                PathSegment::Key(key) => self.inner.shift_remove(&key),
+                PathSegment::Index(_) => None,
+            };
+        }
+
+        match (self.get_path_mut(&parent)?, last) {
+            (ConfigValue::Object(map), PathSegment::Key(key)) => map.shift_remove(&key),
+            (ConfigValue::Array(arr), PathSegment::Index(index)) if index < arr.len() => {
+                Some(arr.remove(index))
+            },
+            _ => None,
+        }
+    }
+
+    /// Deserializes the value at `path` into `T`.
+    ///
+    /// Applies relaxed coercions, so `"8080"` binds to a `u16`, `"30s"` to a
+    /// [`std::time::Duration`] and `"10MB"` to an integer byte count. Errors
+    /// name the offending key path.
+    ///
+    /// # Example
+    /// ```
+    /// # use vortex_core::ConfigMap;
+    /// let config = ConfigMap::from_json(r#"{"server": {"port": "8080"}}"#).unwrap();
+    /// let port: u16 = config.get_as("server.port").unwrap();
+    /// assert_eq!(port, 8080);
+    /// ```
+    pub fn get_as<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let value = self
+            .get(path)
+            .ok_or_else(|| VortexError::property_not_found(path))?;
+        T::deserialize(ValueDeserializer::new(value, path)).map_err(VortexError::from)
+    }
+
+    /// Deserializes the whole map into `T`, with the same coercions as [`get_as`].
+    ///
+    /// [`get_as`]: ConfigMap::get_as
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T> {
+        T::deserialize(RootDeserializer::new(&self.inner)).map_err(VortexError::from)
     }
 
     /// Parses a JSON string into a ConfigMap.
@@ -103,9 +232,11 @@ impl ConfigMap {
     }
 
     /// Parses a YAML string into a ConfigMap.
+    ///
+    /// Merge keys (`<<`) are expanded; custom tags are rejected (see
+    /// [`crate::format::yaml`]).
     pub fn from_yaml(yaml: &str) -> Result<Self> {
-        serde_yaml::from_str(yaml)
-            .map_err(|e| VortexError::parse_error("yaml_source", e.to_string()))
+        crate::format::yaml::parse(yaml, &Default::default())
     }
 
     /// Serializes the map to a YAML string.
@@ -115,6 +246,26 @@ impl ConfigMap {
     }
 }
 
+fn object_mut(value: &mut ConfigValue) -> &mut IndexMap<String, ConfigValue> {
+    if !matches!(value, ConfigValue::Object(_)) {
+        *value = ConfigValue::Object(IndexMap::new());
+    }
+    match value {
+        ConfigValue::Object(map) => map,
+        _ => unreachable!("value was just made an object"),
+    }
+}
+
+fn array_mut(value: &mut ConfigValue) -> &mut Vec<ConfigValue> {
+    if !matches!(value, ConfigValue::Array(_)) {
+        *value = ConfigValue::Array(Vec::new());
+    }
+    match value {
+        ConfigValue::Array(arr) => arr,
+        _ => unreachable!("value was just made an array"),
+    }
+}
+
 // Implement From<IndexMap>
 impl From<IndexMap<String, ConfigValue>> for ConfigMap {
     fn from(map: IndexMap<String, ConfigValue>) -> Self {
@@ -156,6 +307,118 @@ mod tests {
         assert_eq!(config.get("server.port.sub"), None); // port is integer, not object
     }
 
+    #[test]
+    fn test_get_with_indices_and_quoted_keys() {
+        let json = r#"
+        {
+            "servers": [{"host": "a"}, {"host": "b", "ports": [80, 443]}],
+            "labels": {"app.kubernetes.io/name": "payments"}
+        }
+        "#;
+        let config = ConfigMap::from_json(json).unwrap();
+
+        assert_eq!(config.get("servers[0].host").unwrap().as_str(), Some("a"));
+        assert_eq!(
+            config.get("servers[1].ports[1]").unwrap().as_i64(),
+            Some(443)
+        );
+        assert_eq!(
+            config
+                .get(r#"labels."app.kubernetes.io/name""#)
+                .unwrap()
+                .as_str(),
+            Some("payments")
+        );
+        assert_eq!(
+            config
+                .get("labels[app.kubernetes.io/name]")
+                .unwrap()
+                .as_str(),
+            Some("payments")
+        );
+        assert_eq!(config.get("servers[2].host"), None);
+        assert_eq!(config.get("servers.host"), None);
+    }
+
+    #[test]
+    fn test_set_path_creates_containers() {
+        let mut config = ConfigMap::new();
+
+        let path = ConfigPath::parse("servers[1].host").unwrap();
+        assert_eq!(config.set_path(&path, "b").unwrap(), None);
+
+        let servers = config.get("servers").unwrap().as_array().unwrap();
+        assert_eq!(servers.len(), 2);
+        assert!(servers[0].is_null());
+        assert_eq!(config.get("servers[1].host").unwrap().as_str(), Some("b"));
+
+        let previous = config.set_path(&path, "c").unwrap();
+        assert_eq!(previous, Some(ConfigValue::from("b")));
+
+        // A scalar in the way is replaced, last write wins.
+        let path = ConfigPath::parse("servers[1].host.name").unwrap();
+        config.set_path(&path, "d").unwrap();
+        assert_eq!(
+            config.get("servers[1].host.name").unwrap().as_str(),
+            Some("d")
+        );
+    }
+
+    #[test]
+    fn test_entry_path() {
+        let mut config = ConfigMap::new();
+        let path = ConfigPath::parse("counters.hits").unwrap();
+
+        *config.entry_path(&path).unwrap() = ConfigValue::Integer(1);
+        if let ConfigValue::Integer(n) = config.entry_path(&path).unwrap() {
+            *n += 1;
+        }
+
+        assert_eq!(config.get("counters.hits").unwrap().as_i64(), Some(2));
+        assert!(
+            config
+                .entry_path(&ConfigPath::parse("[0]").unwrap())
+                .is_err()
+        );
+        assert!(config.entry_path(&ConfigPath::root()).is_err());
+    }
+
+    #[test]
+    fn test_remove_path() {
+        let json = r#"{"a": {"x": 1, "y": 2, "z": 3}, "list": [1, 2, 3]}"#;
+        let mut config = ConfigMap::from_json(json).unwrap();
+
+        let removed = config.remove_path(&ConfigPath::parse("a.y").unwrap());
+        assert_eq!(removed, Some(ConfigValue::Integer(2)));
+        let keys: Vec<_> = config
+            .get("a")
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        assert_eq!(keys, ["x", "z"]);
+
+        let removed = config.remove_path(&ConfigPath::parse("list[0]").unwrap());
+        assert_eq!(removed, Some(ConfigValue::Integer(1)));
+        assert_eq!(config.get("list[0]").unwrap().as_i64(), Some(2));
+
+        assert_eq!(
+            config.remove_path(&ConfigPath::parse("list[9]").unwrap()),
+            None
+        );
+        assert_eq!(
+            config.remove_path(&ConfigPath::parse("missing.key").unwrap()),
+            None
+        );
+        assert!(
+            config
+                .remove_path(&ConfigPath::parse("a").unwrap())
+                .is_some()
+        );
+        assert!(config.get("a").is_none());
+    }
+
     #[test]
     fn test_yaml_roundtrip() {
         let mut map = ConfigMap::new();
@@ -1,7 +1,11 @@
+pub mod de;
 pub mod map;
+pub mod path;
 pub mod source;
 pub mod value;
 
+pub use de::DeError;
 pub use map::ConfigMap;
+pub use path::{ConfigPath, PathSegment};
 pub use source::PropertySource;
 pub use value::ConfigValue;
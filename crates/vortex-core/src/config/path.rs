@@ -0,0 +1,377 @@
+//! Path expressions addressing values inside a [`ConfigMap`](crate::ConfigMap).
+//!
+//! A path is a sequence of keys and array indices:
+//!
+//! | Expression            | Segments                         |
+//! |-----------------------|----------------------------------|
+//! | `server.port`         | `server`, `port`                 |
+//! | `servers[1].host`     | `servers`, `1`, `host`           |
+//! | `logging.level[com.acme]` | `logging`, `level`, `com.acme` |
+//! | `labels."app.kubernetes.io/name"` | `labels`, `app.kubernetes.io/name` |
+//!
+//! Brackets holding digits are array indices; any other bracket content is a
+//! map key, as in Spring's `map[key.with.dots]` syntax. Keys may also be
+//! double- or single-quoted, with `\` escaping the quote character.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Result, VortexError};
+
+/// One step of a [`ConfigPath`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// Key of an object.
+    Key(String),
+    /// Index of an array.
+    Index(usize),
+}
+
+/// A parsed path expression such as `servers[1].host`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ConfigPath {
+    segments: Vec<PathSegment>,
+}
+
+impl ConfigPath {
+    /// Creates an empty path (the root of a map).
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Parses a path expression.
+    ///
+    /// # Example
+    /// ```
+    /// use vortex_core::{ConfigPath, PathSegment};
+    ///
+    /// let path = ConfigPath::parse("servers[1].host").unwrap();
+    /// assert_eq!(path.segments()[1], PathSegment::Index(1));
+    /// assert_eq!(path.to_string(), "servers[1].host");
+    /// ```
+    pub fn parse(expr: &str) -> Result<Self> {
+        Parser::new(expr).parse()
+    }
+
+    /// Returns this path extended with an object key.
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.push_key(key);
+        self
+    }
+
+    /// Returns this path extended with an array index.
+    pub fn index(mut self, index: usize) -> Self {
+        self.push_index(index);
+        self
+    }
+
+    /// Appends an object key.
+    pub fn push_key(&mut self, key: impl Into<String>) {
+        self.segments.push(PathSegment::Key(key.into()));
+    }
+
+    /// Appends an array index.
+    pub fn push_index(&mut self, index: usize) {
+        self.segments.push(PathSegment::Index(index));
+    }
+
+    /// Removes and returns the last segment.
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.segments.pop()
+    }
+
+    /// Returns the segments of the path.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Returns true if this is the root path.
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Returns the number of segments.
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns true if the path has no segments.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl fmt::Display for ConfigPath {
+    /// Renders the canonical form: plain keys are dot-separated, keys that
+    /// would be ambiguous use brackets (quoted if they contain `]` or quotes).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+                PathSegment::Key(key) => write_key(f, key, i == 0)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes `key` as the next segment of a rendered path.
+pub(crate) fn write_key<W: fmt::Write>(out: &mut W, key: &str, first: bool) -> fmt::Result {
+    if is_plain_key(key) {
+        if !first {
+            out.write_char('.')?;
+        }
+        out.write_str(key)
+    } else if !key.contains([']', '"', '\'']) && !is_index(key) {
+        write!(out, "[{}]", key)
+    } else {
+        let escaped = key.replace('\\', "\\\\").replace('"', "\\\"");
+        write!(out, "[\"{}\"]", escaped)
+    }
+}
+
+impl FromStr for ConfigPath {
+    type Err = VortexError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl From<Vec<PathSegment>> for ConfigPath {
+    fn from(segments: Vec<PathSegment>) -> Self {
+        Self { segments }
+    }
+}
+
+impl FromIterator<PathSegment> for ConfigPath {
+    fn from_iter<I: IntoIterator<Item = PathSegment>>(iter: I) -> Self {
+        Self {
+            segments: iter.into_iter().collect(),
+        }
+    }
+}
+
+fn is_plain_key(key: &str) -> bool {
+    !key.is_empty() && !key.contains(['.', '[', ']', '"', '\''])
+}
+
+fn is_index(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+struct Parser<'a> {
+    expr: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    segments: Vec<PathSegment>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(expr: &'a str) -> Self {
+        Self {
+            expr,
+            chars: expr.char_indices().peekable(),
+            segments: Vec::new(),
+        }
+    }
+
+    fn error(&self, message: impl fmt::Display) -> VortexError {
+        VortexError::parse_error(
+            "config_path",
+            format!("Invalid path '{}': {}", self.expr, message),
+        )
+    }
+
+    fn parse(mut self) -> Result<ConfigPath> {
+        if self.expr.is_empty() {
+            return Err(self.error("path is empty"));
+        }
+
+        // A path starts with a key (plain or quoted) or a bracket.
+        self.parse_key_or_bracket()?;
+
+        while let Some(&(pos, c)) = self.chars.peek() {
+            match c {
+                '.' => {
+                    self.chars.next();
+                    match self.chars.peek().copied() {
+                        Some((_, '"' | '\'')) => self.parse_quoted_key()?,
+                        Some((_, '.' | '[')) | None => {
+                            return Err(self.error(format!("empty key at position {}", pos)));
+                        },
+                        Some(_) => self.parse_plain_key(),
+                    }
+                },
+                '[' => self.parse_bracket()?,
+                _ => return Err(self.error(format!("unexpected '{}' at position {}", c, pos))),
+            }
+        }
+
+        Ok(ConfigPath {
+            segments: self.segments,
+        })
+    }
+
+    fn parse_key_or_bracket(&mut self) -> Result<()> {
+        match self.chars.peek().copied() {
+            Some((_, '[')) => self.parse_bracket(),
+            Some((_, '"' | '\'')) => self.parse_quoted_key(),
+            Some((pos, '.')) => Err(self.error(format!("empty key at position {}", pos))),
+            _ => {
+                self.parse_plain_key();
+                Ok(())
+            },
+        }
+    }
+
+    fn parse_plain_key(&mut self) {
+        let mut key = String::new();
+        while let Some(&(_, c)) = self.chars.peek() {
+            if c == '.' || c == '[' {
+                break;
+            }
+            key.push(c);
+            self.chars.next();
+        }
+        self.segments.push(PathSegment::Key(key));
+    }
+
+    fn parse_quoted_key(&mut self) -> Result<()> {
+        let key = self.quoted()?;
+        self.segments.push(PathSegment::Key(key));
+        Ok(())
+    }
+
+    fn quoted(&mut self) -> Result<String> {
+        let (start, quote) = self.chars.next().expect("caller peeked a quote");
+        let mut key = String::new();
+        while let Some((_, c)) = self.chars.next() {
+            match c {
+                '\\' => match self.chars.next() {
+                    Some((_, escaped)) => key.push(escaped),
+                    None => break,
+                },
+                c if c == quote => return Ok(key),
+                c => key.push(c),
+            }
+        }
+        Err(self.error(format!("unterminated quote at position {}", start)))
+    }
+
+    fn parse_bracket(&mut self) -> Result<()> {
+        let (start, _) = self.chars.next().expect("caller peeked '['");
+
+        let segment = match self.chars.peek() {
+            Some((_, '"' | '\'')) => PathSegment::Key(self.quoted()?),
+            _ => {
+                let mut content = String::new();
+                while let Some(&(_, c)) = self.chars.peek() {
+                    if c == ']' {
+                        break;
+                    }
+                    content.push(c);
+                    self.chars.next();
+                }
+                if content.is_empty() {
+                    return Err(self.error(format!("empty brackets at position {}", start)));
+                }
+                match content.parse::<usize>() {
+                    Ok(index) if is_index(&content) => PathSegment::Index(index),
+                    _ => PathSegment::Key(content),
+                }
+            },
+        };
+
+        match self.chars.next() {
+            Some((_, ']')) => {
+                self.segments.push(segment);
+                Ok(())
+            },
+            _ => Err(self.error(format!("unclosed '[' at position {}", start))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(path: &ConfigPath) -> Vec<String> {
+        path.segments()
+            .iter()
+            .map(|s| match s {
+                PathSegment::Key(k) => k.clone(),
+                PathSegment::Index(i) => format!("#{}", i),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_paths() {
+        assert_eq!(
+            keys(&ConfigPath::parse("server.port").unwrap()),
+            ["server", "port"]
+        );
+        assert_eq!(
+            keys(&ConfigPath::parse("servers[1].host").unwrap()),
+            ["servers", "#1", "host"]
+        );
+        assert_eq!(
+            keys(&ConfigPath::parse("matrix[0][2]").unwrap()),
+            ["matrix", "#0", "#2"]
+        );
+        assert_eq!(
+            keys(&ConfigPath::parse("logging.level[com.acme]").unwrap()),
+            ["logging", "level", "com.acme"]
+        );
+        assert_eq!(
+            keys(&ConfigPath::parse(r#"labels."app.io/name".value"#).unwrap()),
+            ["labels", "app.io/name", "value"]
+        );
+        assert_eq!(
+            keys(&ConfigPath::parse(r#"a['it\'s']"#).unwrap()),
+            ["a", "it's"]
+        );
+        assert_eq!(
+            keys(&ConfigPath::parse("[0].name").unwrap()),
+            ["#0", "name"]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for expr in ["", "a..b", "a.", ".a", "a[1", "a[]", "a.\"open", "a[1]b"] {
+            assert!(ConfigPath::parse(expr).is_err(), "{} should fail", expr);
+        }
+    }
+
+    #[test]
+    fn test_display_roundtrip() {
+        for expr in [
+            "server.port",
+            "servers[1].host",
+            "logging.level[com.acme]",
+            r#"weird["has]bracket"]"#,
+        ] {
+            let path = ConfigPath::parse(expr).unwrap();
+            assert_eq!(path.to_string(), expr);
+            assert_eq!(ConfigPath::parse(&path.to_string()).unwrap(), path);
+        }
+    }
+
+    #[test]
+    fn test_quoted_plain_key_is_rendered_plain() {
+        let path = ConfigPath::parse(r#"codes["42"]"#).unwrap();
+
+        assert_eq!(path.to_string(), "codes.42");
+        assert_eq!(ConfigPath::parse("codes.42").unwrap(), path);
+    }
+
+    #[test]
+    fn test_builder() {
+        let path = ConfigPath::root().key("servers").index(0).key("a.b");
+
+        assert_eq!(path.to_string(), "servers[0][a.b]");
+        assert_eq!(path.len(), 3);
+    }
+}
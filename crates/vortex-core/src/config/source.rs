@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use crate::config::map::ConfigMap;
 use serde::{Deserialize, Serialize};
 
@@ -22,6 +24,14 @@ pub struct PropertySource {
 
     /// The actual configuration properties.
     pub config: ConfigMap,
+
+    /// Files that keys were included from, by key path (e.g.
+    /// `kafka.bootstrap-servers` → `shared/kafka.yml`).
+    ///
+    /// Only keys taken from an included fragment are listed; keys defined
+    /// in the source itself are not.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub origins: BTreeMap<String, String>,
 }
 
 impl PropertySource {
@@ -32,6 +42,7 @@ impl PropertySource {
             origin: String::new(),
             priority: 0,
             config,
+            origins: BTreeMap::new(),
         }
     }
 }
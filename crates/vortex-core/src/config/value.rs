@@ -38,6 +38,11 @@ pub enum ConfigValue {
     Array(Vec<ConfigValue>),
     /// Object (Map) of values
     Object(IndexMap<String, ConfigValue>),
+    /// Date/time value kept in its RFC 3339 textual form (e.g. from TOML).
+    ///
+    /// Serializes as a plain string so JSON/YAML outputs are unaffected; it is
+    /// declared after `String` so untagged deserialization never produces it.
+    DateTime(String),
 }
 
 impl ConfigValue {
@@ -79,6 +84,14 @@ impl ConfigValue {
         }
     }
 
+    /// Returns the RFC 3339 representation if the value is a DateTime.
+    pub fn as_datetime(&self) -> Option<&str> {
+        match self {
+            ConfigValue::DateTime(dt) => Some(dt),
+            _ => None,
+        }
+    }
+
     /// Returns the value as an array slice if it matches.
     pub fn as_array(&self) -> Option<&[ConfigValue]> {
         match self {
@@ -164,6 +177,16 @@ mod tests {
         assert_eq!(json, "[1,2]");
     }
 
+    #[test]
+    fn test_datetime_serializes_as_string() {
+        let v = ConfigValue::DateTime("1979-05-27T07:32:00Z".to_string());
+        assert_eq!(v.as_datetime(), Some("1979-05-27T07:32:00Z"));
+        assert_eq!(v.as_str(), None);
+
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(json, r#""1979-05-27T07:32:00Z""#);
+    }
+
     #[test]
     fn test_serde_deserialization() {
         let json = r#"{"key": "value", "num": 10.5}"#;
@@ -0,0 +1,344 @@
+//! Structural diff between configurations.
+//!
+//! Compares two [`ConfigMap`]s (or the merged result of two
+//! [`PropertySourceList`]s) and reports every leaf that was added, removed or
+//! changed, addressed by its [`ConfigPath`].
+//!
+//! Objects are compared key by key and arrays element by element, so a change
+//! deep inside a list is reported as `servers[1].port` rather than as a
+//! replacement of the whole list.
+//!
+//! # Example
+//!
+//! ```
+//! use vortex_core::ConfigMap;
+//! use vortex_core::diff::{ChangeKind, diff};
+//!
+//! let old = ConfigMap::from_json(r#"{"server": {"port": 8080}}"#).unwrap();
+//! let new = ConfigMap::from_json(r#"{"server": {"port": 9090, "ssl": true}}"#).unwrap();
+//!
+//! let changes = diff(&old, &new);
+//! assert_eq!(changes.len(), 2);
+//! assert_eq!(changes.changes()[0].path.to_string(), "server.port");
+//! assert_eq!(changes.changes()[0].kind, ChangeKind::Changed);
+//! ```
+
+use serde::{Serialize, Serializer};
+
+use crate::config::{ConfigMap, ConfigPath, ConfigValue};
+use crate::merge::PropertySourceList;
+
+/// Kind of a single change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// The path exists only in the new configuration.
+    Added,
+    /// The path exists only in the old configuration.
+    Removed,
+    /// The path exists in both with different values.
+    Changed,
+}
+
+/// A change at a single path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change {
+    /// Path of the value that changed.
+    #[serde(serialize_with = "serialize_path")]
+    pub path: ConfigPath,
+    /// Kind of change.
+    pub kind: ChangeKind,
+    /// Previous value (absent for additions).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<ConfigValue>,
+    /// New value (absent for removals).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<ConfigValue>,
+}
+
+/// Ordered list of changes between two configurations.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct ConfigDiff {
+    changes: Vec<Change>,
+}
+
+impl ConfigDiff {
+    /// Returns all changes, in the key order of the compared configurations.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Returns true if the configurations are equal.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the number of changes.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Iterates over changes of the given kind.
+    pub fn of_kind(&self, kind: ChangeKind) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(move |c| c.kind == kind)
+    }
+
+    /// Renders the diff as unified-style text.
+    ///
+    /// Each changed path becomes `-path=old` / `+path=new` lines, with values
+    /// in JSON notation so types stay visible (`"8080"` vs `8080`).
+    pub fn to_unified(&self, from: &str, to: &str) -> String {
+        let mut output = format!("--- {}\n+++ {}\n", from, to);
+
+        for change in &self.changes {
+            if let Some(old) = &change.old {
+                output.push_str(&format!("-{}={}\n", change.path, render(old)));
+            }
+            if let Some(new) = &change.new {
+                output.push_str(&format!("+{}={}\n", change.path, render(new)));
+            }
+        }
+
+        output
+    }
+}
+
+impl IntoIterator for ConfigDiff {
+    type Item = Change;
+    type IntoIter = std::vec::IntoIter<Change>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.into_iter()
+    }
+}
+
+/// Computes the changes needed to go from `old` to `new`.
+pub fn diff(old: &ConfigMap, new: &ConfigMap) -> ConfigDiff {
+    let mut changes = Vec::new();
+    let mut path = ConfigPath::root();
+
+    diff_objects(old.as_inner(), new.as_inner(), &mut path, &mut changes);
+
+    ConfigDiff { changes }
+}
+
+/// Computes the changes between the merged results of two source lists.
+pub fn diff_sources(old: &PropertySourceList, new: &PropertySourceList) -> ConfigDiff {
+    diff(&old.merge(), &new.merge())
+}
+
+fn diff_objects(
+    old: &indexmap::IndexMap<String, ConfigValue>,
+    new: &indexmap::IndexMap<String, ConfigValue>,
+    path: &mut ConfigPath,
+    changes: &mut Vec<Change>,
+) {
+    for (key, old_value) in old {
+        path.push_key(key.as_str());
+        match new.get(key) {
+            Some(new_value) => diff_values(old_value, new_value, path, changes),
+            None => changes.push(removed(path, old_value)),
+        }
+        path.pop();
+    }
+
+    for (key, new_value) in new {
+        if !old.contains_key(key) {
+            path.push_key(key.as_str());
+            changes.push(added(path, new_value));
+            path.pop();
+        }
+    }
+}
+
+fn diff_values(
+    old: &ConfigValue,
+    new: &ConfigValue,
+    path: &mut ConfigPath,
+    changes: &mut Vec<Change>,
+) {
+    match (old, new) {
+        (ConfigValue::Object(old), ConfigValue::Object(new)) => {
+            diff_objects(old, new, path, changes)
+        },
+        (ConfigValue::Array(old), ConfigValue::Array(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                path.push_index(index);
+                match (old.get(index), new.get(index)) {
+                    (Some(o), Some(n)) => diff_values(o, n, path, changes),
+                    (Some(o), None) => changes.push(removed(path, o)),
+                    (None, Some(n)) => changes.push(added(path, n)),
+                    (None, None) => {},
+                }
+                path.pop();
+            }
+        },
+        (old, new) if old != new => changes.push(Change {
+            path: path.clone(),
+            kind: ChangeKind::Changed,
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {},
+    }
+}
+
+fn added(path: &ConfigPath, value: &ConfigValue) -> Change {
+    Change {
+        path: path.clone(),
+        kind: ChangeKind::Added,
+        old: None,
+        new: Some(value.clone()),
+    }
+}
+
+fn removed(path: &ConfigPath, value: &ConfigValue) -> Change {
+    Change {
+        path: path.clone(),
+        kind: ChangeKind::Removed,
+        old: Some(value.clone()),
+        new: None,
+    }
+}
+
+fn render(value: &ConfigValue) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+fn serialize_path<S: Serializer>(path: &ConfigPath, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PropertySource;
+
+    fn map(json: &str) -> ConfigMap {
+        ConfigMap::from_json(json).unwrap()
+    }
+
+    fn summary(result: &ConfigDiff) -> Vec<(String, ChangeKind)> {
+        result
+            .changes()
+            .iter()
+            .map(|c| (c.path.to_string(), c.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_identical_configs_have_no_changes() {
+        let config = map(r#"{"a": {"b": [1, 2]}, "c": null}"#);
+
+        assert!(diff(&config, &config.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_added_removed_changed() {
+        let old = map(r#"{"server": {"port": 8080, "host": "a"}, "legacy": true}"#);
+        let new = map(r#"{"server": {"port": 9090, "host": "a", "ssl": true}}"#);
+
+        let result = diff(&old, &new);
+
+        assert_eq!(
+            summary(&result),
+            vec![
+                ("server.port".to_string(), ChangeKind::Changed),
+                ("server.ssl".to_string(), ChangeKind::Added),
+                ("legacy".to_string(), ChangeKind::Removed),
+            ]
+        );
+        let port = &result.changes()[0];
+        assert_eq!(port.old, Some(ConfigValue::Integer(8080)));
+        assert_eq!(port.new, Some(ConfigValue::Integer(9090)));
+        assert_eq!(result.of_kind(ChangeKind::Added).count(), 1);
+    }
+
+    #[test]
+    fn test_arrays_are_compared_by_index() {
+        let old = map(r#"{"servers": [{"host": "a"}, {"host": "b"}, {"host": "c"}]}"#);
+        let new = map(r#"{"servers": [{"host": "a"}, {"host": "x"}]}"#);
+
+        let result = diff(&old, &new);
+
+        assert_eq!(
+            summary(&result),
+            vec![
+                ("servers[1].host".to_string(), ChangeKind::Changed),
+                ("servers[2]".to_string(), ChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_change_is_reported_at_the_path() {
+        let old = map(r#"{"logging": "INFO"}"#);
+        let new = map(r#"{"logging": {"level": "DEBUG"}}"#);
+
+        let result = diff(&old, &new);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.changes()[0].kind, ChangeKind::Changed);
+        assert!(
+            result.changes()[0]
+                .new
+                .as_ref()
+                .unwrap()
+                .as_object()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_diff_sources_compares_merged_result() {
+        let source = |name: &str, priority: i32, json: &str| {
+            let mut ps = PropertySource::new(name, map(json));
+            ps.priority = priority;
+            ps
+        };
+
+        let mut old = PropertySourceList::new();
+        old.add(source(
+            "application.yml",
+            0,
+            r#"{"port": 8080, "debug": false}"#,
+        ));
+        old.add(source("app-prod.yml", 10, r#"{"port": 9090}"#));
+
+        let mut new = PropertySourceList::new();
+        new.add(source(
+            "application.yml",
+            0,
+            r#"{"port": 8080, "debug": false}"#,
+        ));
+        new.add(source("app-prod.yml", 10, r#"{"port": 9091}"#));
+
+        let result = diff_sources(&old, &new);
+
+        assert_eq!(
+            summary(&result),
+            vec![("port".to_string(), ChangeKind::Changed)]
+        );
+        assert_eq!(result.changes()[0].old, Some(ConfigValue::Integer(9090)));
+    }
+
+    #[test]
+    fn test_unified_and_json_output() {
+        let old = map(r#"{"port": "8080", "gone": 1}"#);
+        let new = map(r#"{"port": 8080, "new": [1]}"#);
+
+        let result = diff(&old, &new);
+
+        assert_eq!(
+            result.to_unified("v41", "v42"),
+            "--- v41\n+++ v42\n-port=\"8080\"\n+port=8080\n-gone=1\n+new=[1]\n"
+        );
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json[0]["path"], "port");
+        assert_eq!(json[0]["kind"], "changed");
+        assert_eq!(json[1]["kind"], "removed");
+        assert!(json[1].get("new").is_none());
+    }
+}
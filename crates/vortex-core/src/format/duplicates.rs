@@ -0,0 +1,374 @@
+//! Detection of duplicate keys in configuration source text.
+//!
+//! Parsing into a [`ConfigMap`](crate::ConfigMap) keeps the last value of a
+//! repeated key, so two `datasource:` blocks in one file silently shadow each
+//! other. [`find_duplicate_keys`] scans the source text instead and reports
+//! every repeated key with the lines of both occurrences.
+//!
+//! # Example
+//!
+//! ```
+//! use vortex_core::format::ConfigFormat;
+//! use vortex_core::format::duplicates::find_duplicate_keys;
+//!
+//! let yaml = "datasource:\n  url: a\nport: 80\ndatasource:\n  url: b\n";
+//! let duplicates = find_duplicate_keys(ConfigFormat::Yaml, yaml).unwrap();
+//!
+//! assert_eq!(duplicates[0].path.to_string(), "datasource");
+//! assert_eq!((duplicates[0].first_line, duplicates[0].line), (1, 4));
+//! ```
+
+use std::collections::HashMap;
+
+use yaml_rust2::parser::{Event, Parser};
+
+use crate::config::ConfigPath;
+use crate::error::{Result, VortexError};
+use crate::format::ConfigFormat;
+
+/// A key defined more than once in the same mapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKey {
+    /// Full path of the repeated key.
+    pub path: ConfigPath,
+    /// Line (1-based) of the repeated definition.
+    pub line: usize,
+    /// Line (1-based) of the first definition.
+    pub first_line: usize,
+}
+
+/// Finds keys defined more than once in `input`.
+///
+/// YAML and JSON report keys repeated within the same mapping; properties,
+/// INI and dotenv report keys repeated within the file (or INI section). TOML
+/// rejects duplicates while parsing and HOCON merges them by design, so both
+/// always return an empty list.
+///
+/// # Errors
+///
+/// Returns a parse error if YAML or JSON input is not well formed.
+pub fn find_duplicate_keys(format: ConfigFormat, input: &str) -> Result<Vec<DuplicateKey>> {
+    match format {
+        ConfigFormat::Yaml | ConfigFormat::Json => scan_yaml(format, input),
+        ConfigFormat::Properties => Ok(scan_lines(input, LineSyntax::Properties)),
+        ConfigFormat::Ini => Ok(scan_lines(input, LineSyntax::Ini)),
+        ConfigFormat::Env => Ok(scan_lines(input, LineSyntax::Env)),
+        ConfigFormat::Toml | ConfigFormat::Hocon => Ok(Vec::new()),
+    }
+}
+
+/// Open collection while walking YAML events.
+enum Frame {
+    Mapping {
+        /// Key name to the line of its first definition.
+        keys: HashMap<String, usize>,
+        /// True when the next node is a key.
+        expecting_key: bool,
+    },
+    Sequence {
+        next_index: usize,
+    },
+}
+
+/// Walks YAML (and JSON, a subset of YAML) events keeping the current path.
+fn scan_yaml(format: ConfigFormat, input: &str) -> Result<Vec<DuplicateKey>> {
+    let mut parser = Parser::new_from_str(input);
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut path = ConfigPath::root();
+    let mut duplicates = Vec::new();
+
+    loop {
+        let (event, marker) = parser
+            .next_token()
+            .map_err(|e| VortexError::parse_error(format.name(), e.to_string()))?;
+        let line = marker.line();
+
+        match event {
+            Event::StreamEnd => break,
+            Event::DocumentStart => {
+                frames.clear();
+                path = ConfigPath::root();
+            },
+            Event::Scalar(value, ..) if expecting_key(&frames) => {
+                if let Some(Frame::Mapping {
+                    keys,
+                    expecting_key,
+                }) = frames.last_mut()
+                {
+                    let key_path = path.clone().key(value.as_str());
+                    match keys.get(&value) {
+                        Some(&first_line) => duplicates.push(DuplicateKey {
+                            path: key_path,
+                            line,
+                            first_line,
+                        }),
+                        None => {
+                            keys.insert(value.clone(), line);
+                        },
+                    }
+                    *expecting_key = false;
+                    path.push_key(value);
+                }
+            },
+            Event::Scalar(..) | Event::Alias(_) => end_value(&mut frames, &mut path),
+            Event::MappingStart(..) | Event::SequenceStart(..) => {
+                if expecting_key(&frames) {
+                    // Complex keys are not representable in a ConfigMap.
+                    return Err(VortexError::parse_error(
+                        format.name(),
+                        format!("unsupported complex mapping key at line {}", line),
+                    ));
+                }
+                if let Some(Frame::Sequence { next_index }) = frames.last() {
+                    path.push_index(*next_index);
+                }
+                frames.push(match event {
+                    Event::MappingStart(..) => Frame::Mapping {
+                        keys: HashMap::new(),
+                        expecting_key: true,
+                    },
+                    _ => Frame::Sequence { next_index: 0 },
+                });
+            },
+            Event::MappingEnd | Event::SequenceEnd => {
+                frames.pop();
+                end_value(&mut frames, &mut path);
+            },
+            _ => {},
+        }
+    }
+
+    Ok(duplicates)
+}
+
+fn expecting_key(frames: &[Frame]) -> bool {
+    matches!(
+        frames.last(),
+        Some(Frame::Mapping {
+            expecting_key: true,
+            ..
+        })
+    )
+}
+
+/// Records that a value finished in the innermost collection.
+fn end_value(frames: &mut [Frame], path: &mut ConfigPath) {
+    match frames.last_mut() {
+        Some(Frame::Mapping { expecting_key, .. }) => {
+            *expecting_key = true;
+            path.pop();
+        },
+        Some(Frame::Sequence { next_index }) => {
+            *next_index += 1;
+            path.pop();
+        },
+        None => {},
+    }
+}
+
+#[derive(Clone, Copy)]
+enum LineSyntax {
+    Properties,
+    Ini,
+    Env,
+}
+
+/// Scans line-oriented formats for repeated keys.
+fn scan_lines(input: &str, syntax: LineSyntax) -> Vec<DuplicateKey> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut duplicates = Vec::new();
+    let mut section = String::new();
+    let mut continuation = false;
+
+    for (index, raw) in input.lines().enumerate() {
+        let line = index + 1;
+        let was_continuation = continuation;
+        continuation = matches!(syntax, LineSyntax::Properties) && ends_with_escape(raw);
+        if was_continuation {
+            continue;
+        }
+
+        let text = raw.trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+
+        let key = match syntax {
+            LineSyntax::Properties => {
+                if text.starts_with('!') {
+                    continue;
+                }
+                properties_key(text)
+            },
+            LineSyntax::Ini => {
+                if text.starts_with(';') {
+                    continue;
+                }
+                if let Some(name) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+                    section = name.trim().to_string();
+                    continue;
+                }
+                let key = text.split(['=', ':']).next().unwrap_or("").trim();
+                if section.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}.{}", section, key)
+                }
+            },
+            LineSyntax::Env => {
+                let text = text.strip_prefix("export ").unwrap_or(text);
+                match text.split_once('=') {
+                    Some((key, _)) => key.trim().to_string(),
+                    None => continue,
+                }
+            },
+        };
+
+        if key.is_empty() {
+            continue;
+        }
+
+        match seen.get(&key) {
+            Some(&first_line) => duplicates.push(DuplicateKey {
+                path: ConfigPath::parse(&key).unwrap_or_else(|_| ConfigPath::root().key(&key)),
+                line,
+                first_line,
+            }),
+            None => {
+                seen.insert(key, line);
+            },
+        }
+    }
+
+    duplicates
+}
+
+/// Extracts the key of a properties line: up to the first unescaped `=`,
+/// `:` or whitespace, with escapes removed.
+fn properties_key(line: &str) -> String {
+    let mut key = String::new();
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    key.push(escaped);
+                }
+            },
+            '=' | ':' => break,
+            c if c.is_whitespace() => break,
+            c => key.push(c),
+        }
+    }
+
+    key
+}
+
+/// True if the line ends with an odd number of backslashes.
+fn ends_with_escape(line: &str) -> bool {
+    line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(format: ConfigFormat, input: &str) -> Vec<(String, usize, usize)> {
+        find_duplicate_keys(format, input)
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.path.to_string(), d.first_line, d.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_yaml_duplicates_by_mapping() {
+        let yaml = "\
+server:
+  port: 80
+  host: a
+  port: 81
+clients:
+  - name: a
+    name: b
+  - name: c
+server:
+  ssl: true
+";
+
+        assert_eq!(
+            found(ConfigFormat::Yaml, yaml),
+            vec![
+                ("server.port".to_string(), 2, 4),
+                ("clients[0].name".to_string(), 6, 7),
+                ("server".to_string(), 1, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_yaml_same_key_in_different_mappings_is_fine() {
+        let yaml = "a:\n  name: x\nb:\n  name: y\nlist: [1, 1]\n";
+
+        assert!(found(ConfigFormat::Yaml, yaml).is_empty());
+    }
+
+    #[test]
+    fn test_yaml_documents_are_independent() {
+        let yaml = "a: 1\n---\na: 2\n";
+
+        assert!(found(ConfigFormat::Yaml, yaml).is_empty());
+    }
+
+    #[test]
+    fn test_json_duplicates() {
+        let json = "{\n  \"db\": {\"url\": \"a\"},\n  \"db\": {\"url\": \"b\"}\n}";
+
+        assert_eq!(
+            found(ConfigFormat::Json, json),
+            vec![("db".to_string(), 2, 3)]
+        );
+    }
+
+    #[test]
+    fn test_malformed_yaml_is_error() {
+        assert!(find_duplicate_keys(ConfigFormat::Yaml, "a: [1, 2").is_err());
+    }
+
+    #[test]
+    fn test_properties_duplicates() {
+        let props = "\
+# comment
+server.port=80
+long.value=a \\
+  server.port=ignored
+server.port : 81
+key\\=with\\=equals=1
+key\\=with\\=equals=2
+";
+
+        assert_eq!(
+            found(ConfigFormat::Properties, props),
+            vec![
+                ("server.port".to_string(), 2, 5),
+                ("key=with=equals".to_string(), 6, 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ini_and_env_duplicates() {
+        let ini = "[db]\nurl=a\n[cache]\nurl=b\n[db]\nurl=c\n";
+        assert_eq!(
+            found(ConfigFormat::Ini, ini),
+            vec![("db.url".to_string(), 2, 6)]
+        );
+
+        let env = "PORT=1\nexport PORT=2\nHOST=a\n";
+        assert_eq!(
+            found(ConfigFormat::Env, env),
+            vec![("PORT".to_string(), 1, 2)]
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use crate::config::{ConfigMap, ConfigValue};
+use crate::error::{Result, VortexError};
+use crate::format::properties::insert_nested;
+use crate::format::spring::flatten_config_map;
+use crate::format::{FormatParser, FormatSerializer};
+
+/// Dotenv (`.env`) format, as read by Docker/Compose `env_file`.
+///
+/// Both directions follow Spring's relaxed binding: parsing maps variable
+/// names to property names (`SERVER_PORT` becomes `server.port`) so they
+/// merge with YAML and properties files, and serialization flattens the
+/// config back to variable names.
+pub struct EnvFormat;
+
+impl FormatParser for EnvFormat {
+    fn parse(&self, input: &str) -> Result<ConfigMap> {
+        let mut config = ConfigMap::new();
+
+        for (line_num, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(VortexError::parse_error(
+                    "env",
+                    format!("Invalid syntax at line {}: missing '='", line_num + 1),
+                ));
+            };
+
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(VortexError::parse_error(
+                    "env",
+                    format!("Empty variable name at line {}", line_num + 1),
+                ));
+            }
+
+            let value = parse_value(value.trim()).map_err(|reason| {
+                VortexError::parse_error("env", format!("{} at line {}", reason, line_num + 1))
+            })?;
+            insert_nested(&mut config, &property_name(key), &value);
+        }
+
+        Ok(config)
+    }
+}
+
+impl FormatSerializer for EnvFormat {
+    fn serialize(&self, config: &ConfigMap) -> Result<String> {
+        let mut output = String::new();
+
+        for (key, value) in flatten_config_map(config) {
+            output.push_str(&format!(
+                "{}={}\n",
+                to_env_name(&key),
+                quote(&scalar(&value))
+            ));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Maps a dotted property key to an environment variable name.
+///
+/// Follows Spring Boot's relaxed binding: `.` becomes `_`, `-` is removed and
+/// the result is upper-cased (`my-app.server.port` -> `MYAPP_SERVER_PORT`).
+/// Brackets separate like dots (`hosts[0].name` -> `HOSTS_0_NAME`).
+pub fn to_env_name(key: &str) -> String {
+    let mut name = String::with_capacity(key.len());
+    for c in key.chars().filter(|c| *c != '-') {
+        match c {
+            '.' | '[' | ']' => {
+                if !name.is_empty() && !name.ends_with('_') {
+                    name.push('_');
+                }
+            },
+            c => name.push(c.to_ascii_uppercase()),
+        }
+    }
+    let len = name.trim_end_matches('_').len();
+    name.truncate(len);
+    name
+}
+
+/// Maps an environment variable name to a property name.
+///
+/// A name without lowercase letters is lowercased, `_` becomes `.`, and
+/// numeric parts become list indexes (`HOSTS_0_NAME` is `hosts[0].name`).
+/// Other names are property names already.
+pub fn property_name(key: &str) -> String {
+    if key.chars().any(|c| c.is_lowercase()) {
+        return key.to_string();
+    }
+
+    let mut name = String::new();
+    for part in key.split('_').filter(|p| !p.is_empty()) {
+        if !name.is_empty() && part.chars().all(|c| c.is_ascii_digit()) {
+            name.push('[');
+            name.push_str(part);
+            name.push(']');
+        } else {
+            if !name.is_empty() {
+                name.push('.');
+            }
+            name.push_str(&part.to_lowercase());
+        }
+    }
+    name
+}
+
+fn scalar(value: &ConfigValue) -> String {
+    match value {
+        ConfigValue::Null => String::new(),
+        ConfigValue::Bool(b) => b.to_string(),
+        ConfigValue::Integer(i) => i.to_string(),
+        ConfigValue::Float(f) => f.to_string(),
+        ConfigValue::String(s) | ConfigValue::DateTime(s) => s.clone(),
+        // Comma-separated lists bind to collections in Spring.
+        ConfigValue::Array(arr) => arr.iter().map(scalar).collect::<Vec<_>>().join(","),
+        ConfigValue::Object(_) => serde_json::to_string(value).unwrap_or_default(),
+    }
+}
+
+/// Double-quotes values that would otherwise be altered by a dotenv reader.
+fn quote(value: &str) -> String {
+    let needs_quotes = value
+        .chars()
+        .any(|c| c.is_whitespace() || matches!(c, '#' | '"' | '\'' | '\\' | '$'));
+
+    if !needs_quotes {
+        return value.to_string();
+    }
+
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t");
+    format!("\"{}\"", escaped)
+}
+
+fn parse_value(raw: &str) -> std::result::Result<String, &'static str> {
+    if let Some(rest) = raw.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Ok(value),
+                '\\' => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some(other) => value.push(other),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        return Err("Unterminated double-quoted value");
+    }
+
+    if let Some(rest) = raw.strip_prefix('\'') {
+        return rest
+            .split_once('\'')
+            .map(|(value, _)| value.to_string())
+            .ok_or("Unterminated single-quoted value");
+    }
+
+    // Unquoted: an inline comment starts at " #".
+    let value = raw.split_once(" #").map_or(raw, |(value, _)| value);
+    Ok(value.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env() {
+        let input = r#"
+        # Database
+        DB_HOST=localhost
+        export DB_PORT=5432
+        GREETING="Hello\nWorld"
+        LITERAL='no $expansion here'
+        WITH_COMMENT=value # trailing
+        EMPTY=
+        "#;
+
+        let config = EnvFormat.parse(input).unwrap();
+
+        assert_eq!(config.get("db.host").unwrap().as_str(), Some("localhost"));
+        assert_eq!(config.get("db.port").unwrap().as_str(), Some("5432"));
+        assert_eq!(
+            config.get("greeting").unwrap().as_str(),
+            Some("Hello\nWorld")
+        );
+        assert_eq!(
+            config.get("literal").unwrap().as_str(),
+            Some("no $expansion here")
+        );
+        assert_eq!(config.get("with.comment").unwrap().as_str(), Some("value"));
+        assert_eq!(config.get("empty").unwrap().as_str(), Some(""));
+    }
+
+    #[test]
+    fn test_parse_binds_property_names() {
+        let env = EnvFormat
+            .parse("SERVER_PORT=8080\nHOSTS_0_NAME=a\nfeatureFlags=on\n")
+            .unwrap();
+
+        let mut yaml =
+            ConfigMap::from_json(r#"{"server": {"port": 80, "address": "0.0.0.0"}}"#).unwrap();
+        crate::merge::deep_merge(&mut yaml, &env);
+
+        assert_eq!(yaml.get("server.port").unwrap().as_str(), Some("8080"));
+        assert_eq!(
+            yaml.get("server.address").unwrap().as_str(),
+            Some("0.0.0.0")
+        );
+        assert_eq!(yaml.get("hosts[0].name").unwrap().as_str(), Some("a"));
+        assert_eq!(yaml.get("featureFlags").unwrap().as_str(), Some("on"));
+    }
+
+    #[test]
+    fn test_property_name() {
+        assert_eq!(property_name("SERVER_PORT"), "server.port");
+        assert_eq!(property_name("HOSTS_0_NAME"), "hosts[0].name");
+        assert_eq!(property_name("LOG__LEVEL"), "log.level");
+        assert_eq!(
+            property_name("server.max-http-header-size"),
+            "server.max-http-header-size"
+        );
+        assert_eq!(property_name("featureFlags"), "featureFlags");
+    }
+
+    #[test]
+    fn test_parse_invalid_env() {
+        assert!(EnvFormat.parse("NO_SEPARATOR").is_err());
+        assert!(EnvFormat.parse("KEY=\"unterminated").is_err());
+    }
+
+    #[test]
+    fn test_serialize_env() {
+        let json = r#"{
+            "server": {"port": 8080, "context-path": "/api"},
+            "app": {"greeting": "Hello World", "tags": ["a", "b"]}
+        }"#;
+        let config = ConfigMap::from_json(json).unwrap();
+
+        let output = EnvFormat.serialize(&config).unwrap();
+
+        assert!(output.contains("SERVER_PORT=8080\n"));
+        assert!(output.contains("SERVER_CONTEXTPATH=/api\n"));
+        assert!(output.contains("APP_GREETING=\"Hello World\"\n"));
+        assert!(output.contains("APP_TAGS=a,b\n"));
+    }
+
+    #[test]
+    fn test_serialize_dotted_yaml_keys() {
+        let yaml = "spring.datasource.url: jdbc:x\nlogging.level:\n  com.acme: DEBUG\n";
+        let config = ConfigMap::from_yaml(yaml).unwrap();
+
+        let output = EnvFormat.serialize(&config).unwrap();
+
+        assert_eq!(
+            output,
+            "SPRING_DATASOURCE_URL=jdbc:x\nLOGGING_LEVEL_COM_ACME=DEBUG\n"
+        );
+    }
+
+    #[test]
+    fn test_to_env_name() {
+        assert_eq!(to_env_name("my-app.server.port"), "MYAPP_SERVER_PORT");
+        assert_eq!(to_env_name("hosts[0].name"), "HOSTS_0_NAME");
+        assert_eq!(
+            to_env_name("logging.level[com.acme]"),
+            "LOGGING_LEVEL_COM_ACME"
+        );
+        assert_eq!(
+            to_env_name("[spring.datasource.url]"),
+            "SPRING_DATASOURCE_URL"
+        );
+    }
+
+    #[test]
+    fn test_serialized_output_parses_back() {
+        let config = ConfigMap::from_json(r#"{"msg": "say \"hi\"\nnow"}"#).unwrap();
+
+        let output = EnvFormat.serialize(&config).unwrap();
+        let parsed = EnvFormat.parse(&output).unwrap();
+
+        assert_eq!(parsed.get("msg").unwrap().as_str(), Some("say \"hi\"\nnow"));
+    }
+}
@@ -0,0 +1,468 @@
+//! HOCON (Human-Optimized Config Object Notation) parser.
+//!
+//! Supports the subset of HOCON used in practice for application config:
+//! braces-optional root objects, `=`/`:` separators (optional before `{`),
+//! dotted key paths, quoted and triple-quoted strings, arrays, `#` and `//`
+//! comments, comma or newline separators, and object merging when the same
+//! key is defined twice. Substitutions (`${...}`) are kept verbatim as strings
+//! so the placeholder resolver can handle them later. `include` is rejected.
+
+use crate::config::{ConfigMap, ConfigValue};
+use crate::error::{Result, VortexError};
+use crate::format::FormatParser;
+use indexmap::IndexMap;
+use ordered_float::OrderedFloat;
+
+pub struct HoconFormat;
+
+impl FormatParser for HoconFormat {
+    fn parse(&self, input: &str) -> Result<ConfigMap> {
+        let mut parser = Parser::new(input);
+        let root = parser.parse_root()?;
+        Ok(ConfigMap::from_inner(root))
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            pos: 0,
+            line: 1,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> VortexError {
+        VortexError::parse_error("hocon", format!("{} at line {}", message.into(), self.line))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.peek_at(i) == Some(c))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn at_comment(&self) -> bool {
+        self.peek() == Some('#') || self.starts_with("//")
+    }
+
+    fn skip_comment(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    /// Skips spaces and tabs (not newlines).
+    fn skip_inline_ws(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace() && c != '\n') {
+            self.bump();
+        }
+    }
+
+    /// Skips whitespace, newlines, comments and, optionally, commas.
+    fn skip_separators(&mut self, commas: bool) {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                },
+                Some(',') if commas => {
+                    self.bump();
+                },
+                Some(_) if self.at_comment() => self.skip_comment(),
+                _ => break,
+            }
+        }
+    }
+
+    fn parse_root(&mut self) -> Result<IndexMap<String, ConfigValue>> {
+        self.skip_separators(false);
+        let root = if self.peek() == Some('{') {
+            self.bump();
+            let obj = self.parse_object_body(true)?;
+            self.skip_separators(false);
+            obj
+        } else {
+            self.parse_object_body(false)?
+        };
+
+        if self.peek().is_some() {
+            return Err(self.error("Unexpected content after root object"));
+        }
+
+        Ok(root)
+    }
+
+    /// Parses object fields until `}` (when `braced`) or end of input.
+    fn parse_object_body(&mut self, braced: bool) -> Result<IndexMap<String, ConfigValue>> {
+        let mut object = IndexMap::new();
+
+        loop {
+            self.skip_separators(true);
+
+            match self.peek() {
+                None if braced => return Err(self.error("Unclosed object, expected '}'")),
+                None => break,
+                Some('}') if braced => {
+                    self.bump();
+                    break;
+                },
+                Some('}') => return Err(self.error("Unexpected '}'")),
+                _ => {},
+            }
+
+            if self.starts_with("include") && self.is_include_directive() {
+                return Err(self.error("'include' directives are not supported"));
+            }
+
+            let path = self.parse_key_path()?;
+            self.skip_inline_ws();
+
+            let value = match self.peek() {
+                Some('{') => self.parse_value()?,
+                Some('=') | Some(':') => {
+                    self.bump();
+                    self.skip_inline_ws();
+                    self.parse_value()?
+                },
+                Some('+') if self.peek_at(1) == Some('=') => {
+                    return Err(self.error("'+=' is not supported"));
+                },
+                _ => {
+                    return Err(self.error(format!(
+                        "Expected '=', ':' or '{{' after key '{}'",
+                        path.join(".")
+                    )));
+                },
+            };
+
+            insert_path(&mut object, &path, value);
+        }
+
+        Ok(object)
+    }
+
+    fn is_include_directive(&self) -> bool {
+        matches!(self.peek_at("include".len()), Some(c) if c == ' ' || c == '\t')
+    }
+
+    fn parse_key_path(&mut self) -> Result<Vec<String>> {
+        let mut segments = Vec::new();
+
+        loop {
+            let segment = if self.peek() == Some('"') {
+                self.parse_quoted_string()?
+            } else {
+                let mut s = String::new();
+                while let Some(c) = self.peek() {
+                    if c.is_whitespace() || ".=:{}[],\"#+".contains(c) || self.starts_with("//") {
+                        break;
+                    }
+                    s.push(c);
+                    self.bump();
+                }
+                if s.is_empty() {
+                    return Err(self.error("Expected a key"));
+                }
+                s
+            };
+            segments.push(segment);
+
+            if self.peek() == Some('.') {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+
+        Ok(segments)
+    }
+
+    fn parse_value(&mut self) -> Result<ConfigValue> {
+        match self.peek() {
+            Some('{') => {
+                self.bump();
+                Ok(ConfigValue::Object(self.parse_object_body(true)?))
+            },
+            Some('[') => {
+                self.bump();
+                self.parse_array()
+            },
+            Some('"') if self.starts_with("\"\"\"") => {
+                Ok(ConfigValue::String(self.parse_triple_quoted_string()?))
+            },
+            Some('"') => Ok(ConfigValue::String(self.parse_quoted_string()?)),
+            Some(_) => self.parse_unquoted(),
+            None => Err(self.error("Expected a value")),
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<ConfigValue> {
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_separators(true);
+            match self.peek() {
+                Some(']') => {
+                    self.bump();
+                    break;
+                },
+                None => return Err(self.error("Unclosed array, expected ']'")),
+                _ => items.push(self.parse_value()?),
+            }
+        }
+
+        Ok(ConfigValue::Array(items))
+    }
+
+    fn parse_quoted_string(&mut self) -> Result<String> {
+        self.bump(); // opening quote
+        let mut s = String::new();
+
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.bump() {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some(c @ ('"' | '\\' | '/')) => s.push(c),
+                    Some(c) => return Err(self.error(format!("Invalid escape '\\{}'", c))),
+                    None => return Err(self.error("Unterminated string")),
+                },
+                Some('\n') | None => return Err(self.error("Unterminated string")),
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn parse_triple_quoted_string(&mut self) -> Result<String> {
+        for _ in 0..3 {
+            self.bump();
+        }
+        let mut s = String::new();
+
+        loop {
+            // Quotes beyond the closing three belong to the string.
+            if self.starts_with("\"\"\"\"") {
+                s.push('"');
+                self.bump();
+                continue;
+            }
+            if self.starts_with("\"\"\"") {
+                for _ in 0..3 {
+                    self.bump();
+                }
+                return Ok(s);
+            }
+            match self.bump() {
+                Some(c) => s.push(c),
+                None => return Err(self.error("Unterminated multi-line string")),
+            }
+        }
+    }
+
+    /// Parses an unquoted scalar up to the end of the line or a delimiter.
+    fn parse_unquoted(&mut self) -> Result<ConfigValue> {
+        let mut raw = String::new();
+
+        while let Some(c) = self.peek() {
+            if c == '\n' || ",}]#".contains(c) || self.starts_with("//") {
+                break;
+            }
+            // Substitutions may contain characters that would otherwise terminate the value.
+            if self.starts_with("${") {
+                while let Some(c) = self.bump() {
+                    raw.push(c);
+                    if c == '}' {
+                        break;
+                    }
+                }
+                continue;
+            }
+            raw.push(c);
+            self.bump();
+        }
+
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Err(self.error("Expected a value"));
+        }
+
+        Ok(scalar_from_str(raw))
+    }
+}
+
+fn scalar_from_str(raw: &str) -> ConfigValue {
+    match raw {
+        "true" => ConfigValue::Bool(true),
+        "false" => ConfigValue::Bool(false),
+        "null" => ConfigValue::Null,
+        _ => {
+            if let Ok(i) = raw.parse::<i64>() {
+                ConfigValue::Integer(i)
+            } else if let Ok(f) = raw.parse::<f64>()
+                && raw
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_digit() || c == '-')
+            {
+                ConfigValue::Float(OrderedFloat(f))
+            } else {
+                ConfigValue::String(raw.to_string())
+            }
+        },
+    }
+}
+
+/// Inserts a value at a key path, merging objects defined more than once.
+fn insert_path(object: &mut IndexMap<String, ConfigValue>, path: &[String], value: ConfigValue) {
+    let (first, rest) = path.split_first().expect("key path is never empty");
+
+    if rest.is_empty() {
+        match (object.get_mut(first), value) {
+            (Some(ConfigValue::Object(existing)), ConfigValue::Object(new)) => {
+                for (k, v) in new {
+                    insert_path(existing, std::slice::from_ref(&k), v);
+                }
+            },
+            (_, value) => {
+                object.insert(first.clone(), value);
+            },
+        }
+        return;
+    }
+
+    let child = object
+        .entry(first.clone())
+        .or_insert_with(|| ConfigValue::Object(IndexMap::new()));
+    if !matches!(child, ConfigValue::Object(_)) {
+        *child = ConfigValue::Object(IndexMap::new());
+    }
+    if let ConfigValue::Object(map) = child {
+        insert_path(map, rest, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_basic_hocon() {
+        let input = r#"
+        # Server settings
+        server {
+            port = 8080
+            host: "localhost"
+            ssl.enabled = true
+        }
+        app.name = My App   // trailing comment
+        app.ratio = 0.5
+        "#;
+
+        let config = HoconFormat.parse(input).unwrap();
+
+        assert_eq!(config.get("server.port").unwrap().as_i64(), Some(8080));
+        assert_eq!(
+            config.get("server.host").unwrap().as_str(),
+            Some("localhost")
+        );
+        assert_eq!(
+            config.get("server.ssl.enabled").unwrap().as_bool(),
+            Some(true)
+        );
+        assert_eq!(config.get("app.name").unwrap().as_str(), Some("My App"));
+        assert_eq!(config.get("app.ratio").unwrap().as_f64(), Some(0.5));
+    }
+
+    #[test]
+    fn test_parse_braced_root_and_arrays() {
+        let input = r#"{
+            "hosts": ["a", "b",
+                      "c"]
+            ports = [1, 2, 3], empty = []
+        }"#;
+
+        let config = HoconFormat.parse(input).unwrap();
+
+        assert_eq!(config.get("hosts").unwrap().as_array().unwrap().len(), 3);
+        assert_eq!(config.get("ports").unwrap().as_array().unwrap().len(), 3);
+        assert!(config.get("empty").unwrap().as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_objects_are_merged() {
+        let input = r#"
+        db { url = "jdbc:x", pool = 5 }
+        db { pool = 10 }
+        db.user = admin
+        "#;
+
+        let config = HoconFormat.parse(input).unwrap();
+
+        assert_eq!(config.get("db.url").unwrap().as_str(), Some("jdbc:x"));
+        assert_eq!(config.get("db.pool").unwrap().as_i64(), Some(10));
+        assert_eq!(config.get("db.user").unwrap().as_str(), Some("admin"));
+    }
+
+    #[test]
+    fn test_substitutions_kept_verbatim() {
+        let input = "url = ${DB_HOST}\nfallback = ${?DB_PORT}\n";
+
+        let config = HoconFormat.parse(input).unwrap();
+
+        assert_eq!(config.get("url").unwrap().as_str(), Some("${DB_HOST}"));
+        assert_eq!(
+            config.get("fallback").unwrap().as_str(),
+            Some("${?DB_PORT}")
+        );
+    }
+
+    #[test]
+    fn test_triple_quoted_string() {
+        let input = "text = \"\"\"line one\nline \"two\"\"\"\"\n";
+
+        let config = HoconFormat.parse(input).unwrap();
+
+        assert_eq!(
+            config.get("text").unwrap().as_str(),
+            Some("line one\nline \"two\"")
+        );
+    }
+
+    #[test]
+    fn test_errors_report_line() {
+        let err = HoconFormat.parse("a = 1\nb {\n c = 2\n").unwrap_err();
+        assert!(err.to_string().contains("line 4"));
+
+        let err = HoconFormat.parse("include \"other.conf\"").unwrap_err();
+        assert!(err.to_string().contains("include"));
+    }
+}
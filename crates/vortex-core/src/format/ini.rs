@@ -0,0 +1,103 @@
+use crate::config::ConfigMap;
+use crate::error::{Result, VortexError};
+use crate::format::FormatParser;
+use crate::format::properties::insert_nested;
+
+/// INI file parser.
+///
+/// `[section]` headers become nested objects (`[a.b]` nests twice), keys
+/// before the first header go to the root. Values are kept as strings, like
+/// the properties format, with surrounding quotes removed.
+pub struct IniFormat;
+
+impl FormatParser for IniFormat {
+    fn parse(&self, input: &str) -> Result<ConfigMap> {
+        let mut config = ConfigMap::new();
+        let mut section: Option<String> = None;
+
+        for (line_num, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let name = header.strip_suffix(']').map(str::trim).ok_or_else(|| {
+                    VortexError::parse_error(
+                        "ini",
+                        format!("Invalid section header at line {}", line_num + 1),
+                    )
+                })?;
+                section = (!name.is_empty()).then(|| name.to_string());
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once(['=', ':']) else {
+                return Err(VortexError::parse_error(
+                    "ini",
+                    format!("Invalid syntax at line {}: missing separator", line_num + 1),
+                ));
+            };
+
+            let key = match &section {
+                Some(section) => format!("{}.{}", section, key.trim()),
+                None => key.trim().to_string(),
+            };
+            insert_nested(&mut config, &key, unquote(value.trim()));
+        }
+
+        Ok(config)
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ini() {
+        let input = r#"
+        ; global settings
+        name = payments
+
+        [server]
+        port = 8080
+        host = "0.0.0.0"
+
+        [database.primary]
+        url: jdbc:postgresql://db/payments
+        "#;
+
+        let config = IniFormat.parse(input).unwrap();
+
+        assert_eq!(config.get("name").unwrap().as_str(), Some("payments"));
+        assert_eq!(config.get("server.port").unwrap().as_str(), Some("8080"));
+        assert_eq!(config.get("server.host").unwrap().as_str(), Some("0.0.0.0"));
+        assert_eq!(
+            config.get("database.primary.url").unwrap().as_str(),
+            Some("jdbc:postgresql://db/payments")
+        );
+        assert!(
+            config
+                .get("database.primary")
+                .unwrap()
+                .as_object()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_ini() {
+        assert!(IniFormat.parse("[server\nport=1").is_err());
+        assert!(IniFormat.parse("[server]\nport").is_err());
+    }
+}
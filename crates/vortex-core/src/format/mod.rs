@@ -1,36 +1,152 @@
+use std::path::Path;
+
 use crate::config::ConfigMap;
 use crate::error::Result;
 
+pub mod duplicates;
+pub mod env;
+pub mod hocon;
+pub mod ini;
 pub mod json;
 pub mod properties;
+pub mod registry;
 pub mod spring;
+pub mod toml;
 pub mod yaml;
 
-/// Supported configuration formats.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub use registry::{FormatEntry, FormatRegistry};
+
+/// Key of an object that includes configuration fragments.
+///
+/// `{"$ref": "shared/kafka.yml"}` (or a list of paths) stands for the
+/// contents of the named file. YAML's `!include path` and the properties
+/// `@include path` directive parse to the same object. Parsers keep it as
+/// is; readers that can see the other files, such as the Git resolver,
+/// replace it with the fragment. A `$ref` that is not a file path, such as
+/// the JSON pointer `#/components/schemas/Order`, is not an include.
+pub const REF_KEY: &str = "$ref";
+
+/// Built-in configuration formats.
+///
+/// These identify the formats that ship with Vortex. Lookups by extension or
+/// MIME type, including custom formats, go through [`FormatRegistry`], which is
+/// pre-populated from this enum by [`FormatRegistry::builtin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ConfigFormat {
-    Json,
+    /// YAML format (.yml, .yaml)
     Yaml,
+    /// JSON format (.json)
+    Json,
+    /// Java Properties format (.properties)
     Properties,
+    /// TOML format (.toml)
+    Toml,
+    /// HOCON format (.conf, .hocon)
+    Hocon,
+    /// INI format (.ini)
+    Ini,
+    /// Dotenv format (.env)
+    Env,
 }
 
 impl ConfigFormat {
-    /// Returns the file extensions associated with this format.
-    pub fn extensions(&self) -> &[&str] {
+    /// Returns the registry name of this format (e.g. `"yaml"`).
+    pub fn name(&self) -> &'static str {
         match self {
-            ConfigFormat::Json => &["json"],
-            ConfigFormat::Yaml => &["yaml", "yml"],
-            ConfigFormat::Properties => &["properties"],
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+            Self::Properties => "properties",
+            Self::Toml => "toml",
+            Self::Hocon => "hocon",
+            Self::Ini => "ini",
+            Self::Env => "env",
         }
     }
 
+    /// Looks up a built-in format by its registry name (e.g. `"yaml"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|format| format.name() == name)
+    }
+
+    /// Detects the format from a file path based on extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+    }
+
     /// Guesses the format from a file extension (without dot).
     pub fn from_extension(ext: &str) -> Option<Self> {
-        match ext.to_lowercase().as_str() {
-            "json" => Some(ConfigFormat::Json),
-            "yaml" | "yml" => Some(ConfigFormat::Yaml),
-            "properties" => Some(ConfigFormat::Properties),
-            _ => None,
+        let ext = ext.to_lowercase();
+        Self::all()
+            .iter()
+            .copied()
+            .find(|format| format.extensions().contains(&ext.as_str()))
+    }
+
+    /// Returns the primary file extension for this format.
+    pub fn extension(&self) -> &'static str {
+        self.extensions()[0]
+    }
+
+    /// Returns all file extensions for this format, primary first.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Self::Yaml => &["yml", "yaml"],
+            Self::Json => &["json"],
+            Self::Properties => &["properties"],
+            Self::Toml => &["toml"],
+            Self::Hocon => &["conf", "hocon"],
+            Self::Ini => &["ini"],
+            Self::Env => &["env"],
+        }
+    }
+
+    /// Returns the primary MIME type for this format.
+    pub fn mime_type(&self) -> &'static str {
+        self.mime_types()[0]
+    }
+
+    /// Returns all MIME types accepted for this format, primary first.
+    pub fn mime_types(&self) -> &'static [&'static str] {
+        match self {
+            Self::Yaml => &["application/x-yaml", "application/yaml", "text/yaml"],
+            Self::Json => &["application/json"],
+            Self::Properties => &["text/plain"],
+            Self::Toml => &["application/toml"],
+            Self::Hocon => &["application/hocon"],
+            Self::Ini => &["text/x-ini"],
+            Self::Env => &["text/x-env"],
+        }
+    }
+
+    /// Returns all built-in formats, in file resolution order.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::Yaml,
+            Self::Json,
+            Self::Properties,
+            Self::Toml,
+            Self::Hocon,
+            Self::Ini,
+            Self::Env,
+        ]
+    }
+}
+
+impl std::fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Yaml => write!(f, "YAML"),
+            Self::Json => write!(f, "JSON"),
+            Self::Properties => write!(f, "Properties"),
+            Self::Toml => write!(f, "TOML"),
+            Self::Hocon => write!(f, "HOCON"),
+            Self::Ini => write!(f, "INI"),
+            Self::Env => write!(f, "Dotenv"),
         }
     }
 }
@@ -46,3 +162,98 @@ pub trait FormatSerializer: Send + Sync {
     /// Serializes the ConfigMap into a string.
     fn serialize(&self, config: &ConfigMap) -> Result<String>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.yml")),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.yaml")),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.json")),
+            Some(ConfigFormat::Json)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.properties")),
+            Some(ConfigFormat::Properties)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.toml")),
+            Some(ConfigFormat::Toml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("application.conf")),
+            Some(ConfigFormat::Hocon)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.ini")),
+            Some(ConfigFormat::Ini)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("myapp-prod.env")),
+            Some(ConfigFormat::Env)
+        );
+        assert_eq!(ConfigFormat::from_path(Path::new("config.txt")), None);
+        assert_eq!(ConfigFormat::from_path(Path::new("config")), None);
+    }
+
+    #[test]
+    fn test_from_extension() {
+        assert_eq!(
+            ConfigFormat::from_extension("yml"),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(
+            ConfigFormat::from_extension("YML"),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(
+            ConfigFormat::from_extension("json"),
+            Some(ConfigFormat::Json)
+        );
+        assert_eq!(
+            ConfigFormat::from_extension("properties"),
+            Some(ConfigFormat::Properties)
+        );
+        assert_eq!(ConfigFormat::from_extension("txt"), None);
+    }
+
+    #[test]
+    fn test_from_name() {
+        for format in ConfigFormat::all() {
+            assert_eq!(ConfigFormat::from_name(format.name()), Some(*format));
+        }
+        assert_eq!(ConfigFormat::from_name("yml"), None);
+    }
+
+    #[test]
+    fn test_extension() {
+        assert_eq!(ConfigFormat::Yaml.extension(), "yml");
+        assert_eq!(ConfigFormat::Json.extension(), "json");
+        assert_eq!(ConfigFormat::Properties.extension(), "properties");
+        assert_eq!(ConfigFormat::Toml.extension(), "toml");
+    }
+
+    #[test]
+    fn test_extensions() {
+        assert_eq!(ConfigFormat::Yaml.extensions(), &["yml", "yaml"]);
+        assert_eq!(ConfigFormat::Json.extensions(), &["json"]);
+        assert_eq!(ConfigFormat::Properties.extensions(), &["properties"]);
+    }
+
+    #[test]
+    fn test_mime_type() {
+        assert_eq!(ConfigFormat::Yaml.mime_type(), "application/x-yaml");
+        assert_eq!(ConfigFormat::Json.mime_type(), "application/json");
+        assert_eq!(ConfigFormat::Properties.mime_type(), "text/plain");
+        assert_eq!(ConfigFormat::Toml.mime_type(), "application/toml");
+    }
+}
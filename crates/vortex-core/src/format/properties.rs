@@ -1,13 +1,20 @@
-use crate::config::{ConfigMap, ConfigValue};
+use crate::config::{ConfigMap, ConfigPath, ConfigValue};
 use crate::error::{Result, VortexError};
-use crate::format::{FormatParser, FormatSerializer};
-use indexmap::IndexMap;
+use crate::format::{FormatParser, FormatSerializer, REF_KEY};
 
+/// Directive including a fragment: `@include shared/kafka.properties`.
+const INCLUDE_DIRECTIVE: &str = "@include";
+
+/// Java properties format.
+///
+/// `@include path` lines are collected, in order, into a top-level
+/// [`REF_KEY`] list.
 pub struct PropertiesFormat;
 
 impl FormatParser for PropertiesFormat {
     fn parse(&self, input: &str) -> Result<ConfigMap> {
-        let mut root = IndexMap::new();
+        let mut config = ConfigMap::new();
+        let mut includes = Vec::new();
 
         for (line_num, line) in input.lines().enumerate() {
             let line = line.trim();
@@ -15,8 +22,22 @@ impl FormatParser for PropertiesFormat {
                 continue;
             }
 
+            if let Some(path) = line.strip_prefix(INCLUDE_DIRECTIVE)
+                && (path.is_empty() || path.starts_with(char::is_whitespace))
+            {
+                let path = path.trim();
+                if path.is_empty() {
+                    return Err(VortexError::parse_error(
+                        "properties",
+                        format!("Missing path after @include at line {}", line_num + 1),
+                    ));
+                }
+                includes.push(ConfigValue::String(path.to_string()));
+                continue;
+            }
+
             if let Some((key, value)) = split_property_line(line) {
-                insert_nested(&mut root, key.trim(), value.trim());
+                insert_nested(&mut config, key.trim(), value.trim());
             } else {
                 return Err(VortexError::parse_error(
                     "properties",
@@ -25,7 +46,11 @@ impl FormatParser for PropertiesFormat {
             }
         }
 
-        Ok(ConfigMap::from_inner(root))
+        if !includes.is_empty() {
+            config.insert(REF_KEY, ConfigValue::Array(includes));
+        }
+
+        Ok(config)
     }
 }
 
@@ -49,6 +74,7 @@ impl FormatSerializer for PropertiesFormat {
                 ConfigValue::Bool(b) => b.to_string(),
                 ConfigValue::Integer(i) => i.to_string(),
                 ConfigValue::Float(f) => f.to_string(),
+                ConfigValue::DateTime(dt) => dt,
                 // Arrays and Objects shouldn't happen if flattened correctly,
                 // but if an array is a leaf, we print it as string representation for now
                 // or just skip. Spring Properties handling of arrays is complex (indices).
@@ -68,49 +94,15 @@ fn split_property_line(line: &str) -> Option<(&str, &str)> {
     line.split_once(['=', ':'])
 }
 
-fn insert_nested(root: &mut IndexMap<String, ConfigValue>, key: &str, value: &str) {
-    if !key.contains('.') {
-        root.insert(key.to_string(), ConfigValue::String(value.to_string()));
-        return;
-    }
+/// Inserts a string value at a property key such as `servers[0].host`.
+///
+/// Keys that are not valid path expressions are stored verbatim.
+pub(crate) fn insert_nested(config: &mut ConfigMap, key: &str, value: &str) {
+    let value = ConfigValue::String(value.to_string());
 
-    let parts: Vec<&str> = key.split('.').collect();
-    let val = ConfigValue::String(value.to_string());
-
-    // Recursive insertion simulation using references
-    // This is tricky with Rust ownership.
-    // Easier approach: Recursive function or iterative pointer chase.
-
-    // Iterative approach to find/create the parent object
-    let mut current_map = root;
-
-    for (i, part) in parts.iter().enumerate() {
-        if i == parts.len() - 1 {
-            // Last part: insert value
-            current_map.insert(part.to_string(), val.clone());
-        } else {
-            // Intermediate part: ensure it exists and is an object
-            current_map
-                .entry(part.to_string())
-                .and_modify(|v| {
-                    if !matches!(v, ConfigValue::Object(_)) {
-                        // Conflict: key exists but is not an object.
-                        // In properties logic, last write usually wins or merges.
-                        // We overwrite with a new object to support the nesting.
-                        *v = ConfigValue::Object(IndexMap::new());
-                    }
-                })
-                .or_insert_with(|| ConfigValue::Object(IndexMap::new()));
-
-            // Move pointer down
-            // We need to re-get mutably to bypass borrow checker limitations with `entry` when moving deeper
-            // Unwrapping is safe because we just inserted/ensured it.
-            if let Some(ConfigValue::Object(next_map)) = current_map.get_mut(*part) {
-                current_map = next_map;
-            } else {
-                unreachable!("Should be an object");
-            }
-        }
+    match ConfigPath::parse(key) {
+        Ok(path) if config.set_path(&path, value.clone()).is_ok() => {},
+        _ => config.insert(key, value),
     }
 }
 
@@ -145,6 +137,52 @@ mod tests {
         assert_eq!(config.get("app.name").unwrap().as_str(), Some("Test App"));
     }
 
+    #[test]
+    fn test_parse_indexed_and_bracketed_keys() {
+        let input = "
+        servers[0].host=a
+        servers[1].host=b
+        logging.level[com.acme]=DEBUG
+        ";
+
+        let config = PropertiesFormat.parse(input).unwrap();
+
+        assert_eq!(config.get("servers").unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(config.get("servers[1].host").unwrap().as_str(), Some("b"));
+        assert_eq!(
+            config
+                .get("logging.level")
+                .unwrap()
+                .as_object()
+                .unwrap()
+                .get("com.acme")
+                .unwrap()
+                .as_str(),
+            Some("DEBUG")
+        );
+    }
+
+    #[test]
+    fn test_parse_include_directives() {
+        let input = "
+        @include shared/kafka.properties
+        kafka.topic=orders
+        @include   shared/db.properties
+        ";
+
+        let config = PropertiesFormat.parse(input).unwrap();
+
+        assert_eq!(
+            config.as_inner().get(REF_KEY),
+            Some(&ConfigValue::Array(vec![
+                ConfigValue::String("shared/kafka.properties".into()),
+                ConfigValue::String("shared/db.properties".into()),
+            ]))
+        );
+        assert_eq!(config.get("kafka.topic").unwrap().as_str(), Some("orders"));
+        assert!(PropertiesFormat.parse("@include\n").is_err());
+    }
+
     #[test]
     fn test_serialize_properties() {
         let json = r#"{"a": {"b": "c"}, "d": 10}"#;
@@ -156,4 +194,17 @@ mod tests {
         assert!(output.contains("a.b=c"));
         assert!(output.contains("d=10"));
     }
+
+    #[test]
+    fn test_serialize_dotted_yaml_keys() {
+        let yaml = "spring.datasource.url: jdbc:x\nlogging.level:\n  com.acme: DEBUG\n";
+        let config = ConfigMap::from_yaml(yaml).unwrap();
+
+        let output = PropertiesFormat.serialize(&config).unwrap();
+
+        assert_eq!(
+            output,
+            "spring.datasource.url=jdbc:x\nlogging.level.com.acme=DEBUG\n"
+        );
+    }
 }
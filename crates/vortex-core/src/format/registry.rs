@@ -0,0 +1,342 @@
+//! Pluggable registry of configuration formats.
+//!
+//! The registry maps file extensions and MIME types to [`FormatParser`] and
+//! [`FormatSerializer`] implementations. Readers use it to decide which files
+//! are configuration and how to parse them; servers use it for content
+//! negotiation. Adding a format is a single [`FormatRegistry::register`] call.
+//!
+//! # Example
+//!
+//! ```
+//! use vortex_core::ConfigMap;
+//! use vortex_core::format::{FormatEntry, FormatParser, FormatRegistry};
+//!
+//! struct KeyValueFormat;
+//!
+//! impl FormatParser for KeyValueFormat {
+//!     fn parse(&self, input: &str) -> vortex_core::Result<ConfigMap> {
+//!         let mut map = ConfigMap::new();
+//!         for (k, v) in input.lines().filter_map(|l| l.split_once(' ')) {
+//!             map.insert(k, v);
+//!         }
+//!         Ok(map)
+//!     }
+//! }
+//!
+//! let mut registry = FormatRegistry::builtin();
+//! registry.register(
+//!     FormatEntry::new("kv")
+//!         .extensions(["kv"])
+//!         .media_types(["text/x-kv"])
+//!         .parser(KeyValueFormat),
+//! );
+//!
+//! let entry = registry.by_extension("kv").unwrap();
+//! assert_eq!(entry.parse("port 8080").unwrap().get("port").unwrap().as_str(), Some("8080"));
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::config::ConfigMap;
+use crate::error::{Result, VortexError};
+use crate::format::env::EnvFormat;
+use crate::format::hocon::HoconFormat;
+use crate::format::ini::IniFormat;
+use crate::format::json::JsonFormat;
+use crate::format::properties::PropertiesFormat;
+use crate::format::toml::TomlFormat;
+use crate::format::yaml::YamlFormat;
+use crate::format::{ConfigFormat, FormatParser, FormatSerializer};
+
+/// A format known to a [`FormatRegistry`].
+///
+/// Names, extensions and media types are matched case-insensitively. The
+/// first extension and media type are the primary ones.
+#[derive(Clone)]
+pub struct FormatEntry {
+    name: String,
+    extensions: Vec<String>,
+    media_types: Vec<String>,
+    parser: Option<Arc<dyn FormatParser>>,
+    serializer: Option<Arc<dyn FormatSerializer>>,
+}
+
+impl FormatEntry {
+    /// Creates an entry with the given name and no capabilities.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into().to_lowercase(),
+            extensions: Vec::new(),
+            media_types: Vec::new(),
+            parser: None,
+            serializer: None,
+        }
+    }
+
+    /// Sets the file extensions (without dot) handled by this format.
+    pub fn extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extensions = extensions
+            .into_iter()
+            .map(|e| e.into().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Sets the MIME types this format is served as.
+    pub fn media_types<I, S>(mut self, media_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.media_types = media_types
+            .into_iter()
+            .map(|m| m.into().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Sets the parser for this format.
+    pub fn parser(mut self, parser: impl FormatParser + 'static) -> Self {
+        self.parser = Some(Arc::new(parser));
+        self
+    }
+
+    /// Sets the serializer for this format.
+    pub fn serializer(mut self, serializer: impl FormatSerializer + 'static) -> Self {
+        self.serializer = Some(Arc::new(serializer));
+        self
+    }
+
+    /// Returns the format name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the file extensions, primary first.
+    pub fn file_extensions(&self) -> &[String] {
+        &self.extensions
+    }
+
+    /// Returns the MIME types, primary first.
+    pub fn mime_types(&self) -> &[String] {
+        &self.media_types
+    }
+
+    /// Returns the primary MIME type, if any.
+    pub fn mime_type(&self) -> Option<&str> {
+        self.media_types.first().map(String::as_str)
+    }
+
+    /// Returns true if this format can parse input.
+    pub fn can_parse(&self) -> bool {
+        self.parser.is_some()
+    }
+
+    /// Returns true if this format can serialize output.
+    pub fn can_serialize(&self) -> bool {
+        self.serializer.is_some()
+    }
+
+    /// Parses input with this format's parser.
+    pub fn parse(&self, input: &str) -> Result<ConfigMap> {
+        match &self.parser {
+            Some(parser) => parser.parse(input),
+            None => Err(VortexError::parse_error(
+                &self.name,
+                "format does not support parsing",
+            )),
+        }
+    }
+
+    /// Serializes a config with this format's serializer.
+    pub fn serialize(&self, config: &ConfigMap) -> Result<String> {
+        match &self.serializer {
+            Some(serializer) => serializer.serialize(config),
+            None => Err(VortexError::parse_error(
+                &self.name,
+                "format does not support serialization",
+            )),
+        }
+    }
+
+    fn matches_media_type(&self, media_type: &str) -> bool {
+        self.media_types
+            .iter()
+            .any(|m| m.eq_ignore_ascii_case(media_type))
+    }
+}
+
+impl std::fmt::Debug for FormatEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FormatEntry")
+            .field("name", &self.name)
+            .field("extensions", &self.extensions)
+            .field("media_types", &self.media_types)
+            .field("can_parse", &self.can_parse())
+            .field("can_serialize", &self.can_serialize())
+            .finish()
+    }
+}
+
+/// Registry mapping extensions and MIME types to formats.
+///
+/// Entries are kept in registration order, which is also the order in which
+/// readers try extensions when resolving a file name.
+#[derive(Debug, Clone, Default)]
+pub struct FormatRegistry {
+    entries: Vec<FormatEntry>,
+}
+
+impl FormatRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with all built-in formats registered.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        for format in ConfigFormat::all() {
+            registry.register(builtin_entry(*format));
+        }
+        registry
+    }
+
+    /// Registers a format, replacing any existing entry with the same name.
+    pub fn register(&mut self, entry: FormatEntry) -> &mut Self {
+        match self.entries.iter_mut().find(|e| e.name == entry.name) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+        self
+    }
+
+    /// Returns the entry with the given name.
+    pub fn by_name(&self, name: &str) -> Option<&FormatEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the entry handling a file extension (without dot).
+    pub fn by_extension(&self, ext: &str) -> Option<&FormatEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.extensions.iter().any(|x| x.eq_ignore_ascii_case(ext)))
+    }
+
+    /// Returns the entry handling a file, based on its extension.
+    pub fn by_path(&self, path: &Path) -> Option<&FormatEntry> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.by_extension(ext))
+    }
+
+    /// Returns the entry served as a MIME type.
+    ///
+    /// Parameters such as `; charset=utf-8` are ignored.
+    pub fn by_media_type(&self, media_type: &str) -> Option<&FormatEntry> {
+        let essence = media_type.split(';').next().unwrap_or("").trim();
+        self.entries.iter().find(|e| e.matches_media_type(essence))
+    }
+
+    /// Iterates over all entries in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &FormatEntry> {
+        self.entries.iter()
+    }
+
+    /// Iterates over entries that can parse input.
+    pub fn parsers(&self) -> impl Iterator<Item = &FormatEntry> {
+        self.entries.iter().filter(|e| e.can_parse())
+    }
+
+    /// Iterates over entries that can serialize output.
+    pub fn serializers(&self) -> impl Iterator<Item = &FormatEntry> {
+        self.entries.iter().filter(|e| e.can_serialize())
+    }
+}
+
+fn builtin_entry(format: ConfigFormat) -> FormatEntry {
+    let entry = FormatEntry::new(format.name())
+        .extensions(format.extensions().iter().copied())
+        .media_types(format.mime_types().iter().copied());
+
+    match format {
+        ConfigFormat::Yaml => entry.parser(YamlFormat).serializer(YamlFormat),
+        ConfigFormat::Json => entry.parser(JsonFormat).serializer(JsonFormat),
+        ConfigFormat::Properties => entry.parser(PropertiesFormat).serializer(PropertiesFormat),
+        ConfigFormat::Toml => entry.parser(TomlFormat).serializer(TomlFormat),
+        ConfigFormat::Hocon => entry.parser(HoconFormat),
+        ConfigFormat::Ini => entry.parser(IniFormat),
+        ConfigFormat::Env => entry.parser(EnvFormat).serializer(EnvFormat),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_lookup() {
+        let registry = FormatRegistry::builtin();
+
+        assert_eq!(registry.by_extension("yml").unwrap().name(), "yaml");
+        assert_eq!(registry.by_extension("YAML").unwrap().name(), "yaml");
+        assert_eq!(registry.by_extension("conf").unwrap().name(), "hocon");
+        assert_eq!(
+            registry.by_path(Path::new("app-prod.toml")).unwrap().name(),
+            "toml"
+        );
+        assert!(registry.by_extension("txt").is_none());
+    }
+
+    #[test]
+    fn test_media_type_lookup_ignores_parameters() {
+        let registry = FormatRegistry::builtin();
+
+        assert_eq!(registry.by_media_type("text/yaml").unwrap().name(), "yaml");
+        assert_eq!(
+            registry
+                .by_media_type("text/plain; charset=utf-8")
+                .unwrap()
+                .name(),
+            "properties"
+        );
+        assert!(registry.by_media_type("image/png").is_none());
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let registry = FormatRegistry::builtin();
+
+        let hocon = registry.by_name("hocon").unwrap();
+        assert!(hocon.can_parse());
+        assert!(!hocon.can_serialize());
+        assert!(hocon.serialize(&ConfigMap::new()).is_err());
+
+        let json = registry.by_name("json").unwrap();
+        let config = json.parse(r#"{"a": 1}"#).unwrap();
+        assert!(json.serialize(&config).unwrap().contains("\"a\": 1"));
+    }
+
+    #[test]
+    fn test_register_replaces_by_name() {
+        let mut registry = FormatRegistry::builtin();
+        let count = registry.iter().count();
+
+        registry.register(
+            FormatEntry::new("JSON")
+                .extensions(["json5"])
+                .parser(JsonFormat),
+        );
+
+        assert_eq!(registry.iter().count(), count);
+        assert_eq!(registry.by_extension("json5").unwrap().name(), "json");
+        assert!(registry.by_extension("json").is_none());
+    }
+}
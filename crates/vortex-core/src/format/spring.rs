@@ -78,6 +78,10 @@ impl From<&PropertySource> for SpringPropertySource {
 ///
 /// Example:
 /// {"server": {"port": 80}} -> {"server.port": 80}
+///
+/// Keys are joined with `.` as Spring's `YamlProcessor` does, so a key that
+/// already contains dots reads as the same property:
+/// {"logging": {"level": {"com.acme": "DEBUG"}}} -> {"logging.level.com.acme": "DEBUG"}
 pub fn flatten_config_map(config: &ConfigMap) -> IndexMap<String, ConfigValue> {
     let mut flat_map = IndexMap::new();
     for (key, value) in config.as_inner() {
@@ -107,6 +111,23 @@ mod tests {
     use super::*;
     use crate::config::ConfigMap;
 
+    #[test]
+    fn test_flatten_keys_with_dots() {
+        let yaml = "spring.datasource.url: jdbc:x\nlogging.level:\n  com.acme: DEBUG\n";
+        let config = ConfigMap::from_yaml(yaml).unwrap();
+
+        let flat = flatten_config_map(&config);
+
+        assert_eq!(
+            flat.get("spring.datasource.url").unwrap().as_str(),
+            Some("jdbc:x")
+        );
+        assert_eq!(
+            flat.get("logging.level.com.acme").unwrap().as_str(),
+            Some("DEBUG")
+        );
+    }
+
     #[test]
     fn test_flattening_logic() {
         let json = r#"{
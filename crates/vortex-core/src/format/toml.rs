@@ -0,0 +1,165 @@
+use crate::config::{ConfigMap, ConfigValue};
+use crate::error::{Result, VortexError};
+use crate::format::{FormatParser, FormatSerializer};
+use indexmap::IndexMap;
+use ordered_float::OrderedFloat;
+
+pub struct TomlFormat;
+
+impl FormatParser for TomlFormat {
+    fn parse(&self, input: &str) -> Result<ConfigMap> {
+        let table: toml::Table = toml::from_str(input)
+            .map_err(|e| VortexError::parse_error("toml_source", e.to_string()))?;
+
+        let inner = table
+            .into_iter()
+            .map(|(k, v)| (k, from_toml_value(v)))
+            .collect();
+
+        Ok(ConfigMap::from_inner(inner))
+    }
+}
+
+impl FormatSerializer for TomlFormat {
+    fn serialize(&self, config: &ConfigMap) -> Result<String> {
+        let table = to_toml_table(config.as_inner());
+        toml::to_string(&table).map_err(|e| VortexError::parse_error("toml_target", e.to_string()))
+    }
+}
+
+fn from_toml_value(value: toml::Value) -> ConfigValue {
+    match value {
+        toml::Value::String(s) => ConfigValue::String(s),
+        toml::Value::Integer(i) => ConfigValue::Integer(i),
+        toml::Value::Float(f) => ConfigValue::Float(OrderedFloat(f)),
+        toml::Value::Boolean(b) => ConfigValue::Bool(b),
+        toml::Value::Datetime(dt) => ConfigValue::DateTime(dt.to_string()),
+        toml::Value::Array(arr) => {
+            ConfigValue::Array(arr.into_iter().map(from_toml_value).collect())
+        },
+        toml::Value::Table(table) => ConfigValue::Object(
+            table
+                .into_iter()
+                .map(|(k, v)| (k, from_toml_value(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Converts a map into a TOML table.
+///
+/// TOML has no null type, so `Null` entries are omitted from the output.
+fn to_toml_table(map: &IndexMap<String, ConfigValue>) -> toml::Table {
+    map.iter()
+        .filter_map(|(k, v)| to_toml_value(v).map(|v| (k.clone(), v)))
+        .collect()
+}
+
+fn to_toml_value(value: &ConfigValue) -> Option<toml::Value> {
+    let converted = match value {
+        ConfigValue::Null => return None,
+        ConfigValue::Bool(b) => toml::Value::Boolean(*b),
+        ConfigValue::Integer(i) => toml::Value::Integer(*i),
+        ConfigValue::Float(f) => toml::Value::Float(f.into_inner()),
+        ConfigValue::String(s) => toml::Value::String(s.clone()),
+        // Fall back to a string if the stored text is not a valid TOML datetime.
+        ConfigValue::DateTime(dt) => dt
+            .parse::<toml::value::Datetime>()
+            .map(toml::Value::Datetime)
+            .unwrap_or_else(|_| toml::Value::String(dt.clone())),
+        ConfigValue::Array(arr) => {
+            toml::Value::Array(arr.iter().filter_map(to_toml_value).collect())
+        },
+        ConfigValue::Object(map) => toml::Value::Table(to_toml_table(map)),
+    };
+
+    Some(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toml() {
+        let input = r#"
+title = "Test App"
+
+[server]
+port = 8080
+host = "localhost"
+ratio = 0.75
+
+[server.ssl]
+enabled = true
+
+[[servers]]
+name = "a"
+
+[[servers]]
+name = "b"
+"#;
+
+        let config = TomlFormat.parse(input).unwrap();
+
+        assert_eq!(config.get("title").unwrap().as_str(), Some("Test App"));
+        assert_eq!(config.get("server.port").unwrap().as_i64(), Some(8080));
+        assert_eq!(config.get("server.ratio").unwrap().as_f64(), Some(0.75));
+        assert_eq!(
+            config.get("server.ssl.enabled").unwrap().as_bool(),
+            Some(true)
+        );
+        assert_eq!(config.get("servers").unwrap().as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_datetimes() {
+        let input = r#"
+released = 1979-05-27T07:32:00Z
+local_date = 1979-05-27
+local_time = 07:32:00
+"#;
+
+        let config = TomlFormat.parse(input).unwrap();
+
+        assert_eq!(
+            config.get("released").unwrap().as_datetime(),
+            Some("1979-05-27T07:32:00Z")
+        );
+        assert_eq!(
+            config.get("local_date").unwrap().as_datetime(),
+            Some("1979-05-27")
+        );
+        assert_eq!(
+            config.get("local_time").unwrap().as_datetime(),
+            Some("07:32:00")
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_preserves_datetime() {
+        let input = "released = 1979-05-27T07:32:00Z\n\n[server]\nport = 8080\n";
+
+        let config = TomlFormat.parse(input).unwrap();
+        let output = TomlFormat.serialize(&config).unwrap();
+
+        assert!(output.contains("released = 1979-05-27T07:32:00Z"));
+        assert!(output.contains("[server]"));
+        assert_eq!(TomlFormat.parse(&output).unwrap(), config);
+    }
+
+    #[test]
+    fn test_serialize_skips_null() {
+        let config = ConfigMap::from_json(r#"{"a": null, "b": {"c": 1}}"#).unwrap();
+
+        let output = TomlFormat.serialize(&config).unwrap();
+
+        assert!(!output.contains("a ="));
+        assert!(output.contains("c = 1"));
+    }
+
+    #[test]
+    fn test_parse_invalid_toml() {
+        assert!(TomlFormat.parse("key = [invalid").is_err());
+    }
+}
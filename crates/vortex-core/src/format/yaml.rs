@@ -1,7 +1,53 @@
-use crate::config::ConfigMap;
-use crate::error::Result;
-use crate::format::{FormatParser, FormatSerializer};
+//! YAML format.
+//!
+//! Anchors and aliases are resolved by the YAML parser. Merge keys (`<<`)
+//! are expanded after parsing: keys written in the mapping win over merged
+//! ones, and with a sequence of merged mappings (`<<: [*a, *b]`) earlier
+//! mappings win over later ones, as in YAML 1.1.
+//!
+//! Custom tags such as `!secret` or `!env` are resolved by
+//! [`YamlTagHandler`]s registered on a [`TaggedYamlFormat`]. A tag without
+//! a handler is rejected with an error naming the tag and its location, so
+//! a tagged value never reaches clients as if it were plain data.
+//!
+//! `!include path` is the exception: unless a handler is registered for it,
+//! it parses to `{"$ref": path}` (see [`REF_KEY`]), which readers with
+//! access to the repository replace with the included file.
+//!
+//! # Example
+//!
+//! ```
+//! use vortex_core::format::FormatParser;
+//! use vortex_core::format::yaml::{EnvTag, TaggedYamlFormat, YamlFormat};
+//! use vortex_core::ConfigValue;
+//!
+//! let yaml = "defaults: &defaults\n  timeout: 30\n  retries: 3\n\
+//!             client:\n  <<: *defaults\n  retries: 5\n";
+//! let config = YamlFormat.parse(yaml).unwrap();
+//! assert_eq!(config.get("client.timeout"), Some(&ConfigValue::Integer(30)));
+//! assert_eq!(config.get("client.retries"), Some(&ConfigValue::Integer(5)));
+//!
+//! assert!(YamlFormat.parse("home: !env HOME\n").is_err());
+//! let format = TaggedYamlFormat::new().with_handler("!env", EnvTag);
+//! assert!(format.parse("path: !env PATH\n").is_ok());
+//! ```
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::de::{self, Deserialize, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess};
+use serde_yaml::value::{Tag, TaggedValue};
+use serde_yaml::{Mapping, Value};
+
+use crate::config::{ConfigMap, ConfigPath, ConfigValue};
+use crate::error::{Result, VortexError};
+use crate::format::{FormatParser, FormatSerializer, REF_KEY};
+
+const MERGE_KEY: &str = "<<";
+
+const INCLUDE_TAG: &str = "!include";
+
+/// YAML format with merge keys and no custom tags other than `!include`.
 pub struct YamlFormat;
 
 impl FormatParser for YamlFormat {
@@ -15,3 +61,579 @@ impl FormatSerializer for YamlFormat {
         config.to_yaml()
     }
 }
+
+/// Resolves the value of a node carrying a custom YAML tag.
+///
+/// The handler receives the tagged node with the tag removed (for
+/// `!env HOME`, the string `"HOME"`) and returns the value to use in its
+/// place. Errors are reported with the tag and the key it was found at.
+///
+/// Closures of the right signature are handlers:
+///
+/// ```
+/// use vortex_core::format::FormatParser;
+/// use vortex_core::format::yaml::TaggedYamlFormat;
+/// use vortex_core::ConfigValue;
+///
+/// let format = TaggedYamlFormat::new().with_handler("!upper", |value: ConfigValue| {
+///     Ok(ConfigValue::String(value.as_str().unwrap_or_default().to_uppercase()))
+/// });
+/// let config = format.parse("name: !upper vortex\n").unwrap();
+/// assert_eq!(config.get("name").and_then(|v| v.as_str()), Some("VORTEX"));
+/// ```
+pub trait YamlTagHandler: Send + Sync {
+    /// Returns the value that replaces the tagged node.
+    fn resolve(&self, value: ConfigValue) -> Result<ConfigValue>;
+}
+
+impl<F> YamlTagHandler for F
+where
+    F: Fn(ConfigValue) -> Result<ConfigValue> + Send + Sync,
+{
+    fn resolve(&self, value: ConfigValue) -> Result<ConfigValue> {
+        self(value)
+    }
+}
+
+/// YAML format that resolves custom tags with registered handlers.
+///
+/// Behaves like [`YamlFormat`] for untagged documents. Tags without a
+/// handler are still rejected.
+#[derive(Clone, Default)]
+pub struct TaggedYamlFormat {
+    handlers: HashMap<String, Arc<dyn YamlTagHandler>>,
+}
+
+impl TaggedYamlFormat {
+    /// Creates a format with no tag handlers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a format with the named built-in handlers.
+    ///
+    /// The only built-in handler is `env` ([`EnvTag`]). Unknown names are
+    /// an error.
+    pub fn builtin<S: AsRef<str>>(names: &[S]) -> Result<Self> {
+        names.iter().try_fold(Self::new(), |format, name| {
+            match name.as_ref().trim().trim_start_matches('!') {
+                "env" => Ok(format.with_handler("!env", EnvTag)),
+                other => Err(VortexError::validation_error(
+                    "yaml_tags",
+                    format!("unknown YAML tag handler '{}'", other),
+                )),
+            }
+        })
+    }
+
+    /// Registers the handler for `tag` (e.g. `"!env"`; the `!` is optional).
+    ///
+    /// Replaces any handler previously registered for the same tag.
+    pub fn with_handler(mut self, tag: &str, handler: impl YamlTagHandler + 'static) -> Self {
+        self.handlers.insert(normalize_tag(tag), Arc::new(handler));
+        self
+    }
+
+    /// Returns `true` if a handler is registered for `tag`.
+    pub fn handles(&self, tag: &str) -> bool {
+        self.handlers.contains_key(&normalize_tag(tag))
+    }
+}
+
+impl FormatParser for TaggedYamlFormat {
+    fn parse(&self, input: &str) -> Result<ConfigMap> {
+        parse(input, &self.handlers)
+    }
+}
+
+impl FormatSerializer for TaggedYamlFormat {
+    fn serialize(&self, config: &ConfigMap) -> Result<String> {
+        config.to_yaml()
+    }
+}
+
+impl std::fmt::Debug for TaggedYamlFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut tags: Vec<_> = self.handlers.keys().collect();
+        tags.sort();
+        f.debug_struct("TaggedYamlFormat")
+            .field("tags", &tags)
+            .finish()
+    }
+}
+
+/// Handler for `!env NAME` and `!env NAME:default`.
+///
+/// Reads the variable from the environment of the process parsing the file.
+/// On the server that exposes the server's environment to anyone who can
+/// commit to the configuration repository, so it is never registered by
+/// default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvTag;
+
+impl YamlTagHandler for EnvTag {
+    fn resolve(&self, value: ConfigValue) -> Result<ConfigValue> {
+        let Some(spec) = value.as_str() else {
+            return Err(VortexError::parse_error(
+                "yaml_source",
+                "expected a variable name, as in `!env NAME` or `!env NAME:default`",
+            ));
+        };
+
+        let (name, default) = match spec.split_once(':') {
+            Some((name, default)) => (name.trim(), Some(default)),
+            None => (spec.trim(), None),
+        };
+
+        match (std::env::var(name), default) {
+            (Ok(value), _) => Ok(ConfigValue::String(value)),
+            (Err(_), Some(default)) => Ok(ConfigValue::String(default.to_string())),
+            (Err(_), None) => Err(VortexError::parse_error(
+                "yaml_source",
+                format!("environment variable '{}' is not set", name),
+            )),
+        }
+    }
+}
+
+/// Parses YAML, resolving tags with `handlers` and expanding merge keys.
+pub(crate) fn parse(
+    input: &str,
+    handlers: &HashMap<String, Arc<dyn YamlTagHandler>>,
+) -> Result<ConfigMap> {
+    let node: Node = serde_yaml::from_str(input)
+        .map_err(|e| VortexError::parse_error("yaml_source", e.to_string()))?;
+    let value = resolve(node.into_value(), &mut ConfigPath::root(), handlers)?;
+
+    if value.is_null() {
+        return Ok(ConfigMap::new());
+    }
+    serde_yaml::from_value(value)
+        .map_err(|e| VortexError::parse_error("yaml_source", e.to_string()))
+}
+
+/// A parsed YAML node.
+///
+/// Unlike [`Value`], mappings keep repeated keys, so that duplicates can be
+/// reported (see [`crate::format::duplicates`]) instead of failing the
+/// parse; the last value wins, as for the other formats.
+enum Node {
+    Scalar(Value),
+    Sequence(Vec<Node>),
+    Mapping(Vec<(Node, Node)>),
+    Tagged(String, Box<Node>),
+}
+
+impl Node {
+    fn into_value(self) -> Value {
+        match self {
+            Node::Scalar(value) => value,
+            Node::Sequence(items) => {
+                Value::Sequence(items.into_iter().map(Node::into_value).collect())
+            },
+            Node::Mapping(entries) => Value::Mapping(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.into_value(), value.into_value()))
+                    .collect(),
+            ),
+            Node::Tagged(tag, value) => Value::Tagged(Box::new(TaggedValue {
+                tag: Tag::new(tag),
+                value: value.into_value(),
+            })),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+struct NodeVisitor;
+
+impl<'de> de::Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("any YAML value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> std::result::Result<Node, E> {
+        Ok(Node::Scalar(Value::Bool(v)))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> std::result::Result<Node, E> {
+        Ok(Node::Scalar(Value::Number(v.into())))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> std::result::Result<Node, E> {
+        Ok(Node::Scalar(Value::Number(v.into())))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> std::result::Result<Node, E> {
+        Ok(Node::Scalar(Value::Number(v.into())))
+    }
+
+    fn visit_str<E>(self, v: &str) -> std::result::Result<Node, E> {
+        Ok(Node::Scalar(Value::String(v.to_string())))
+    }
+
+    fn visit_string<E>(self, v: String) -> std::result::Result<Node, E> {
+        Ok(Node::Scalar(Value::String(v)))
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<Node, E> {
+        Ok(Node::Scalar(Value::Null))
+    }
+
+    fn visit_none<E>(self) -> std::result::Result<Node, E> {
+        Ok(Node::Scalar(Value::Null))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> std::result::Result<Node, D::Error> {
+        Node::deserialize(d)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Node, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Node::Sequence(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Node, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Node::Mapping(entries))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> std::result::Result<Node, A::Error> {
+        let (tag, variant): (String, _) = data.variant()?;
+        Ok(Node::Tagged(tag, Box::new(variant.newtype_variant()?)))
+    }
+}
+
+/// Resolves tags and merge keys below `value`, innermost first.
+///
+/// Tags are resolved before merging, so a handler may produce the mapping
+/// to merge (`<<: !include base.yml`).
+fn resolve(
+    value: Value,
+    path: &mut ConfigPath,
+    handlers: &HashMap<String, Arc<dyn YamlTagHandler>>,
+) -> Result<Value> {
+    match value {
+        Value::Tagged(tagged) => {
+            let tag = tagged.tag.to_string();
+            let inner = resolve(tagged.value, path, handlers)?;
+            if tag == INCLUDE_TAG && !handlers.contains_key(&tag) {
+                let mut reference = Mapping::new();
+                reference.insert(Value::String(REF_KEY.to_string()), inner);
+                return Ok(Value::Mapping(reference));
+            }
+            let Some(handler) = handlers.get(&tag) else {
+                return Err(VortexError::parse_error(
+                    "yaml_source",
+                    format!(
+                        "unsupported tag '{}' at '{}': no handler is registered for it",
+                        tag,
+                        location(path)
+                    ),
+                ));
+            };
+
+            let context = |message: String| {
+                VortexError::parse_error(
+                    "yaml_source",
+                    format!("tag '{}' at '{}': {}", tag, location(path), message),
+                )
+            };
+            let inner: ConfigValue =
+                serde_yaml::from_value(inner).map_err(|e| context(e.to_string()))?;
+            let resolved = handler.resolve(inner).map_err(|e| match e {
+                VortexError::ParseError { message, .. } => context(message),
+                other => context(other.to_string()),
+            })?;
+            serde_yaml::to_value(resolved)
+                .map_err(|e| VortexError::parse_error("yaml_source", e.to_string()))
+        },
+        Value::Mapping(mapping) => {
+            let mut resolved = Mapping::with_capacity(mapping.len());
+            for (key, value) in mapping {
+                // Scalar keys read as strings (`1:` is the key "1").
+                let key = match key {
+                    Value::Mapping(_) | Value::Sequence(_) | Value::Tagged(_) => key,
+                    scalar => Value::String(key_name(&scalar)),
+                };
+                path.push_key(key_name(&key));
+                let value = resolve(value, path, handlers);
+                path.pop();
+                resolved.insert(key, value?);
+            }
+            expand_merge(resolved, path).map(Value::Mapping)
+        },
+        Value::Sequence(sequence) => sequence
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                path.push_index(index);
+                let item = resolve(item, path, handlers);
+                path.pop();
+                item
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Value::Sequence),
+        scalar => Ok(scalar),
+    }
+}
+
+/// Replaces the merge key of `mapping` with the keys it merges in.
+///
+/// Merged keys take the position of the `<<` entry; keys written in the
+/// mapping keep theirs and their values.
+fn expand_merge(mapping: Mapping, path: &ConfigPath) -> Result<Mapping> {
+    let merge_key = Value::String(MERGE_KEY.to_string());
+    if !mapping.contains_key(&merge_key) {
+        return Ok(mapping);
+    }
+
+    let mut expanded = Mapping::with_capacity(mapping.len());
+    for (key, value) in &mapping {
+        if *key != merge_key {
+            expanded.insert(key.clone(), value.clone());
+            continue;
+        }
+
+        let sources = match value {
+            Value::Mapping(source) => vec![source],
+            Value::Sequence(items) => items
+                .iter()
+                .map(|item| match item {
+                    Value::Mapping(source) => Ok(source),
+                    _ => Err(invalid_merge(path)),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            _ => return Err(invalid_merge(path)),
+        };
+
+        for source in sources {
+            for (key, value) in source {
+                if !mapping.contains_key(key) && !expanded.contains_key(key) {
+                    expanded.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+fn invalid_merge(path: &ConfigPath) -> VortexError {
+    VortexError::parse_error(
+        "yaml_source",
+        format!(
+            "merge key '<<' at '{}' must be a mapping or a sequence of mappings",
+            location(path)
+        ),
+    )
+}
+
+fn key_name(key: &Value) -> String {
+    match key {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => "~".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+fn location(path: &ConfigPath) -> String {
+    if path.is_root() {
+        "(root)".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+fn normalize_tag(tag: &str) -> String {
+    if tag.starts_with('!') {
+        tag.to_string()
+    } else {
+        format!("!{}", tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get<'a>(config: &'a ConfigMap, path: &str) -> Option<&'a ConfigValue> {
+        config.get(path)
+    }
+
+    #[test]
+    fn test_merge_key_explicit_keys_win() {
+        let yaml = "\
+base: &base
+  host: localhost
+  port: 5432
+db:
+  <<: *base
+  port: 6543
+";
+        let config = YamlFormat.parse(yaml).unwrap();
+
+        assert_eq!(
+            get(&config, "db.host"),
+            Some(&ConfigValue::String("localhost".into()))
+        );
+        assert_eq!(get(&config, "db.port"), Some(&ConfigValue::Integer(6543)));
+        assert!(get(&config, "db.<<").is_none());
+        let keys: Vec<_> = config
+            .get("db")
+            .and_then(|v| v.as_object())
+            .unwrap()
+            .keys()
+            .collect();
+        assert_eq!(keys, ["host", "port"]);
+    }
+
+    #[test]
+    fn test_merge_key_sequence_earlier_wins_and_chains() {
+        let yaml = "\
+a: &a
+  x: 1
+  y: 1
+b: &b
+  <<: *a
+  y: 2
+  z: 2
+c:
+  <<: [*b, *a]
+  w: 3
+";
+        let config = YamlFormat.parse(yaml).unwrap();
+
+        assert_eq!(get(&config, "b.x"), Some(&ConfigValue::Integer(1)));
+        assert_eq!(get(&config, "c.x"), Some(&ConfigValue::Integer(1)));
+        assert_eq!(get(&config, "c.y"), Some(&ConfigValue::Integer(2)));
+        assert_eq!(get(&config, "c.z"), Some(&ConfigValue::Integer(2)));
+        assert_eq!(get(&config, "c.w"), Some(&ConfigValue::Integer(3)));
+        assert!(get(&config, "c.<<").is_none());
+    }
+
+    #[test]
+    fn test_merge_key_rejects_scalars() {
+        let err = YamlFormat.parse("db:\n  <<: 42\n").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("merge key '<<' at 'db' must be a mapping"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_unknown_tag_is_rejected_with_location() {
+        let err = YamlFormat
+            .parse("db:\n  users:\n    - password: !secret db/pass\n")
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unsupported tag '!secret' at 'db.users[0].password'"),
+            "{}",
+            err
+        );
+
+        let format = TaggedYamlFormat::new().with_handler("env", EnvTag);
+        let err = format.parse("key: !vault db/pass\n").unwrap_err();
+        assert!(err.to_string().contains("'!vault' at 'key'"), "{}", err);
+    }
+
+    #[test]
+    fn test_tag_handlers() {
+        let format = TaggedYamlFormat::new()
+            .with_handler("!base", |_| {
+                Ok(ConfigValue::Object(
+                    [("timeout".to_string(), ConfigValue::Integer(30))].into(),
+                ))
+            })
+            .with_handler("env", EnvTag);
+        assert!(format.handles("!env"));
+        assert!(!format.handles("secret"));
+
+        let yaml = "\
+client:
+  <<: !base defaults
+  home: !env VORTEX_TEST_UNSET_VARIABLE:/srv
+";
+        let config = format.parse(yaml).unwrap();
+
+        assert_eq!(
+            get(&config, "client.timeout"),
+            Some(&ConfigValue::Integer(30))
+        );
+        assert_eq!(
+            get(&config, "client.home"),
+            Some(&ConfigValue::String("/srv".into()))
+        );
+
+        let err = format
+            .parse("home: !env VORTEX_TEST_UNSET_VARIABLE\n")
+            .unwrap_err();
+        assert!(
+            err.to_string().contains(
+                "tag '!env' at 'home': environment variable 'VORTEX_TEST_UNSET_VARIABLE' is not set"
+            ),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_include_parses_to_reference() {
+        let yaml =
+            "kafka: !include shared/kafka.yml\nbase:\n  <<: !include [a.yml, b.yml]\n  x: 1\n";
+        let config = YamlFormat.parse(yaml).unwrap();
+
+        assert_eq!(
+            get(&config, "kafka").unwrap().as_object().unwrap()[REF_KEY],
+            ConfigValue::String("shared/kafka.yml".into())
+        );
+        let base = get(&config, "base").unwrap().as_object().unwrap();
+        assert_eq!(base[REF_KEY].as_array().map(<[_]>::len), Some(2));
+        assert_eq!(base["x"], ConfigValue::Integer(1));
+    }
+
+    #[test]
+    fn test_builtin_handlers() {
+        let format = TaggedYamlFormat::builtin(&["env"]).unwrap();
+        assert!(format.handles("env"));
+
+        let err = TaggedYamlFormat::builtin(&["env", "secret"]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unknown YAML tag handler 'secret'")
+        );
+    }
+
+    #[test]
+    fn test_plain_documents_unchanged() {
+        assert!(YamlFormat.parse("").unwrap().is_empty());
+        let config = YamlFormat.parse("1: a\ntrue: b\nc: !!str 123\n").unwrap();
+        assert_eq!(get(&config, "1"), Some(&ConfigValue::String("a".into())));
+        assert_eq!(get(&config, "c"), Some(&ConfigValue::String("123".into())));
+
+        let config = YamlFormat.parse("a: 1\nb: 2\na: 3\n").unwrap();
+        assert_eq!(get(&config, "a"), Some(&ConfigValue::Integer(3)));
+        assert_eq!(
+            config.as_inner().keys().next().map(String::as_str),
+            Some("a")
+        );
+    }
+}
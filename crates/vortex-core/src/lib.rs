@@ -5,6 +5,7 @@
 //! # Key Types
 //!
 //! - [`ConfigMap`]: Complete configuration for an application
+//! - [`ConfigPath`]: Path expression addressing a value (`servers[0].host`)
 //! - [`PropertySource`]: Configuration from a single source
 //! - [`Application`], [`Profile`], [`Label`]: Identifiers for configuration
 //! - [`VortexError`]: Main error type
@@ -12,13 +13,16 @@
 
 mod config;
 
+pub mod diff;
 mod error;
 pub mod format;
 pub mod merge;
+pub mod placeholder;
 mod types;
+pub mod validation;
 
 // Re-export public types
-pub use config::{ConfigMap, ConfigValue, PropertySource};
+pub use config::{ConfigMap, ConfigPath, ConfigValue, DeError, PathSegment, PropertySource};
 pub use error::{Result, VortexError};
 pub use types::{Application, Label, Profile};
 
@@ -58,6 +58,7 @@ mod tests {
             priority: 10,
             config: t1,
             origin: "".into(),
+            origins: Default::default(),
         });
 
         let mut t2 = ConfigMap::new();
@@ -67,6 +68,7 @@ mod tests {
             priority: 100,
             config: t2,
             origin: "".into(),
+            origins: Default::default(),
         });
 
         // Add middle one last to verify sorting
@@ -77,6 +79,7 @@ mod tests {
             priority: 50,
             config: t3,
             origin: "".into(),
+            origins: Default::default(),
         });
 
         // Expected order application: 10 (low) -> 50 (mid) -> 100 (high)
@@ -0,0 +1,244 @@
+//! Spring-style `${key}` placeholders in configuration values.
+//!
+//! A placeholder references another property of the same configuration,
+//! optionally with a default: `${db.host}` or `${db.port:5432}`. A `\` before
+//! `$` escapes the placeholder. Defaults may contain placeholders themselves.
+//!
+//! # Example
+//!
+//! ```
+//! use vortex_core::ConfigMap;
+//! use vortex_core::placeholder;
+//!
+//! let config = ConfigMap::from_json(r#"{
+//!     "db": {"host": "localhost"},
+//!     "url": "jdbc://${db.host}:${db.port:5432}/${db.name}"
+//! }"#).unwrap();
+//!
+//! let unresolved = placeholder::find_unresolved(&config);
+//! assert_eq!(unresolved.len(), 1);
+//! assert_eq!(unresolved[0].key, "db.name");
+//! assert_eq!(unresolved[0].path.to_string(), "url");
+//! ```
+
+use std::ops::Range;
+
+use crate::config::{ConfigMap, ConfigPath, ConfigValue};
+
+/// A placeholder found in a string value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    /// Referenced property path.
+    pub key: String,
+    /// Default used when the property does not exist.
+    pub default: Option<String>,
+    /// Byte range of the whole `${...}` expression in the value.
+    pub span: Range<usize>,
+}
+
+/// A placeholder that references a missing property and has no default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnresolvedPlaceholder {
+    /// Path of the value containing the placeholder.
+    pub path: ConfigPath,
+    /// Referenced property path.
+    pub key: String,
+}
+
+/// Returns the top-level placeholders in `value`, in order.
+///
+/// An unterminated `${` is treated as literal text.
+pub fn parse(value: &str) -> Vec<Placeholder> {
+    let bytes = value.as_bytes();
+    let mut placeholders = Vec::new();
+    let mut i = 0;
+
+    while i + 1 < bytes.len() {
+        if bytes[i] == b'\\' {
+            i += 2;
+            continue;
+        }
+        if bytes[i] != b'$' || bytes[i + 1] != b'{' {
+            i += 1;
+            continue;
+        }
+
+        let Some(end) = closing_brace(bytes, i + 2) else {
+            break;
+        };
+        let body = &value[i + 2..end];
+        let (key, default) = match split_default(body) {
+            Some((key, default)) => (key, Some(default.to_string())),
+            None => (body, None),
+        };
+        placeholders.push(Placeholder {
+            key: key.trim().to_string(),
+            default,
+            span: i..end + 1,
+        });
+        i = end + 1;
+    }
+
+    placeholders
+}
+
+/// Finds placeholders in `config` that cannot be resolved from `config`.
+///
+/// Placeholders with a default are always resolvable. Values are walked in
+/// key order, so results are stable.
+pub fn find_unresolved(config: &ConfigMap) -> Vec<UnresolvedPlaceholder> {
+    let mut unresolved = Vec::new();
+    let mut path = ConfigPath::root();
+
+    for (key, value) in config.as_inner() {
+        path.push_key(key.as_str());
+        collect_unresolved(config, value, &mut path, &mut unresolved);
+        path.pop();
+    }
+
+    unresolved
+}
+
+fn collect_unresolved(
+    config: &ConfigMap,
+    value: &ConfigValue,
+    path: &mut ConfigPath,
+    unresolved: &mut Vec<UnresolvedPlaceholder>,
+) {
+    match value {
+        ConfigValue::String(s) => {
+            for placeholder in parse(s) {
+                if placeholder.default.is_none() && config.get(&placeholder.key).is_none() {
+                    unresolved.push(UnresolvedPlaceholder {
+                        path: path.clone(),
+                        key: placeholder.key,
+                    });
+                }
+            }
+        },
+        ConfigValue::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                path.push_index(index);
+                collect_unresolved(config, item, path, unresolved);
+                path.pop();
+            }
+        },
+        ConfigValue::Object(map) => {
+            for (key, item) in map {
+                path.push_key(key.as_str());
+                collect_unresolved(config, item, path, unresolved);
+                path.pop();
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Finds the `}` closing a placeholder body starting at `start`.
+fn closing_brace(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = start;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'{' if i > 0 && bytes[i - 1] == b'$' => depth += 1,
+            b'}' if depth == 0 => return Some(i),
+            b'}' => depth -= 1,
+            _ => {},
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// Splits `key:default` at the first `:` outside nested placeholders.
+fn split_default(body: &str) -> Option<(&str, &str)> {
+    let bytes = body.as_bytes();
+    let mut depth = 0;
+
+    for (i, b) in bytes.iter().enumerate() {
+        match b {
+            b'{' if i > 0 && bytes[i - 1] == b'$' => depth += 1,
+            b'}' => depth -= 1,
+            b':' if depth == 0 => return Some((&body[..i], &body[i + 1..])),
+            _ => {},
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(value: &str) -> Vec<(String, Option<String>)> {
+        parse(value)
+            .into_iter()
+            .map(|p| (p.key, p.default))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_placeholders() {
+        assert_eq!(keys("plain"), vec![]);
+        assert_eq!(keys("${a.b}"), vec![("a.b".to_string(), None)]);
+        assert_eq!(
+            keys("x${a}-${b:8080}"),
+            vec![
+                ("a".to_string(), None),
+                ("b".to_string(), Some("8080".to_string()))
+            ]
+        );
+        assert_eq!(keys("${a:}"), vec![("a".to_string(), Some(String::new()))]);
+        assert_eq!(
+            keys("${url:http://${host:localhost}:80}"),
+            vec![(
+                "url".to_string(),
+                Some("http://${host:localhost}:80".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_escaped_and_unterminated() {
+        assert_eq!(keys(r"\${a} ${b}"), vec![("b".to_string(), None)]);
+        assert_eq!(keys("${a"), vec![]);
+        assert_eq!(keys("$a {b}"), vec![]);
+    }
+
+    #[test]
+    fn test_span_covers_expression() {
+        let value = "jdbc:${host}/db";
+        let placeholder = &parse(value)[0];
+
+        assert_eq!(&value[placeholder.span.clone()], "${host}");
+    }
+
+    #[test]
+    fn test_find_unresolved_walks_nested_values() {
+        let config = ConfigMap::from_json(
+            r#"{
+                "host": "a",
+                "servers": [{"url": "${host}"}, {"url": "${missing}"}],
+                "logging": {"file": "${log.dir}/app.log", "level": "${level:INFO}"}
+            }"#,
+        )
+        .unwrap();
+
+        let unresolved: Vec<_> = find_unresolved(&config)
+            .into_iter()
+            .map(|u| (u.path.to_string(), u.key))
+            .collect();
+
+        assert_eq!(
+            unresolved,
+            vec![
+                ("servers[1].url".to_string(), "missing".to_string()),
+                ("logging.file".to_string(), "log.dir".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! JSON Schema validation of resolved configuration.
+//!
+//! A [`SchemaValidator`] is compiled once from a JSON Schema document and
+//! checks a [`ConfigMap`] (usually the merged result of a query), reporting
+//! every violation with the [`ConfigPath`] of the offending value.
+//!
+//! # Example
+//!
+//! ```
+//! use vortex_core::ConfigMap;
+//! use vortex_core::validation::SchemaValidator;
+//!
+//! let validator = SchemaValidator::from_json(r#"{
+//!     "type": "object",
+//!     "properties": {
+//!         "server": {
+//!             "type": "object",
+//!             "properties": { "port": { "type": "integer", "maximum": 65535 } }
+//!         }
+//!     },
+//!     "required": ["server"]
+//! }"#).unwrap();
+//!
+//! let config = ConfigMap::from_json(r#"{"server": {"port": 70000}}"#).unwrap();
+//! let report = validator.validate(&config);
+//!
+//! assert!(!report.is_valid());
+//! assert_eq!(report.violations()[0].path.to_string(), "server.port");
+//! ```
+
+use serde::{Serialize, Serializer};
+
+use crate::config::{ConfigMap, ConfigPath};
+use crate::error::{Result, VortexError};
+
+/// A single schema violation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    /// Path of the offending value; the root path for whole-document errors.
+    #[serde(serialize_with = "serialize_path")]
+    pub path: ConfigPath,
+    /// Human-readable description of the violation.
+    pub message: String,
+}
+
+impl From<&Violation> for VortexError {
+    fn from(violation: &Violation) -> Self {
+        VortexError::validation_error(field_name(&violation.path), &violation.message)
+    }
+}
+
+/// Result of validating a configuration against a schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationReport {
+    valid: bool,
+    violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    /// Creates a report from a list of violations.
+    pub fn new(violations: Vec<Violation>) -> Self {
+        Self {
+            valid: violations.is_empty(),
+            violations,
+        }
+    }
+
+    /// Returns true if there are no violations.
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Returns all violations, in schema evaluation order.
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
+    /// Converts the report into a `Result`.
+    ///
+    /// An invalid report becomes a [`VortexError::ValidationError`] whose
+    /// field is the first offending path and whose message lists every
+    /// violation.
+    pub fn into_result(self) -> Result<()> {
+        let Some(first) = self.violations.first() else {
+            return Ok(());
+        };
+
+        let message = self
+            .violations
+            .iter()
+            .map(|v| format!("{}: {}", field_name(&v.path), v.message))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        Err(VortexError::validation_error(
+            field_name(&first.path),
+            message,
+        ))
+    }
+}
+
+/// A compiled JSON Schema.
+#[derive(Debug)]
+pub struct SchemaValidator {
+    validator: jsonschema::Validator,
+}
+
+impl SchemaValidator {
+    /// Compiles a schema document.
+    ///
+    /// The draft is taken from `$schema`, defaulting to the latest supported
+    /// one. Remote `$ref`s are not resolved.
+    pub fn new(schema: &serde_json::Value) -> Result<Self> {
+        jsonschema::validator_for(schema)
+            .map(|validator| Self { validator })
+            .map_err(|e| VortexError::parse_error("json_schema", e.to_string()))
+    }
+
+    /// Parses and compiles a schema from JSON text.
+    pub fn from_json(schema: &str) -> Result<Self> {
+        let schema: serde_json::Value = serde_json::from_str(schema)
+            .map_err(|e| VortexError::parse_error("json_schema", e.to_string()))?;
+        Self::new(&schema)
+    }
+
+    /// Validates a configuration, collecting every violation.
+    pub fn validate(&self, config: &ConfigMap) -> ValidationReport {
+        let instance = match serde_json::to_value(config) {
+            Ok(instance) => instance,
+            Err(e) => {
+                return ValidationReport::new(vec![Violation {
+                    path: ConfigPath::root(),
+                    message: format!("configuration is not representable as JSON: {}", e),
+                }]);
+            },
+        };
+
+        let violations = self
+            .validator
+            .iter_errors(&instance)
+            .map(|error| Violation {
+                path: pointer_to_path(error.instance_path().as_str(), &instance),
+                message: error.to_string(),
+            })
+            .collect();
+
+        ValidationReport::new(violations)
+    }
+}
+
+/// Converts a JSON pointer into a path, using `instance` to tell array
+/// indices from object keys that look like numbers.
+fn pointer_to_path(pointer: &str, instance: &serde_json::Value) -> ConfigPath {
+    let mut path = ConfigPath::root();
+    let mut current = Some(instance);
+
+    for token in pointer.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        match (current, token.parse::<usize>()) {
+            (Some(serde_json::Value::Array(items)), Ok(index)) => {
+                current = items.get(index);
+                path.push_index(index);
+            },
+            _ => {
+                current = current.and_then(|value| value.get(&token));
+                path.push_key(token);
+            },
+        }
+    }
+
+    path
+}
+
+fn field_name(path: &ConfigPath) -> String {
+    if path.is_root() {
+        "<root>".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+fn serialize_path<S: Serializer>(
+    path: &ConfigPath,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validator() -> SchemaValidator {
+        SchemaValidator::from_json(
+            r#"{
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "servers": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": { "port": { "type": "integer" } }
+                        }
+                    },
+                    "labels": {
+                        "type": "object",
+                        "additionalProperties": { "type": "string" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn paths(report: &ValidationReport) -> Vec<String> {
+        report
+            .violations()
+            .iter()
+            .map(|v| v.path.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_config() {
+        let config = ConfigMap::from_json(r#"{"name": "app", "servers": [{"port": 80}]}"#).unwrap();
+
+        let report = validator().validate(&config);
+
+        assert!(report.is_valid());
+        assert!(report.into_result().is_ok());
+    }
+
+    #[test]
+    fn test_collects_all_violations_with_paths() {
+        let config = ConfigMap::from_json(
+            r#"{"servers": [{"port": 80}, {"port": "http"}], "labels": {"0": 1, "app.io/tier": 2}}"#,
+        )
+        .unwrap();
+
+        let report = validator().validate(&config);
+
+        let mut found = paths(&report);
+        found.sort();
+        assert_eq!(
+            found,
+            ["", "labels.0", "labels[app.io/tier]", "servers[1].port"]
+        );
+    }
+
+    #[test]
+    fn test_into_result_maps_to_validation_error() {
+        let config = ConfigMap::from_json(r#"{"name": 42}"#).unwrap();
+
+        let err = validator().validate(&config).into_result().unwrap_err();
+
+        assert!(err.is_validation_error());
+        assert!(err.to_string().contains("'name'"));
+    }
+
+    #[test]
+    fn test_root_violation_field_name() {
+        let config = ConfigMap::new();
+        let report = validator().validate(&config);
+
+        let err = VortexError::from(&report.violations()[0]);
+
+        assert!(err.to_string().contains("<root>"));
+    }
+
+    #[test]
+    fn test_invalid_schema_is_parse_error() {
+        assert!(
+            SchemaValidator::from_json(r#"{"type": 12}"#)
+                .unwrap_err()
+                .is_parse_error()
+        );
+        assert!(SchemaValidator::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_report_serialization() {
+        let config = ConfigMap::from_json(r#"{"name": 1}"#).unwrap();
+
+        let json = serde_json::to_value(validator().validate(&config)).unwrap();
+
+        assert_eq!(json["valid"], false);
+        assert_eq!(json["violations"][0]["path"], "name");
+    }
+}
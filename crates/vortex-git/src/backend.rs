@@ -1,12 +1,16 @@
 //! Git backend implementation.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use tracing::{debug, info};
+use parking_lot::Mutex;
+use tracing::{debug, info, warn};
+use vortex_core::format::FormatRegistry;
+use vortex_core::validation::SchemaValidator;
 
 use crate::error::ConfigSourceError;
-use crate::reader::ConfigFileResolver;
+use crate::reader::{AgeIdentityFile, ConfigFileResolver, SopsDecryptor};
 use crate::repository::{GitBackendConfig, GitRef, GitRepository};
 use crate::source::{ConfigQuery, ConfigResult, ConfigSource};
 use crate::sync::{GitState, RefreshConfig, RefreshHandle, RefreshScheduler};
@@ -26,6 +30,8 @@ pub struct GitBackend {
     resolver: ConfigFileResolver,
     /// Optional refresh handle.
     refresh_handle: Option<RefreshHandle>,
+    /// Compiled schemas by commit and application.
+    schemas: SchemaCache,
 }
 
 impl GitBackend {
@@ -44,8 +50,7 @@ impl GitBackend {
         let commit = repository.checkout(&default_ref).await?;
         state.record_success(&commit);
 
-        let resolver =
-            ConfigFileResolver::new(config.local_path().clone(), config.search_paths().to_vec());
+        let resolver = file_resolver(&config, Arc::new(FormatRegistry::builtin()));
 
         info!(
             "Git backend initialized: {} at commit {}",
@@ -59,6 +64,7 @@ impl GitBackend {
             config,
             resolver,
             refresh_handle: None,
+            schemas: SchemaCache::default(),
         })
     }
 
@@ -80,6 +86,14 @@ impl GitBackend {
         Ok(backend)
     }
 
+    /// Uses the given format registry to recognize and parse config files.
+    ///
+    /// Allows serving custom formats in addition to the built-in ones.
+    pub fn with_format_registry(mut self, formats: Arc<FormatRegistry>) -> Self {
+        self.resolver = file_resolver(&self.config, formats);
+        self
+    }
+
     /// Returns the current commit SHA.
     pub fn current_commit(&self) -> Option<String> {
         self.state.commit()
@@ -101,6 +115,70 @@ impl GitBackend {
             handle.stop();
         }
     }
+
+    /// Returns the schema for `application` in the checkout of `commit`.
+    fn load_schema(
+        &self,
+        commit: &str,
+        application: &str,
+    ) -> Result<CachedSchema, ConfigSourceError> {
+        self.schemas.get_or_load(commit, application, || {
+            self.resolver.load_schema(application)
+        })
+    }
+}
+
+/// Creates the file resolver for a backend configuration.
+fn file_resolver(config: &GitBackendConfig, formats: Arc<FormatRegistry>) -> ConfigFileResolver {
+    let resolver =
+        ConfigFileResolver::new(config.local_path().clone(), config.search_paths().to_vec())
+            .with_format_registry(formats)
+            .with_duplicate_keys(config.duplicate_keys());
+
+    match config.sops_age_key_file() {
+        Some(identity) => {
+            resolver.with_sops(Arc::new(SopsDecryptor::new(AgeIdentityFile::new(identity))))
+        },
+        None => resolver,
+    }
+}
+
+/// Maximum number of compiled schemas kept before the cache is cleared.
+const SCHEMA_CACHE_CAPACITY: usize = 256;
+
+/// A compiled schema, or `None` if the application has no schema file.
+type CachedSchema = Option<Arc<SchemaValidator>>;
+
+/// Compiled schemas keyed by commit and application.
+///
+/// A commit never changes, so a schema compiled for it stays valid; only
+/// the number of entries is bounded.
+#[derive(Default)]
+struct SchemaCache {
+    entries: Mutex<HashMap<(String, String), CachedSchema>>,
+}
+
+impl SchemaCache {
+    /// Returns the schema for `application` at `commit`, loading it on a miss.
+    fn get_or_load(
+        &self,
+        commit: &str,
+        application: &str,
+        load: impl FnOnce() -> Result<Option<SchemaValidator>, ConfigSourceError>,
+    ) -> Result<CachedSchema, ConfigSourceError> {
+        let key = (commit.to_string(), application.to_string());
+        if let Some(schema) = self.entries.lock().get(&key) {
+            return Ok(schema.clone());
+        }
+
+        let schema = load()?.map(Arc::new);
+        let mut entries = self.entries.lock();
+        if entries.len() >= SCHEMA_CACHE_CAPACITY {
+            entries.clear();
+        }
+        entries.insert(key, schema.clone());
+        Ok(schema)
+    }
 }
 
 #[async_trait]
@@ -115,19 +193,42 @@ impl ConfigSource for GitBackend {
         // Checkout the requested reference
         let commit = self.repository.checkout(&git_ref).await?;
 
-        // Resolve configuration files
-        let sources = self.resolver.resolve(query, label)?;
+        // Resolve configuration files and the schema from the same checkout.
+        // A schema that cannot be loaded leaves the configuration unvalidated
+        // here; `schema` reports the error to callers that validate.
+        let (sources, warnings) = self.resolver.resolve_with_warnings(query, label)?;
+        let schema = self
+            .load_schema(&commit, query.application())
+            .unwrap_or_else(|e| {
+                warn!("Cannot load the schema for {}: {}", query.application(), e);
+                None
+            });
 
         // Build result
         let mut result = ConfigResult::new(query.application(), query.profiles().to_vec(), label);
         result.set_version(&commit);
         result.add_property_sources(sources);
+        result.add_warnings(warnings);
+        if let Some(schema) = schema {
+            result.set_schema(schema);
+        }
 
         debug!("Resolved {} property sources for {}", result.len(), query);
 
         Ok(result)
     }
 
+    async fn schema(
+        &self,
+        query: &ConfigQuery,
+    ) -> Result<Option<Arc<SchemaValidator>>, ConfigSourceError> {
+        // `fetch` attaches the schema when it loads; this is reached when it
+        // did not, so a load error surfaces here.
+        let label = query.effective_label(self.config.default_label());
+        let commit = self.repository.checkout(&GitRef::parse(label)).await?;
+        self.load_schema(&commit, query.application())
+    }
+
     async fn health_check(&self) -> Result<(), ConfigSourceError> {
         if !self.state.is_healthy()
             && let Some(error) = self.state.last_error()
@@ -189,4 +290,46 @@ impl std::fmt::Debug for GitBackend {
 mod tests {
     // Integration tests would go here
     // They require actual Git repositories to test properly
+
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_schema_cache_compiles_once_per_commit() {
+        let cache = SchemaCache::default();
+        let loads = Cell::new(0);
+        let load = || {
+            loads.set(loads.get() + 1);
+            SchemaValidator::from_json(r#"{"type": "object"}"#)
+                .map(Some)
+                .map_err(|e| ConfigSourceError::parse("myapp.schema.json", e.to_string()))
+        };
+
+        let first = cache.get_or_load("abc123", "myapp", load).unwrap().unwrap();
+        let again = cache.get_or_load("abc123", "myapp", load).unwrap().unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        assert_eq!(loads.get(), 1);
+
+        cache.get_or_load("def456", "myapp", load).unwrap();
+        assert_eq!(loads.get(), 2);
+
+        // A missing schema is cached too.
+        let none = || {
+            loads.set(loads.get() + 1);
+            Ok(None)
+        };
+        assert!(
+            cache
+                .get_or_load("abc123", "other", none)
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            cache
+                .get_or_load("abc123", "other", none)
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(loads.get(), 3);
+    }
 }
@@ -48,6 +48,22 @@ pub enum ConfigSourceError {
     /// Invalid configuration.
     #[error("invalid configuration: {0}")]
     InvalidConfig(String),
+
+    /// A write expected another version of the document.
+    #[error("{document} changed: expected version {expected}, found {actual}")]
+    VersionConflict {
+        document: String,
+        expected: String,
+        actual: String,
+    },
+
+    /// The key to delete does not exist.
+    #[error("key not found: {0}")]
+    KeyNotFound(String),
+
+    /// The source cannot be written to.
+    #[error("source is read-only: {0}")]
+    ReadOnly(String),
 }
 
 impl ConfigSourceError {
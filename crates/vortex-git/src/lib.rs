@@ -36,13 +36,21 @@ pub mod reader;
 pub mod repository;
 pub mod source;
 pub mod sync;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
 
 // Re-exports
 pub use backend::GitBackend;
 pub use error::ConfigSourceError;
-pub use reader::{ConfigFileResolver, ConfigFormat, ConfigParser};
+pub use reader::{
+    AgeIdentityFile, ConfigFileResolver, ConfigFormat, ConfigParser, DuplicateKeyPolicy,
+    FormatRegistry, ParsedFile, SopsDecryptor,
+};
 pub use repository::{GitBackendConfig, GitRef, GitRepository};
-pub use source::{ConfigQuery, ConfigResult, ConfigSource};
+pub use source::{
+    ConfigQuery, ConfigResult, ConfigSource, ConfigStore, ConfigWarning, DocumentId,
+    StoredProperties, check_version,
+};
 pub use sync::{GitState, RefreshConfig, RefreshHandle, RefreshScheduler};
 
 // Re-export vortex_core for consumers
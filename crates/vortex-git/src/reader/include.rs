@@ -0,0 +1,523 @@
+//! Expansion of included configuration fragments.
+//!
+//! An object with an [`REF_KEY`] entry (`$ref` in any format,
+//! `!include` in YAML, `@include` in properties) is replaced by the named
+//! files, merged in order, with the object's other keys merged on top. Paths
+//! are relative to the repository root, so a fragment reads the same from
+//! every file and search path, and are read from the checked-out commit like
+//! the file that includes them. A `$ref` that is not a file path, such as a
+//! `#/...` JSON pointer, a URL or a path with a `#` fragment, is left alone,
+//! so embedded JSON Schema and OpenAPI documents keep their references.
+//!
+//! Fragments may include other fragments; a file that ends up including
+//! itself is an error. For every key taken from a fragment, the fragment's
+//! path is recorded so responses can show where the value came from.
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use vortex_core::format::duplicates::DuplicateKey;
+use vortex_core::format::{FormatRegistry, REF_KEY};
+use vortex_core::merge::deep_merge;
+use vortex_core::{ConfigMap, ConfigPath, ConfigValue};
+
+use super::sops::{self, SopsDecryptor};
+use super::{ConfigParser, DuplicateKeyPolicy};
+use crate::error::ConfigSourceError;
+
+/// A configuration with its fragments included.
+#[derive(Debug)]
+pub(crate) struct Expanded {
+    /// The configuration, without any [`REF_KEY`] entries.
+    pub config: ConfigMap,
+    /// Fragment path for each key taken from a fragment, by key path.
+    pub origins: BTreeMap<String, String>,
+    /// Duplicate keys found in the fragments, with the fragment's path.
+    pub duplicates: Vec<(PathBuf, DuplicateKey)>,
+}
+
+/// Expands the includes of one file.
+pub(crate) struct IncludeExpander<'a> {
+    /// Repository root; include paths are relative to it.
+    root: &'a Path,
+    /// Formats used to parse fragments.
+    formats: &'a FormatRegistry,
+    /// How duplicate keys in fragments are handled.
+    duplicate_keys: DuplicateKeyPolicy,
+    /// Decrypts SOPS-encrypted fragments.
+    sops: Option<&'a SopsDecryptor>,
+    /// Files being expanded, outermost first, relative to `root`.
+    stack: Vec<PathBuf>,
+    /// Duplicate keys found in fragments so far.
+    duplicates: Vec<(PathBuf, DuplicateKey)>,
+}
+
+impl<'a> IncludeExpander<'a> {
+    pub(crate) fn new(
+        root: &'a Path,
+        formats: &'a FormatRegistry,
+        duplicate_keys: DuplicateKeyPolicy,
+        sops: Option<&'a SopsDecryptor>,
+    ) -> Self {
+        Self {
+            root,
+            formats,
+            duplicate_keys,
+            sops,
+            stack: Vec::new(),
+            duplicates: Vec::new(),
+        }
+    }
+
+    /// Includes the fragments referenced from `config`, read from `file`.
+    pub(crate) fn expand(
+        mut self,
+        file: &Path,
+        config: ConfigMap,
+    ) -> Result<Expanded, ConfigSourceError> {
+        let file = file.strip_prefix(self.root).unwrap_or(file).to_path_buf();
+        let (config, origins) = self.expand_file(file, config)?;
+
+        Ok(Expanded {
+            config,
+            origins,
+            duplicates: self.duplicates,
+        })
+    }
+
+    fn expand_file(
+        &mut self,
+        file: PathBuf,
+        mut config: ConfigMap,
+    ) -> Result<(ConfigMap, BTreeMap<String, String>), ConfigSourceError> {
+        self.stack.push(file);
+        let mut origins = BTreeMap::new();
+        let value = self.expand_value(
+            ConfigValue::Object(std::mem::take(config.as_inner_mut())),
+            &mut ConfigPath::root(),
+            &mut origins,
+        );
+        self.stack.pop();
+
+        match value? {
+            ConfigValue::Object(map) => Ok((ConfigMap::from_inner(map), origins)),
+            _ => unreachable!("objects expand to objects"),
+        }
+    }
+
+    fn expand_value(
+        &mut self,
+        value: ConfigValue,
+        path: &mut ConfigPath,
+        origins: &mut BTreeMap<String, String>,
+    ) -> Result<ConfigValue, ConfigSourceError> {
+        match value {
+            ConfigValue::Object(mut map) => {
+                let reference = match map.get(REF_KEY) {
+                    Some(reference) if is_include(reference) => map.shift_remove(REF_KEY),
+                    _ => None,
+                };
+                let Some(reference) = reference else {
+                    return map
+                        .into_iter()
+                        .map(|(key, value)| {
+                            path.push_key(key.as_str());
+                            let value = self.expand_value(value, path, origins);
+                            path.pop();
+                            value.map(|value| (key, value))
+                        })
+                        .collect::<Result<_, _>>()
+                        .map(ConfigValue::Object);
+                };
+
+                let references = self.references(reference, path)?;
+                let siblings = match self.expand_value(ConfigValue::Object(map), path, origins)? {
+                    ConfigValue::Object(map) => ConfigMap::from_inner(map),
+                    _ => unreachable!("objects expand to objects"),
+                };
+
+                let mut merged = ConfigMap::new();
+                let mut included = BTreeMap::new();
+                for reference in references {
+                    let (fragment, fragment_origins, name) = self.include(&reference, path)?;
+                    for leaf in leaves(&fragment) {
+                        let key = join(path, &leaf);
+                        remove_under(&mut included, &key);
+                        let origin = fragment_origins.get(&leaf.to_string()).unwrap_or(&name);
+                        included.insert(key, origin.clone());
+                    }
+                    deep_merge(&mut merged, &fragment);
+                }
+
+                // Keys written next to the reference win over included ones.
+                for leaf in leaves(&siblings) {
+                    remove_under(&mut included, &join(path, &leaf));
+                }
+                deep_merge(&mut merged, &siblings);
+                origins.extend(included);
+
+                Ok(ConfigValue::Object(std::mem::take(merged.as_inner_mut())))
+            },
+            ConfigValue::Array(items) => items
+                .into_iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    path.push_index(index);
+                    let item = self.expand_value(item, path, origins);
+                    path.pop();
+                    item
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(ConfigValue::Array),
+            other => Ok(other),
+        }
+    }
+
+    /// Returns the paths named by a [`REF_KEY`] value.
+    fn references(
+        &self,
+        reference: ConfigValue,
+        path: &ConfigPath,
+    ) -> Result<Vec<String>, ConfigSourceError> {
+        let items = match reference {
+            ConfigValue::Array(items) => items,
+            single => vec![single],
+        };
+
+        items
+            .into_iter()
+            .map(|item| match item {
+                ConfigValue::String(s) if !s.trim().is_empty() => Ok(s.trim().to_string()),
+                _ => Err(self.error(format!(
+                    "'{}' at '{}' must be a file path or a list of file paths",
+                    REF_KEY,
+                    location(path)
+                ))),
+            })
+            .collect()
+    }
+
+    /// Reads, parses and expands the fragment at `reference`.
+    ///
+    /// Returns the fragment, the origins of its own includes and its path.
+    fn include(
+        &mut self,
+        reference: &str,
+        path: &ConfigPath,
+    ) -> Result<(ConfigMap, BTreeMap<String, String>, String), ConfigSourceError> {
+        let relative = normalize(reference).ok_or_else(|| {
+            self.error(format!(
+                "include '{}' at '{}' must be a path inside the repository, \
+                 relative to its root",
+                reference,
+                location(path)
+            ))
+        })?;
+
+        let full = self.root.join(&relative);
+        if !full.is_file() {
+            return Err(self.error(format!(
+                "included file '{}' at '{}' does not exist",
+                reference,
+                location(path)
+            )));
+        }
+        // Symlinks must not lead out of the repository either.
+        if let (Ok(root), Ok(target)) = (self.root.canonicalize(), full.canonicalize())
+            && !target.starts_with(root)
+        {
+            return Err(self.error(format!(
+                "included file '{}' at '{}' is outside the repository",
+                reference,
+                location(path)
+            )));
+        }
+
+        if self.stack.contains(&relative) {
+            let chain = self
+                .stack
+                .iter()
+                .chain(std::iter::once(&relative))
+                .map(|file| display(file))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(self.error(format!("include cycle: {}", chain)));
+        }
+
+        let parsed = ConfigParser::parse_file_strict(self.formats, &full, self.duplicate_keys)?;
+        self.duplicates.extend(
+            parsed
+                .duplicates
+                .into_iter()
+                .map(|duplicate| (relative.clone(), duplicate)),
+        );
+
+        let config = sops::decrypt(self.sops, &full, parsed.config)?;
+        let name = display(&relative);
+        let (fragment, origins) = self.expand_file(relative, config)?;
+        Ok((fragment, origins, name))
+    }
+
+    /// Creates an error for the file currently being expanded.
+    fn error(&self, reason: String) -> ConfigSourceError {
+        let file = self
+            .stack
+            .last()
+            .map(PathBuf::as_path)
+            .unwrap_or(Path::new(""));
+        ConfigSourceError::parse(self.root.join(file), reason)
+    }
+}
+
+/// Returns whether a [`REF_KEY`] value names files to include rather than
+/// a JSON reference such as `#/definitions/port` or `https://...`.
+fn is_include(reference: &ConfigValue) -> bool {
+    let is_document = |s: &str| s.contains('#') || s.contains("://");
+    match reference {
+        ConfigValue::String(s) => !is_document(s),
+        ConfigValue::Array(items) => !items
+            .iter()
+            .any(|item| item.as_str().is_some_and(is_document)),
+        _ => true,
+    }
+}
+
+/// Normalizes an include path, rejecting absolute paths and `..`.
+fn normalize(reference: &str) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in Path::new(reference).components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {},
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!normalized.as_os_str().is_empty()).then_some(normalized)
+}
+
+/// Returns the paths of the values in `config` that are not objects.
+fn leaves(config: &ConfigMap) -> Vec<ConfigPath> {
+    fn walk(value: &ConfigValue, path: &mut ConfigPath, out: &mut Vec<ConfigPath>) {
+        match value {
+            ConfigValue::Object(map) => {
+                for (key, value) in map {
+                    path.push_key(key.as_str());
+                    walk(value, path, out);
+                    path.pop();
+                }
+            },
+            _ => out.push(path.clone()),
+        }
+    }
+
+    let mut out = Vec::new();
+    for (key, value) in config.as_inner() {
+        walk(value, &mut ConfigPath::root().key(key.as_str()), &mut out);
+    }
+    out
+}
+
+/// Appends `leaf` to `path`, as a key path string.
+fn join(path: &ConfigPath, leaf: &ConfigPath) -> String {
+    let mut joined = path.clone();
+    for segment in leaf.segments() {
+        match segment {
+            vortex_core::PathSegment::Key(key) => joined.push_key(key.as_str()),
+            vortex_core::PathSegment::Index(index) => joined.push_index(*index),
+        }
+    }
+    joined.to_string()
+}
+
+/// Removes `key` and every key below it.
+fn remove_under(origins: &mut BTreeMap<String, String>, key: &str) {
+    origins.retain(|existing, _| {
+        existing != key
+            && !existing
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('['))
+    });
+}
+
+fn location(path: &ConfigPath) -> String {
+    if path.is_root() {
+        "(root)".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Formats a repository path with `/` separators.
+fn display(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::repo;
+    use tempfile::TempDir;
+
+    fn expand(dir: &TempDir, file: &str) -> Result<Expanded, ConfigSourceError> {
+        let formats = FormatRegistry::builtin();
+        let path = dir.path().join(file);
+        let config = ConfigParser::parse_file(&path).unwrap();
+        IncludeExpander::new(dir.path(), &formats, DuplicateKeyPolicy::Warn, None)
+            .expand(&path, config)
+    }
+
+    #[test]
+    fn test_include_in_each_format_with_overrides() {
+        let dir = repo(&[
+            (
+                "shared/kafka.yml",
+                "kafka:\n  bootstrap: broker:9092\n  acks: all\n",
+            ),
+            (
+                "shared/db.json",
+                r#"{"url": "jdbc:pg", "pool": {"size": 10}}"#,
+            ),
+            ("shared/log.properties", "logging.level=INFO\n"),
+            (
+                "orders.yml",
+                "<<: !include shared/kafka.yml\nkafka:\n  acks: '1'\ndb: !include shared/db.json\n",
+            ),
+            (
+                "billing.json",
+                r#"{"db": {"$ref": "shared/db.json", "pool": {"size": 20}}}"#,
+            ),
+            (
+                "audit.properties",
+                "@include shared/log.properties\nlogging.file=a.log\n",
+            ),
+        ]);
+
+        let orders = expand(&dir, "orders.yml").unwrap();
+        assert_eq!(
+            orders.config.get("kafka.bootstrap").unwrap().as_str(),
+            Some("broker:9092")
+        );
+        assert_eq!(orders.config.get("kafka.acks").unwrap().as_str(), Some("1"));
+        assert_eq!(
+            orders.config.get("db.pool.size"),
+            Some(&ConfigValue::Integer(10))
+        );
+        assert_eq!(
+            orders.origins,
+            BTreeMap::from([
+                ("db.pool.size".to_string(), "shared/db.json".to_string()),
+                ("db.url".to_string(), "shared/db.json".to_string()),
+                (
+                    "kafka.bootstrap".to_string(),
+                    "shared/kafka.yml".to_string()
+                ),
+            ])
+        );
+
+        let billing = expand(&dir, "billing.json").unwrap();
+        assert_eq!(
+            billing.config.get("db.pool.size"),
+            Some(&ConfigValue::Integer(20))
+        );
+        assert!(
+            billing.config.as_inner()["db"]
+                .as_object()
+                .unwrap()
+                .get(REF_KEY)
+                .is_none()
+        );
+        assert_eq!(billing.origins.len(), 1);
+
+        let audit = expand(&dir, "audit.properties").unwrap();
+        assert_eq!(
+            audit.config.get("logging.level").unwrap().as_str(),
+            Some("INFO")
+        );
+        assert_eq!(
+            audit.config.get("logging.file").unwrap().as_str(),
+            Some("a.log")
+        );
+        assert_eq!(audit.origins["logging.level"], "shared/log.properties");
+    }
+
+    #[test]
+    fn test_json_references_are_not_includes() {
+        let dir = repo(&[(
+            "api.json",
+            r##"{"openapi": {
+                "schema": {"$ref": "#/components/schemas/Order"},
+                "remote": {"$ref": "https://example.com/order.json"},
+                "external": {"$ref": "schemas/order.json#/Order"}
+            }}"##,
+        )]);
+
+        let api = expand(&dir, "api.json").unwrap();
+
+        for (key, reference) in [
+            ("openapi.schema", "#/components/schemas/Order"),
+            ("openapi.remote", "https://example.com/order.json"),
+            ("openapi.external", "schemas/order.json#/Order"),
+        ] {
+            let object = api.config.get(key).unwrap().as_object().unwrap();
+            assert_eq!(object[REF_KEY].as_str(), Some(reference));
+        }
+        assert!(api.origins.is_empty());
+    }
+
+    #[test]
+    fn test_nested_includes_keep_innermost_origin() {
+        let dir = repo(&[
+            ("shared/base.yml", "timeout: 30\n"),
+            (
+                "shared/client.yml",
+                "<<: !include shared/base.yml\nretries: 3\n",
+            ),
+            ("app.yml", "client: !include shared/client.yml\n"),
+        ]);
+
+        let expanded = expand(&dir, "app.yml").unwrap();
+
+        assert_eq!(expanded.origins["client.timeout"], "shared/base.yml");
+        assert_eq!(expanded.origins["client.retries"], "shared/client.yml");
+    }
+
+    #[test]
+    fn test_include_cycle_is_rejected() {
+        let dir = repo(&[
+            ("a.yml", "x: !include b.yml\n"),
+            ("b.yml", "y: !include ./a.yml\n"),
+        ]);
+
+        let err = expand(&dir, "a.yml").unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("include cycle: a.yml -> b.yml -> a.yml"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_include_paths_must_stay_in_repository() {
+        let dir = repo(&[
+            ("escape.yml", "x: !include ../secrets.yml\n"),
+            ("absolute.yml", "x: !include /etc/passwd\n"),
+            ("missing.yml", "x: !include shared/none.yml\n"),
+            ("invalid.json", r#"{"x": {"$ref": 42}}"#),
+        ]);
+
+        for (file, message) in [
+            ("escape.yml", "must be a path inside the repository"),
+            ("absolute.yml", "must be a path inside the repository"),
+            ("missing.yml", "'shared/none.yml' at 'x' does not exist"),
+            ("invalid.json", "'$ref' at 'x' must be a file path"),
+        ] {
+            let err = expand(&dir, file).unwrap_err();
+            assert!(err.to_string().contains(message), "{}: {}", file, err);
+        }
+    }
+}
@@ -3,10 +3,12 @@
 //! This module provides functionality for reading and parsing configuration files
 //! following Spring Cloud Config conventions.
 
-mod format;
+mod include;
 mod parser;
 mod resolver;
+pub mod sops;
 
-pub use format::ConfigFormat;
-pub use parser::ConfigParser;
+pub use parser::{ConfigParser, DuplicateKeyPolicy, ParsedFile};
 pub use resolver::ConfigFileResolver;
+pub use sops::{AgeIdentityFile, SopsDecryptor, SopsKeySource};
+pub use vortex_core::format::{ConfigFormat, FormatRegistry};
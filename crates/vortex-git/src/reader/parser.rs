@@ -1,64 +1,171 @@
 //! Configuration file parsing.
 
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
 
+use serde::{Deserialize, Serialize};
 use vortex_core::ConfigMap;
-use vortex_core::format::FormatParser;
-use vortex_core::format::properties::PropertiesFormat;
+use vortex_core::format::FormatRegistry;
+use vortex_core::format::duplicates::{DuplicateKey, find_duplicate_keys};
 
 use super::ConfigFormat;
 use crate::error::ConfigSourceError;
 
+/// How keys defined more than once in a configuration file are handled.
+///
+/// Plain deserialization keeps the last value of a repeated key, so two
+/// `datasource:` blocks in one file silently shadow each other.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateKeyPolicy {
+    /// Keep the last value without checking.
+    Ignore,
+    /// Keep the last value and report each duplicate.
+    #[default]
+    Warn,
+    /// Reject the file.
+    Error,
+}
+
+impl FromStr for DuplicateKeyPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ignore" | "off" => Ok(Self::Ignore),
+            "warn" | "warning" => Ok(Self::Warn),
+            "error" | "reject" => Ok(Self::Error),
+            other => Err(format!(
+                "invalid duplicate key policy '{}': expected ignore, warn or error",
+                other
+            )),
+        }
+    }
+}
+
+/// A parsed configuration file and the duplicate keys found in it.
+#[derive(Debug, Clone)]
+pub struct ParsedFile {
+    /// The parsed configuration (last value wins for duplicates).
+    pub config: ConfigMap,
+    /// Repeated keys, empty unless the policy is [`DuplicateKeyPolicy::Warn`].
+    pub duplicates: Vec<DuplicateKey>,
+}
+
 /// Parser for configuration files.
+///
+/// Parsing is delegated to a [`FormatRegistry`]. The plain `parse`/`parse_file`
+/// functions use the built-in formats; the `*_with` variants accept a custom
+/// registry.
 pub struct ConfigParser;
 
 impl ConfigParser {
     /// Parses configuration content based on the specified format.
     pub fn parse(content: &str, format: ConfigFormat) -> Result<ConfigMap, ConfigSourceError> {
-        match format {
-            ConfigFormat::Yaml => Self::parse_yaml(content),
-            ConfigFormat::Json => Self::parse_json(content),
-            ConfigFormat::Properties => Self::parse_properties(content),
-        }
+        Self::parse_with(builtin_registry(), content, format.name())
+    }
+
+    /// Parses configuration content with the named format from `registry`.
+    pub fn parse_with(
+        registry: &FormatRegistry,
+        content: &str,
+        format_name: &str,
+    ) -> Result<ConfigMap, ConfigSourceError> {
+        let entry = registry
+            .by_name(format_name)
+            .filter(|e| e.can_parse())
+            .ok_or_else(|| ConfigSourceError::UnsupportedFormat(format_name.to_string()))?;
+
+        entry
+            .parse(content)
+            .map_err(|e| ConfigSourceError::parse("", e.to_string()))
     }
 
     /// Parses configuration from a file, detecting format from extension.
     pub fn parse_file(path: &Path) -> Result<ConfigMap, ConfigSourceError> {
-        let format = ConfigFormat::from_path(path).ok_or_else(|| {
-            ConfigSourceError::UnsupportedFormat(
-                path.extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("unknown")
-                    .to_string(),
-            )
-        })?;
+        Self::parse_file_with(builtin_registry(), path)
+    }
+
+    /// Parses configuration from a file using the formats in `registry`.
+    pub fn parse_file_with(
+        registry: &FormatRegistry,
+        path: &Path,
+    ) -> Result<ConfigMap, ConfigSourceError> {
+        let entry = registry
+            .by_path(path)
+            .filter(|e| e.can_parse())
+            .ok_or_else(|| {
+                ConfigSourceError::UnsupportedFormat(
+                    path.extension()
+                        .and_then(|e| e.to_str())
+                        .unwrap_or("unknown")
+                        .to_string(),
+                )
+            })?;
 
         let content = std::fs::read_to_string(path)?;
-        Self::parse(&content, format).map_err(|e| match e {
-            ConfigSourceError::Parse { reason, .. } => ConfigSourceError::parse(path, reason),
-            other => other,
-        })
+        entry
+            .parse(&content)
+            .map_err(|e| ConfigSourceError::parse(path, e.to_string()))
     }
 
-    /// Parses YAML content.
-    fn parse_yaml(content: &str) -> Result<ConfigMap, ConfigSourceError> {
-        ConfigMap::from_yaml(content).map_err(|e| ConfigSourceError::parse("", e.to_string()))
-    }
+    /// Parses a file and checks it for duplicate keys according to `policy`.
+    ///
+    /// Only built-in formats are checked; custom formats parse as with
+    /// [`parse_file_with`](Self::parse_file_with).
+    ///
+    /// # Errors
+    ///
+    /// With [`DuplicateKeyPolicy::Error`], a file with duplicate keys fails
+    /// with a parse error listing every duplicate and its line.
+    pub fn parse_file_strict(
+        registry: &FormatRegistry,
+        path: &Path,
+        policy: DuplicateKeyPolicy,
+    ) -> Result<ParsedFile, ConfigSourceError> {
+        let config = Self::parse_file_with(registry, path)?;
+        let format = registry
+            .by_path(path)
+            .and_then(|entry| ConfigFormat::from_name(entry.name()));
 
-    /// Parses JSON content.
-    fn parse_json(content: &str) -> Result<ConfigMap, ConfigSourceError> {
-        ConfigMap::from_json(content).map_err(|e| ConfigSourceError::parse("", e.to_string()))
-    }
+        let duplicates = match (policy, format) {
+            (DuplicateKeyPolicy::Ignore, _) | (_, None) => Vec::new(),
+            (_, Some(format)) => {
+                let content = std::fs::read_to_string(path)?;
+                // The file already parsed; a scanner error only means the
+                // check does not apply (e.g. YAML complex keys).
+                find_duplicate_keys(format, &content).unwrap_or_default()
+            },
+        };
 
-    /// Parses Java Properties content.
-    fn parse_properties(content: &str) -> Result<ConfigMap, ConfigSourceError> {
-        let parser = PropertiesFormat;
-        parser
-            .parse(content)
-            .map_err(|e: vortex_core::VortexError| ConfigSourceError::parse("", e.to_string()))
+        if policy == DuplicateKeyPolicy::Error && !duplicates.is_empty() {
+            let reason = duplicates
+                .iter()
+                .map(describe_duplicate)
+                .collect::<Vec<_>>()
+                .join("; ");
+            return Err(ConfigSourceError::parse(path, reason));
+        }
+
+        Ok(ParsedFile { config, duplicates })
     }
 }
 
+/// Describes a duplicate key for error and warning messages.
+pub(crate) fn describe_duplicate(duplicate: &DuplicateKey) -> String {
+    format!(
+        "duplicate key '{}' at line {} (first defined at line {})",
+        duplicate.path, duplicate.line, duplicate.first_line
+    )
+}
+
+/// Returns the shared registry of built-in formats.
+fn builtin_registry() -> &'static FormatRegistry {
+    static REGISTRY: OnceLock<FormatRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(FormatRegistry::builtin)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -134,6 +241,63 @@ app.debug=true
         );
     }
 
+    #[test]
+    fn test_parse_toml() {
+        let toml = r#"
+[server]
+port = 8080
+host = "localhost"
+
+[app]
+released = 2024-01-15T10:00:00Z
+"#;
+
+        let map = ConfigParser::parse(toml, ConfigFormat::Toml).unwrap();
+        assert_eq!(map.get("server.port"), Some(&ConfigValue::Integer(8080)));
+        assert_eq!(
+            map.get("server.host"),
+            Some(&ConfigValue::String("localhost".to_string()))
+        );
+        assert_eq!(
+            map.get("app.released"),
+            Some(&ConfigValue::DateTime("2024-01-15T10:00:00Z".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_hocon() {
+        let hocon = r#"
+server {
+  port = 8080
+  host = localhost
+}
+"#;
+
+        let map = ConfigParser::parse(hocon, ConfigFormat::Hocon).unwrap();
+        assert_eq!(map.get("server.port"), Some(&ConfigValue::Integer(8080)));
+        assert_eq!(
+            map.get("server.host"),
+            Some(&ConfigValue::String("localhost".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_ini_and_env() {
+        let ini = "[server]\nport = 8080\n";
+        let map = ConfigParser::parse(ini, ConfigFormat::Ini).unwrap();
+        assert_eq!(
+            map.get("server.port"),
+            Some(&ConfigValue::String("8080".to_string()))
+        );
+
+        let env = "SERVER_PORT=8080\n";
+        let map = ConfigParser::parse(env, ConfigFormat::Env).unwrap();
+        assert_eq!(
+            map.get("server.port"),
+            Some(&ConfigValue::String("8080".to_string()))
+        );
+    }
+
     #[test]
     fn test_parse_yaml_with_arrays() {
         let yaml = r#"
@@ -162,10 +326,94 @@ servers:
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_with_custom_registry() {
+        use vortex_core::format::FormatEntry;
+        use vortex_core::format::json::JsonFormat;
+
+        let mut registry = FormatRegistry::builtin();
+        registry.register(
+            FormatEntry::new("json5")
+                .extensions(["json5"])
+                .parser(JsonFormat),
+        );
+
+        let map = ConfigParser::parse_with(&registry, r#"{"a": 1}"#, "json5").unwrap();
+        assert_eq!(map.get("a"), Some(&ConfigValue::Integer(1)));
+
+        let result = ConfigParser::parse_with(&registry, "", "unknown");
+        assert!(matches!(
+            result,
+            Err(ConfigSourceError::UnsupportedFormat(_))
+        ));
+    }
+
     #[test]
     fn test_parse_invalid_json() {
         let invalid = "{ invalid }";
         let result = ConfigParser::parse(invalid, ConfigFormat::Json);
         assert!(result.is_err());
     }
+
+    fn write(dir: &tempfile::TempDir, name: &str, content: &str) -> std::path::PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_file_strict_duplicate_policies() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "app.yml",
+            "datasource:\n  url: a\nport: 80\ndatasource:\n  url: b\n",
+        );
+        let registry = FormatRegistry::builtin();
+
+        let parsed =
+            ConfigParser::parse_file_strict(&registry, &path, DuplicateKeyPolicy::Warn).unwrap();
+        assert_eq!(
+            parsed.config.get("datasource.url"),
+            Some(&ConfigValue::String("b".to_string()))
+        );
+        assert_eq!(parsed.duplicates.len(), 1);
+        assert_eq!(parsed.duplicates[0].line, 4);
+
+        let parsed =
+            ConfigParser::parse_file_strict(&registry, &path, DuplicateKeyPolicy::Ignore).unwrap();
+        assert!(parsed.duplicates.is_empty());
+
+        let err = ConfigParser::parse_file_strict(&registry, &path, DuplicateKeyPolicy::Error)
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("duplicate key 'datasource' at line 4 (first defined at line 1)")
+        );
+    }
+
+    #[test]
+    fn test_parse_file_strict_json_and_clean_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let registry = FormatRegistry::builtin();
+
+        let json = write(&dir, "app.json", "{\n  \"a\": 1,\n  \"a\": 2\n}");
+        let parsed =
+            ConfigParser::parse_file_strict(&registry, &json, DuplicateKeyPolicy::Warn).unwrap();
+        assert_eq!(parsed.duplicates[0].first_line, 2);
+        assert_eq!(parsed.duplicates[0].line, 3);
+
+        let clean = write(&dir, "clean.yml", "a: 1\nb: 2\n");
+        let parsed =
+            ConfigParser::parse_file_strict(&registry, &clean, DuplicateKeyPolicy::Error).unwrap();
+        assert!(parsed.duplicates.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_key_policy_from_str() {
+        assert_eq!("warn".parse(), Ok(DuplicateKeyPolicy::Warn));
+        assert_eq!(" ERROR ".parse(), Ok(DuplicateKeyPolicy::Error));
+        assert_eq!("ignore".parse(), Ok(DuplicateKeyPolicy::Ignore));
+        assert!("strict".parse::<DuplicateKeyPolicy>().is_err());
+    }
 }
@@ -1,13 +1,19 @@
 //! Configuration file resolution following Spring Cloud Config conventions.
 
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use tracing::debug;
+use vortex_core::format::FormatRegistry;
+use vortex_core::validation::SchemaValidator;
 use vortex_core::{ConfigMap, PropertySource};
 
-use super::{ConfigFormat, ConfigParser};
+use super::include::IncludeExpander;
+use super::parser::describe_duplicate;
+use super::sops::{self, SOPS_EXTENSION_MARKER, SopsDecryptor};
+use super::{ConfigParser, DuplicateKeyPolicy};
 use crate::error::ConfigSourceError;
-use crate::source::ConfigQuery;
+use crate::source::{ConfigQuery, ConfigWarning};
 
 /// Resolves and reads configuration files from a repository.
 ///
@@ -16,11 +22,33 @@ use crate::source::ConfigQuery;
 /// - `application-{profile}.yml` - Profile-specific base config
 /// - `{application}.yml` - Application-specific config
 /// - `{application}-{profile}.yml` - Application + profile config
+///
+/// Each name is tried with every extension known to the format registry
+/// (built-in: `.yml`, `.yaml`, `.json`, `.properties`, `.toml`, `.conf`,
+/// `.ini`, `.env`), in registration order; the first match wins.
+///
+/// Fragments included with `$ref`, `!include` or `@include` are read
+/// relative to the repository root and merged in place; keys taken from them
+/// are listed in [`PropertySource::origins`].
+///
+/// Files encrypted with SOPS are decrypted with the configured
+/// [`SopsDecryptor`]; `{name}.sops.{ext}` is tried after `{name}.{ext}`.
+///
+/// JSON Schemas for applications live in `schemas/{application}.schema.json`
+/// at the repository root; that directory is never read as configuration.
 pub struct ConfigFileResolver {
     /// Base path of the repository.
     base_path: PathBuf,
     /// Search paths within the repository.
     search_paths: Vec<String>,
+    /// Formats used to recognize and parse files.
+    formats: Arc<FormatRegistry>,
+    /// How duplicate keys in resolved files are handled.
+    duplicate_keys: DuplicateKeyPolicy,
+    /// Decrypts SOPS files; without it they are rejected.
+    sops: Option<Arc<SopsDecryptor>>,
+    /// Prefix of property source names, e.g. `git` in `git:main:app.yml`.
+    source_scheme: String,
 }
 
 impl ConfigFileResolver {
@@ -29,9 +57,43 @@ impl ConfigFileResolver {
         Self {
             base_path: base_path.into(),
             search_paths,
+            formats: Arc::new(FormatRegistry::builtin()),
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            sops: None,
+            source_scheme: "git".to_string(),
         }
     }
 
+    /// Uses the given format registry instead of the built-in formats.
+    pub fn with_format_registry(mut self, formats: Arc<FormatRegistry>) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    /// Sets how duplicate keys in resolved files are handled.
+    pub fn with_duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Decrypts SOPS-encrypted files with `sops`.
+    pub fn with_sops(mut self, sops: Arc<SopsDecryptor>) -> Self {
+        self.sops = Some(sops);
+        self
+    }
+
+    /// Names property sources `{scheme}:{label}:{path}` instead of
+    /// `git:{label}:{path}`.
+    pub fn with_source_scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.source_scheme = scheme.into();
+        self
+    }
+
+    /// Returns the format registry used by this resolver.
+    pub fn format_registry(&self) -> &Arc<FormatRegistry> {
+        &self.formats
+    }
+
     /// Resolves configuration for the given query.
     ///
     /// Returns property sources in order of precedence (highest first):
@@ -44,7 +106,19 @@ impl ConfigFileResolver {
         query: &ConfigQuery,
         label: &str,
     ) -> Result<Vec<PropertySource>, ConfigSourceError> {
+        self.resolve_with_warnings(query, label)
+            .map(|(sources, _)| sources)
+    }
+
+    /// Resolves configuration like [`resolve`](Self::resolve), also returning
+    /// the warnings found in the resolved files (e.g. duplicate keys).
+    pub fn resolve_with_warnings(
+        &self,
+        query: &ConfigQuery,
+        label: &str,
+    ) -> Result<(Vec<PropertySource>, Vec<ConfigWarning>), ConfigSourceError> {
         let mut sources = Vec::new();
+        let mut warnings = Vec::new();
 
         let effective_search_paths = if self.search_paths.is_empty() {
             vec!["".to_string()]
@@ -60,29 +134,37 @@ impl ConfigFileResolver {
             };
 
             // 1. application.yml (lowest priority)
-            if let Some(source) = self.try_read_config(&base, "application", None, label)? {
+            if let Some(source) =
+                self.try_read_config(&base, "application", None, label, &mut warnings)?
+            {
                 sources.push(source);
             }
 
             // 2. application-{profile}.yml
             for profile in query.profiles() {
                 if let Some(source) =
-                    self.try_read_config(&base, "application", Some(profile), label)?
+                    self.try_read_config(&base, "application", Some(profile), label, &mut warnings)?
                 {
                     sources.push(source);
                 }
             }
 
             // 3. {app}.yml
-            if let Some(source) = self.try_read_config(&base, query.application(), None, label)? {
+            if let Some(source) =
+                self.try_read_config(&base, query.application(), None, label, &mut warnings)?
+            {
                 sources.push(source);
             }
 
             // 4. {app}-{profile}.yml (highest priority)
             for profile in query.profiles() {
-                if let Some(source) =
-                    self.try_read_config(&base, query.application(), Some(profile), label)?
-                {
+                if let Some(source) = self.try_read_config(
+                    &base,
+                    query.application(),
+                    Some(profile),
+                    label,
+                    &mut warnings,
+                )? {
                     sources.push(source);
                 }
             }
@@ -93,34 +175,84 @@ impl ConfigFileResolver {
 
         debug!("Resolved {} property sources for {}", sources.len(), query);
 
-        Ok(sources)
+        Ok((sources, warnings))
+    }
+
+    /// Loads the JSON Schema for an application, if the repository has one.
+    ///
+    /// Reads `schemas/{application}.schema.json` from the repository root.
+    pub fn load_schema(
+        &self,
+        application: &str,
+    ) -> Result<Option<SchemaValidator>, ConfigSourceError> {
+        let path = self
+            .base_path
+            .join(SCHEMAS_DIR)
+            .join(format!("{}.schema.json", application));
+
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        debug!("Reading schema file: {:?}", path);
+
+        let content = std::fs::read_to_string(&path)?;
+        SchemaValidator::from_json(&content)
+            .map(Some)
+            .map_err(|e| ConfigSourceError::parse(&path, e.to_string()))
     }
 
     /// Tries to read a configuration file, returning None if not found.
+    ///
+    /// Duplicate keys found in the file are added to `warnings`.
     fn try_read_config(
         &self,
         base: &Path,
         name: &str,
         profile: Option<&str>,
         label: &str,
+        warnings: &mut Vec<ConfigWarning>,
     ) -> Result<Option<PropertySource>, ConfigSourceError> {
         let filename = match profile {
             Some(p) => format!("{}-{}", name, p),
             None => name.to_string(),
         };
 
-        // Try each supported format
-        for format in ConfigFormat::all() {
-            for ext in format.extensions() {
-                let file_path = base.join(format!("{}.{}", filename, ext));
+        // Try each registered format, plain before SOPS-encrypted
+        for format in self.formats.parsers() {
+            for ext in format.file_extensions() {
+                let candidates = [
+                    base.join(format!("{}.{}", filename, ext)),
+                    base.join(format!("{}.{}.{}", filename, SOPS_EXTENSION_MARKER, ext)),
+                ];
 
-                if file_path.exists() {
+                if let Some(file_path) = candidates.into_iter().find(|path| path.exists()) {
                     debug!("Reading config file: {:?}", file_path);
 
-                    let config = ConfigParser::parse_file(&file_path)?;
+                    let parsed = ConfigParser::parse_file_strict(
+                        &self.formats,
+                        &file_path,
+                        self.duplicate_keys,
+                    )?;
+                    let config = sops::decrypt(self.sops.as_deref(), &file_path, parsed.config)?;
+                    let expanded = self.includes().expand(&file_path, config)?;
                     let source_name = self.make_source_name(&file_path, label);
 
-                    return Ok(Some(PropertySource::new(source_name, config)));
+                    warnings.extend(parsed.duplicates.iter().map(|duplicate| {
+                        ConfigWarning::new(&source_name, describe_duplicate(duplicate))
+                            .at_line(duplicate.line)
+                    }));
+                    warnings.extend(expanded.duplicates.iter().map(|(fragment, duplicate)| {
+                        ConfigWarning::new(
+                            self.make_source_name(&self.base_path.join(fragment), label),
+                            describe_duplicate(duplicate),
+                        )
+                        .at_line(duplicate.line)
+                    }));
+
+                    let mut source = PropertySource::new(source_name, expanded.config);
+                    source.origins = expanded.origins;
+                    return Ok(Some(source));
                 }
             }
         }
@@ -128,6 +260,16 @@ impl ConfigFileResolver {
         Ok(None)
     }
 
+    /// Creates an expander for the includes of one file.
+    fn includes(&self) -> IncludeExpander<'_> {
+        IncludeExpander::new(
+            &self.base_path,
+            &self.formats,
+            self.duplicate_keys,
+            self.sops.as_deref(),
+        )
+    }
+
     /// Creates a property source name following Spring Cloud Config conventions.
     fn make_source_name(&self, path: &Path, label: &str) -> String {
         let relative = path
@@ -135,7 +277,7 @@ impl ConfigFileResolver {
             .unwrap_or(path)
             .to_string_lossy();
 
-        format!("git:{}:{}", label, relative)
+        format!("{}:{}:{}", self.source_scheme, label, relative)
     }
 
     /// Lists all configuration files in the repository.
@@ -180,13 +322,17 @@ impl ConfigFileResolver {
             let path = entry.path();
 
             if path.is_file() {
-                if ConfigFormat::from_path(&path).is_some() {
+                if self.formats.by_path(&path).is_some_and(|f| f.can_parse()) {
                     files.push(path);
                 }
             } else if path.is_dir() {
                 // Skip hidden directories and common non-config directories
                 let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                if !name.starts_with('.') && name != "node_modules" && name != "target" {
+                if !name.starts_with('.')
+                    && name != "node_modules"
+                    && name != "target"
+                    && !(name == SCHEMAS_DIR && dir == self.base_path)
+                {
                     self.find_config_files(&path, files)?;
                 }
             }
@@ -195,13 +341,18 @@ impl ConfigFileResolver {
         Ok(())
     }
 
-    /// Reads a specific configuration file.
+    /// Reads a specific configuration file, with its includes expanded.
     pub fn read_file(&self, path: &Path) -> Result<ConfigMap, ConfigSourceError> {
         let full_path = self.base_path.join(path);
-        ConfigParser::parse_file(&full_path)
+        let config = ConfigParser::parse_file_with(&self.formats, &full_path)?;
+        let config = sops::decrypt(self.sops.as_deref(), &full_path, config)?;
+        Ok(self.includes().expand(&full_path, config)?.config)
     }
 }
 
+/// Directory holding per-application JSON Schemas.
+const SCHEMAS_DIR: &str = "schemas";
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -286,6 +437,103 @@ mod tests {
         assert_eq!(files.len(), 4);
     }
 
+    #[test]
+    fn test_resolve_toml_profile() {
+        let dir = create_test_repo();
+        fs::write(
+            dir.path().join("myapp-prod.toml"),
+            "[server]\nport = 9090\n",
+        )
+        .unwrap();
+
+        let resolver = ConfigFileResolver::new(dir.path(), vec![]);
+        let query = ConfigQuery::new("myapp", vec!["prod"]);
+        let sources = resolver.resolve(&query, "main").unwrap();
+
+        assert_eq!(sources.len(), 3);
+        assert!(sources[0].name.ends_with("myapp-prod.toml"));
+        assert_eq!(
+            sources[0].config.get("server.port"),
+            Some(&vortex_core::ConfigValue::Integer(9090))
+        );
+    }
+
+    #[test]
+    fn test_resolve_hocon_and_env_sources() {
+        let dir = create_test_repo();
+        fs::write(
+            dir.path().join("billing.conf"),
+            "billing { currency = EUR }\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("billing-dev.env"), "BILLING_DEBUG=true\n").unwrap();
+
+        let resolver = ConfigFileResolver::new(dir.path(), vec![]);
+        let query = ConfigQuery::new("billing", vec!["dev"]);
+        let sources = resolver.resolve(&query, "main").unwrap();
+
+        assert_eq!(sources.len(), 4);
+        assert!(sources[0].name.ends_with("billing-dev.env"));
+        assert!(sources[1].name.ends_with("billing.conf"));
+        assert_eq!(
+            sources[1].config.get("billing.currency"),
+            Some(&vortex_core::ConfigValue::String("EUR".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_resolve_with_custom_format() {
+        use vortex_core::format::FormatEntry;
+        use vortex_core::format::json::JsonFormat;
+
+        let dir = create_test_repo();
+        fs::write(dir.path().join("myapp-qa.cfg"), r#"{"qa": true}"#).unwrap();
+
+        let mut registry = FormatRegistry::builtin();
+        registry.register(
+            FormatEntry::new("cfg")
+                .extensions(["cfg"])
+                .parser(JsonFormat),
+        );
+        let resolver =
+            ConfigFileResolver::new(dir.path(), vec![]).with_format_registry(Arc::new(registry));
+
+        let query = ConfigQuery::new("myapp", vec!["qa"]);
+        let sources = resolver.resolve(&query, "main").unwrap();
+
+        assert!(sources[0].name.ends_with("myapp-qa.cfg"));
+        assert_eq!(resolver.list_config_files().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_load_schema() {
+        let dir = create_test_repo();
+        fs::create_dir(dir.path().join("schemas")).unwrap();
+        fs::write(
+            dir.path().join("schemas/myapp.schema.json"),
+            r#"{"type": "object", "required": ["app"]}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("schemas/broken.schema.json"), "{").unwrap();
+        let resolver = ConfigFileResolver::new(dir.path(), vec![]);
+
+        let schema = resolver.load_schema("myapp").unwrap().unwrap();
+        assert!(schema.validate(&ConfigMap::new()).violations().len() == 1);
+        assert!(resolver.load_schema("other").unwrap().is_none());
+        assert!(matches!(
+            resolver.load_schema("broken"),
+            Err(ConfigSourceError::Parse { .. })
+        ));
+
+        // Schemas are not configuration files.
+        let files = resolver.list_config_files().unwrap();
+        assert!(
+            files
+                .iter()
+                .all(|f| !f.starts_with(dir.path().join("schemas")))
+        );
+    }
+
     #[test]
     fn test_source_name_format() {
         let dir = TempDir::new().unwrap();
@@ -297,5 +545,106 @@ mod tests {
 
         assert_eq!(sources.len(), 1);
         assert!(sources[0].name.starts_with("git:main:"));
+
+        let resolver = ConfigFileResolver::new(dir.path(), vec![]).with_source_scheme("file");
+        let sources = resolver.resolve(&query, "main").unwrap();
+        assert_eq!(sources[0].name, "file:main:test.yml");
+    }
+
+    #[test]
+    fn test_resolve_reports_duplicate_keys() {
+        let dir = create_test_repo();
+        fs::write(
+            dir.path().join("myapp-prod.yml"),
+            "datasource:\n  url: a\ndatasource:\n  url: b\n",
+        )
+        .unwrap();
+        let query = ConfigQuery::new("myapp", vec!["prod"]);
+
+        let resolver = ConfigFileResolver::new(dir.path(), vec![]);
+        let (sources, warnings) = resolver.resolve_with_warnings(&query, "main").unwrap();
+        assert_eq!(sources.len(), 3);
+        assert_eq!(
+            warnings,
+            vec![
+                ConfigWarning::new(
+                    "git:main:myapp-prod.yml",
+                    "duplicate key 'datasource' at line 3 (first defined at line 1)"
+                )
+                .at_line(3)
+            ]
+        );
+
+        let resolver = resolver.with_duplicate_keys(DuplicateKeyPolicy::Error);
+        assert!(matches!(
+            resolver.resolve(&query, "main"),
+            Err(ConfigSourceError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_resolve_expands_includes_from_repository_root() {
+        let dir = create_test_repo();
+        fs::create_dir_all(dir.path().join("shared")).unwrap();
+        fs::create_dir_all(dir.path().join("services")).unwrap();
+        fs::write(
+            dir.path().join("shared/kafka.yml"),
+            "kafka:\n  bootstrap: broker:9092\nkafka:\n  acks: all\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("services/myapp.yml"),
+            "<<: !include shared/kafka.yml\napp:\n  name: myapp\n",
+        )
+        .unwrap();
+        let query = ConfigQuery::new("myapp", vec![] as Vec<String>);
+
+        let resolver = ConfigFileResolver::new(dir.path(), vec!["services".to_string()]);
+        let (sources, warnings) = resolver.resolve_with_warnings(&query, "main").unwrap();
+
+        assert_eq!(sources[0].name, "git:main:services/myapp.yml");
+        assert_eq!(
+            sources[0].config.get("kafka.acks").unwrap().as_str(),
+            Some("all")
+        );
+        assert_eq!(sources[0].origins.len(), 1);
+        assert_eq!(sources[0].origins["kafka.acks"], "shared/kafka.yml");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].source, "git:main:shared/kafka.yml");
+    }
+
+    #[test]
+    fn test_resolve_decrypts_sops_files() {
+        use crate::reader::sops::tests::{TestKeys, sops_yaml};
+
+        let dir = create_test_repo();
+        fs::write(
+            dir.path().join("myapp-prod.sops.yml"),
+            sops_yaml(&[("db.password", "hunter2", "str")]),
+        )
+        .unwrap();
+        let query = ConfigQuery::new("myapp", vec!["prod"]);
+
+        let resolver = ConfigFileResolver::new(dir.path(), vec![]);
+        let err = resolver.resolve(&query, "main").unwrap_err();
+        assert!(err.to_string().contains("myapp-prod.sops.yml"));
+
+        let resolver = resolver.with_sops(Arc::new(SopsDecryptor::new(TestKeys)));
+        let sources = resolver.resolve(&query, "main").unwrap();
+        assert_eq!(sources[0].name, "git:main:myapp-prod.sops.yml");
+        assert_eq!(
+            sources[0].config.get("db.password").unwrap().as_str(),
+            Some("hunter2")
+        );
+        assert!(sources[0].config.get("sops").is_none());
+        assert_eq!(
+            resolver
+                .read_file(Path::new("myapp-prod.sops.yml"))
+                .unwrap()
+                .get("db.password")
+                .unwrap()
+                .as_str(),
+            Some("hunter2")
+        );
     }
 }
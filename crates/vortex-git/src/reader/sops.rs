@@ -0,0 +1,688 @@
+//! Decryption of SOPS-encrypted configuration files.
+//!
+//! A file encrypted with [SOPS](https://github.com/getsops/sops) keeps its
+//! keys in clear text and replaces each value with
+//! `ENC[AES256_GCM,data:...,iv:...,tag:...,type:...]`. The AES data key is
+//! stored, wrapped for each recipient, in the top-level `sops` metadata
+//! together with a MAC over all values.
+//!
+//! Files are recognized by that metadata, not by their name, so any YAML or
+//! JSON file can be encrypted; the resolver additionally looks for
+//! `{name}.sops.{ext}` next to `{name}.{ext}`. The data key is unwrapped by a
+//! [`SopsKeySource`] (by default [`AgeIdentityFile`]), values are decrypted
+//! in memory and the MAC is checked before the file takes part in resolution.
+//! Plaintext is never logged nor written to disk.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use aes_gcm::aead::consts::U32;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::aes::Aes256;
+use aes_gcm::{AesGcm, Key, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use parking_lot::Mutex;
+use sha2::{Digest, Sha512};
+use vortex_core::{ConfigMap, ConfigValue};
+
+use crate::error::ConfigSourceError;
+
+/// Top-level key holding the SOPS metadata.
+pub const SOPS_KEY: &str = "sops";
+
+/// Marker before the extension of SOPS files looked up by the resolver
+/// (`payments-prod.sops.yaml`).
+pub const SOPS_EXTENSION_MARKER: &str = "sops";
+
+/// Maximum number of unwrapped data keys kept before the cache is cleared.
+const DATA_KEY_CACHE_CAPACITY: usize = 256;
+
+/// AES-GCM with the 256-bit nonces SOPS uses.
+type SopsCipher = AesGcm<Aes256, U32>;
+
+/// Unwraps the data key of a SOPS file.
+pub trait SopsKeySource: Send + Sync {
+    /// Decrypts the data key wrapped in an `age` entry of the metadata.
+    ///
+    /// `enc` is the armored age ciphertext; `recipient` is the public key it
+    /// was encrypted for.
+    fn decrypt_age(&self, recipient: &str, enc: &str) -> Result<Vec<u8>, String>;
+}
+
+/// Unwraps data keys with an age identity file.
+///
+/// The identities are read from the file when a data key is unwrapped, which
+/// happens once per data key; the X25519 work is done in process.
+#[derive(Debug, Clone)]
+pub struct AgeIdentityFile {
+    identity: PathBuf,
+}
+
+impl AgeIdentityFile {
+    /// Uses the identities in `identity` (as written by `age-keygen`).
+    pub fn new(identity: impl Into<PathBuf>) -> Self {
+        Self {
+            identity: identity.into(),
+        }
+    }
+}
+
+impl SopsKeySource for AgeIdentityFile {
+    fn decrypt_age(&self, _recipient: &str, enc: &str) -> Result<Vec<u8>, String> {
+        let identities = age::IdentityFile::from_file(self.identity.display().to_string())
+            .map_err(|e| format!("cannot read {}: {}", self.identity.display(), e))?
+            .into_identities()
+            .map_err(|e| format!("invalid identity in {}: {}", self.identity.display(), e))?;
+
+        let decryptor =
+            age::Decryptor::new_buffered(age::armor::ArmoredReader::new(enc.as_bytes()))
+                .map_err(|e| e.to_string())?;
+        let mut reader = decryptor
+            .decrypt(identities.iter().map(|identity| identity.as_ref()))
+            .map_err(|e| e.to_string())?;
+
+        let mut key = Vec::new();
+        reader.read_to_end(&mut key).map_err(|e| e.to_string())?;
+        Ok(key)
+    }
+}
+
+/// Decrypts SOPS files.
+///
+/// Unwrapped data keys are kept in memory, so each key is unwrapped once
+/// rather than on every request. Every file and every key rotation has its
+/// own data key, so the number kept is bounded.
+pub struct SopsDecryptor {
+    keys: Arc<dyn SopsKeySource>,
+    data_keys: Mutex<HashMap<String, [u8; 32]>>,
+}
+
+impl SopsDecryptor {
+    /// Creates a decryptor unwrapping data keys with `keys`.
+    pub fn new(keys: impl SopsKeySource + 'static) -> Self {
+        Self {
+            keys: Arc::new(keys),
+            data_keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Decrypts `config`, read from `path`, if it is a SOPS file.
+    ///
+    /// Other files are returned unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Fails if no data key can be unwrapped, a value does not decrypt or
+    /// the MAC does not match the values.
+    pub fn decrypt(&self, path: &Path, config: ConfigMap) -> Result<ConfigMap, ConfigSourceError> {
+        let Some(metadata) = metadata(&config) else {
+            return Ok(config);
+        };
+        let metadata = Metadata::read(metadata).map_err(|e| ConfigSourceError::parse(path, e))?;
+        let key = self
+            .data_key(&metadata)
+            .map_err(|e| ConfigSourceError::parse(path, e))?;
+
+        decrypt_tree(config, &key, &metadata).map_err(|e| ConfigSourceError::parse(path, e))
+    }
+
+    fn data_key(&self, metadata: &Metadata) -> Result<[u8; 32], String> {
+        if metadata.age.is_empty() {
+            return Err("SOPS file has no age recipients; only age keys are supported".into());
+        }
+
+        let mut errors = Vec::new();
+        for (recipient, enc) in &metadata.age {
+            if let Some(key) = self.data_keys.lock().get(enc) {
+                return Ok(*key);
+            }
+            match self.keys.decrypt_age(recipient, enc) {
+                Ok(key) => {
+                    let key: [u8; 32] = key
+                        .try_into()
+                        .map_err(|_| "SOPS data key must be 32 bytes".to_string())?;
+                    let mut data_keys = self.data_keys.lock();
+                    if data_keys.len() >= DATA_KEY_CACHE_CAPACITY {
+                        data_keys.clear();
+                    }
+                    data_keys.insert(enc.clone(), key);
+                    return Ok(key);
+                },
+                Err(e) => errors.push(format!("{}: {}", recipient, e)),
+            }
+        }
+
+        Err(format!(
+            "cannot decrypt the SOPS data key with the configured age identity ({})",
+            errors.join("; ")
+        ))
+    }
+}
+
+impl std::fmt::Debug for SopsDecryptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SopsDecryptor").finish_non_exhaustive()
+    }
+}
+
+/// Decrypts `config` with `sops` if it is a SOPS file.
+///
+/// Without a decryptor, SOPS files are rejected rather than served with
+/// their values still encrypted.
+pub(crate) fn decrypt(
+    sops: Option<&SopsDecryptor>,
+    path: &Path,
+    config: ConfigMap,
+) -> Result<ConfigMap, ConfigSourceError> {
+    match sops {
+        Some(sops) => sops.decrypt(path, config),
+        None if metadata(&config).is_some() => Err(ConfigSourceError::parse(
+            path,
+            "file is encrypted with SOPS but no age identity is configured",
+        )),
+        None => Ok(config),
+    }
+}
+
+/// Returns the SOPS metadata of `config`, if it has any.
+fn metadata(config: &ConfigMap) -> Option<&indexmap::IndexMap<String, ConfigValue>> {
+    match config.as_inner().get(SOPS_KEY) {
+        Some(ConfigValue::Object(map)) if map.contains_key("mac") => Some(map),
+        _ => None,
+    }
+}
+
+/// The parts of the SOPS metadata needed to decrypt.
+struct Metadata {
+    /// `(recipient, enc)` for each age recipient.
+    age: Vec<(String, String)>,
+    /// Encrypted MAC.
+    mac: String,
+    /// Authenticated with the MAC.
+    last_modified: String,
+    /// Only encrypted values are part of the MAC.
+    mac_only_encrypted: bool,
+}
+
+impl Metadata {
+    fn read(map: &indexmap::IndexMap<String, ConfigValue>) -> Result<Self, String> {
+        let text = |key: &str| match map.get(key) {
+            Some(ConfigValue::String(s) | ConfigValue::DateTime(s)) => Ok(s.clone()),
+            _ => Err(format!("SOPS metadata is missing '{}'", key)),
+        };
+
+        let age = match map.get("age") {
+            Some(ConfigValue::Array(entries)) => entries
+                .iter()
+                .filter_map(ConfigValue::as_object)
+                .filter_map(|entry| {
+                    let recipient = entry.get("recipient")?.as_str()?;
+                    let enc = entry.get("enc")?.as_str()?;
+                    Some((recipient.to_string(), enc.to_string()))
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        Ok(Self {
+            age,
+            mac: text("mac")?,
+            last_modified: text("lastmodified")?,
+            mac_only_encrypted: matches!(
+                map.get("mac_only_encrypted"),
+                Some(ConfigValue::Bool(true))
+            ),
+        })
+    }
+}
+
+/// Decrypts every value of `config` and checks the MAC.
+fn decrypt_tree(
+    mut config: ConfigMap,
+    key: &[u8; 32],
+    metadata: &Metadata,
+) -> Result<ConfigMap, String> {
+    config.as_inner_mut().shift_remove(SOPS_KEY);
+
+    let cipher = SopsCipher::new(Key::<SopsCipher>::from_slice(key));
+    let mut hash = Sha512::new();
+    let mut path = Vec::new();
+    for (name, value) in config.as_inner_mut() {
+        path.push(name.clone());
+        decrypt_value(
+            value,
+            &cipher,
+            &mut path,
+            &mut hash,
+            metadata.mac_only_encrypted,
+        )?;
+        path.pop();
+    }
+
+    let mac = match decrypt_string(&metadata.mac, &cipher, &metadata.last_modified)? {
+        Some((ConfigValue::String(mac), _)) => mac,
+        _ => return Err("SOPS MAC is not a string".to_string()),
+    };
+    let computed: String = hash
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect();
+    if !mac.eq_ignore_ascii_case(&computed) {
+        return Err("SOPS MAC mismatch: the file was modified after it was encrypted".into());
+    }
+
+    Ok(config)
+}
+
+fn decrypt_value(
+    value: &mut ConfigValue,
+    cipher: &SopsCipher,
+    path: &mut Vec<String>,
+    hash: &mut Sha512,
+    mac_only_encrypted: bool,
+) -> Result<(), String> {
+    match value {
+        ConfigValue::Object(map) => {
+            for (name, value) in map {
+                path.push(name.clone());
+                decrypt_value(value, cipher, path, hash, mac_only_encrypted)?;
+                path.pop();
+            }
+        },
+        // SOPS authenticates list items with the path of the list.
+        ConfigValue::Array(items) => {
+            for item in items {
+                decrypt_value(item, cipher, path, hash, mac_only_encrypted)?;
+            }
+        },
+        _ => {
+            let aad = format!("{}:", path.join(":"));
+            let decrypted = match value.as_str() {
+                Some(text) => decrypt_string(text, cipher, &aad)
+                    .map_err(|e| format!("'{}': {}", path.join("."), e))?,
+                None => None,
+            };
+
+            match decrypted {
+                Some((plain, bytes)) => {
+                    hash.update(bytes);
+                    *value = plain;
+                },
+                None if !mac_only_encrypted => hash.update(mac_bytes(value)),
+                None => {},
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Decrypts an `ENC[...]` value, returning it with the bytes it adds to the
+/// MAC; other strings return `None`.
+fn decrypt_string(
+    text: &str,
+    cipher: &SopsCipher,
+    aad: &str,
+) -> Result<Option<(ConfigValue, Vec<u8>)>, String> {
+    let Some(fields) = text
+        .strip_prefix("ENC[AES256_GCM,")
+        .and_then(|rest| rest.strip_suffix(']'))
+    else {
+        return Ok(None);
+    };
+
+    let mut data = None;
+    let mut iv = None;
+    let mut tag = None;
+    let mut kind = None;
+    for field in fields.split(',') {
+        match field.split_once(':') {
+            Some(("data", v)) => data = Some(v),
+            Some(("iv", v)) => iv = Some(v),
+            Some(("tag", v)) => tag = Some(v),
+            Some(("type", v)) => kind = Some(v),
+            _ => {},
+        }
+    }
+    let (Some(data), Some(iv), Some(tag), Some(kind)) = (data, iv, tag, kind) else {
+        return Err("malformed ENC[] value".to_string());
+    };
+
+    let decode = |v: &str| {
+        STANDARD
+            .decode(v)
+            .map_err(|_| "malformed ENC[] value".to_string())
+    };
+    let iv = decode(iv)?;
+    if iv.len() != 32 {
+        return Err("unsupported ENC[] nonce size".to_string());
+    }
+    let mut sealed = decode(data)?;
+    sealed.extend(decode(tag)?);
+
+    let plain = cipher
+        .decrypt(
+            Nonce::from_slice(&iv),
+            Payload {
+                msg: &sealed,
+                aad: aad.as_bytes(),
+            },
+        )
+        .map_err(|_| "cannot decrypt value: wrong data key or tampered file".to_string())?;
+    let text = String::from_utf8(plain.clone())
+        .map_err(|_| "decrypted value is not valid UTF-8".to_string())?;
+
+    let value = match kind {
+        "str" | "bytes" | "comment" => ConfigValue::String(text),
+        "int" => text
+            .parse()
+            .map(ConfigValue::Integer)
+            .map_err(|_| "decrypted value is not an int".to_string())?,
+        "float" => text
+            .parse::<f64>()
+            .map(|f| ConfigValue::Float(f.into()))
+            .map_err(|_| "decrypted value is not a float".to_string())?,
+        "bool" => ConfigValue::Bool(text.eq_ignore_ascii_case("true")),
+        other => return Err(format!("unknown ENC[] type '{}'", other)),
+    };
+    Ok(Some((value, plain)))
+}
+
+/// Bytes an unencrypted value adds to the MAC, as SOPS formats them.
+fn mac_bytes(value: &ConfigValue) -> Vec<u8> {
+    match value {
+        ConfigValue::String(s) | ConfigValue::DateTime(s) => s.clone().into_bytes(),
+        ConfigValue::Integer(i) => i.to_string().into_bytes(),
+        ConfigValue::Float(f) => f.to_string().into_bytes(),
+        ConfigValue::Bool(true) => b"True".to_vec(),
+        ConfigValue::Bool(false) => b"False".to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use aes_gcm::aead::{AeadCore, OsRng};
+    use vortex_core::ConfigPath;
+
+    pub(crate) const DATA_KEY: [u8; 32] = [42; 32];
+    pub(crate) const RECIPIENT: &str = "age1testrecipient";
+
+    /// Returns [`DATA_KEY`] for [`RECIPIENT`].
+    pub(crate) struct TestKeys;
+
+    impl SopsKeySource for TestKeys {
+        fn decrypt_age(&self, recipient: &str, _enc: &str) -> Result<Vec<u8>, String> {
+            if recipient == RECIPIENT {
+                Ok(DATA_KEY.to_vec())
+            } else {
+                Err("no identity matched any of the recipients".to_string())
+            }
+        }
+    }
+
+    fn encrypt(plain: &str, kind: &str, aad: &str) -> String {
+        let cipher = SopsCipher::new(Key::<SopsCipher>::from_slice(&DATA_KEY));
+        let nonce = SopsCipher::generate_nonce(&mut OsRng);
+        let mut sealed = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plain.as_bytes(),
+                    aad: aad.as_bytes(),
+                },
+            )
+            .unwrap();
+        let tag = sealed.split_off(sealed.len() - 16);
+        format!(
+            "ENC[AES256_GCM,data:{},iv:{},tag:{},type:{}]",
+            STANDARD.encode(sealed),
+            STANDARD.encode(nonce),
+            STANDARD.encode(tag),
+            kind
+        )
+    }
+
+    /// Encrypts a SOPS YAML file like `sops --encrypt` would.
+    ///
+    /// `values` are `(path, plaintext, type)`; keys ending in
+    /// `_unencrypted` are left in clear text.
+    pub(crate) fn sops_yaml(values: &[(&str, &str, &str)]) -> String {
+        const LAST_MODIFIED: &str = "2024-05-01T10:00:00Z";
+        let mut config = ConfigMap::new();
+        let mut hash = Sha512::new();
+
+        for (path, plain, kind) in values {
+            hash.update(plain.as_bytes());
+            let value = if path.ends_with("_unencrypted") {
+                ConfigValue::String(plain.to_string())
+            } else {
+                let aad = format!("{}:", path.replace('.', ":"));
+                ConfigValue::String(encrypt(plain, kind, &aad))
+            };
+            config
+                .set_path(&ConfigPath::parse(path).unwrap(), value)
+                .unwrap();
+        }
+
+        let mac: String = hash
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect();
+        let mut yaml = config.to_yaml().unwrap();
+        yaml.push_str(&format!(
+            "sops:\n  age:\n    - recipient: {}\n      enc: |\n        -----BEGIN AGE ENCRYPTED FILE-----\n        dGVzdA==\n        -----END AGE ENCRYPTED FILE-----\n  lastmodified: \"{}\"\n  mac: {}\n  unencrypted_suffix: _unencrypted\n  version: 3.9.0\n",
+            RECIPIENT,
+            LAST_MODIFIED,
+            encrypt(&mac, "str", LAST_MODIFIED)
+        ));
+        yaml
+    }
+
+    fn parse(yaml: &str) -> ConfigMap {
+        ConfigMap::from_yaml(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_decrypts_values_with_their_types() {
+        let yaml = sops_yaml(&[
+            ("db.password", "hunter2", "str"),
+            ("db.pool", "10", "int"),
+            ("db.ratio", "0.5", "float"),
+            ("db.ssl", "True", "bool"),
+            ("db.host_unencrypted", "db.internal", "str"),
+        ]);
+
+        let config = SopsDecryptor::new(TestKeys)
+            .decrypt(Path::new("app.sops.yaml"), parse(&yaml))
+            .unwrap();
+
+        assert_eq!(config.get("db.password").unwrap().as_str(), Some("hunter2"));
+        assert_eq!(config.get("db.pool"), Some(&ConfigValue::Integer(10)));
+        assert_eq!(
+            config.get("db.ratio"),
+            Some(&ConfigValue::Float(0.5.into()))
+        );
+        assert_eq!(config.get("db.ssl"), Some(&ConfigValue::Bool(true)));
+        assert_eq!(
+            config.get("db.host_unencrypted").unwrap().as_str(),
+            Some("db.internal")
+        );
+        assert!(config.get(SOPS_KEY).is_none());
+    }
+
+    #[test]
+    fn test_tampered_files_are_rejected() {
+        let yaml = sops_yaml(&[
+            ("db.password", "hunter2", "str"),
+            ("db.host_unencrypted", "db.internal", "str"),
+        ]);
+        let decryptor = SopsDecryptor::new(TestKeys);
+
+        // A clear-text value changed after encryption breaks the MAC.
+        let changed = yaml.replace("db.internal", "evil.example.com");
+        let err = decryptor
+            .decrypt(Path::new("app.sops.yaml"), parse(&changed))
+            .unwrap_err();
+        assert!(err.to_string().contains("MAC mismatch"));
+
+        // A value moved to another key does not authenticate.
+        let moved = yaml.replace("  password:", "  user:");
+        let err = decryptor
+            .decrypt(Path::new("app.sops.yaml"), parse(&moved))
+            .unwrap_err();
+        assert!(err.to_string().contains("'db.user'"));
+        assert!(!err.to_string().contains("hunter2"));
+    }
+
+    #[test]
+    fn test_missing_identity() {
+        let yaml = sops_yaml(&[("token", "t0k3n", "str")]).replace(RECIPIENT, "age1other");
+
+        let err = SopsDecryptor::new(TestKeys)
+            .decrypt(Path::new("app.sops.yaml"), parse(&yaml))
+            .unwrap_err();
+        assert!(err.to_string().contains("age1other"));
+
+        let err = decrypt(None, Path::new("app.sops.yaml"), parse(&yaml)).unwrap_err();
+        assert!(err.to_string().contains("no age identity is configured"));
+    }
+
+    #[test]
+    fn test_plain_files_are_unchanged() {
+        let config = parse("sops: true\nname: app\n");
+        assert_eq!(
+            decrypt(None, Path::new("app.yml"), config.clone()).unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn test_data_key_cache_is_bounded() {
+        let decryptor = SopsDecryptor::new(TestKeys);
+        for i in 0..DATA_KEY_CACHE_CAPACITY + 10 {
+            let metadata = Metadata {
+                age: vec![(RECIPIENT.to_string(), format!("enc-{}", i))],
+                mac: String::new(),
+                last_modified: String::new(),
+                mac_only_encrypted: false,
+            };
+            assert_eq!(decryptor.data_key(&metadata).unwrap(), DATA_KEY);
+        }
+        assert!(decryptor.data_keys.lock().len() <= DATA_KEY_CACHE_CAPACITY);
+    }
+
+    #[test]
+    fn test_age_identity_file() {
+        use age::secrecy::ExposeSecret;
+
+        let identity = age::x25519::Identity::generate();
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("keys.txt");
+        std::fs::write(&path, identity.to_string().expose_secret()).unwrap();
+
+        // Wrap the data key for our identity, as `sops --encrypt --age` does.
+        let enc = age::encrypt_and_armor(&identity.to_public(), &DATA_KEY).unwrap();
+        let indented: String = enc
+            .lines()
+            .map(|line| format!("        {}\n", line))
+            .collect();
+        let yaml = sops_yaml(&[("token", "t0k3n", "str")]).replace(
+            "        -----BEGIN AGE ENCRYPTED FILE-----\n        dGVzdA==\n        -----END AGE ENCRYPTED FILE-----\n",
+            &indented,
+        );
+
+        let decryptor = SopsDecryptor::new(AgeIdentityFile::new(&path));
+        let config = decryptor
+            .decrypt(Path::new("app.sops.yaml"), parse(&yaml))
+            .unwrap();
+        assert_eq!(config.get("token").unwrap().as_str(), Some("t0k3n"));
+
+        // Another identity cannot unwrap the key.
+        let other = dir.path().join("other.txt");
+        let other_identity = age::x25519::Identity::generate();
+        std::fs::write(&other, other_identity.to_string().expose_secret()).unwrap();
+        let err = SopsDecryptor::new(AgeIdentityFile::new(&other))
+            .decrypt(Path::new("app.sops.yaml"), parse(&yaml))
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot decrypt the SOPS data key"));
+
+        let missing = SopsDecryptor::new(AgeIdentityFile::new(dir.path().join("missing.txt")));
+        assert!(
+            missing
+                .decrypt(Path::new("app.sops.yaml"), parse(&yaml))
+                .is_err()
+        );
+    }
+
+    /// Encrypts files with the `sops` binary on the `PATH` and decrypts them.
+    #[test]
+    #[ignore = "requires sops"]
+    fn test_files_encrypted_by_sops() {
+        use crate::reader::ConfigParser;
+        use age::secrecy::ExposeSecret;
+
+        let identity = age::x25519::Identity::generate();
+        let dir = tempfile::TempDir::new().unwrap();
+        let keys = dir.path().join("keys.txt");
+        std::fs::write(&keys, identity.to_string().expose_secret()).unwrap();
+        let decryptor = SopsDecryptor::new(AgeIdentityFile::new(&keys));
+
+        for (name, plain) in [
+            (
+                "app.sops.yaml",
+                "db:\n  password: hunter2\n  pool: 10\n  ratio: 0.5\n  ssl: true\n  \
+                 host_unencrypted: db.internal\nhosts:\n  - a\n  - b\n",
+            ),
+            (
+                "app.sops.json",
+                r#"{"db": {"password": "hunter2", "pool": 10, "ratio": 0.5, "ssl": true,
+                    "host_unencrypted": "db.internal"}, "hosts": ["a", "b"]}"#,
+            ),
+        ] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, plain).unwrap();
+            let output = std::process::Command::new("sops")
+                .args(["--encrypt", "--in-place", "--age"])
+                .arg(identity.to_public().to_string())
+                .arg(&path)
+                .output()
+                .expect("sops must be on the PATH");
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let encrypted = std::fs::read_to_string(&path).unwrap();
+            assert!(!encrypted.contains("hunter2"), "{}", encrypted);
+            let config = decryptor
+                .decrypt(&path, ConfigParser::parse_file(&path).unwrap())
+                .unwrap();
+
+            assert_eq!(
+                config.get("db.password").unwrap().as_str(),
+                Some("hunter2"),
+                "{}",
+                name
+            );
+            assert_eq!(config.get("db.pool"), Some(&ConfigValue::Integer(10)));
+            assert_eq!(
+                config.get("db.ratio"),
+                Some(&ConfigValue::Float(0.5.into()))
+            );
+            assert_eq!(config.get("db.ssl"), Some(&ConfigValue::Bool(true)));
+            assert_eq!(
+                config.get("db.host_unencrypted").unwrap().as_str(),
+                Some("db.internal")
+            );
+            assert_eq!(config.get("hosts[1]").unwrap().as_str(), Some("b"));
+            assert!(config.get(SOPS_KEY).is_none());
+        }
+    }
+}
@@ -5,6 +5,8 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::reader::DuplicateKeyPolicy;
+
 /// Configuration for the Git backend.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -58,6 +60,14 @@ pub struct GitBackendConfig {
     /// Whether to skip SSL verification (not recommended).
     #[serde(default)]
     skip_ssl_verification: bool,
+
+    /// How duplicate keys in configuration files are handled.
+    #[serde(default)]
+    duplicate_keys: DuplicateKeyPolicy,
+
+    /// age identity file for SOPS-encrypted files (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sops_age_key_file: Option<PathBuf>,
 }
 
 fn default_label() -> String {
@@ -147,6 +157,16 @@ impl GitBackendConfig {
         self.skip_ssl_verification
     }
 
+    /// Returns how duplicate keys in configuration files are handled.
+    pub fn duplicate_keys(&self) -> DuplicateKeyPolicy {
+        self.duplicate_keys
+    }
+
+    /// Returns the age identity file used to decrypt SOPS files.
+    pub fn sops_age_key_file(&self) -> Option<&PathBuf> {
+        self.sops_age_key_file.as_ref()
+    }
+
     /// Returns effective search paths (defaults to root if empty).
     pub fn effective_search_paths(&self) -> Vec<&str> {
         if self.search_paths.is_empty() {
@@ -173,6 +193,8 @@ pub struct GitBackendConfigBuilder {
     private_key: Option<PathBuf>,
     passphrase: Option<String>,
     skip_ssl_verification: bool,
+    duplicate_keys: DuplicateKeyPolicy,
+    sops_age_key_file: Option<PathBuf>,
 }
 
 impl GitBackendConfigBuilder {
@@ -255,6 +277,18 @@ impl GitBackendConfigBuilder {
         self
     }
 
+    /// Sets how duplicate keys in configuration files are handled.
+    pub fn duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Sets the age identity file used to decrypt SOPS-encrypted files.
+    pub fn sops_age_key_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sops_age_key_file = Some(path.into());
+        self
+    }
+
     /// Builds the configuration.
     ///
     /// # Errors
@@ -278,6 +312,8 @@ impl GitBackendConfigBuilder {
             private_key: self.private_key,
             passphrase: self.passphrase,
             skip_ssl_verification: self.skip_ssl_verification,
+            duplicate_keys: self.duplicate_keys,
+            sops_age_key_file: self.sops_age_key_file,
         })
     }
 }
@@ -297,6 +333,8 @@ mod tests {
         assert_eq!(config.uri(), "https://github.com/org/repo.git");
         assert_eq!(config.local_path(), &PathBuf::from("/tmp/repo"));
         assert_eq!(config.default_label(), "main");
+        assert_eq!(config.duplicate_keys(), DuplicateKeyPolicy::Warn);
+        assert!(config.sops_age_key_file().is_none());
     }
 
     #[test]
@@ -310,6 +348,8 @@ mod tests {
             .fetch_timeout(Duration::from_secs(15))
             .force_pull(true)
             .basic_auth("user", "token")
+            .duplicate_keys(DuplicateKeyPolicy::Error)
+            .sops_age_key_file("/etc/vortex/age.txt")
             .build()
             .unwrap();
 
@@ -319,6 +359,11 @@ mod tests {
         assert!(config.force_pull());
         assert_eq!(config.username(), Some("user"));
         assert_eq!(config.password(), Some("token"));
+        assert_eq!(config.duplicate_keys(), DuplicateKeyPolicy::Error);
+        assert_eq!(
+            config.sops_age_key_file(),
+            Some(&PathBuf::from("/etc/vortex/age.txt"))
+        );
     }
 
     #[test]
@@ -4,8 +4,10 @@
 
 mod query;
 mod result;
+mod store;
 mod traits;
 
 pub use query::ConfigQuery;
-pub use result::ConfigResult;
+pub use result::{ConfigResult, ConfigWarning};
+pub use store::{ConfigStore, DocumentId, StoredProperties, check_version};
 pub use traits::ConfigSource;
@@ -1,7 +1,11 @@
 //! Configuration result types.
 
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
-use vortex_core::PropertySource;
+use vortex_core::merge::PropertySourceList;
+use vortex_core::validation::SchemaValidator;
+use vortex_core::{ConfigMap, PropertySource};
 
 /// The result of fetching configuration from a source.
 ///
@@ -26,6 +30,48 @@ pub struct ConfigResult {
 
     /// The property sources in order of precedence (first = highest).
     property_sources: Vec<PropertySource>,
+
+    /// Non-fatal problems found while resolving the configuration.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<ConfigWarning>,
+
+    /// The JSON Schema loaded with the configuration, if the source has one.
+    #[serde(skip)]
+    schema: Option<Arc<SchemaValidator>>,
+
+    /// Whether part of the configuration could not be loaded.
+    #[serde(skip)]
+    partial: bool,
+}
+
+/// A non-fatal problem found while resolving configuration, such as a key
+/// defined twice in the same file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigWarning {
+    /// Name of the property source the warning refers to.
+    pub source: String,
+    /// Line (1-based) in the source file, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Human-readable description.
+    pub message: String,
+}
+
+impl ConfigWarning {
+    /// Creates a warning for a property source.
+    pub fn new(source: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    /// Sets the line the warning refers to.
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
 }
 
 impl ConfigResult {
@@ -38,6 +84,9 @@ impl ConfigResult {
             version: None,
             state: None,
             property_sources: Vec::new(),
+            warnings: Vec::new(),
+            schema: None,
+            partial: false,
         }
     }
 
@@ -71,6 +120,34 @@ impl ConfigResult {
         &self.property_sources
     }
 
+    /// Returns the property sources for in-place changes.
+    pub fn property_sources_mut(&mut self) -> &mut [PropertySource] {
+        &mut self.property_sources
+    }
+
+    /// Returns the warnings found while resolving the configuration.
+    pub fn warnings(&self) -> &[ConfigWarning] {
+        &self.warnings
+    }
+
+    /// Returns the schema loaded together with the configuration.
+    ///
+    /// Sources that read the schema from the same snapshot as the property
+    /// sources (e.g. the same Git checkout) set it here, so validation never
+    /// mixes a schema and a configuration from different versions.
+    pub fn schema(&self) -> Option<&Arc<SchemaValidator>> {
+        self.schema.as_ref()
+    }
+
+    /// Returns whether part of the configuration could not be loaded, e.g.
+    /// because one backend of a composite failed.
+    ///
+    /// Partial results are served, but should not be cached as long as
+    /// complete ones.
+    pub fn is_partial(&self) -> bool {
+        self.partial
+    }
+
     /// Sets the version.
     pub fn set_version(&mut self, version: impl Into<String>) {
         self.version = Some(version.into());
@@ -81,6 +158,16 @@ impl ConfigResult {
         self.state = Some(state.into());
     }
 
+    /// Marks the result as partial; see [`is_partial`](Self::is_partial).
+    pub fn set_partial(&mut self) {
+        self.partial = true;
+    }
+
+    /// Sets the schema loaded together with the configuration.
+    pub fn set_schema(&mut self, schema: Arc<SchemaValidator>) {
+        self.schema = Some(schema);
+    }
+
     /// Adds a property source.
     ///
     /// Sources added first have higher precedence.
@@ -93,6 +180,11 @@ impl ConfigResult {
         self.property_sources.extend(sources);
     }
 
+    /// Adds warnings.
+    pub fn add_warnings(&mut self, warnings: impl IntoIterator<Item = ConfigWarning>) {
+        self.warnings.extend(warnings);
+    }
+
     /// Returns true if there are no property sources.
     pub fn is_empty(&self) -> bool {
         self.property_sources.is_empty()
@@ -103,6 +195,24 @@ impl ConfigResult {
         self.property_sources.len()
     }
 
+    /// Returns the property sources as a list that merges in precedence order.
+    ///
+    /// Property sources are stored highest precedence first, while a
+    /// [`PropertySourceList`] applies sources in order, so they are added in
+    /// reverse.
+    pub fn source_list(&self) -> PropertySourceList {
+        let mut list = PropertySourceList::new();
+        for source in self.property_sources.iter().rev() {
+            list.add(source.clone());
+        }
+        list
+    }
+
+    /// Merges all property sources into a single configuration.
+    pub fn merged(&self) -> ConfigMap {
+        self.source_list().merge()
+    }
+
     /// Builder-style method to set version.
     pub fn with_version(mut self, version: impl Into<String>) -> Self {
         self.version = Some(version.into());
@@ -164,4 +274,32 @@ mod tests {
         assert!(json.contains("\"name\":\"myapp\""));
         assert!(json.contains("\"propertySources\""));
     }
+
+    #[test]
+    fn test_warnings_serialized_only_when_present() {
+        let mut result = ConfigResult::new("myapp", vec!["dev".to_string()], "main");
+        let json = serde_json::to_value(&result).unwrap();
+        assert!(json.get("warnings").is_none());
+
+        result.add_warnings([ConfigWarning::new("git:main:myapp.yml", "duplicate").at_line(4)]);
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["warnings"][0]["source"], "git:main:myapp.yml");
+        assert_eq!(json["warnings"][0]["line"], 4);
+    }
+
+    #[test]
+    fn test_merged_respects_precedence() {
+        let source =
+            |name: &str, json: &str| PropertySource::new(name, ConfigMap::from_json(json).unwrap());
+        let result = ConfigResult::new("myapp", vec!["prod".to_string()], "main")
+            .with_property_sources(vec![
+                source("myapp-prod.yml", r#"{"port": 9090}"#),
+                source("application.yml", r#"{"port": 8080, "debug": false}"#),
+            ]);
+
+        let merged = result.merged();
+
+        assert_eq!(merged.get("port").unwrap().as_i64(), Some(9090));
+        assert!(merged.get("debug").is_some());
+    }
 }
@@ -0,0 +1,122 @@
+//! Write capability of configuration sources.
+
+use std::fmt;
+
+use async_trait::async_trait;
+use vortex_core::ConfigMap;
+
+use crate::error::ConfigSourceError;
+
+/// The properties of one application, profile and label in a writable
+/// source: a row group, a hash or a file, depending on the backend.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DocumentId {
+    /// The application.
+    pub application: String,
+    /// A single profile.
+    pub profile: String,
+    /// The label.
+    pub label: String,
+}
+
+impl DocumentId {
+    /// Creates a document identifier.
+    pub fn new(
+        application: impl Into<String>,
+        profile: impl Into<String>,
+        label: impl Into<String>,
+    ) -> Self {
+        Self {
+            application: application.into(),
+            profile: profile.into(),
+            label: label.into(),
+        }
+    }
+}
+
+impl fmt::Display for DocumentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.application, self.profile, self.label)
+    }
+}
+
+/// A document as stored, with its version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredProperties {
+    /// The properties, nested like a parsed file.
+    pub properties: ConfigMap,
+    /// Opaque version of the stored document; it changes whenever the
+    /// document does. A document that does not exist has a version too.
+    pub version: String,
+}
+
+/// Writes configuration to a source.
+///
+/// Writes take the version the caller last read: if the document changed
+/// since, they fail with [`ConfigSourceError::VersionConflict`] and change
+/// nothing (optimistic concurrency). Without an expected version they
+/// overwrite whatever is stored.
+#[async_trait]
+pub trait ConfigStore: Send + Sync {
+    /// Reads a document. A document that does not exist is empty.
+    async fn get_properties(&self, id: &DocumentId) -> Result<StoredProperties, ConfigSourceError>;
+
+    /// Replaces a document with `properties`, and returns it as stored.
+    ///
+    /// An empty map deletes the document.
+    async fn put_properties(
+        &self,
+        id: &DocumentId,
+        properties: &ConfigMap,
+        expected_version: Option<&str>,
+    ) -> Result<StoredProperties, ConfigSourceError>;
+
+    /// Deletes a key, and everything nested below it, from a document, and
+    /// returns the document as stored.
+    ///
+    /// # Errors
+    ///
+    /// - `ConfigSourceError::KeyNotFound` if the document does not have the
+    ///   key
+    async fn delete_key(
+        &self,
+        id: &DocumentId,
+        key: &str,
+        expected_version: Option<&str>,
+    ) -> Result<StoredProperties, ConfigSourceError>;
+}
+
+/// Checks the version a write expects against the stored one.
+pub fn check_version(
+    id: &DocumentId,
+    expected: Option<&str>,
+    actual: &str,
+) -> Result<(), ConfigSourceError> {
+    match expected {
+        Some(expected) if expected != actual => Err(ConfigSourceError::VersionConflict {
+            document: id.to_string(),
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_version() {
+        let id = DocumentId::new("payments", "prod", "main");
+        assert_eq!(id.to_string(), "payments/prod/main");
+
+        assert!(check_version(&id, None, "a1").is_ok());
+        assert!(check_version(&id, Some("a1"), "a1").is_ok());
+        let error = check_version(&id, Some("a0"), "a1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "payments/prod/main changed: expected version a0, found a1"
+        );
+    }
+}
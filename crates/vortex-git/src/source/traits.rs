@@ -1,8 +1,11 @@
 //! Configuration source trait definition.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use vortex_core::validation::SchemaValidator;
 
-use super::{ConfigQuery, ConfigResult};
+use super::{ConfigQuery, ConfigResult, ConfigStore};
 use crate::error::ConfigSourceError;
 
 /// A source of configuration data.
@@ -13,8 +16,8 @@ use crate::error::ConfigSourceError;
 /// # Implementors
 ///
 /// - `GitBackend` - Fetches configuration from a Git repository
-/// - (Future) `S3Backend` - Fetches configuration from S3
-/// - (Future) `DatabaseBackend` - Fetches configuration from a database
+/// - `FilesystemBackend`, `SqlBackend` and `S3Backend` in `vortex-sources`
+/// - `CompositeConfigSource` in `vortex-sources` - Combines several sources by priority
 ///
 /// # Example
 ///
@@ -66,6 +69,15 @@ pub trait ConfigSource: Send + Sync {
     /// `Ok(())` if the source is healthy, or an error describing the problem.
     async fn health_check(&self) -> Result<(), ConfigSourceError>;
 
+    /// Reports the health of each backend behind this source.
+    ///
+    /// Sources combining several backends report one entry per backend. The
+    /// default implementation reports this source's own health check under
+    /// its name.
+    async fn backend_health(&self) -> Vec<(String, Result<(), ConfigSourceError>)> {
+        vec![(self.name().to_string(), self.health_check().await)]
+    }
+
     /// Returns the name of this configuration source.
     ///
     /// This is used for logging and identification purposes.
@@ -83,6 +95,22 @@ pub trait ConfigSource: Send + Sync {
         Ok(())
     }
 
+    /// Returns the JSON Schema the resolved configuration must satisfy.
+    ///
+    /// The schema may depend on the query's label, so it is versioned with
+    /// the configuration. Sources that load it during [`fetch`] also attach
+    /// it to the [`ConfigResult`], which callers should prefer. The default
+    /// implementation returns `None`, meaning the configuration is not
+    /// validated.
+    ///
+    /// [`fetch`]: ConfigSource::fetch
+    async fn schema(
+        &self,
+        _query: &ConfigQuery,
+    ) -> Result<Option<Arc<SchemaValidator>>, ConfigSourceError> {
+        Ok(None)
+    }
+
     /// Returns whether this source supports refresh operations.
     ///
     /// Sources that don't support refresh (e.g., static file sources)
@@ -97,6 +125,14 @@ pub trait ConfigSource: Send + Sync {
     fn default_label(&self) -> &str {
         "main"
     }
+
+    /// Returns the write capability of this source.
+    ///
+    /// The default implementation returns `None`, meaning the source is
+    /// read-only.
+    fn store(&self) -> Option<&dyn ConfigStore> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -158,5 +194,6 @@ mod tests {
 
         assert!(!source.supports_refresh());
         assert!(source.refresh().await.is_ok());
+        assert!(source.store().is_none());
     }
 }
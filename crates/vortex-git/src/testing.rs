@@ -0,0 +1,23 @@
+//! Test fixtures for code that reads configuration repositories.
+//!
+//! Built for this crate's tests and, with the `test-support` feature, for
+//! the tests of dependent crates.
+
+use std::fs;
+
+use tempfile::TempDir;
+
+/// Creates a repository directory holding `files`, as `(path, content)`
+/// pairs with paths relative to the root.
+///
+/// Parent directories are created as needed. The directory is not a Git
+/// repository; tests that need commits run `git init` on it.
+pub fn repo(files: &[(&str, &str)]) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    for (name, content) in files {
+        let path = dir.path().join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
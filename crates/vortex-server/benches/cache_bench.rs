@@ -6,7 +6,7 @@ use vortex_server::handlers::response::{ConfigResponse, PropertySourceResponse};
 
 /// Crea un ConfigResponse de prueba con N propiedades
 fn create_test_response(num_properties: usize) -> ConfigResponse {
-    let mut source = std::collections::HashMap::new();
+    let mut source = std::collections::BTreeMap::new();
     for i in 0..num_properties {
         source.insert(
             format!("property.key.{}", i),
@@ -23,7 +23,11 @@ fn create_test_response(num_properties: usize) -> ConfigResponse {
         property_sources: vec![PropertySourceResponse {
             name: "test-source".to_string(),
             source,
+            origins: std::collections::BTreeMap::new(),
         }],
+        validation: None,
+        warnings: Vec::new(),
+        partial: false,
     }
 }
 
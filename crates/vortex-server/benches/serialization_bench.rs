@@ -1,5 +1,5 @@
 use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use vortex_server::handlers::response::{ConfigResponse, PropertySourceResponse};
 
 /// Crea un ConfigResponse de prueba con estructura anidada
@@ -18,7 +18,7 @@ fn create_nested_response(depth: usize, breadth: usize) -> ConfigResponse {
         }
     }
 
-    let mut source = HashMap::new();
+    let mut source = BTreeMap::new();
     for i in 0..breadth {
         let key = format!("root-{}", i);
         source.insert(key, create_nested_value(depth, breadth, &format!("{}", i)));
@@ -33,13 +33,17 @@ fn create_nested_response(depth: usize, breadth: usize) -> ConfigResponse {
         property_sources: vec![PropertySourceResponse {
             name: "test-source".to_string(),
             source,
+            origins: BTreeMap::new(),
         }],
+        validation: None,
+        warnings: Vec::new(),
+        partial: false,
     }
 }
 
 /// Crea un ConfigResponse plano con N propiedades
 fn create_flat_response(num_properties: usize) -> ConfigResponse {
-    let mut source = HashMap::new();
+    let mut source = BTreeMap::new();
     for i in 0..num_properties {
         source.insert(
             format!("property.key.{}", i),
@@ -56,7 +60,11 @@ fn create_flat_response(num_properties: usize) -> ConfigResponse {
         property_sources: vec![PropertySourceResponse {
             name: "application.properties".to_string(),
             source,
+            origins: BTreeMap::new(),
         }],
+        validation: None,
+        warnings: Vec::new(),
+        partial: false,
     }
 }
 
@@ -151,7 +159,7 @@ fn bench_config_response_serialization(c: &mut Criterion) {
     for num_sources in [1, 3, 5].iter() {
         let mut property_sources = Vec::new();
         for i in 0..*num_sources {
-            let mut source = HashMap::new();
+            let mut source = BTreeMap::new();
             for j in 0..100 {
                 source.insert(
                     format!("key.{}.{}", i, j),
@@ -161,6 +169,7 @@ fn bench_config_response_serialization(c: &mut Criterion) {
             property_sources.push(PropertySourceResponse {
                 name: format!("source-{}", i),
                 source,
+                origins: BTreeMap::new(),
             });
         }
 
@@ -171,6 +180,9 @@ fn bench_config_response_serialization(c: &mut Criterion) {
             version: Some("abc123".to_string()),
             state: None,
             property_sources,
+            validation: None,
+            warnings: Vec::new(),
+            partial: false,
         };
 
         group.bench_with_input(
@@ -0,0 +1,1180 @@
+//! Construccion de los backends de configuracion.
+//!
+//! Un backend se declara con variables de entorno (`VORTEX_BACKEND` y las
+//! `GIT_*`, `NATIVE_*`, `SQL_*`, `S3_*`, `CONSUL_*`, `REDIS_*`, `UPSTREAM_*`)
+//! o, para combinar varios, en la seccion `composite` del archivo de
+//! configuracion del servidor (`VORTEX_CONFIG_FILE`):
+//!
+//! ```yaml
+//! composite:
+//!   fail_fast: false
+//!   backends:
+//!     - type: sql
+//!       priority: 100
+//!       url: sqlite:/var/lib/vortex/overrides.db
+//!     - type: git
+//!       priority: 10
+//!       uri: https://github.com/acme/config-repo.git
+//! ```
+//!
+//! Ambas formas comparten [`BackendSettings`], asi que cada backend se
+//! construye igual venga de donde venga. Sobre el backend resultante,
+//! [`OverrideSettings`] aplica los overrides de emergencia
+//! (`VORTEX_OVERRIDES_*`).
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::sync::watch;
+use vortex_git::vortex_core::format::FormatRegistry;
+use vortex_git::{
+    AgeIdentityFile, ConfigSource, DuplicateKeyPolicy, GitBackend, GitBackendConfig, SopsDecryptor,
+};
+use vortex_sources::s3::Credentials;
+use vortex_sources::{
+    CompositeConfigSource, ConsulBackend, FilesystemBackend, HttpUpstreamSource, OverrideSource,
+    S3Backend, SqlBackend,
+};
+
+/// Directorio por defecto de los clones de git.
+pub const DEFAULT_GIT_LOCAL_PATH: &str = "/var/lib/vortex/repos";
+
+/// Intervalo por defecto, en segundos, entre listados de S3.
+pub const DEFAULT_S3_POLL_INTERVAL_SECONDS: u64 = 30;
+
+/// Agente de Consul por defecto.
+pub const DEFAULT_CONSUL_ADDRESS: &str = "http://127.0.0.1:8500";
+
+/// Espera maxima por defecto, en segundos, de las blocking queries de Consul.
+pub const DEFAULT_CONSUL_WAIT_SECONDS: u64 = 55;
+
+/// Timeout por defecto, en segundos, de cada peticion al servidor upstream.
+pub const DEFAULT_UPSTREAM_TIMEOUT_SECONDS: u64 = 10;
+
+/// Errores al leer o construir los backends.
+#[derive(Debug, thiserror::Error)]
+pub enum BackendError {
+    /// El archivo de configuracion no se pudo leer.
+    #[error("Cannot read {path}: {reason}")]
+    Read {
+        /// Ruta del archivo
+        path: PathBuf,
+        /// Motivo
+        reason: String,
+    },
+
+    /// El archivo o una variable de entorno tiene un valor invalido.
+    #[error("Invalid backend configuration: {0}")]
+    Invalid(String),
+
+    /// Un backend no se pudo inicializar.
+    #[error("Cannot initialize backend '{name}': {reason}")]
+    Init {
+        /// Nombre del backend
+        name: String,
+        /// Motivo
+        reason: String,
+    },
+}
+
+/// Opciones comunes a todos los backends, que no dependen del backend.
+#[derive(Clone)]
+pub struct BackendContext {
+    /// Formatos de los archivos de configuracion.
+    pub formats: Arc<FormatRegistry>,
+    /// Que hacer con las claves duplicadas en un archivo.
+    pub duplicate_keys: DuplicateKeyPolicy,
+    /// Identidad age para descifrar archivos SOPS.
+    pub sops_age_key_file: Option<String>,
+}
+
+/// Un backend construido.
+pub struct BuiltBackend {
+    /// El backend.
+    pub source: Arc<dyn ConfigSource>,
+    /// Avisa de cambios en los archivos, si el backend los detecta.
+    pub changes: Option<watch::Receiver<u64>>,
+}
+
+/// Configuracion de un backend, segun su tipo.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum BackendSettings {
+    /// Repositorio git.
+    Git(GitSettings),
+    /// Directorio local.
+    Native(NativeSettings),
+    /// Tabla `PROPERTIES` en SQLite o PostgreSQL.
+    Sql(SqlSettings),
+    /// Bucket S3.
+    S3(S3Settings),
+    /// KV de Consul.
+    Consul(ConsulSettings),
+    /// Hashes de Redis.
+    #[cfg(feature = "redis")]
+    Redis(RedisSettings),
+    /// Otro servidor Vortex o Spring Cloud Config.
+    Upstream(UpstreamSettings),
+}
+
+/// Configuracion del backend git.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GitSettings {
+    /// URL del repositorio.
+    pub uri: String,
+    /// Directorio del clon; por defecto uno por backend bajo
+    /// [`DEFAULT_GIT_LOCAL_PATH`].
+    pub local_path: Option<PathBuf>,
+    /// Branch por defecto.
+    #[serde(default = "default_label")]
+    pub default_label: String,
+    /// Paths de busqueda.
+    #[serde(default)]
+    pub search_paths: Vec<String>,
+    /// Usuario para autenticacion.
+    pub username: Option<String>,
+    /// Password o token para autenticacion.
+    pub password: Option<String>,
+}
+
+/// Configuracion del backend nativo.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NativeSettings {
+    /// Directorio con los archivos.
+    pub path: PathBuf,
+    /// Label servido desde la raiz del directorio.
+    #[serde(default = "default_label")]
+    pub default_label: String,
+    /// Paths de busqueda.
+    #[serde(default)]
+    pub search_paths: Vec<String>,
+    /// Detectar cambios en el directorio.
+    #[serde(default = "default_true")]
+    pub watch: bool,
+}
+
+/// Configuracion del backend SQL.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SqlSettings {
+    /// `sqlite:...` o `postgres://...`.
+    pub url: String,
+    /// Query propio que devuelve clave y valor.
+    pub query: Option<String>,
+    /// Conexiones en el pool.
+    #[serde(default = "default_pool_size")]
+    pub pool_size: usize,
+    /// Label si la peticion no indica uno.
+    #[serde(default = "default_label")]
+    pub default_label: String,
+    /// Crear o actualizar la tabla `PROPERTIES` al arrancar.
+    #[serde(default = "default_true")]
+    pub migrate: bool,
+}
+
+/// Configuracion del backend S3.
+///
+/// Sin `access_key_id`, las credenciales se leen de `AWS_ACCESS_KEY_ID`,
+/// `AWS_SECRET_ACCESS_KEY` y `AWS_SESSION_TOKEN`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Settings {
+    /// Bucket con los archivos.
+    pub bucket: String,
+    /// Endpoint; por defecto el de AWS para la region.
+    pub endpoint: Option<String>,
+    /// Region para firmar las peticiones.
+    #[serde(default = "default_region")]
+    pub region: String,
+    /// Bucket en el path en vez del host (MinIO).
+    #[serde(default)]
+    pub path_style: bool,
+    /// Prefijo de las keys delante de los labels.
+    pub prefix: Option<String>,
+    /// Label si la peticion no indica uno.
+    #[serde(default = "default_label")]
+    pub default_label: String,
+    /// Cada cuantos segundos buscar cambios (`0` desactiva).
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval_seconds: u64,
+    /// Access key.
+    pub access_key_id: Option<String>,
+    /// Secret key.
+    pub secret_access_key: Option<String>,
+    /// Token de credenciales temporales.
+    pub session_token: Option<String>,
+}
+
+/// Configuracion del backend Consul.
+///
+/// Sin `token`, se usa `CONSUL_HTTP_TOKEN`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConsulSettings {
+    /// Direccion del agente.
+    #[serde(default = "default_consul_address")]
+    pub address: String,
+    /// Carpeta con la configuracion.
+    #[serde(default = "default_consul_prefix")]
+    pub prefix: String,
+    /// Token ACL.
+    pub token: Option<String>,
+    /// Datacenter; por defecto el del agente.
+    pub datacenter: Option<String>,
+    /// Carpeta compartida por todas las aplicaciones.
+    #[serde(default = "default_consul_context")]
+    pub default_context: String,
+    /// Separador entre aplicacion y perfil.
+    #[serde(default = "default_profile_separator")]
+    pub profile_separator: String,
+    /// Clave con un documento YAML para su carpeta (`null` la desactiva).
+    #[serde(default = "default_data_key")]
+    pub data_key: Option<String>,
+    /// Label que se informa si la peticion no indica uno.
+    #[serde(default = "default_label")]
+    pub default_label: String,
+    /// Detectar cambios con blocking queries.
+    #[serde(default = "default_true")]
+    pub watch: bool,
+    /// Espera maxima de cada blocking query, en segundos.
+    #[serde(default = "default_consul_wait")]
+    pub wait_seconds: u64,
+}
+/// Configuracion del backend Redis.
+#[cfg(feature = "redis")]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedisSettings {
+    /// `redis://...`, o `rediss://...` con TLS.
+    pub url: String,
+    /// Prefijo delante del nombre de los hashes.
+    #[serde(default)]
+    pub key_prefix: String,
+    /// Label si la peticion no indica uno.
+    #[serde(default = "default_label")]
+    pub default_label: String,
+    /// Escuchar notificaciones de cambios.
+    #[serde(default = "default_true")]
+    pub watch: bool,
+    /// Canal donde se publican cambios a mano.
+    #[serde(default = "default_redis_channel")]
+    pub channel: String,
+}
+
+/// Configuracion del backend upstream: otro servidor de configuracion.
+///
+/// Con `token` se autentica con un bearer token; con `username` y
+/// `password`, con basic.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpstreamSettings {
+    /// URL del servidor, p. ej. `https://config.central:8888`.
+    pub url: String,
+    /// Usuario para autenticacion basic.
+    pub username: Option<String>,
+    /// Password para autenticacion basic.
+    pub password: Option<String>,
+    /// Bearer token.
+    pub token: Option<String>,
+    /// Headers adicionales en cada peticion.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Label si la peticion no indica uno.
+    #[serde(default = "default_label")]
+    pub default_label: String,
+    /// Timeout de cada intento, en segundos.
+    #[serde(default = "default_upstream_timeout")]
+    pub timeout_seconds: u64,
+    /// Reintentos tras un fallo.
+    #[serde(default = "default_upstream_retries")]
+    pub retries: u32,
+    /// Servir la ultima copia buena si el servidor falla.
+    #[serde(default = "default_true")]
+    pub stale_on_error: bool,
+    /// Edad maxima, en segundos, de esa copia (`0` sin limite).
+    #[serde(default)]
+    pub max_stale_seconds: u64,
+    /// Path del health check del servidor.
+    #[serde(default = "default_upstream_health_path")]
+    pub health_path: String,
+}
+
+fn default_label() -> String {
+    "main".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_pool_size() -> usize {
+    vortex_sources::sql::DEFAULT_POOL_SIZE
+}
+
+fn default_region() -> String {
+    std::env::var("AWS_REGION").unwrap_or_else(|_| vortex_sources::s3::DEFAULT_REGION.to_string())
+}
+
+fn default_poll_interval() -> u64 {
+    DEFAULT_S3_POLL_INTERVAL_SECONDS
+}
+
+fn default_consul_address() -> String {
+    DEFAULT_CONSUL_ADDRESS.to_string()
+}
+
+fn default_consul_prefix() -> String {
+    vortex_sources::consul::DEFAULT_PREFIX.to_string()
+}
+
+fn default_consul_context() -> String {
+    vortex_sources::consul::DEFAULT_CONTEXT.to_string()
+}
+
+fn default_profile_separator() -> String {
+    vortex_sources::consul::DEFAULT_PROFILE_SEPARATOR.to_string()
+}
+
+fn default_data_key() -> Option<String> {
+    Some(vortex_sources::consul::DEFAULT_DATA_KEY.to_string())
+}
+
+fn default_consul_wait() -> u64 {
+    DEFAULT_CONSUL_WAIT_SECONDS
+}
+
+fn default_upstream_timeout() -> u64 {
+    DEFAULT_UPSTREAM_TIMEOUT_SECONDS
+}
+
+fn default_upstream_retries() -> u32 {
+    vortex_sources::upstream::DEFAULT_RETRIES
+}
+
+fn default_upstream_health_path() -> String {
+    vortex_sources::upstream::DEFAULT_HEALTH_PATH.to_string()
+}
+
+#[cfg(feature = "redis")]
+fn default_redis_channel() -> String {
+    vortex_sources::redis::DEFAULT_CHANNEL.to_string()
+}
+
+impl BackendSettings {
+    /// Lee la configuracion del backend `kind` de variables de entorno.
+    pub fn from_env(kind: &str) -> Result<Self, BackendError> {
+        match kind {
+            "git" => Ok(Self::Git(GitSettings {
+                uri: required("GIT_URI")?,
+                local_path: Some(PathBuf::from(env_or(
+                    "GIT_LOCAL_PATH",
+                    DEFAULT_GIT_LOCAL_PATH,
+                ))),
+                default_label: env_or("GIT_DEFAULT_LABEL", "main"),
+                search_paths: env_list("GIT_SEARCH_PATHS"),
+                username: std::env::var("GIT_USERNAME").ok(),
+                password: std::env::var("GIT_PASSWORD").ok(),
+            })),
+            "native" => Ok(Self::Native(NativeSettings {
+                path: PathBuf::from(required("NATIVE_PATH")?),
+                default_label: env_or("NATIVE_DEFAULT_LABEL", "main"),
+                search_paths: env_list("NATIVE_SEARCH_PATHS"),
+                watch: env_parse("NATIVE_WATCH", true),
+            })),
+            "sql" => Ok(Self::Sql(SqlSettings {
+                url: required("SQL_URL")?,
+                query: std::env::var("SQL_QUERY").ok(),
+                pool_size: env_parse("SQL_POOL_SIZE", default_pool_size()),
+                default_label: env_or("SQL_DEFAULT_LABEL", vortex_sources::sql::DEFAULT_LABEL),
+                migrate: env_parse("SQL_MIGRATE", true),
+            })),
+            "s3" => Ok(Self::S3(S3Settings {
+                bucket: required("S3_BUCKET")?,
+                endpoint: std::env::var("S3_ENDPOINT").ok(),
+                region: std::env::var("S3_REGION").unwrap_or_else(|_| default_region()),
+                path_style: env_parse("S3_PATH_STYLE", false),
+                prefix: std::env::var("S3_PREFIX").ok(),
+                default_label: env_or("S3_DEFAULT_LABEL", vortex_sources::s3::DEFAULT_LABEL),
+                poll_interval_seconds: env_parse(
+                    "S3_POLL_INTERVAL_SECONDS",
+                    DEFAULT_S3_POLL_INTERVAL_SECONDS,
+                ),
+                access_key_id: None,
+                secret_access_key: None,
+                session_token: None,
+            })),
+            "consul" => Ok(Self::Consul(ConsulSettings {
+                address: env_or("CONSUL_HTTP_ADDR", DEFAULT_CONSUL_ADDRESS),
+                prefix: env_or("CONSUL_PREFIX", vortex_sources::consul::DEFAULT_PREFIX),
+                token: None,
+                datacenter: std::env::var("CONSUL_DATACENTER").ok(),
+                default_context: env_or(
+                    "CONSUL_DEFAULT_CONTEXT",
+                    vortex_sources::consul::DEFAULT_CONTEXT,
+                ),
+                profile_separator: env_or(
+                    "CONSUL_PROFILE_SEPARATOR",
+                    vortex_sources::consul::DEFAULT_PROFILE_SEPARATOR,
+                ),
+                data_key: match std::env::var("CONSUL_DATA_KEY") {
+                    Ok(key) if key.is_empty() => None,
+                    Ok(key) => Some(key),
+                    Err(_) => default_data_key(),
+                },
+                default_label: env_or(
+                    "CONSUL_DEFAULT_LABEL",
+                    vortex_sources::consul::DEFAULT_LABEL,
+                ),
+                watch: env_parse("CONSUL_WATCH", true),
+                wait_seconds: env_parse("CONSUL_WAIT_SECONDS", DEFAULT_CONSUL_WAIT_SECONDS),
+            })),
+            #[cfg(feature = "redis")]
+            "redis" => Ok(Self::Redis(RedisSettings {
+                url: required("REDIS_URL")?,
+                key_prefix: env_or("REDIS_KEY_PREFIX", ""),
+                default_label: env_or("REDIS_DEFAULT_LABEL", vortex_sources::redis::DEFAULT_LABEL),
+                watch: env_parse("REDIS_WATCH", true),
+                channel: env_or("REDIS_CHANNEL", vortex_sources::redis::DEFAULT_CHANNEL),
+            })),
+            "upstream" => Ok(Self::Upstream(UpstreamSettings {
+                url: required("UPSTREAM_URL")?,
+                username: std::env::var("UPSTREAM_USERNAME").ok(),
+                password: std::env::var("UPSTREAM_PASSWORD").ok(),
+                token: std::env::var("UPSTREAM_TOKEN").ok(),
+                headers: BTreeMap::new(),
+                default_label: env_or(
+                    "UPSTREAM_DEFAULT_LABEL",
+                    vortex_sources::upstream::DEFAULT_LABEL,
+                ),
+                timeout_seconds: env_parse(
+                    "UPSTREAM_TIMEOUT_SECONDS",
+                    DEFAULT_UPSTREAM_TIMEOUT_SECONDS,
+                ),
+                retries: env_parse("UPSTREAM_RETRIES", default_upstream_retries()),
+                stale_on_error: env_parse("UPSTREAM_STALE_ON_ERROR", true),
+                max_stale_seconds: env_parse("UPSTREAM_MAX_STALE_SECONDS", 0),
+                health_path: env_or(
+                    "UPSTREAM_HEALTH_PATH",
+                    vortex_sources::upstream::DEFAULT_HEALTH_PATH,
+                ),
+            })),
+            #[cfg(not(feature = "redis"))]
+            "redis" => Err(BackendError::Invalid(
+                "the redis backend needs vortex-server built with the redis feature".to_string(),
+            )),
+            other => Err(BackendError::Invalid(format!(
+                "VORTEX_BACKEND must be git, native, sql, s3, consul, redis, upstream or composite, got '{}'",
+                other
+            ))),
+        }
+    }
+
+    /// Devuelve el tipo del backend (`git`, `native`, `sql`, `s3`, `consul`,
+    /// `redis` o `upstream`).
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Git(_) => "git",
+            Self::Native(_) => "native",
+            Self::Sql(_) => "sql",
+            Self::S3(_) => "s3",
+            Self::Consul(_) => "consul",
+            #[cfg(feature = "redis")]
+            Self::Redis(_) => "redis",
+            Self::Upstream(_) => "upstream",
+        }
+    }
+
+    /// Construye el backend. `name` identifica al backend en los logs y,
+    /// para git, nombra su clon si no se indico `local_path`.
+    pub async fn build(
+        &self,
+        name: &str,
+        context: &BackendContext,
+    ) -> Result<BuiltBackend, BackendError> {
+        let init = |reason: String| BackendError::Init {
+            name: name.to_string(),
+            reason,
+        };
+
+        match self {
+            Self::Git(settings) => {
+                let local_path = settings
+                    .local_path
+                    .clone()
+                    .unwrap_or_else(|| Path::new(DEFAULT_GIT_LOCAL_PATH).join(name));
+
+                let mut builder = GitBackendConfig::builder()
+                    .uri(&settings.uri)
+                    .local_path(local_path.clone())
+                    .default_label(&settings.default_label)
+                    .duplicate_keys(context.duplicate_keys);
+                if !settings.search_paths.is_empty() {
+                    builder = builder.search_paths(settings.search_paths.clone());
+                }
+                if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+                    builder = builder.basic_auth(username.clone(), password.clone());
+                }
+                if let Some(path) = &context.sops_age_key_file {
+                    builder = builder.sops_age_key_file(path.clone());
+                }
+                let config = builder.build().map_err(|e| init(e.to_string()))?;
+
+                tracing::info!("[{}] Git repository: {}", name, settings.uri);
+                tracing::info!("[{}] Local path: {}", name, local_path.display());
+                tracing::info!("[{}] Default label: {}", name, settings.default_label);
+
+                let backend = GitBackend::new(config)
+                    .await
+                    .map_err(|e| init(e.to_string()))?
+                    .with_format_registry(Arc::clone(&context.formats));
+                Ok(BuiltBackend {
+                    source: Arc::new(backend),
+                    changes: None,
+                })
+            },
+            Self::Native(settings) => {
+                let mut backend = FilesystemBackend::new(&settings.path)
+                    .with_default_label(&settings.default_label)
+                    .with_duplicate_keys(context.duplicate_keys)
+                    .with_format_registry(Arc::clone(&context.formats));
+                if !settings.search_paths.is_empty() {
+                    backend = backend.with_search_paths(settings.search_paths.clone());
+                }
+                if let Some(identity) = &context.sops_age_key_file {
+                    backend = backend.with_sops(Arc::new(SopsDecryptor::new(
+                        AgeIdentityFile::new(identity.clone()),
+                    )));
+                }
+
+                tracing::info!("[{}] Native directory: {}", name, settings.path.display());
+                tracing::info!("[{}] Default label: {}", name, settings.default_label);
+
+                if settings.watch {
+                    tracing::info!(
+                        "[{}] Watching {} for changes",
+                        name,
+                        settings.path.display()
+                    );
+                    backend = backend.watch().map_err(|e| init(e.to_string()))?;
+                }
+                let changes = backend.changes();
+                Ok(BuiltBackend {
+                    source: Arc::new(backend),
+                    changes,
+                })
+            },
+            Self::Sql(settings) => {
+                let mut backend = SqlBackend::connect(&settings.url, settings.pool_size)
+                    .await
+                    .map_err(|e| init(e.to_string()))?
+                    .with_default_label(&settings.default_label);
+                if let Some(query) = &settings.query {
+                    backend = backend.with_query(query.clone());
+                }
+                if settings.migrate {
+                    let applied = backend.migrate().await.map_err(|e| init(e.to_string()))?;
+                    if !applied.is_empty() {
+                        tracing::info!("[{}] Applied SQL migrations {:?}", name, applied);
+                    }
+                }
+
+                tracing::info!(
+                    "[{}] SQL database: {:?}",
+                    name,
+                    backend.database().dialect()
+                );
+                tracing::info!("[{}] Default label: {}", name, settings.default_label);
+                // Only writes through this server are reported.
+                let changes = backend.changes();
+                Ok(BuiltBackend {
+                    source: Arc::new(backend),
+                    changes: Some(changes),
+                })
+            },
+            Self::S3(settings) => {
+                let endpoint = settings
+                    .endpoint
+                    .clone()
+                    .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", settings.region));
+                let mut backend = S3Backend::new(&endpoint, &settings.bucket)
+                    .map_err(|e| init(e.to_string()))?
+                    .with_region(&settings.region)
+                    .with_path_style(settings.path_style)
+                    .with_default_label(&settings.default_label)
+                    .with_format_registry(Arc::clone(&context.formats));
+                if let Some(prefix) = &settings.prefix {
+                    backend = backend.with_prefix(prefix);
+                }
+                if let Some(credentials) = settings.credentials() {
+                    backend = backend.with_credentials(credentials);
+                }
+
+                tracing::info!("[{}] S3 bucket: {} at {}", name, settings.bucket, endpoint);
+                tracing::info!("[{}] Default label: {}", name, settings.default_label);
+
+                if settings.poll_interval_seconds == 0 {
+                    return Ok(BuiltBackend {
+                        source: Arc::new(backend),
+                        changes: None,
+                    });
+                }
+                let interval = Duration::from_secs(settings.poll_interval_seconds);
+                tracing::info!("[{}] Polling S3 for changes every {:?}", name, interval);
+                let backend = backend.poll(interval);
+                let changes = backend.changes();
+                Ok(BuiltBackend {
+                    source: Arc::new(backend),
+                    changes: Some(changes),
+                })
+            },
+            Self::Consul(settings) => {
+                // CONSUL_HTTP_ADDR suele omitir el esquema.
+                let address = if settings.address.contains("://") {
+                    settings.address.clone()
+                } else {
+                    format!("http://{}", settings.address)
+                };
+                let mut backend = ConsulBackend::new(&address)
+                    .map_err(|e| init(e.to_string()))?
+                    .with_prefix(&settings.prefix)
+                    .with_default_context(&settings.default_context)
+                    .with_profile_separator(&settings.profile_separator)
+                    .with_data_key(settings.data_key.clone())
+                    .with_default_label(&settings.default_label);
+                if let Some(token) = settings
+                    .token
+                    .clone()
+                    .or_else(|| std::env::var("CONSUL_HTTP_TOKEN").ok())
+                {
+                    backend = backend.with_token(token);
+                }
+                if let Some(datacenter) = &settings.datacenter {
+                    backend = backend.with_datacenter(datacenter);
+                }
+
+                tracing::info!("[{}] Consul agent: {}", name, address);
+                tracing::info!("[{}] Prefix: {}", name, settings.prefix);
+
+                if !settings.watch {
+                    return Ok(BuiltBackend {
+                        source: Arc::new(backend),
+                        changes: None,
+                    });
+                }
+                let wait = Duration::from_secs(settings.wait_seconds.max(1));
+                tracing::info!("[{}] Watching Consul for changes", name);
+                let backend = backend.watch(wait);
+                let changes = backend.changes();
+                Ok(BuiltBackend {
+                    source: Arc::new(backend),
+                    changes: Some(changes),
+                })
+            },
+            #[cfg(feature = "redis")]
+            Self::Redis(settings) => {
+                let backend = vortex_sources::RedisBackend::connect(&settings.url)
+                    .await
+                    .map_err(|e| init(e.to_string()))?
+                    .with_key_prefix(&settings.key_prefix)
+                    .with_default_label(&settings.default_label)
+                    .with_channel(&settings.channel);
+
+                tracing::info!("[{}] Redis server: {}", name, backend.url());
+                tracing::info!("[{}] Default label: {}", name, settings.default_label);
+
+                if !settings.watch {
+                    return Ok(BuiltBackend {
+                        source: Arc::new(backend),
+                        changes: None,
+                    });
+                }
+                tracing::info!(
+                    "[{}] Watching Redis keyspace notifications and channel {}",
+                    name,
+                    settings.channel
+                );
+                let backend = backend.watch();
+                let changes = backend.changes();
+                Ok(BuiltBackend {
+                    source: Arc::new(backend),
+                    changes: Some(changes),
+                })
+            },
+            Self::Upstream(settings) => {
+                let mut backend = HttpUpstreamSource::new(&settings.url)
+                    .map_err(|e| init(e.to_string()))?
+                    .with_default_label(&settings.default_label)
+                    .with_timeout(Duration::from_secs(settings.timeout_seconds.max(1)))
+                    .with_retries(
+                        settings.retries,
+                        vortex_sources::upstream::DEFAULT_RETRY_BACKOFF,
+                    )
+                    .with_stale_on_error(settings.stale_on_error)
+                    .with_health_path(&settings.health_path);
+                if settings.max_stale_seconds > 0 {
+                    backend =
+                        backend.with_max_stale(Duration::from_secs(settings.max_stale_seconds));
+                }
+                if let Some(token) = &settings.token {
+                    backend = backend.with_bearer_token(token);
+                } else if let Some(username) = &settings.username {
+                    backend = backend.with_basic_auth(
+                        username,
+                        settings.password.as_deref().unwrap_or_default(),
+                    );
+                }
+                for (name, value) in &settings.headers {
+                    backend = backend.with_header(name, value);
+                }
+
+                tracing::info!("[{}] Upstream server: {}", name, backend.base_url());
+                tracing::info!("[{}] Default label: {}", name, settings.default_label);
+                Ok(BuiltBackend {
+                    source: Arc::new(backend),
+                    changes: None,
+                })
+            },
+        }
+    }
+}
+
+impl S3Settings {
+    /// Devuelve las credenciales configuradas, o las de las variables `AWS_*`.
+    fn credentials(&self) -> Option<Credentials> {
+        let (access_key, secret_key, token) = match &self.access_key_id {
+            Some(access_key) => (
+                access_key.clone(),
+                self.secret_access_key.clone()?,
+                self.session_token.clone(),
+            ),
+            None => (
+                std::env::var("AWS_ACCESS_KEY_ID").ok()?,
+                std::env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+                std::env::var("AWS_SESSION_TOKEN").ok(),
+            ),
+        };
+        let credentials = Credentials::new(access_key, secret_key);
+        Some(match token {
+            Some(token) => credentials.with_session_token(token),
+            None => credentials,
+        })
+    }
+}
+
+/// Un backend de la seccion `composite`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BackendEntry {
+    /// Nombre en logs, avisos y `/health/sources`; por defecto
+    /// `{tipo}-{posicion}`.
+    pub name: Option<String>,
+    /// Precedencia; gana la mas alta.
+    #[serde(default)]
+    pub priority: i32,
+    /// Tipo y opciones del backend.
+    #[serde(flatten)]
+    pub settings: BackendSettings,
+}
+
+/// La seccion `composite` del archivo de configuracion.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompositeSettings {
+    /// Si un backend que falla hace fallar la peticion.
+    #[serde(default)]
+    pub fail_fast: bool,
+    /// Backends a combinar.
+    pub backends: Vec<BackendEntry>,
+}
+
+/// Archivo de configuracion del servidor.
+///
+/// Solo se leen las secciones que el servidor implementa; el resto se ignora.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ServerConfigFile {
+    /// Backends combinados.
+    pub composite: Option<CompositeSettings>,
+}
+
+impl ServerConfigFile {
+    /// Lee el archivo YAML en `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, BackendError> {
+        let path = path.as_ref();
+        let read_error = |reason: String| BackendError::Read {
+            path: path.to_path_buf(),
+            reason,
+        };
+        let content = std::fs::read_to_string(path).map_err(|e| read_error(e.to_string()))?;
+        Self::from_yaml(&content).map_err(|e| match e {
+            BackendError::Invalid(reason) => read_error(reason),
+            other => other,
+        })
+    }
+
+    /// Lee la configuracion de un documento YAML.
+    pub fn from_yaml(content: &str) -> Result<Self, BackendError> {
+        serde_yaml::from_str(content).map_err(|e| BackendError::Invalid(e.to_string()))
+    }
+}
+
+impl CompositeSettings {
+    /// Construye cada backend y los combina.
+    ///
+    /// Devuelve tambien los receptores de cambios de los backends que los
+    /// detectan.
+    pub async fn build(
+        &self,
+        context: &BackendContext,
+    ) -> Result<(CompositeConfigSource, Vec<watch::Receiver<u64>>), BackendError> {
+        if self.backends.is_empty() {
+            return Err(BackendError::Invalid(
+                "composite must declare at least one backend".to_string(),
+            ));
+        }
+
+        let mut composite = CompositeConfigSource::new().with_fail_fast(self.fail_fast);
+        let mut changes = Vec::new();
+        for (index, entry) in self.backends.iter().enumerate() {
+            let name = entry
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("{}-{}", entry.settings.kind(), index));
+            if composite.backends().iter().any(|b| b.name == name) {
+                return Err(BackendError::Invalid(format!(
+                    "backend name '{}' is used twice",
+                    name
+                )));
+            }
+
+            let built = entry.settings.build(&name, context).await?;
+            tracing::info!("[{}] Priority: {}", name, entry.priority);
+            composite = composite.with_backend(name, entry.priority, built.source);
+            changes.extend(built.changes);
+        }
+        Ok((composite, changes))
+    }
+}
+
+/// Overrides de emergencia sobre el backend, de variables de entorno con
+/// prefijo y de un directorio montado (ver [`vortex_sources::overrides`]).
+#[derive(Debug, Clone)]
+pub struct OverrideSettings {
+    /// Prefijo de las variables con overrides; vacio las desactiva.
+    pub prefix: String,
+    /// Directorio con un archivo por clave en `{app}/{profile}/`.
+    pub directory: Option<PathBuf>,
+}
+
+impl OverrideSettings {
+    /// Lee `VORTEX_OVERRIDES_PREFIX` y `VORTEX_OVERRIDES_DIR`.
+    pub fn from_env() -> Self {
+        Self {
+            prefix: env_or(
+                "VORTEX_OVERRIDES_PREFIX",
+                vortex_sources::overrides::DEFAULT_PREFIX,
+            ),
+            directory: std::env::var_os("VORTEX_OVERRIDES_DIR").map(PathBuf::from),
+        }
+    }
+
+    /// Pone los overrides delante de `source`.
+    ///
+    /// Sin variables ni directorio devuelve `source` tal cual. El directorio
+    /// se vigila si existe, para invalidar la cache cuando cambia.
+    pub fn apply(&self, source: Arc<dyn ConfigSource>) -> Result<BuiltBackend, BackendError> {
+        let mut overrides = OverrideSource::new(Arc::clone(&source));
+        if !self.prefix.is_empty() {
+            overrides = overrides.with_environment(&self.prefix);
+        }
+        if !overrides.variables().is_empty() {
+            tracing::info!(
+                "Overrides: {} keys from {}* variables",
+                overrides.variables().len(),
+                self.prefix
+            );
+        }
+
+        if let Some(directory) = &self.directory {
+            tracing::info!("Overrides directory: {}", directory.display());
+            overrides = overrides.with_directory(directory);
+            if directory.is_dir() {
+                overrides = overrides.watch().map_err(|e| BackendError::Init {
+                    name: "overrides".to_string(),
+                    reason: e.to_string(),
+                })?;
+            } else {
+                tracing::warn!(
+                    "Overrides directory {} does not exist yet; it is not watched",
+                    directory.display()
+                );
+            }
+        } else if overrides.variables().is_empty() {
+            return Ok(BuiltBackend {
+                source,
+                changes: None,
+            });
+        }
+
+        let changes = overrides.changes();
+        Ok(BuiltBackend {
+            source: Arc::new(overrides),
+            changes,
+        })
+    }
+}
+
+fn required(name: &str) -> Result<String, BackendError> {
+    std::env::var(name)
+        .map_err(|_| BackendError::Invalid(format!("{} environment variable is required", name)))
+}
+
+fn env_or(name: &str, default: &str) -> String {
+    std::env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Lee una lista separada por comas.
+fn env_list(name: &str) -> Vec<String> {
+    std::env::var(name)
+        .map(|list| list.split(',').map(|s| s.trim().to_string()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use vortex_git::ConfigQuery;
+    use vortex_git::vortex_core::ConfigValue;
+
+    fn context() -> BackendContext {
+        BackendContext {
+            formats: Arc::new(FormatRegistry::builtin()),
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            sops_age_key_file: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let file = ServerConfigFile::from_yaml(
+            r#"
+server:
+  port: 8888
+composite:
+  fail_fast: true
+  backends:
+    - type: sql
+      name: overrides
+      priority: 100
+      url: sqlite:/var/lib/vortex/overrides.db
+    - type: git
+      priority: 10
+      uri: https://github.com/acme/config-repo.git
+      search_paths: [config]
+    - type: s3
+      bucket: config
+      path_style: true
+    - type: consul
+      address: consul:8500
+      data_key: null
+    - type: upstream
+      url: https://config.central:8888
+      token: s3cret
+      headers:
+        x-region: eu-west-1
+"#,
+        )
+        .unwrap();
+
+        let composite = file.composite.unwrap();
+        assert!(composite.fail_fast);
+        assert_eq!(composite.backends.len(), 5);
+
+        let sql = &composite.backends[0];
+        assert_eq!(sql.name.as_deref(), Some("overrides"));
+        assert_eq!(sql.priority, 100);
+        let BackendSettings::Sql(settings) = &sql.settings else {
+            panic!("expected a SQL backend");
+        };
+        assert_eq!(settings.pool_size, vortex_sources::sql::DEFAULT_POOL_SIZE);
+        assert!(settings.migrate);
+
+        let BackendSettings::Git(settings) = &composite.backends[1].settings else {
+            panic!("expected a git backend");
+        };
+        assert_eq!(settings.search_paths, ["config"]);
+        assert_eq!(settings.default_label, "main");
+        assert!(settings.local_path.is_none());
+
+        let s3 = &composite.backends[2];
+        assert_eq!(s3.priority, 0);
+        let BackendSettings::S3(settings) = &s3.settings else {
+            panic!("expected an S3 backend");
+        };
+        assert_eq!(
+            settings.poll_interval_seconds,
+            DEFAULT_S3_POLL_INTERVAL_SECONDS
+        );
+
+        let BackendSettings::Consul(settings) = &composite.backends[3].settings else {
+            panic!("expected a Consul backend");
+        };
+        assert_eq!(settings.prefix, "config");
+        assert!(settings.data_key.is_none());
+        assert_eq!(settings.wait_seconds, DEFAULT_CONSUL_WAIT_SECONDS);
+
+        let BackendSettings::Upstream(settings) = &composite.backends[4].settings else {
+            panic!("expected an upstream backend");
+        };
+        assert_eq!(settings.headers["x-region"], "eu-west-1");
+        assert_eq!(settings.retries, vortex_sources::upstream::DEFAULT_RETRIES);
+        assert!(settings.stale_on_error);
+        assert_eq!(settings.max_stale_seconds, 0);
+    }
+
+    #[cfg(feature = "redis")]
+    #[test]
+    fn test_parse_redis() {
+        let file = ServerConfigFile::from_yaml(
+            "composite:\n  backends:\n    - type: redis\n      url: rediss://cache:6380/1\n      key_prefix: 'config:'\n",
+        )
+        .unwrap();
+
+        let BackendSettings::Redis(settings) = &file.composite.unwrap().backends[0].settings else {
+            panic!("expected a Redis backend");
+        };
+        assert_eq!(settings.key_prefix, "config:");
+        assert_eq!(settings.channel, vortex_sources::redis::DEFAULT_CHANNEL);
+        assert!(settings.watch);
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        assert!(
+            ServerConfigFile::from_yaml("server: {}")
+                .unwrap()
+                .composite
+                .is_none()
+        );
+        assert!(matches!(
+            ServerConfigFile::from_yaml("composite:\n  backends:\n    - type: ftp\n"),
+            Err(BackendError::Invalid(_))
+        ));
+        assert!(matches!(
+            ServerConfigFile::from_yaml("composite:\n  backends:\n    - type: sql\n      uri: x\n"),
+            Err(BackendError::Invalid(_))
+        ));
+        assert!(matches!(
+            ServerConfigFile::from_file("/nonexistent/vortex.yaml"),
+            Err(BackendError::Read { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_build_composite() {
+        let dir = tempfile::tempdir().unwrap();
+        let low = dir.path().join("low");
+        let high = dir.path().join("high");
+        std::fs::create_dir_all(&low).unwrap();
+        std::fs::create_dir_all(&high).unwrap();
+        std::fs::write(low.join("payments.yml"), "pool: 10\nport: 80\n").unwrap();
+        std::fs::write(high.join("payments.yml"), "pool: 20\n").unwrap();
+
+        let file = ServerConfigFile::from_yaml(&format!(
+            "composite:\n  backends:\n    - {{type: native, priority: 1, watch: false, path: {}}}\n    - {{type: native, name: high, priority: 2, watch: false, path: {}}}\n",
+            low.display(),
+            high.display()
+        ))
+        .unwrap();
+        let (composite, changes) = file.composite.unwrap().build(&context()).await.unwrap();
+        assert!(changes.is_empty());
+
+        let names: Vec<&str> = composite
+            .backends()
+            .iter()
+            .map(|b| b.name.as_str())
+            .collect();
+        assert_eq!(names, ["high", "native-0"]);
+
+        let result = composite
+            .fetch(&ConfigQuery::new("payments", vec!["default"]))
+            .await
+            .unwrap();
+        let merged = result.merged();
+        assert_eq!(merged.get("pool"), Some(&ConfigValue::Integer(20)));
+        assert_eq!(merged.get("port"), Some(&ConfigValue::Integer(80)));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_names() {
+        let settings = CompositeSettings {
+            fail_fast: false,
+            backends: vec![
+                BackendEntry {
+                    name: Some("files".to_string()),
+                    priority: 0,
+                    settings: BackendSettings::Native(NativeSettings {
+                        path: PathBuf::from("/tmp"),
+                        default_label: default_label(),
+                        search_paths: Vec::new(),
+                        watch: false,
+                    }),
+                };
+                2
+            ],
+        };
+        assert!(matches!(
+            settings.build(&context()).await,
+            Err(BackendError::Invalid(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_apply_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("config");
+        let overrides = dir.path().join("overrides");
+        std::fs::create_dir_all(&config).unwrap();
+        std::fs::create_dir_all(overrides.join("payments/default")).unwrap();
+        std::fs::write(config.join("payments.yml"), "server:\n  port: 8080\n").unwrap();
+        std::fs::write(overrides.join("payments/default/SERVER_PORT"), "9090").unwrap();
+        let source: Arc<dyn ConfigSource> = Arc::new(FilesystemBackend::new(&config));
+
+        // Nothing to apply: the backend is served as is.
+        let settings = OverrideSettings {
+            prefix: String::new(),
+            directory: None,
+        };
+        let built = settings.apply(Arc::clone(&source)).unwrap();
+        assert!(Arc::ptr_eq(&built.source, &source));
+        assert!(built.changes.is_none());
+
+        let settings = OverrideSettings {
+            prefix: String::new(),
+            directory: Some(overrides),
+        };
+        let built = settings.apply(source).unwrap();
+        assert!(built.changes.is_some());
+        let merged = built
+            .source
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap()
+            .merged();
+        assert_eq!(
+            merged.get("server.port"),
+            Some(&ConfigValue::String("9090".to_string()))
+        );
+    }
+}
@@ -3,17 +3,30 @@
 use crate::cache::keys::CacheKey;
 use crate::handlers::response::ConfigResponse;
 use crate::metrics::CacheMetrics;
+use moka::Expiry;
 use moka::future::Cache;
 use std::future::Future;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use thiserror::Error;
+use vortex_git::vortex_core::validation::ValidationReport;
 
 /// Error del sistema de cache
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum CacheError {
     #[error("failed to fetch config: {0}")]
     FetchError(String),
+
+    /// La configuracion resuelta no cumple su schema (modo `reject`).
+    #[error("configuration does not match its schema")]
+    Validation(ValidationReport),
+}
+
+impl CacheError {
+    /// Retorna true si el error es de validacion de schema.
+    pub fn is_validation(&self) -> bool {
+        matches!(self, CacheError::Validation(_))
+    }
 }
 
 /// Configuracion del cache.
@@ -21,6 +34,9 @@ pub enum CacheError {
 pub struct CacheConfig {
     /// TTL en segundos (default: 300 = 5 minutos)
     pub ttl_seconds: u64,
+    /// TTL en segundos de las respuestas parciales, a las que les falta un
+    /// backend que fallo (default: 10)
+    pub partial_ttl_seconds: u64,
     /// Maximo numero de entries (default: 10000)
     pub max_capacity: u64,
     /// Time-to-idle en segundos (opcional)
@@ -31,6 +47,7 @@ impl Default for CacheConfig {
     fn default() -> Self {
         Self {
             ttl_seconds: 300,
+            partial_ttl_seconds: 10,
             max_capacity: 10_000,
             tti_seconds: None,
         }
@@ -69,7 +86,10 @@ impl ConfigCache {
 
         let mut builder = Cache::builder()
             .max_capacity(config.max_capacity)
-            .time_to_live(Duration::from_secs(config.ttl_seconds));
+            .time_to_live(Duration::from_secs(config.ttl_seconds))
+            .expire_after(PartialExpiry(Duration::from_secs(
+                config.partial_ttl_seconds,
+            )));
 
         if let Some(tti) = config.tti_seconds {
             builder = builder.time_to_idle(Duration::from_secs(tti));
@@ -173,7 +193,7 @@ impl ConfigCache {
                 Ok(Arc::new(response))
             })
             .await
-            .map_err(|e: std::sync::Arc<CacheError>| CacheError::FetchError(e.to_string()))?;
+            .map_err(Arc::unwrap_or_clone)?;
 
         self.metrics
             .record_operation_duration("get_or_insert_miss", start.elapsed());
@@ -252,6 +272,37 @@ impl ConfigCache {
     }
 }
 
+/// Expira antes las respuestas parciales, para que un backend que se
+/// recupera vuelva a servirse sin esperar el TTL completo.
+struct PartialExpiry(Duration);
+
+impl PartialExpiry {
+    fn ttl(&self, value: &ConfigResponse) -> Option<Duration> {
+        value.partial.then_some(self.0)
+    }
+}
+
+impl Expiry<CacheKey, Arc<ConfigResponse>> for PartialExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &CacheKey,
+        value: &Arc<ConfigResponse>,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        self.ttl(value)
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &CacheKey,
+        value: &Arc<ConfigResponse>,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        self.ttl(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -401,4 +452,34 @@ mod tests {
         // (Moka previene thundering herd)
         assert_eq!(call_count.load(Ordering::SeqCst), 1);
     }
+
+    #[tokio::test]
+    async fn test_partial_responses_expire_first() {
+        let cache = ConfigCache::new(CacheConfig {
+            partial_ttl_seconds: 0,
+            ..CacheConfig::default()
+        });
+        let complete = CacheKey::new("myapp", "prod", "main");
+        let partial = CacheKey::new("myapp", "dev", "main");
+
+        cache
+            .insert(
+                complete.clone(),
+                ConfigResponse::empty("myapp", vec!["prod".to_string()]),
+            )
+            .await;
+        cache
+            .insert(
+                partial.clone(),
+                ConfigResponse {
+                    partial: true,
+                    ..ConfigResponse::empty("myapp", vec!["dev".to_string()])
+                },
+            )
+            .await;
+        cache.sync().await;
+
+        assert!(cache.get(&complete).await.is_some());
+        assert!(cache.get(&partial).await.is_none());
+    }
 }
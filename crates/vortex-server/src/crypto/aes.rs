@@ -0,0 +1,303 @@
+//! Claves simetricas AES-256.
+//!
+//! [`AesCbcEncryptor`] es el formato de Spring (`Encryptors.text`) y el que se
+//! usa por defecto. [`AesGcmEncryptor`] es un formato propio de Vortex, que
+//! hay que pedir explicitamente y que un servidor Spring no puede leer.
+
+use aes_gcm::aead::consts::U16;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::aes::Aes256;
+use aes_gcm::{Aes256Gcm, AesGcm, Key, Nonce};
+use base64::Engine;
+use base64::engine::DecodePaddingMode;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig, STANDARD};
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+
+use super::{CryptoError, TextEncryptor};
+
+/// Iteraciones de PBKDF2 al derivar la clave de un password (formato GCM).
+const PBKDF2_ROUNDS: u32 = 65_536;
+
+/// Iteraciones de PBKDF2-HMAC-SHA1 en Spring Security.
+const SPRING_PBKDF2_ROUNDS: u32 = 1024;
+
+/// Largo del nonce de AES-GCM.
+const NONCE_LEN: usize = 12;
+
+/// Largo del IV de Spring Security, tanto en CBC como en GCM.
+const IV_LEN: usize = 16;
+
+/// AES-GCM con el IV de 16 bytes de Spring Security.
+type SpringGcm = AesGcm<Aes256, U16>;
+
+/// El `BytesEncryptor` de Spring Security (`Encryptors.standard` y
+/// `Encryptors.stronger`).
+///
+/// La clave se deriva con PBKDF2-HMAC-SHA1 (1024 iteraciones) del password y
+/// del salt; cada valor lleva un IV aleatorio de 16 bytes delante:
+/// `iv || AES/CBC/PKCS5Padding(datos)`, o `iv || datos || tag` con GCM.
+#[derive(Clone)]
+pub(super) struct SpringBytesEncryptor {
+    key: [u8; 32],
+    gcm: bool,
+}
+
+impl SpringBytesEncryptor {
+    /// Deriva la clave; `salt` son los bytes ya decodificados del hex.
+    pub(super) fn new(password: &str, salt: &[u8], gcm: bool) -> Self {
+        let key = pbkdf2::pbkdf2_hmac_array::<sha1::Sha1, 32>(
+            password.as_bytes(),
+            salt,
+            SPRING_PBKDF2_ROUNDS,
+        );
+        Self { key, gcm }
+    }
+
+    pub(super) fn encrypt(&self, plain: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let mut iv = [0u8; IV_LEN];
+        OsRng.fill_bytes(&mut iv);
+
+        let data = if self.gcm {
+            SpringGcm::new(Key::<SpringGcm>::from_slice(&self.key))
+                .encrypt(Nonce::<U16>::from_slice(&iv), plain)
+                .map_err(|e| CryptoError::Encrypt(e.to_string()))?
+        } else {
+            cbc::Encryptor::<Aes256>::new(&self.key.into(), &iv.into())
+                .encrypt_padded_vec_mut::<Pkcs7>(plain)
+        };
+
+        let mut out = iv.to_vec();
+        out.extend(data);
+        Ok(out)
+    }
+
+    pub(super) fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let wrong = || CryptoError::Decrypt("wrong key or corrupted ciphertext".to_string());
+        let (iv, data) = sealed
+            .split_at_checked(IV_LEN)
+            .ok_or_else(|| CryptoError::Decrypt("ciphertext is too short".to_string()))?;
+
+        if self.gcm {
+            SpringGcm::new(Key::<SpringGcm>::from_slice(&self.key))
+                .decrypt(Nonce::<U16>::from_slice(iv), data)
+                .map_err(|_| wrong())
+        } else {
+            cbc::Decryptor::<Aes256>::new(&self.key.into(), iv.into())
+                .decrypt_padded_vec_mut::<Pkcs7>(data)
+                .map_err(|_| wrong())
+        }
+    }
+}
+
+/// Decodifica un salt en hex, como lo interpreta Spring.
+pub(super) fn hex_salt(salt: &str) -> Result<Vec<u8>, String> {
+    hex::decode(salt.trim()).map_err(|e| format!("salt '{}' is not hex: {}", salt, e))
+}
+
+/// Cifra como el `TextEncryptor` simetrico de Spring Cloud Config.
+///
+/// Es `Encryptors.text(password, salt)`: AES-256-CBC con la clave derivada
+/// por [`SpringBytesEncryptor`], y el texto cifrado en hex. Los valores son
+/// intercambiables con un servidor Spring que use el mismo `encrypt.key` y
+/// `encrypt.salt`.
+#[derive(Clone)]
+pub struct AesCbcEncryptor {
+    bytes: SpringBytesEncryptor,
+}
+
+impl AesCbcEncryptor {
+    /// Deriva la clave de un password y un salt en hex.
+    pub fn from_password(password: &str, salt: &str) -> Result<Self, CryptoError> {
+        let salt = hex_salt(salt).map_err(|reason| CryptoError::InvalidKey {
+            id: "salt".to_string(),
+            reason,
+        })?;
+        Ok(Self {
+            bytes: SpringBytesEncryptor::new(password, &salt, false),
+        })
+    }
+}
+
+impl TextEncryptor for AesCbcEncryptor {
+    fn encrypt(&self, plain: &str) -> Result<String, CryptoError> {
+        Ok(hex::encode(self.bytes.encrypt(plain.as_bytes())?))
+    }
+
+    fn decrypt(&self, cipher: &str) -> Result<String, CryptoError> {
+        let compact: String = cipher.chars().filter(|c| !c.is_whitespace()).collect();
+        let sealed = hex::decode(compact)
+            .map_err(|e| CryptoError::Decrypt(format!("invalid hex: {}", e)))?;
+        utf8(self.bytes.decrypt(&sealed)?)
+    }
+}
+
+/// Cifra con AES-256-GCM; el texto cifrado es `base64(nonce || datos || tag)`.
+///
+/// Formato propio de Vortex, solo con `ENCRYPT_FORMAT=gcm`.
+#[derive(Clone)]
+pub struct AesGcmEncryptor {
+    cipher: Aes256Gcm,
+}
+
+impl AesGcmEncryptor {
+    /// Deriva la clave de un password y un salt con PBKDF2-HMAC-SHA256.
+    ///
+    /// El salt se usa tal cual, sin decodificar.
+    pub fn from_password(password: &str, salt: &str) -> Self {
+        let key = pbkdf2::pbkdf2_hmac_array::<sha2::Sha256, 32>(
+            password.as_bytes(),
+            salt.as_bytes(),
+            PBKDF2_ROUNDS,
+        );
+        Self::from_key(&key)
+    }
+
+    /// Usa una clave de 32 bytes.
+    pub fn from_key(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        }
+    }
+
+    /// Cifra bytes y retorna `nonce || datos || tag`.
+    fn seal(&self, plain: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let mut out = nonce.to_vec();
+        out.extend(
+            self.cipher
+                .encrypt(&nonce, plain)
+                .map_err(|e| CryptoError::Encrypt(e.to_string()))?,
+        );
+        Ok(out)
+    }
+
+    /// Inverso de [`seal`](Self::seal).
+    fn open(&self, sealed: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if sealed.len() < NONCE_LEN {
+            return Err(CryptoError::Decrypt("ciphertext is too short".to_string()));
+        }
+        let (nonce, data) = sealed.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), data)
+            .map_err(|_| CryptoError::Decrypt("wrong key or corrupted ciphertext".to_string()))
+    }
+}
+
+impl TextEncryptor for AesGcmEncryptor {
+    fn encrypt(&self, plain: &str) -> Result<String, CryptoError> {
+        Ok(STANDARD.encode(self.seal(plain.as_bytes())?))
+    }
+
+    fn decrypt(&self, cipher: &str) -> Result<String, CryptoError> {
+        let sealed = decode(cipher)?;
+        utf8(self.open(&sealed)?)
+    }
+}
+
+pub(super) fn utf8(plain: Vec<u8>) -> Result<String, CryptoError> {
+    String::from_utf8(plain)
+        .map_err(|_| CryptoError::Decrypt("plaintext is not valid UTF-8".to_string()))
+}
+
+/// Base64 estandar que acepta el texto con o sin padding (`curl -d` lo
+/// pierde al enviar el cuerpo como formulario).
+const LENIENT: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Decodifica base64, aceptando espacios, saltos de linea y padding ausente.
+pub(super) fn decode(cipher: &str) -> Result<Vec<u8>, CryptoError> {
+    let compact: String = cipher.chars().filter(|c| !c.is_whitespace()).collect();
+    LENIENT
+        .decode(compact)
+        .map_err(|e| CryptoError::Decrypt(format!("invalid base64: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decrypts_values_encrypted_by_spring() {
+        // Produced in Java by Encryptors.text("s3cr3t", "deadbeef").encrypt("hunter2").
+        let key = AesCbcEncryptor::from_password("s3cr3t", "deadbeef").unwrap();
+        assert_eq!(
+            key.decrypt("07c4e707512c674f2d891e599a708baa12acd86376b712609bf61d6b107d55b4")
+                .unwrap(),
+            "hunter2"
+        );
+
+        let key = AesCbcEncryptor::from_password("pässwörd", "cafebabe").unwrap();
+        assert_eq!(
+            key.decrypt("f8228204652d6b754e832672d3f93939891d0fbb655200651102dc9ba4d5734e")
+                .unwrap(),
+            "héllo wörld"
+        );
+    }
+
+    #[test]
+    fn test_cbc_round_trip_and_errors() {
+        let key = AesCbcEncryptor::from_password("s3cr3t", "deadbeef").unwrap();
+
+        let first = key.encrypt("hunter2").unwrap();
+        let second = key.encrypt("hunter2").unwrap();
+        assert_ne!(first, second);
+        assert!(first.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(key.decrypt(&first).unwrap(), "hunter2");
+
+        let other = AesCbcEncryptor::from_password("s3cr3t", "cafebabe").unwrap();
+        assert!(matches!(
+            other.decrypt(&first),
+            Err(CryptoError::Decrypt(_))
+        ));
+        assert!(key.decrypt("not hex").is_err());
+        assert!(key.decrypt("abcd").is_err());
+        assert!(matches!(
+            AesCbcEncryptor::from_password("s3cr3t", "salt"),
+            Err(CryptoError::InvalidKey { .. })
+        ));
+    }
+
+    #[test]
+    fn test_spring_gcm_round_trip() {
+        let key = SpringBytesEncryptor::new("s3cr3t", &[0xde, 0xad, 0xbe, 0xef], true);
+
+        let sealed = key.encrypt(b"hunter2").unwrap();
+        assert_eq!(sealed.len(), IV_LEN + 7 + 16);
+        assert_eq!(key.decrypt(&sealed).unwrap(), b"hunter2");
+    }
+
+    #[test]
+    fn test_round_trip_uses_fresh_nonces() {
+        let key = AesGcmEncryptor::from_password("s3cr3t", "deadbeef");
+
+        let first = key.encrypt("hunter2").unwrap();
+        let second = key.encrypt("hunter2").unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(key.decrypt(&first).unwrap(), "hunter2");
+        assert_eq!(key.decrypt(&second).unwrap(), "hunter2");
+        assert_eq!(key.decrypt(first.trim_end_matches('=')).unwrap(), "hunter2");
+    }
+
+    #[test]
+    fn test_wrong_key_or_salt_fails() {
+        let cipher = AesGcmEncryptor::from_password("s3cr3t", "deadbeef")
+            .encrypt("hunter2")
+            .unwrap();
+
+        for other in [
+            AesGcmEncryptor::from_password("other", "deadbeef"),
+            AesGcmEncryptor::from_password("s3cr3t", "cafebabe"),
+        ] {
+            assert!(matches!(
+                other.decrypt(&cipher),
+                Err(CryptoError::Decrypt(_))
+            ));
+        }
+        assert!(AesGcmEncryptor::from_key(&[7; 32]).decrypt("AAAA").is_err());
+    }
+}
@@ -0,0 +1,632 @@
+//! Valores cifrados en la configuracion (`{cipher}`), como en Spring Cloud Config.
+//!
+//! Un valor `'{cipher}AQB3...'` se descifra al resolver la configuracion, antes
+//! de validarla y de cachearla, asi que los clientes reciben el texto plano.
+//! Opcionalmente el valor indica la clave a usar con `{cipher}{key:prod}AQB3...`;
+//! sin ese prefijo se usa la clave por defecto.
+//!
+//! Las claves pueden ser simetricas ([`AesCbcEncryptor`]) o RSA
+//! ([`RsaEncryptor`]) y se cargan con [`KeyRing::from_env`]. Un valor que no se
+//! puede descifrar se reemplaza, como en Spring, por `invalid.<clave>: <n/a>`
+//! (o por `<n/a>` en su sitio dentro de un array) y se reporta como
+//! advertencia. Sin claves instaladas, los valores cifrados
+//! se devuelven tal cual.
+//!
+//! Los formatos son los de Spring, asi que los valores cifrados por un
+//! servidor Spring con la misma clave se descifran sin cambios, y al reves.
+//! [`AesGcmEncryptor`] es un formato propio que solo se usa con
+//! `ENCRYPT_FORMAT=gcm`.
+
+mod aes;
+mod rsa;
+
+pub use self::aes::{AesCbcEncryptor, AesGcmEncryptor};
+pub use self::rsa::{RsaAlgorithm, RsaEncryptor};
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use vortex_git::ConfigWarning;
+use vortex_git::vortex_core::{ConfigPath, ConfigValue, PathSegment, PropertySource};
+
+/// Prefijo de los valores cifrados.
+pub const CIPHER_PREFIX: &str = "{cipher}";
+
+/// Id de la clave usada cuando el valor no indica `{key:...}`.
+pub const DEFAULT_KEY_ID: &str = "default";
+
+/// Salt por defecto para derivar claves simetricas (el de Spring).
+pub const DEFAULT_SALT: &str = "deadbeef";
+
+/// Clave raiz bajo la que quedan los valores que no se pudieron descifrar.
+const INVALID_KEY: &str = "invalid";
+
+/// Valor de las propiedades que no se pudieron descifrar.
+pub(crate) const INVALID_VALUE: &str = "<n/a>";
+
+/// Errores de cifrado y descifrado.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CryptoError {
+    /// No hay ninguna clave instalada.
+    #[error("No key was installed for encryption service")]
+    NoKey,
+
+    /// El valor pide una clave que no esta instalada.
+    #[error("No key named '{0}' is installed")]
+    UnknownKey(String),
+
+    /// El texto cifrado es invalido o no corresponde a la clave.
+    #[error("Cannot decrypt: {0}")]
+    Decrypt(String),
+
+    /// El cifrado fallo.
+    #[error("Cannot encrypt: {0}")]
+    Encrypt(String),
+
+    /// Una clave no se pudo cargar.
+    #[error("Invalid key '{id}': {reason}")]
+    InvalidKey {
+        /// Id de la clave
+        id: String,
+        /// Motivo
+        reason: String,
+    },
+}
+
+/// Cifra y descifra texto con una clave.
+pub trait TextEncryptor: Send + Sync {
+    /// Cifra `plain` y devuelve el texto cifrado (sin prefijos).
+    fn encrypt(&self, plain: &str) -> Result<String, CryptoError>;
+
+    /// Descifra un texto producido por [`encrypt`](Self::encrypt).
+    fn decrypt(&self, cipher: &str) -> Result<String, CryptoError>;
+}
+
+/// Opciones de las claves, como `encrypt.*` en Spring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySettings {
+    /// Salt en hex de las claves simetricas (`encrypt.salt`).
+    pub salt: String,
+    /// Usar [`AesGcmEncryptor`] en vez del formato de Spring para las claves
+    /// simetricas.
+    pub gcm: bool,
+    /// Algoritmo de las claves RSA (`encrypt.rsa.algorithm`).
+    pub rsa_algorithm: RsaAlgorithm,
+    /// Salt en hex de las claves RSA (`encrypt.rsa.salt`).
+    pub rsa_salt: String,
+    /// Cifrar los datos de las claves RSA con AES-GCM (`encrypt.rsa.strong`).
+    pub rsa_strong: bool,
+}
+
+impl Default for KeySettings {
+    fn default() -> Self {
+        Self {
+            salt: DEFAULT_SALT.to_string(),
+            gcm: false,
+            rsa_algorithm: RsaAlgorithm::Default,
+            rsa_salt: DEFAULT_SALT.to_string(),
+            rsa_strong: false,
+        }
+    }
+}
+
+impl KeySettings {
+    /// Lee las opciones de variables de entorno.
+    ///
+    /// - `ENCRYPT_SALT`: salt de las claves simetricas (por defecto `deadbeef`).
+    /// - `ENCRYPT_FORMAT`: `spring` (por defecto) o `gcm`.
+    /// - `ENCRYPT_RSA_ALGORITHM`: `DEFAULT` (PKCS#1 v1.5) u `OAEP`.
+    /// - `ENCRYPT_RSA_SALT`: salt de las claves RSA (por defecto `deadbeef`).
+    /// - `ENCRYPT_RSA_STRONG`: `true` para AES-GCM en las claves RSA.
+    pub fn from_env() -> Result<Self, CryptoError> {
+        let invalid = |var: &str, reason: String| CryptoError::InvalidKey {
+            id: var.to_string(),
+            reason,
+        };
+        let mut settings = Self::default();
+
+        if let Ok(salt) = std::env::var("ENCRYPT_SALT") {
+            settings.salt = salt;
+        }
+        if let Ok(format) = std::env::var("ENCRYPT_FORMAT") {
+            settings.gcm = match format.trim().to_ascii_lowercase().as_str() {
+                "spring" => false,
+                "gcm" => true,
+                other => {
+                    return Err(invalid(
+                        "ENCRYPT_FORMAT",
+                        format!("unknown format '{}' (expected spring or gcm)", other),
+                    ));
+                },
+            };
+        }
+        if let Ok(algorithm) = std::env::var("ENCRYPT_RSA_ALGORITHM") {
+            settings.rsa_algorithm = algorithm
+                .parse()
+                .map_err(|e| invalid("ENCRYPT_RSA_ALGORITHM", e))?;
+        }
+        if let Ok(salt) = std::env::var("ENCRYPT_RSA_SALT") {
+            settings.rsa_salt = salt;
+        }
+        if let Ok(strong) = std::env::var("ENCRYPT_RSA_STRONG") {
+            settings.rsa_strong = strong.trim().parse().map_err(|_| {
+                invalid(
+                    "ENCRYPT_RSA_STRONG",
+                    format!("'{}' is not a boolean", strong),
+                )
+            })?;
+        }
+
+        Ok(settings)
+    }
+
+    /// Crea una clave simetrica a partir de un password.
+    fn symmetric(&self, password: &str) -> Result<Arc<dyn TextEncryptor>, CryptoError> {
+        Ok(if self.gcm {
+            Arc::new(AesGcmEncryptor::from_password(password, &self.salt))
+        } else {
+            Arc::new(AesCbcEncryptor::from_password(password, &self.salt)?)
+        })
+    }
+
+    /// Lee una clave RSA en PEM con las opciones `rsa_*`.
+    fn rsa(&self, pem: &str) -> Result<Arc<dyn TextEncryptor>, CryptoError> {
+        let key = RsaEncryptor::from_pem(pem)?
+            .with_algorithm(self.rsa_algorithm)
+            .with_salt(&self.rsa_salt)?
+            .with_strong(self.rsa_strong);
+        Ok(Arc::new(key))
+    }
+}
+
+/// Claves instaladas en el servidor, por id.
+#[derive(Clone, Default)]
+pub struct KeyRing {
+    keys: HashMap<String, Arc<dyn TextEncryptor>>,
+    default_key: Option<String>,
+}
+
+impl KeyRing {
+    /// Crea un llavero vacio (los valores cifrados no se tocan).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Instala una clave con el id dado.
+    pub fn with_key(mut self, id: impl Into<String>, key: impl TextEncryptor + 'static) -> Self {
+        self.keys.insert(id.into(), Arc::new(key));
+        self
+    }
+
+    /// Define el id de la clave por defecto (por defecto, `default`).
+    pub fn with_default_key(mut self, id: impl Into<String>) -> Self {
+        self.default_key = Some(id.into());
+        self
+    }
+
+    /// Carga las claves de un directorio.
+    ///
+    /// Cada `{id}.pem` es una clave RSA (privada, o solo publica para cifrar)
+    /// y cada `{id}.key` contiene el password de una clave simetrica. El
+    /// resto de archivos se ignora.
+    pub fn with_key_dir(mut self, dir: &Path, settings: &KeySettings) -> Result<Self, CryptoError> {
+        let entries = std::fs::read_dir(dir).map_err(|e| CryptoError::InvalidKey {
+            id: dir.display().to_string(),
+            reason: e.to_string(),
+        })?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let (Some(id), Some(ext)) = (
+                path.file_stem().and_then(|s| s.to_str()),
+                path.extension().and_then(|s| s.to_str()),
+            ) else {
+                continue;
+            };
+            if ext != "pem" && ext != "key" {
+                continue;
+            }
+
+            let content = std::fs::read_to_string(&path).map_err(|e| CryptoError::InvalidKey {
+                id: id.to_string(),
+                reason: e.to_string(),
+            })?;
+            let key = if ext == "pem" {
+                settings.rsa(&content)
+            } else {
+                settings.symmetric(content.trim())
+            }
+            .map_err(|e| CryptoError::InvalidKey {
+                id: id.to_string(),
+                reason: e.to_string(),
+            })?;
+            self.keys.insert(id.to_string(), key);
+        }
+
+        Ok(self)
+    }
+
+    /// Carga las claves desde variables de entorno.
+    ///
+    /// - `ENCRYPT_KEY`: clave `default`; un PEM RSA o el password de una
+    ///   clave simetrica, como `encrypt.key` en Spring.
+    /// - `ENCRYPT_KEY_DIR`: directorio con mas claves (ver
+    ///   [`with_key_dir`](Self::with_key_dir)).
+    /// - `ENCRYPT_DEFAULT_KEY`: id de la clave por defecto.
+    ///
+    /// Las opciones de las claves se leen con [`KeySettings::from_env`].
+    pub fn from_env() -> Result<Self, CryptoError> {
+        let settings = KeySettings::from_env()?;
+        let mut ring = Self::new();
+
+        if let Ok(key) = std::env::var("ENCRYPT_KEY")
+            && !key.trim().is_empty()
+        {
+            let encryptor = if key.trim_start().starts_with("-----BEGIN") {
+                settings.rsa(&key)
+            } else {
+                settings.symmetric(&key)
+            }
+            .map_err(|e| CryptoError::InvalidKey {
+                id: DEFAULT_KEY_ID.to_string(),
+                reason: e.to_string(),
+            })?;
+            ring.keys.insert(DEFAULT_KEY_ID.to_string(), encryptor);
+        }
+        if let Ok(dir) = std::env::var("ENCRYPT_KEY_DIR") {
+            ring = ring.with_key_dir(Path::new(&dir), &settings)?;
+        }
+        if let Ok(id) = std::env::var("ENCRYPT_DEFAULT_KEY") {
+            ring = ring.with_default_key(id);
+        }
+
+        Ok(ring)
+    }
+
+    /// Retorna `true` si no hay claves instaladas.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Ids de las claves instaladas, ordenados.
+    pub fn key_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self.keys.keys().map(String::as_str).collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Cifra `input`, que puede empezar con `{key:id}` para elegir la clave.
+    ///
+    /// El resultado conserva ese prefijo, como `POST /encrypt` en Spring; se
+    /// guarda en la configuracion como `'{cipher}' + resultado`.
+    pub fn encrypt(&self, input: &str) -> Result<String, CryptoError> {
+        let (key, data) = split_key(input);
+        let encrypted = self.locate(key)?.encrypt(data)?;
+
+        Ok(match key {
+            Some(id) => format!("{{key:{}}}{}", id, encrypted),
+            None => encrypted,
+        })
+    }
+
+    /// Descifra `input`, con o sin `{cipher}` y `{key:id}` delante.
+    pub fn decrypt(&self, input: &str) -> Result<String, CryptoError> {
+        let input = input.strip_prefix(CIPHER_PREFIX).unwrap_or(input);
+        let (key, data) = split_key(input);
+        self.locate(key)?.decrypt(data)
+    }
+
+    /// Descifra los valores `{cipher}` de las property sources.
+    ///
+    /// Los que fallan se reemplazan por `invalid.<clave>: <n/a>` y se
+    /// retornan como advertencias; los elementos de un array, por `<n/a>` en
+    /// su sitio. Sin claves instaladas no hace nada.
+    pub fn decrypt_sources(&self, sources: &mut [PropertySource]) -> Vec<ConfigWarning> {
+        let mut warnings = Vec::new();
+        if self.is_empty() {
+            return warnings;
+        }
+
+        for source in sources {
+            let mut encrypted = Vec::new();
+            for (key, value) in source.config.as_inner() {
+                find_encrypted(
+                    value,
+                    &mut ConfigPath::root().key(key.as_str()),
+                    &mut encrypted,
+                );
+            }
+
+            for (path, cipher) in encrypted {
+                match self.decrypt(&cipher) {
+                    Ok(plain) => {
+                        if let Some(value) = source.config.get_path_mut(&path) {
+                            *value = ConfigValue::String(plain);
+                        }
+                    },
+                    Err(e) => {
+                        warnings.push(ConfigWarning::new(
+                            &source.name,
+                            format!("cannot decrypt '{}': {}", path, e),
+                        ));
+                        let invalid = ConfigValue::String(INVALID_VALUE.to_string());
+                        if let Some(PathSegment::Index(_)) = path.segments().last() {
+                            // Quitar el elemento desplazaria los siguientes, cuyas
+                            // rutas ya estan recolectadas.
+                            if let Some(value) = source.config.get_path_mut(&path) {
+                                *value = invalid;
+                            }
+                        } else {
+                            source.config.remove_path(&path);
+                            let _ = source.config.set_path(&invalid_path(&path), invalid);
+                        }
+                    },
+                }
+            }
+        }
+
+        warnings
+    }
+
+    fn locate(&self, key: Option<&str>) -> Result<&dyn TextEncryptor, CryptoError> {
+        if self.is_empty() {
+            return Err(CryptoError::NoKey);
+        }
+        let id = key
+            .or(self.default_key.as_deref())
+            .unwrap_or(DEFAULT_KEY_ID);
+
+        self.keys
+            .get(id)
+            .map(Arc::as_ref)
+            .ok_or_else(|| CryptoError::UnknownKey(id.to_string()))
+    }
+}
+
+impl std::fmt::Debug for KeyRing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyRing")
+            .field("keys", &self.key_ids())
+            .field("default_key", &self.default_key)
+            .finish()
+    }
+}
+
+/// Separa los prefijos `{name:value}` del texto y retorna el valor de `key`.
+///
+/// Como en Spring, puede haber varios prefijos; los que no son `key` se
+/// ignoran.
+fn split_key(input: &str) -> (Option<&str>, &str) {
+    let mut key = None;
+    let mut rest = input;
+
+    while let Some(option) = rest.strip_prefix('{')
+        && let Some((pair, after)) = option.split_once('}')
+        && let Some((name, value)) = pair.split_once(':')
+    {
+        if name == "key" {
+            key = Some(value);
+        }
+        rest = after;
+    }
+
+    (key, rest)
+}
+
+/// Recolecta las rutas de los valores `{cipher}` bajo `value`.
+fn find_encrypted(value: &ConfigValue, path: &mut ConfigPath, out: &mut Vec<(ConfigPath, String)>) {
+    match value {
+        ConfigValue::String(s) if s.starts_with(CIPHER_PREFIX) => {
+            out.push((path.clone(), s.clone()));
+        },
+        ConfigValue::Object(map) => {
+            for (key, value) in map {
+                path.push_key(key.as_str());
+                find_encrypted(value, path, out);
+                path.pop();
+            }
+        },
+        ConfigValue::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                path.push_index(index);
+                find_encrypted(item, path, out);
+                path.pop();
+            }
+        },
+        _ => {},
+    }
+}
+
+/// `a.b` -> `invalid.a.b`.
+fn invalid_path(path: &ConfigPath) -> ConfigPath {
+    let mut invalid = ConfigPath::root().key(INVALID_KEY);
+    for segment in path.segments() {
+        match segment {
+            PathSegment::Key(key) => invalid.push_key(key.as_str()),
+            PathSegment::Index(index) => invalid.push_index(*index),
+        }
+    }
+    invalid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vortex_git::vortex_core::ConfigMap;
+
+    fn ring() -> KeyRing {
+        KeyRing::new()
+            .with_key(
+                DEFAULT_KEY_ID,
+                AesCbcEncryptor::from_password("s3cr3t", DEFAULT_SALT).unwrap(),
+            )
+            .with_key(
+                "prod",
+                AesCbcEncryptor::from_password("pr0d", DEFAULT_SALT).unwrap(),
+            )
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_round_trip_with_key_prefix() {
+        let ring = ring();
+
+        let cipher = ring.encrypt("hunter2").unwrap();
+        assert_ne!(cipher, "hunter2");
+        assert_eq!(ring.decrypt(&cipher).unwrap(), "hunter2");
+        assert_eq!(
+            ring.decrypt(&format!("{}{}", CIPHER_PREFIX, cipher))
+                .unwrap(),
+            "hunter2"
+        );
+
+        let cipher = ring.encrypt("{key:prod}hunter2").unwrap();
+        assert!(cipher.starts_with("{key:prod}"));
+        assert_eq!(ring.decrypt(&cipher).unwrap(), "hunter2");
+        // The default key cannot read a value encrypted with `prod`.
+        let without_prefix = cipher.trim_start_matches("{key:prod}");
+        assert!(matches!(
+            ring.decrypt(without_prefix),
+            Err(CryptoError::Decrypt(_))
+        ));
+    }
+
+    #[test]
+    fn test_missing_keys() {
+        assert_eq!(KeyRing::new().encrypt("x"), Err(CryptoError::NoKey));
+        assert_eq!(
+            ring().encrypt("{key:staging}x"),
+            Err(CryptoError::UnknownKey("staging".to_string()))
+        );
+        let ring = ring().with_default_key("prod");
+        assert_eq!(ring.decrypt(&ring.encrypt("x").unwrap()).unwrap(), "x");
+    }
+
+    #[test]
+    fn test_split_key_ignores_other_options() {
+        assert_eq!(split_key("{key:prod}{secret:x}abc"), (Some("prod"), "abc"));
+        assert_eq!(split_key("abc"), (None, "abc"));
+        assert_eq!(split_key("{notanoption}abc"), (None, "{notanoption}abc"));
+    }
+
+    #[test]
+    fn test_decrypt_sources_marks_invalid_values() {
+        let ring = ring();
+        let password = format!("{}{}", CIPHER_PREFIX, ring.encrypt("hunter2").unwrap());
+        let token = format!(
+            "{}{}",
+            CIPHER_PREFIX,
+            ring.encrypt("{key:prod}t0k3n").unwrap()
+        );
+
+        let mut config = ConfigMap::new();
+        config.insert("plain", "value");
+        let _ = config.set_path(&ConfigPath::parse("db.password").unwrap(), password);
+        let _ = config.set_path(&ConfigPath::parse("api.tokens[0]").unwrap(), token);
+        let _ = config.set_path(
+            &ConfigPath::parse("db.broken").unwrap(),
+            "{cipher}not-base64!",
+        );
+        let mut sources = vec![PropertySource::new("git:main:app.yml", config)];
+
+        let warnings = ring.decrypt_sources(&mut sources);
+
+        let config = &sources[0].config;
+        assert_eq!(config.get("db.password").unwrap().as_str(), Some("hunter2"));
+        assert_eq!(config.get("api.tokens[0]").unwrap().as_str(), Some("t0k3n"));
+        assert_eq!(config.get("plain").unwrap().as_str(), Some("value"));
+        assert!(config.get("db.broken").is_none());
+        assert_eq!(
+            config.get("invalid.db.broken").unwrap().as_str(),
+            Some(INVALID_VALUE)
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].source, "git:main:app.yml");
+        assert!(
+            warnings[0]
+                .message
+                .starts_with("cannot decrypt 'db.broken'")
+        );
+    }
+
+    #[test]
+    fn test_decrypt_sources_bad_cipher_inside_array() {
+        let ring = ring();
+        let cipher = |plain: &str| format!("{}{}", CIPHER_PREFIX, ring.encrypt(plain).unwrap());
+
+        let mut config = ConfigMap::new();
+        let tokens = ConfigValue::Array(vec![
+            cipher("first").into(),
+            "{cipher}not-base64!".into(),
+            cipher("third").into(),
+            cipher("fourth").into(),
+        ]);
+        let _ = config.set_path(&ConfigPath::parse("api.tokens").unwrap(), tokens);
+        let mut sources = vec![PropertySource::new("app.yml", config)];
+
+        let warnings = ring.decrypt_sources(&mut sources);
+
+        let config = &sources[0].config;
+        let tokens: Vec<_> = config
+            .get("api.tokens")
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t.as_str().unwrap())
+            .collect();
+        assert_eq!(tokens, ["first", INVALID_VALUE, "third", "fourth"]);
+        assert!(config.get("invalid").is_none());
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0]
+                .message
+                .starts_with("cannot decrypt 'api.tokens[1]'")
+        );
+    }
+
+    #[test]
+    fn test_without_keys_values_are_left_encrypted() {
+        let mut config = ConfigMap::new();
+        config.insert("password", "{cipher}abc");
+        let mut sources = vec![PropertySource::new("app.yml", config)];
+
+        assert!(KeyRing::new().decrypt_sources(&mut sources).is_empty());
+        assert_eq!(
+            sources[0].config.get("password").unwrap().as_str(),
+            Some("{cipher}abc")
+        );
+    }
+
+    #[test]
+    fn test_key_dir() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("prod.key"), "pr0d\n").unwrap();
+        std::fs::write(dir.path().join("README.md"), "ignored").unwrap();
+
+        let ring = KeyRing::new()
+            .with_key_dir(dir.path(), &KeySettings::default())
+            .unwrap();
+        assert_eq!(ring.key_ids(), ["prod"]);
+
+        // Same password and salt: the keys are interchangeable.
+        let cipher = ring.encrypt("{key:prod}x").unwrap();
+        assert_eq!(self::ring().decrypt(&cipher).unwrap(), "x");
+
+        // The GCM format is opt-in and cannot be read as the Spring one.
+        let gcm = KeySettings {
+            gcm: true,
+            ..KeySettings::default()
+        };
+        let ring = KeyRing::new().with_key_dir(dir.path(), &gcm).unwrap();
+        let cipher = ring.encrypt("{key:prod}x").unwrap();
+        assert_eq!(ring.decrypt(&cipher).unwrap(), "x");
+        assert!(self::ring().decrypt(&cipher).is_err());
+
+        std::fs::write(dir.path().join("bad.pem"), "not a key").unwrap();
+        assert!(matches!(
+            KeyRing::new().with_key_dir(dir.path(), &KeySettings::default()),
+            Err(CryptoError::InvalidKey { id, .. }) if id == "bad"
+        ));
+    }
+}
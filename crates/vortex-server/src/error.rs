@@ -1,11 +1,13 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
 use serde::Serialize;
+use vortex_git::vortex_core::validation::{ValidationReport, Violation};
 
 use crate::cache::CacheError;
+use crate::crypto::CryptoError;
 
 #[derive(Debug)]
 pub enum AppError {
@@ -15,6 +17,33 @@ pub enum AppError {
     /// Parametros invalidos
     BadRequest(String),
 
+    /// Ningun formato de salida es aceptable para el cliente
+    NotAcceptable(String),
+
+    /// La configuracion no cumple su JSON Schema
+    Validation(ValidationReport),
+
+    /// Error de cifrado o descifrado (`/encrypt`, `/decrypt`)
+    Crypto(CryptoError),
+
+    /// Falta el token de escritura o no es valido
+    Unauthorized(String),
+
+    /// Escrituras deshabilitadas en el servidor
+    Forbidden(String),
+
+    /// El backend no admite escrituras
+    MethodNotAllowed(String),
+
+    /// Clave inexistente en el documento
+    KeyNotFound(String),
+
+    /// El documento cambio desde que se leyo, o no puede escribirse
+    Conflict(String),
+
+    /// El documento no tiene la version de `If-Match`
+    PreconditionFailed(String),
+
     /// Error interno
     Internal(String),
 }
@@ -23,10 +52,13 @@ pub enum AppError {
 struct ErrorResponse {
     error: String,
     message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    violations: Vec<Violation>,
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let mut violations = Vec::new();
         let (status, error, message) = match self {
             AppError::NotFound { app, profile } => (
                 StatusCode::NOT_FOUND,
@@ -34,6 +66,46 @@ impl IntoResponse for AppError {
                 format!("Configuration not found for {}/{}", app, profile),
             ),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "Bad Request", msg),
+            AppError::NotAcceptable(msg) => (StatusCode::NOT_ACCEPTABLE, "Not Acceptable", msg),
+            AppError::Validation(report) => {
+                violations = report.violations().to_vec();
+                let message = match report.into_result() {
+                    Err(e) => e.to_string(),
+                    Ok(()) => "Configuration does not match its schema".to_string(),
+                };
+                (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "Unprocessable Entity",
+                    message,
+                )
+            },
+            AppError::Crypto(err) => match err {
+                CryptoError::NoKey | CryptoError::UnknownKey(_) => {
+                    (StatusCode::NOT_FOUND, "Not Found", err.to_string())
+                },
+                CryptoError::Decrypt(_) => {
+                    (StatusCode::BAD_REQUEST, "Bad Request", err.to_string())
+                },
+                CryptoError::Encrypt(_) | CryptoError::InvalidKey { .. } => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Internal Server Error",
+                    err.to_string(),
+                ),
+            },
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, "Unauthorized", msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, "Forbidden", msg),
+            AppError::MethodNotAllowed(msg) => {
+                (StatusCode::METHOD_NOT_ALLOWED, "Method Not Allowed", msg)
+            },
+            AppError::KeyNotFound(key) => (
+                StatusCode::NOT_FOUND,
+                "Not Found",
+                format!("Key not found: {}", key),
+            ),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, "Conflict", msg),
+            AppError::PreconditionFailed(msg) => {
+                (StatusCode::PRECONDITION_FAILED, "Precondition Failed", msg)
+            },
             AppError::Internal(msg) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal Server Error",
@@ -44,14 +116,27 @@ impl IntoResponse for AppError {
         let body = Json(ErrorResponse {
             error: error.to_string(),
             message,
+            violations,
         });
 
+        if status == StatusCode::UNAUTHORIZED {
+            return (status, [(header::WWW_AUTHENTICATE, "Bearer")], body).into_response();
+        }
         (status, body).into_response()
     }
 }
 
 impl From<CacheError> for AppError {
     fn from(err: CacheError) -> Self {
-        AppError::Internal(err.to_string())
+        match err {
+            CacheError::Validation(report) => AppError::Validation(report),
+            err => AppError::Internal(err.to_string()),
+        }
+    }
+}
+
+impl From<CryptoError> for AppError {
+    fn from(err: CryptoError) -> Self {
+        AppError::Crypto(err)
     }
 }
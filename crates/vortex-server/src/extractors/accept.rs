@@ -1,65 +1,407 @@
+//! Negociacion de contenido segun RFC 9110 (seccion 12.5.1).
+//!
+//! El formato de salida se elige, en orden:
+//! 1. Parametro `?format=` (nombre o extension registrada, p.ej. `yaml`, `yml`).
+//! 2. Header `Accept`, respetando los pesos `q` y la especificidad de cada rango.
+//! 3. JSON, si no hay header `Accept`.
+//!
+//! Si ningun formato registrado es aceptable se responde `406 Not Acceptable`.
+
+use std::cmp::Reverse;
+
 use axum::{
-    extract::FromRequestParts,
+    extract::{FromRef, FromRequestParts, Query},
     http::{header, request::Parts},
 };
+use serde::Deserialize;
+use vortex_git::vortex_core::format::{ConfigFormat, FormatEntry, FormatRegistry};
 
-/// Formatos de salida soportados.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub enum OutputFormat {
-    #[default]
-    Json,
-    Yaml,
-    Properties,
-}
+use crate::error::AppError;
+use crate::state::AppState;
+
+/// Formato de salida negociado, respaldado por una entrada del registro de formatos.
+#[derive(Debug, Clone)]
+pub struct OutputFormat(FormatEntry);
 
 impl OutputFormat {
+    /// Crea un formato de salida a partir de una entrada del registro.
+    pub fn new(entry: FormatEntry) -> Self {
+        Self(entry)
+    }
+
+    /// Formato por defecto (JSON).
+    pub fn json(formats: &FormatRegistry) -> Self {
+        let entry = formats
+            .by_name(ConfigFormat::Json.name())
+            .cloned()
+            .unwrap_or_else(|| FormatEntry::new("json").media_types(["application/json"]));
+        Self(entry)
+    }
+
     /// Determina el formato basado en el header Accept.
-    pub fn from_accept(accept: Option<&str>) -> Self {
-        match accept {
-            None => Self::Json,
-            Some(accept) => {
-                let accept = accept.to_lowercase();
-
-                if accept.contains("application/x-yaml")
-                    || accept.contains("text/yaml")
-                    || accept.contains("application/yaml")
-                {
-                    Self::Yaml
-                } else if accept.contains("text/plain") {
-                    Self::Properties
-                } else {
-                    // Default to JSON for application/json, */*, or unknown
-                    Self::Json
-                }
-            },
+    ///
+    /// Sin header se usa JSON. Retorna `None` si ningun formato con
+    /// serializador registrado es aceptable.
+    pub fn from_accept(accept: Option<&str>, formats: &FormatRegistry) -> Option<Self> {
+        let Some(accept) = accept else {
+            return Some(Self::json(formats));
+        };
+
+        let ranges = parse_accept(accept);
+        if ranges.is_empty() {
+            return Some(Self::json(formats));
         }
+
+        candidates(formats)
+            .filter_map(|(order, entry)| {
+                let preference =
+                    preference(entry.mime_types().iter().map(String::as_str), &ranges)?;
+                Some(((preference, Reverse(order)), entry))
+            })
+            .max_by_key(|(key, _)| *key)
+            .map(|(_, entry)| Self(entry.clone()))
+    }
+
+    /// Determina el formato a partir del parametro `?format=`.
+    ///
+    /// Acepta el nombre del formato o cualquiera de sus extensiones.
+    pub fn from_param(format: &str, formats: &FormatRegistry) -> Option<Self> {
+        formats
+            .by_name(format)
+            .or_else(|| formats.by_extension(format))
+            .filter(|entry| entry.can_serialize())
+            .map(|entry| Self(entry.clone()))
+    }
+
+    /// Nombre del formato en el registro (`json`, `yaml`, ...).
+    pub fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    /// Entrada del registro asociada.
+    pub fn entry(&self) -> &FormatEntry {
+        &self.0
     }
 
     /// Retorna el Content-Type correspondiente.
-    pub fn content_type(&self) -> &'static str {
-        match self {
-            Self::Json => "application/json",
-            Self::Yaml => "application/x-yaml",
-            Self::Properties => "text/plain; charset=utf-8",
+    ///
+    /// Los tipos `text/*` incluyen `charset=utf-8`.
+    pub fn content_type(&self) -> String {
+        match self.0.mime_type() {
+            Some(mime) if mime.starts_with("text/") => format!("{}; charset=utf-8", mime),
+            Some(mime) => mime.to_string(),
+            None => "application/octet-stream".to_string(),
         }
     }
 }
 
-/// Extractor que parsea el header Accept.
+/// Rango de media type de un header Accept (`type/subtype;q=0.8`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MediaRange {
+    main: String,
+    sub: String,
+    /// Peso en milesimas (0..=1000).
+    quality: u16,
+}
+
+impl MediaRange {
+    /// Especificidad del rango: `*/*` < `type/*` < `type/subtype`.
+    fn specificity(&self) -> u8 {
+        match (self.main.as_str(), self.sub.as_str()) {
+            ("*", _) => 0,
+            (_, "*") => 1,
+            _ => 2,
+        }
+    }
+
+    fn matches(&self, media_type: &str) -> bool {
+        let (main, sub) = media_type.split_once('/').unwrap_or((media_type, ""));
+        (self.main == "*" || self.main.eq_ignore_ascii_case(main))
+            && (self.sub == "*" || self.sub.eq_ignore_ascii_case(sub))
+    }
+}
+
+/// Parsea un header Accept en rangos de media type.
+///
+/// Los rangos mal formados se ignoran; un `q` invalido cuenta como `q=1`.
+fn parse_accept(accept: &str) -> Vec<MediaRange> {
+    accept
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let media_type = parts.next()?.trim().to_ascii_lowercase();
+            let (main, sub) = media_type.split_once('/')?;
+            if main.is_empty() || sub.is_empty() || (main == "*" && sub != "*") {
+                return None;
+            }
+
+            let quality = parts
+                .filter_map(|param| param.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .map_or(1000, |(_, value)| parse_quality(value.trim()));
+
+            Some(MediaRange {
+                main: main.to_string(),
+                sub: sub.to_string(),
+                quality,
+            })
+        })
+        .collect()
+}
+
+/// Convierte un `qvalue` (`0`, `0.5`, `1.000`) a milesimas.
+fn parse_quality(value: &str) -> u16 {
+    value
+        .parse::<f32>()
+        .map(|q| (q.clamp(0.0, 1.0) * 1000.0).round() as u16)
+        .unwrap_or(1000)
+}
+
+/// Negocia entre media types fijos ofrecidos por un endpoint.
+///
+/// `offered` va en orden de preferencia del servidor; sin header `Accept` se
+/// elige el primero. Retorna `None` si ninguno es aceptable.
+pub(crate) fn negotiate_media_type<'a>(
+    accept: Option<&str>,
+    offered: &[&'a str],
+) -> Option<&'a str> {
+    let ranges = accept.map(parse_accept).unwrap_or_default();
+    if ranges.is_empty() {
+        return offered.first().copied();
+    }
+
+    offered
+        .iter()
+        .enumerate()
+        .filter_map(|(order, mime)| {
+            let preference = preference([*mime], &ranges)?;
+            Some(((preference, Reverse(order)), *mime))
+        })
+        .max_by_key(|(key, _)| *key)
+        .map(|(_, mime)| mime)
+}
+
+/// Preferencia del cliente por un formato: `(q, especificidad, -posicion)`.
+///
+/// Para cada media type del formato se aplica el rango mas especifico que lo
+/// cubre (RFC 9110); el formato queda excluido si su mejor peso es `q=0`.
+fn preference<'a>(
+    mime_types: impl IntoIterator<Item = &'a str>,
+    ranges: &[MediaRange],
+) -> Option<(u16, u8, Reverse<usize>)> {
+    mime_types
+        .into_iter()
+        .filter_map(|mime| {
+            ranges
+                .iter()
+                .enumerate()
+                .filter(|(_, range)| range.matches(mime))
+                .max_by_key(|(index, range)| (range.specificity(), Reverse(*index)))
+                .map(|(index, range)| (range.quality, range.specificity(), Reverse(index)))
+        })
+        .filter(|(quality, _, _)| *quality > 0)
+        .max()
+}
+
+/// Formatos serializables en orden de preferencia del servidor: JSON primero.
+fn candidates(formats: &FormatRegistry) -> impl Iterator<Item = (usize, &FormatEntry)> {
+    let json = ConfigFormat::Json.name();
+    formats
+        .serializers()
+        .filter(move |entry| entry.name() == json)
+        .chain(
+            formats
+                .serializers()
+                .filter(move |entry| entry.name() != json),
+        )
+        .enumerate()
+}
+
+/// Query parameter `?format=`.
+#[derive(Debug, Deserialize)]
+struct FormatQuery {
+    format: Option<String>,
+}
+
+/// Extractor que negocia el formato de salida usando el registro de formatos del estado.
 pub struct AcceptFormat(pub OutputFormat);
 
 impl<S> FromRequestParts<S> for AcceptFormat
 where
+    AppState: FromRef<S>,
     S: Send + Sync,
 {
-    type Rejection = std::convert::Infallible;
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let state = AppState::from_ref(state);
+        let formats = state.formats();
+
+        let param = Query::<FormatQuery>::try_from_uri(&parts.uri)
+            .ok()
+            .and_then(|Query(query)| query.format);
+
+        let format = match param {
+            Some(param) => OutputFormat::from_param(&param, formats),
+            None => {
+                let accept = parts
+                    .headers
+                    .get(header::ACCEPT)
+                    .and_then(|v| v.to_str().ok());
+                OutputFormat::from_accept(accept, formats)
+            },
+        };
+
+        format.map(AcceptFormat).ok_or_else(|| {
+            let supported: Vec<_> = formats
+                .serializers()
+                .filter_map(FormatEntry::mime_type)
+                .collect();
+            AppError::NotAcceptable(format!(
+                "No acceptable representation; supported: {}",
+                supported.join(", ")
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn negotiate(accept: Option<&str>) -> Option<String> {
+        OutputFormat::from_accept(accept, &FormatRegistry::builtin())
+            .map(|format| format.name().to_string())
+    }
+
+    #[test]
+    fn test_from_accept_builtin_formats() {
+        assert_eq!(negotiate(None).as_deref(), Some("json"));
+        assert_eq!(negotiate(Some("*/*")).as_deref(), Some("json"));
+        assert_eq!(
+            negotiate(Some("application/x-yaml")).as_deref(),
+            Some("yaml")
+        );
+        assert_eq!(
+            negotiate(Some("APPLICATION/X-YAML")).as_deref(),
+            Some("yaml")
+        );
+        assert_eq!(negotiate(Some("text/yaml")).as_deref(), Some("yaml"));
+        assert_eq!(negotiate(Some("application/toml")).as_deref(), Some("toml"));
+        assert_eq!(negotiate(Some("text/x-env")).as_deref(), Some("env"));
+        assert_eq!(negotiate(Some("text/plain")).as_deref(), Some("properties"));
+    }
+
+    #[test]
+    fn test_quality_values_are_respected() {
+        assert_eq!(
+            negotiate(Some("text/plain;q=0.5, application/x-yaml")).as_deref(),
+            Some("yaml")
+        );
+        assert_eq!(
+            negotiate(Some("application/json;q=0.1, text/plain;q=0.9")).as_deref(),
+            Some("properties")
+        );
+        // Same weight: the first listed range wins.
+        assert_eq!(
+            negotiate(Some("application/toml, application/json")).as_deref(),
+            Some("toml")
+        );
+    }
+
+    #[test]
+    fn test_most_specific_range_wins() {
+        // The wildcard would allow JSON, but the explicit q=0 excludes it.
+        assert_eq!(
+            negotiate(Some("*/*;q=0.5, application/json;q=0, text/yaml")).as_deref(),
+            Some("yaml")
+        );
+        assert_eq!(
+            negotiate(Some("text/*;q=0.3, text/plain;q=0.8")).as_deref(),
+            Some("properties")
+        );
+    }
+
+    #[test]
+    fn test_wildcard_prefers_json() {
+        assert_eq!(
+            negotiate(Some("text/html, */*;q=0.8")).as_deref(),
+            Some("json")
+        );
+        assert_eq!(negotiate(Some("application/*")).as_deref(), Some("json"));
+    }
+
+    #[test]
+    fn test_not_acceptable() {
+        assert_eq!(negotiate(Some("image/png")), None);
+        assert_eq!(negotiate(Some("text/plain;q=0")), None);
+        // Parse-only formats cannot be served.
+        assert_eq!(negotiate(Some("application/hocon")), None);
+    }
+
+    #[test]
+    fn test_parse_accept() {
+        let ranges = parse_accept("text/html;level=1;q=0.7, */*;q=abc, bogus, ;q=1");
+
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[0].main, "text");
+        assert_eq!(ranges[0].quality, 700);
+        assert_eq!(ranges[1].specificity(), 0);
+        assert_eq!(ranges[1].quality, 1000);
+    }
+
+    #[test]
+    fn test_negotiate_media_type() {
+        let offered = ["application/json", "text/x-diff"];
+
+        assert_eq!(
+            negotiate_media_type(None, &offered),
+            Some("application/json")
+        );
+        assert_eq!(
+            negotiate_media_type(Some("text/*"), &offered),
+            Some("text/x-diff")
+        );
+        assert_eq!(
+            negotiate_media_type(Some("*/*;q=0.5, text/x-diff;q=0.6"), &offered),
+            Some("text/x-diff")
+        );
+        assert_eq!(negotiate_media_type(Some("image/png"), &offered), None);
+    }
+
+    #[test]
+    fn test_from_param() {
+        let formats = FormatRegistry::builtin();
+
+        assert_eq!(
+            OutputFormat::from_param("yaml", &formats).unwrap().name(),
+            "yaml"
+        );
+        assert_eq!(
+            OutputFormat::from_param("yml", &formats).unwrap().name(),
+            "yaml"
+        );
+        assert_eq!(
+            OutputFormat::from_param("ENV", &formats).unwrap().name(),
+            "env"
+        );
+        assert!(OutputFormat::from_param("hocon", &formats).is_none());
+        assert!(OutputFormat::from_param("xml", &formats).is_none());
+    }
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let accept = parts
-            .headers
-            .get(header::ACCEPT)
-            .and_then(|v| v.to_str().ok());
+    #[test]
+    fn test_content_type() {
+        let formats = FormatRegistry::builtin();
 
-        Ok(AcceptFormat(OutputFormat::from_accept(accept)))
+        assert_eq!(
+            OutputFormat::json(&formats).content_type(),
+            "application/json"
+        );
+        assert_eq!(
+            OutputFormat::from_param("properties", &formats)
+                .unwrap()
+                .content_type(),
+            "text/plain; charset=utf-8"
+        );
     }
 }
@@ -0,0 +1,127 @@
+//! Autenticacion de las escrituras de configuracion y del cifrado.
+//!
+//! Las escrituras requieren `Authorization: Bearer <token>` con uno de los
+//! tokens del estado ([`AppState::with_write_tokens`]), y `/encrypt` y
+//! `/decrypt` uno de los de [`AppState::with_encrypt_tokens`]. Sin tokens
+//! configurados la operacion esta deshabilitada y se responde
+//! `403 Forbidden`; con un token ausente o invalido, `401 Unauthorized`.
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{header, request::Parts},
+};
+use sha2::{Digest, Sha256};
+
+use crate::error::AppError;
+use crate::state::AppState;
+
+/// Extractor que exige un token de escritura valido.
+#[derive(Debug)]
+pub struct WriteAccess;
+
+impl<S> FromRequestParts<S> for WriteAccess
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let state = AppState::from_ref(state);
+        authorize(
+            parts,
+            state.write_tokens(),
+            "Writes are disabled: no write tokens are configured",
+        )?;
+        Ok(WriteAccess)
+    }
+}
+
+/// Extractor que exige un token valido para `/encrypt` y `/decrypt`.
+#[derive(Debug)]
+pub struct EncryptAccess;
+
+impl<S> FromRequestParts<S> for EncryptAccess
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let state = AppState::from_ref(state);
+        authorize(
+            parts,
+            state.encrypt_tokens(),
+            "Encryption endpoints are disabled: no encrypt tokens are configured",
+        )?;
+        Ok(EncryptAccess)
+    }
+}
+
+/// Verifica el token bearer de la peticion contra `allowed`.
+fn authorize(parts: &Parts, allowed: &[String], disabled: &str) -> Result<(), AppError> {
+    if allowed.is_empty() {
+        return Err(AppError::Forbidden(disabled.to_string()));
+    }
+
+    let token = parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(bearer_token)
+        .ok_or_else(|| AppError::Unauthorized("A bearer token is required".to_string()))?;
+
+    if is_allowed(token, allowed) {
+        Ok(())
+    } else {
+        Err(AppError::Unauthorized("Invalid bearer token".to_string()))
+    }
+}
+
+/// Extrae el token de un header `Authorization: Bearer <token>`.
+fn bearer_token(authorization: &str) -> Option<&str> {
+    let (scheme, token) = authorization.split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+/// Compara el token con todos los permitidos en tiempo constante.
+///
+/// Se comparan digests, de modo que el tiempo tampoco depende de la
+/// longitud de los tokens, y se recorren todos aunque uno coincida.
+fn is_allowed(token: &str, allowed: &[String]) -> bool {
+    let digest = Sha256::digest(token.as_bytes());
+    allowed.iter().fold(false, |found, candidate| {
+        let candidate = Sha256::digest(candidate.as_bytes());
+        let difference = digest
+            .iter()
+            .zip(candidate.iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        found | (difference == 0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bearer_token() {
+        assert_eq!(bearer_token("Bearer s3cret"), Some("s3cret"));
+        assert_eq!(bearer_token("bearer  s3cret "), Some("s3cret"));
+        assert_eq!(bearer_token("Basic dXNlcjpwYXNz"), None);
+        assert_eq!(bearer_token("Bearer "), None);
+        assert_eq!(bearer_token("Bearer"), None);
+    }
+
+    #[test]
+    fn test_is_allowed() {
+        let allowed = vec!["first".to_string(), "second".to_string()];
+        assert!(is_allowed("first", &allowed));
+        assert!(is_allowed("second", &allowed));
+        assert!(!is_allowed("third", &allowed));
+        assert!(!is_allowed("firs", &allowed));
+        assert!(!is_allowed("first", &[]));
+    }
+}
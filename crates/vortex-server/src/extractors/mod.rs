@@ -1,3 +1,4 @@
 pub mod accept;
+pub mod auth;
 pub mod path;
 pub mod query;
@@ -15,6 +15,15 @@ pub struct AppProfileLabelPath {
     pub label: String,
 }
 
+/// Extractor para rutas /{app}/{profile}/{label}/{key}
+#[derive(Debug, Deserialize)]
+pub struct AppProfileLabelKeyPath {
+    pub app: String,
+    pub profile: String,
+    pub label: String,
+    pub key: String,
+}
+
 impl AppProfilePath {
     /// Parsea el string de profiles separados por coma.
     pub fn profiles(&self) -> Vec<String> {
@@ -67,6 +76,18 @@ impl AppProfileLabelPath {
     }
 }
 
+impl AppProfileLabelKeyPath {
+    /// Separa la ruta del documento de la clave.
+    pub fn into_parts(self) -> (AppProfileLabelPath, String) {
+        let document = AppProfileLabelPath {
+            app: self.app,
+            profile: self.profile,
+            label: self.label,
+        };
+        (document, self.key)
+    }
+}
+
 // Conversion de AppProfileLabelPath a AppProfilePath
 impl From<AppProfileLabelPath> for AppProfilePath {
     fn from(path: AppProfileLabelPath) -> Self {
@@ -12,3 +12,16 @@ pub struct ConfigQuery {
     #[serde(rename = "forceRefresh")]
     pub force_refresh: bool,
 }
+
+/// Query parameters del endpoint de diff.
+#[derive(Debug, Deserialize)]
+pub struct DiffQuery {
+    /// Label (branch/tag/commit) de origen.
+    pub from: String,
+
+    /// Label (branch/tag/commit) de destino.
+    pub to: String,
+
+    /// Fuerza el formato de salida: `json` o `text` (alias `diff`, `unified`).
+    pub format: Option<String>,
+}
@@ -8,17 +8,22 @@ use axum::{
 };
 use tracing::instrument;
 use vortex_git::ConfigQuery as GitConfigQuery;
+use vortex_git::vortex_core::validation::ValidationReport;
 
 use crate::cache::{CacheError, CacheKey};
+use crate::crypto::KeyRing;
 use crate::error::AppError;
 use crate::extractors::{
     accept::AcceptFormat,
     path::{AppProfileLabelPath, AppProfilePath},
     query::ConfigQuery,
 };
-use crate::handlers::response::{ConfigResponse, PropertySourceResponse};
+use crate::handlers::response::ConfigResponse;
+use crate::metrics::validation as validation_metrics;
 use crate::response::to_format;
+use crate::secrets::resolve_secrets;
 use crate::state::AppState;
+use crate::validation::{ValidationMode, ValidationPolicy};
 
 /// Handler for GET /{app}/{profile} with state.
 #[instrument(skip_all, fields(app = %path.app, profile = %path.profile))]
@@ -47,21 +52,35 @@ pub async fn get_config(
             cache
                 .get_or_insert_with(cache_key, || {
                     let config_source = state.config_source();
+                    let policy = state.validation_policy();
+                    let keys = state.key_ring();
                     let app = path.app.clone();
                     let profiles = profiles.clone();
-                    async move { fetch_config(config_source, &app, profiles, &label).await }
+                    async move {
+                        fetch_config(config_source, policy, keys, &app, profiles, &label).await
+                    }
                 })
                 .await
-                .map_err(|e: CacheError| AppError::Internal(e.to_string()))?
+                .map_err(AppError::from)?
         },
         None => {
             // No cache, fetch directly
-            let response = fetch_config(state.config_source(), &path.app, profiles, &label).await?;
+            let response = fetch_config(
+                state.config_source(),
+                state.validation_policy(),
+                state.key_ring(),
+                &path.app,
+                profiles,
+                &label,
+            )
+            .await?;
             Arc::new(response)
         },
     };
 
-    to_format(response.as_ref(), format).map_err(|e| AppError::Internal(format!("{:?}", e)))
+    let response = resolve_secrets(state.secret_resolver(), response).await;
+
+    to_format(response.as_ref(), &format).map_err(|e| AppError::Internal(format!("{:?}", e)))
 }
 
 /// Handler for GET /{app}/{profile}/{label} with state.
@@ -90,103 +109,106 @@ pub async fn get_config_with_label(
     validate_label(&label)?;
 
     // Get configuration (with cache if enabled)
-    let response =
-        match state.cache() {
-            Some(cache) => {
-                // Create cache key
-                let cache_key = CacheKey::new(&path.app, profiles.join(","), &label);
-
-                // Try to get from cache or fetch from backend
-                match cache
-                    .get_or_insert_with(cache_key.clone(), || {
-                        let config_source = state.config_source();
-                        let app = path.app.clone();
-                        let profiles = profiles.clone();
-                        let label = label.clone();
-                        async move { fetch_config(config_source, &app, profiles, &label).await }
-                    })
-                    .await
-                {
-                    Ok(response) => response,
-                    Err(_) if query.use_default_label => {
-                        // Fallback to default label
-                        let default_label = state.config_source().default_label().to_string();
-                        tracing::info!(
-                            original_label = %label,
-                            default_label = %default_label,
-                            "Label not found, falling back to default"
-                        );
-
-                        let fallback_key =
-                            CacheKey::new(&path.app, profiles.join(","), &default_label);
-                        cache
-                            .get_or_insert_with(fallback_key, || {
-                                let config_source = state.config_source();
-                                let app = path.app.clone();
-                                let profiles = profiles.clone();
-                                async move {
-                                    fetch_config(config_source, &app, profiles, &default_label)
-                                        .await
-                                }
-                            })
-                            .await
-                            .map_err(|e: CacheError| AppError::Internal(e.to_string()))?
-                    },
-                    Err(e) => return Err(AppError::Internal(e.to_string())),
-                }
-            },
-            None => {
-                // No cache, fetch directly with fallback logic
-                let response =
-                    match fetch_config(state.config_source(), &path.app, profiles.clone(), &label)
-                        .await
-                    {
-                        Ok(response) => response,
-                        Err(_) if query.use_default_label => {
-                            let default_label = state.config_source().default_label();
-                            tracing::info!(
-                                original_label = %label,
-                                default_label = %default_label,
-                                "Label not found, falling back to default"
-                            );
-                            fetch_config(state.config_source(), &path.app, profiles, default_label)
-                                .await
-                                .map_err(|e| AppError::Internal(e.to_string()))?
-                        },
-                        Err(e) => return Err(AppError::Internal(e.to_string())),
-                    };
-                Arc::new(response)
-            },
-        };
-
-    to_format(response.as_ref(), format).map_err(|e| AppError::Internal(format!("{:?}", e)))
-}
+    let response = match state.cache() {
+        Some(cache) => {
+            // Create cache key
+            let cache_key = CacheKey::new(&path.app, profiles.join(","), &label);
 
-/// Converts a ConfigValue to serde_json::Value.
-fn config_value_to_json(value: &vortex_git::vortex_core::ConfigValue) -> serde_json::Value {
-    use vortex_git::vortex_core::ConfigValue;
-
-    match value {
-        ConfigValue::Null => serde_json::Value::Null,
-        ConfigValue::Bool(b) => serde_json::Value::Bool(*b),
-        ConfigValue::Integer(i) => serde_json::Value::Number((*i).into()),
-        ConfigValue::Float(f) => serde_json::Number::from_f64(f.into_inner())
-            .map(serde_json::Value::Number)
-            .unwrap_or(serde_json::Value::Null),
-        ConfigValue::String(s) => serde_json::Value::String(s.clone()),
-        ConfigValue::Array(arr) => {
-            serde_json::Value::Array(arr.iter().map(config_value_to_json).collect())
+            // Try to get from cache or fetch from backend
+            match cache
+                .get_or_insert_with(cache_key.clone(), || {
+                    let config_source = state.config_source();
+                    let policy = state.validation_policy();
+                    let keys = state.key_ring();
+                    let app = path.app.clone();
+                    let profiles = profiles.clone();
+                    let label = label.clone();
+                    async move {
+                        fetch_config(config_source, policy, keys, &app, profiles, &label).await
+                    }
+                })
+                .await
+            {
+                Ok(response) => response,
+                Err(e) if query.use_default_label && !e.is_validation() => {
+                    // Fallback to default label
+                    let default_label = state.config_source().default_label().to_string();
+                    tracing::info!(
+                        original_label = %label,
+                        default_label = %default_label,
+                        "Label not found, falling back to default"
+                    );
+
+                    let fallback_key = CacheKey::new(&path.app, profiles.join(","), &default_label);
+                    cache
+                        .get_or_insert_with(fallback_key, || {
+                            let config_source = state.config_source();
+                            let policy = state.validation_policy();
+                            let keys = state.key_ring();
+                            let app = path.app.clone();
+                            let profiles = profiles.clone();
+                            async move {
+                                fetch_config(
+                                    config_source,
+                                    policy,
+                                    keys,
+                                    &app,
+                                    profiles,
+                                    &default_label,
+                                )
+                                .await
+                            }
+                        })
+                        .await
+                        .map_err(AppError::from)?
+                },
+                Err(e) => return Err(AppError::from(e)),
+            }
         },
-        ConfigValue::Object(obj) => serde_json::Value::Object(
-            obj.iter()
-                .map(|(k, v)| (k.clone(), config_value_to_json(v)))
-                .collect(),
-        ),
-    }
+        None => {
+            // No cache, fetch directly with fallback logic
+            let response = match fetch_config(
+                state.config_source(),
+                state.validation_policy(),
+                state.key_ring(),
+                &path.app,
+                profiles.clone(),
+                &label,
+            )
+            .await
+            {
+                Ok(response) => response,
+                Err(e) if query.use_default_label && !e.is_validation() => {
+                    let default_label = state.config_source().default_label();
+                    tracing::info!(
+                        original_label = %label,
+                        default_label = %default_label,
+                        "Label not found, falling back to default"
+                    );
+                    fetch_config(
+                        state.config_source(),
+                        state.validation_policy(),
+                        state.key_ring(),
+                        &path.app,
+                        profiles,
+                        default_label,
+                    )
+                    .await
+                    .map_err(AppError::from)?
+                },
+                Err(e) => return Err(AppError::from(e)),
+            };
+            Arc::new(response)
+        },
+    };
+
+    let response = resolve_secrets(state.secret_resolver(), response).await;
+
+    to_format(response.as_ref(), &format).map_err(|e| AppError::Internal(format!("{:?}", e)))
 }
 
 /// Validates that the label does not contain dangerous characters.
-fn validate_label(label: &str) -> Result<(), AppError> {
+pub(crate) fn validate_label(label: &str) -> Result<(), AppError> {
     // Prevent path traversal
     if label.contains("..") {
         return Err(AppError::BadRequest(
@@ -205,40 +227,293 @@ fn validate_label(label: &str) -> Result<(), AppError> {
 }
 
 /// Fetches configuration from the backend and converts it to ConfigResponse.
-async fn fetch_config(
+///
+/// When the source provides a schema for the application, the merged
+/// configuration is validated according to the application's mode. `{cipher}`
+/// values are decrypted with `keys` before validation. This is
+/// the whole pipeline behind the config endpoints (minus caching and secret
+/// resolution), so tools that render configuration offline call it to get
+/// the same response.
+pub async fn fetch_config(
     config_source: &dyn vortex_git::ConfigSource,
+    policy: &ValidationPolicy,
+    keys: &KeyRing,
     app: &str,
     profiles: Vec<String>,
     label: &str,
 ) -> Result<ConfigResponse, CacheError> {
+    fetch_config_with_result(config_source, policy, keys, app, profiles, label)
+        .await
+        .map(|(response, _)| response)
+}
+
+/// Like [`fetch_config`], also returning the decrypted result the response
+/// was built from, for callers that inspect the merged configuration.
+pub async fn fetch_config_with_result(
+    config_source: &dyn vortex_git::ConfigSource,
+    policy: &ValidationPolicy,
+    keys: &KeyRing,
+    app: &str,
+    profiles: Vec<String>,
+    label: &str,
+) -> Result<(ConfigResponse, vortex_git::ConfigResult), CacheError> {
     // Create query for the config source
     let git_query = GitConfigQuery::new(app, profiles.clone()).with_label_set(label);
 
     // Fetch from the config source
-    let result = config_source
+    let mut result = config_source
         .fetch(&git_query)
         .await
         .map_err(|e| CacheError::FetchError(e.to_string()))?;
 
+    let warnings = keys.decrypt_sources(result.property_sources_mut());
+    result.add_warnings(warnings);
+
+    for warning in result.warnings() {
+        tracing::warn!(source = %warning.source, line = ?warning.line, "{}", warning.message);
+    }
+
+    let validation = validate_config(config_source, policy, &git_query, &result).await?;
+
     // Convert to response format
-    Ok(ConfigResponse {
-        name: result.name().to_string(),
-        profiles: result.profiles().to_vec(),
-        label: Some(result.label().to_string()),
-        version: result.version().map(String::from),
-        state: result.state().map(String::from),
-        property_sources: result
-            .property_sources()
-            .iter()
-            .map(|ps| PropertySourceResponse {
-                name: ps.name.clone(),
-                source: ps
-                    .config
-                    .as_inner()
-                    .iter()
-                    .map(|(k, v)| (k.clone(), config_value_to_json(v)))
-                    .collect(),
-            })
-            .collect(),
-    })
+    let response = ConfigResponse {
+        validation,
+        ..ConfigResponse::from_result(&result)
+    };
+    Ok((response, result))
+}
+
+/// Validates the merged configuration against the application's schema.
+///
+/// Returns the report to annotate the response with, or a validation error
+/// if the application rejects invalid configuration.
+async fn validate_config(
+    config_source: &dyn vortex_git::ConfigSource,
+    policy: &ValidationPolicy,
+    query: &GitConfigQuery,
+    result: &vortex_git::ConfigResult,
+) -> Result<Option<ValidationReport>, CacheError> {
+    let app = query.application();
+    let mode = policy.mode_for(app);
+    if mode == ValidationMode::Off {
+        return Ok(None);
+    }
+
+    // Prefer the schema loaded with the configuration: it comes from the
+    // same version (e.g. the same Git checkout).
+    let loaded = match result.schema() {
+        Some(schema) => Ok(Some(Arc::clone(schema))),
+        None => config_source.schema(query).await,
+    };
+    let schema = match loaded {
+        Ok(Some(schema)) => schema,
+        Ok(None) => return Ok(None),
+        Err(e) => {
+            // Un schema roto solo impide servir a las apps que rechazan.
+            validation_metrics::record_validation_error(app);
+            if mode == ValidationMode::Reject {
+                return Err(CacheError::FetchError(e.to_string()));
+            }
+            tracing::warn!("Cannot load the schema for {}: {}", app, e);
+            return Ok(None);
+        },
+    };
+
+    let report = schema.validate(&result.merged());
+    validation_metrics::record_validation(app, &report);
+
+    if !report.is_valid() {
+        tracing::warn!(
+            violations = report.violations().len(),
+            "Configuration does not match schema for {}",
+            app
+        );
+        if mode == ValidationMode::Reject {
+            return Err(CacheError::Validation(report));
+        }
+    }
+
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+    use vortex_git::vortex_core::validation::SchemaValidator;
+    use vortex_git::vortex_core::{ConfigMap, PropertySource};
+    use vortex_git::{ConfigResult, ConfigSource, ConfigSourceError, ConfigWarning};
+
+    use crate::response::{CONFIG_VIOLATIONS_HEADER, CONFIG_WARNINGS_HEADER};
+
+    /// Source serving `server.port = "http"` with a schema requiring an integer.
+    ///
+    /// The `dupes` application also reports a duplicate key warning, and the
+    /// schema of the `broken` application cannot be loaded.
+    struct SchemaSource;
+
+    #[async_trait::async_trait]
+    impl ConfigSource for SchemaSource {
+        async fn fetch(&self, query: &GitConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+            let config = ConfigMap::from_json(r#"{"server": {"port": "http"}}"#).unwrap();
+            let mut result =
+                ConfigResult::new(query.application(), query.profiles().to_vec(), "main")
+                    .with_property_sources(vec![PropertySource::new("application.yml", config)]);
+            if query.application() == "dupes" {
+                result.add_warnings([ConfigWarning::new(
+                    "application.yml",
+                    "duplicate key 'server' at line 3 (first defined at line 1)",
+                )
+                .at_line(3)]);
+            }
+            Ok(result)
+        }
+
+        async fn schema(
+            &self,
+            query: &GitConfigQuery,
+        ) -> Result<Option<Arc<SchemaValidator>>, ConfigSourceError> {
+            match query.application() {
+                "noschema" => return Ok(None),
+                "broken" => {
+                    return Err(ConfigSourceError::parse(
+                        "schemas/broken.schema.json",
+                        "expected value at line 1 column 1",
+                    ));
+                },
+                _ => {},
+            }
+            let schema = SchemaValidator::from_json(
+                r#"{"properties": {"server": {"properties": {"port": {"type": "integer"}}}}}"#,
+            )
+            .unwrap();
+            Ok(Some(Arc::new(schema)))
+        }
+
+        async fn health_check(&self) -> Result<(), ConfigSourceError> {
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "schema"
+        }
+    }
+
+    async fn get(policy: ValidationPolicy, uri: &str) -> (StatusCode, Option<String>, String) {
+        let state = AppState::without_cache(Arc::new(SchemaSource)).with_validation_policy(policy);
+        let handle = metrics_exporter_prometheus::PrometheusBuilder::new()
+            .build_recorder()
+            .handle();
+        let router = crate::server::create_router_with_state(state, handle);
+
+        let response = router
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let violations = response
+            .headers()
+            .get(CONFIG_VIOLATIONS_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (
+            status,
+            violations,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_annotate_mode_adds_report() {
+        let (status, violations, body) = get(ValidationPolicy::default(), "/myapp/prod").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(violations.as_deref(), Some("1"));
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["validation"]["valid"], false);
+        assert_eq!(json["validation"]["violations"][0]["path"], "server.port");
+    }
+
+    #[tokio::test]
+    async fn test_reject_mode_returns_422() {
+        let policy = ValidationPolicy::default().with_app("myapp", ValidationMode::Reject);
+
+        let (status, _, body) = get(policy, "/myapp/prod/main").await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert!(json["message"].as_str().unwrap().contains("server.port"));
+        assert_eq!(json["violations"][0]["path"], "server.port");
+    }
+
+    #[tokio::test]
+    async fn test_off_mode_and_missing_schema_skip_validation() {
+        let policy = ValidationPolicy::new(ValidationMode::Off);
+        let (status, violations, body) = get(policy, "/myapp/prod").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(violations.is_none());
+        assert!(!body.contains("validation"));
+
+        let policy = ValidationPolicy::new(ValidationMode::Reject);
+        let (status, violations, _) = get(policy, "/noschema/prod").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(violations.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_broken_schema_only_fails_reject_mode() {
+        let (status, violations, body) = get(ValidationPolicy::default(), "/broken/prod").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(violations.is_none());
+        assert!(body.contains("propertySources"));
+
+        let policy = ValidationPolicy::new(ValidationMode::Reject);
+        let (status, _, _) = get(policy, "/broken/prod").await;
+        assert!(!status.is_success());
+    }
+
+    #[tokio::test]
+    async fn test_warnings_in_body_and_header() {
+        let state = AppState::without_cache(Arc::new(SchemaSource))
+            .with_validation_policy(ValidationPolicy::new(ValidationMode::Off));
+        let handle = metrics_exporter_prometheus::PrometheusBuilder::new()
+            .build_recorder()
+            .handle();
+        let router = crate::server::create_router_with_state(state, handle);
+
+        let response = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/dupes/prod")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[CONFIG_WARNINGS_HEADER], "1");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["warnings"][0]["line"], 3);
+        assert_eq!(json["warnings"][0]["source"], "application.yml");
+
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .uri("/myapp/prod")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.headers().get(CONFIG_WARNINGS_HEADER).is_none());
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(!String::from_utf8_lossy(&body).contains("warnings"));
+    }
 }
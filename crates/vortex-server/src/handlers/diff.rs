@@ -0,0 +1,262 @@
+//! Configuration diff endpoint handler.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use tracing::instrument;
+use vortex_git::vortex_core::diff::diff_sources;
+use vortex_git::{ConfigQuery as GitConfigQuery, ConfigResult, ConfigSourceError};
+
+use crate::error::AppError;
+use crate::extractors::{accept::negotiate_media_type, path::AppProfilePath, query::DiffQuery};
+use crate::handlers::config::validate_label;
+use crate::handlers::response::{DiffResponse, DiffSide};
+use crate::state::AppState;
+
+/// Media types offered by the diff endpoint, in server preference order.
+const DIFF_MEDIA_TYPES: [&str; 3] = ["application/json", "text/x-diff", "text/plain"];
+
+/// Handler for GET /diff/{app}/{profile}?from={label}&to={label}.
+///
+/// Compares the merged configuration of both labels. Responses are JSON by
+/// default, or unified text with `?format=text` or `Accept: text/x-diff`.
+#[instrument(skip_all, fields(
+    app = %path.app,
+    profile = %path.profile,
+    from = %query.from,
+    to = %query.to
+))]
+pub async fn get_diff(
+    State(state): State<AppState>,
+    Path(path): Path<AppProfilePath>,
+    Query(query): Query<DiffQuery>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    path.validate().map_err(AppError::BadRequest)?;
+    validate_label(&query.from)?;
+    validate_label(&query.to)?;
+
+    let unified = wants_unified(
+        query.format.as_deref(),
+        headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()),
+    )?;
+
+    let profiles = path.profiles();
+
+    tracing::info!("Diffing config for {}/{:?}", path.app, profiles);
+
+    let from = fetch_result(&state, &path.app, &profiles, &query.from).await?;
+    let to = fetch_result(&state, &path.app, &profiles, &query.to).await?;
+
+    let changes = diff_sources(&from.source_list(), &to.source_list());
+
+    let mut response = if unified {
+        let body = changes.to_unified(
+            &format!("{}/{}@{}", path.app, path.profile, query.from),
+            &format!("{}/{}@{}", path.app, path.profile, query.to),
+        );
+        (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "text/x-diff; charset=utf-8")],
+            body,
+        )
+            .into_response()
+    } else {
+        Json(DiffResponse {
+            name: path.app.clone(),
+            profiles,
+            from: diff_side(&query.from, &from),
+            to: diff_side(&query.to, &to),
+            changes,
+        })
+        .into_response()
+    };
+
+    response
+        .headers_mut()
+        .insert(header::VARY, HeaderValue::from_static("accept"));
+    Ok(response)
+}
+
+/// Decides between JSON and unified text output.
+///
+/// `?format=` takes precedence over the `Accept` header.
+fn wants_unified(format: Option<&str>, accept: Option<&str>) -> Result<bool, AppError> {
+    match format.map(str::to_ascii_lowercase).as_deref() {
+        Some("json") => Ok(false),
+        Some("text" | "diff" | "unified") => Ok(true),
+        Some(other) => Err(AppError::NotAcceptable(format!(
+            "Unsupported diff format '{}'; supported: json, text",
+            other
+        ))),
+        None => match negotiate_media_type(accept, &DIFF_MEDIA_TYPES) {
+            Some(mime) => Ok(mime != "application/json"),
+            None => Err(AppError::NotAcceptable(format!(
+                "No acceptable representation; supported: {}",
+                DIFF_MEDIA_TYPES.join(", ")
+            ))),
+        },
+    }
+}
+
+/// Fetches the configuration of one side of the diff.
+async fn fetch_result(
+    state: &AppState,
+    app: &str,
+    profiles: &[String],
+    label: &str,
+) -> Result<ConfigResult, AppError> {
+    let query = GitConfigQuery::new(app, profiles.to_vec()).with_label_set(label);
+
+    state
+        .config_source()
+        .fetch(&query)
+        .await
+        .map_err(|e| match e {
+            ConfigSourceError::LabelNotFound(label) => {
+                AppError::BadRequest(format!("Label not found: {}", label))
+            },
+            e => AppError::Internal(e.to_string()),
+        })
+}
+
+fn diff_side(label: &str, result: &ConfigResult) -> DiffSide {
+    DiffSide {
+        label: label.to_string(),
+        version: result.version().map(String::from),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+    use vortex_git::ConfigSource;
+    use vortex_git::vortex_core::{ConfigMap, PropertySource};
+
+    fn source(name: &str, json: &str) -> PropertySource {
+        PropertySource::new(name, ConfigMap::from_json(json).unwrap())
+    }
+
+    /// Source whose `application.yml` port depends on the label.
+    struct LabeledSource;
+
+    #[async_trait::async_trait]
+    impl ConfigSource for LabeledSource {
+        async fn fetch(&self, query: &GitConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+            let port = match query.effective_label("main") {
+                "v1" => 8080,
+                "v2" => 9090,
+                other => return Err(ConfigSourceError::LabelNotFound(other.to_string())),
+            };
+            Ok(
+                ConfigResult::new(query.application(), query.profiles().to_vec(), "main")
+                    .with_property_sources(vec![source(
+                        "application.yml",
+                        &format!(r#"{{"server": {{"port": {}}}}}"#, port),
+                    )]),
+            )
+        }
+
+        async fn health_check(&self) -> Result<(), ConfigSourceError> {
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "labeled"
+        }
+    }
+
+    async fn get(uri: &str, accept: Option<&str>) -> (StatusCode, String, String) {
+        let state = AppState::without_cache(Arc::new(LabeledSource));
+        let handle = metrics_exporter_prometheus::PrometheusBuilder::new()
+            .build_recorder()
+            .handle();
+        let router = crate::server::create_router_with_state(state, handle);
+
+        let mut request = Request::builder().uri(uri);
+        if let Some(accept) = accept {
+            request = request.header(header::ACCEPT, accept);
+        }
+        let response = router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (
+            status,
+            content_type,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_diff_endpoint_json() {
+        let (status, content_type, body) = get("/diff/myapp/prod?from=v1&to=v2", None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "application/json");
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json["from"]["label"], "v1");
+        assert_eq!(json["changes"][0]["path"], "server.port");
+        assert_eq!(json["changes"][0]["old"], 8080);
+        assert_eq!(json["changes"][0]["new"], 9090);
+    }
+
+    #[tokio::test]
+    async fn test_diff_endpoint_unified() {
+        let (status, content_type, body) =
+            get("/diff/myapp/prod?from=v1&to=v2", Some("text/x-diff")).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(content_type.starts_with("text/x-diff"));
+        assert_eq!(
+            body,
+            "--- myapp/prod@v1\n+++ myapp/prod@v2\n-server.port=8080\n+server.port=9090\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_diff_endpoint_errors() {
+        let (status, _, _) = get("/diff/myapp/prod?from=v1", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _, body) = get("/diff/myapp/prod?from=v1&to=v9", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("v9"));
+
+        let (status, _, _) = get("/diff/myapp/prod?from=..&to=v2", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _, _) = get("/diff/myapp/prod?from=v1&to=v2&format=xml", None).await;
+        assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[test]
+    fn test_wants_unified() {
+        assert!(!wants_unified(None, None).unwrap());
+        assert!(!wants_unified(None, Some("*/*")).unwrap());
+        assert!(wants_unified(None, Some("text/x-diff")).unwrap());
+        assert!(wants_unified(None, Some("text/plain")).unwrap());
+        assert!(wants_unified(Some("TEXT"), Some("application/json")).unwrap());
+        assert!(!wants_unified(Some("json"), Some("text/x-diff")).unwrap());
+        assert!(wants_unified(Some("yaml"), None).is_err());
+        assert!(wants_unified(None, Some("image/png")).is_err());
+    }
+}
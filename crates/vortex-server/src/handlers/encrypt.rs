@@ -0,0 +1,264 @@
+//! Encryption endpoint handlers, compatible with Spring Cloud Config.
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::crypto::CryptoError;
+use crate::error::AppError;
+use crate::extractors::auth::EncryptAccess;
+use crate::state::AppState;
+
+/// Response of `GET /encrypt/status`.
+#[derive(Debug, Serialize)]
+pub struct EncryptStatusResponse {
+    /// `OK`, or `NO_KEY` when no key is installed.
+    pub status: &'static str,
+    /// Why the service is unavailable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// POST /encrypt
+/// Encrypts the request body. A leading `{key:id}` selects the key and is
+/// kept in the output. Requires an encrypt token.
+#[instrument(skip_all)]
+pub async fn encrypt(
+    State(state): State<AppState>,
+    _access: EncryptAccess,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Response, AppError> {
+    let encrypted = state
+        .key_ring()
+        .encrypt(&strip_form_data(&headers, &body))?;
+    Ok(plain_text(encrypted))
+}
+
+/// POST /decrypt
+/// Decrypts the request body, with or without the `{cipher}` prefix.
+/// Requires an encrypt token and is disabled unless explicitly enabled.
+#[instrument(skip_all)]
+pub async fn decrypt(
+    State(state): State<AppState>,
+    _access: EncryptAccess,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Response, AppError> {
+    if !state.is_decrypt_enabled() {
+        return Err(AppError::Forbidden(
+            "Decryption is disabled: set VORTEX_DECRYPT_ENABLED=true to enable it".to_string(),
+        ));
+    }
+    let decrypted = state
+        .key_ring()
+        .decrypt(&strip_form_data(&headers, &body))?;
+    Ok(plain_text(decrypted))
+}
+
+/// GET /encrypt/status
+/// Reports whether a key is installed.
+#[instrument(skip_all)]
+pub async fn encrypt_status(State(state): State<AppState>) -> Response {
+    if state.key_ring().is_empty() {
+        (
+            StatusCode::NOT_FOUND,
+            Json(EncryptStatusResponse {
+                status: "NO_KEY",
+                description: Some(CryptoError::NoKey.to_string()),
+            }),
+        )
+            .into_response()
+    } else {
+        Json(EncryptStatusResponse {
+            status: "OK",
+            description: None,
+        })
+        .into_response()
+    }
+}
+
+/// Drops the trailing `=` that `curl -d` appends when it posts the body as a
+/// form, as Spring does.
+fn strip_form_data(headers: &HeaderMap, body: &str) -> String {
+    let body = body.trim();
+    let is_form = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/x-www-form-urlencoded"));
+
+    match body.strip_suffix('=') {
+        Some(stripped) if is_form => stripped.to_string(),
+        _ => body.to_string(),
+    }
+}
+
+fn plain_text(body: String) -> Response {
+    ([(header::CONTENT_TYPE, "text/plain")], body).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+    use vortex_git::{ConfigQuery, ConfigResult, ConfigSource, ConfigSourceError};
+
+    use crate::crypto::{AesCbcEncryptor, DEFAULT_KEY_ID, KeyRing};
+
+    /// The encryption endpoints never read configuration.
+    struct NoSource;
+
+    #[async_trait::async_trait]
+    impl ConfigSource for NoSource {
+        async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+            Err(ConfigSourceError::ApplicationNotFound(
+                query.application().to_string(),
+            ))
+        }
+
+        async fn health_check(&self) -> Result<(), ConfigSourceError> {
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "none"
+        }
+    }
+
+    const TOKEN: &str = "t0k3n";
+
+    async fn send(keys: KeyRing, request: Request<Body>) -> (StatusCode, String) {
+        let state = AppState::without_cache(Arc::new(NoSource))
+            .with_key_ring(keys)
+            .with_encrypt_tokens(vec![TOKEN.to_string()])
+            .with_decrypt_enabled(true);
+        send_with_state(state, request).await
+    }
+
+    async fn send_with_state(state: AppState, request: Request<Body>) -> (StatusCode, String) {
+        let handle = metrics_exporter_prometheus::PrometheusBuilder::new()
+            .build_recorder()
+            .handle();
+        let response = crate::server::create_router_with_state(state, handle)
+            .oneshot(request)
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    fn post(uri: &str, content_type: &str, body: &str) -> Request<Body> {
+        Request::post(uri)
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN))
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    fn keys() -> KeyRing {
+        KeyRing::new()
+            .with_key(
+                DEFAULT_KEY_ID,
+                AesCbcEncryptor::from_password("s3cr3t", "deadbeef").unwrap(),
+            )
+            .with_key(
+                "prod",
+                AesCbcEncryptor::from_password("pr0d", "deadbeef").unwrap(),
+            )
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_then_decrypt() {
+        let (status, cipher) =
+            send(keys(), post("/encrypt", "text/plain", "{key:prod}hunter2")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(cipher.starts_with("{key:prod}"));
+
+        let body = format!("{{cipher}}{}", cipher);
+        let (status, plain) = send(keys(), post("/decrypt", "text/plain", &body)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(plain, "hunter2");
+    }
+
+    #[tokio::test]
+    async fn test_form_bodies_lose_trailing_equals() {
+        let (_, cipher) = send(
+            keys(),
+            post("/encrypt", "application/x-www-form-urlencoded", "hunter2="),
+        )
+        .await;
+
+        let (status, plain) = send(keys(), post("/decrypt", "text/plain", &cipher)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(plain, "hunter2");
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let (status, _) = send(KeyRing::new(), post("/encrypt", "text/plain", "x")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send(keys(), post("/encrypt", "text/plain", "{key:qa}x")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, body) = send(keys(), post("/decrypt", "text/plain", "bm90IGEgY2lwaGVy")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("Cannot decrypt"));
+    }
+
+    #[tokio::test]
+    async fn test_endpoints_require_a_token() {
+        let state = || AppState::without_cache(Arc::new(NoSource)).with_key_ring(keys());
+        let unauthenticated = |uri: &str| {
+            Request::post(uri)
+                .header(header::CONTENT_TYPE, "text/plain")
+                .body(Body::from("x"))
+                .unwrap()
+        };
+
+        // No tokens configured: both endpoints are disabled.
+        for uri in ["/encrypt", "/decrypt"] {
+            let (status, _) = send_with_state(state(), post(uri, "text/plain", "x")).await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
+        }
+
+        let state = || state().with_encrypt_tokens(vec![TOKEN.to_string()]);
+        for uri in ["/encrypt", "/decrypt"] {
+            let (status, _) = send_with_state(state(), unauthenticated(uri)).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+
+        // A valid token encrypts, but decryption stays off until enabled.
+        let (status, cipher) =
+            send_with_state(state(), post("/encrypt", "text/plain", "hunter2")).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) =
+            send_with_state(state(), post("/decrypt", "text/plain", &cipher)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body.contains("Decryption is disabled"));
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let status_request = || Request::get("/encrypt/status").body(Body::empty()).unwrap();
+
+        let (status, body) = send(keys(), status_request()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, r#"{"status":"OK"}"#);
+
+        let (status, body) = send(KeyRing::new(), status_request()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body.contains(r#""status":"NO_KEY""#));
+    }
+}
@@ -1,6 +1,10 @@
 use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
 use serde::Serialize;
 
+use crate::state::AppState;
+
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
     pub status: String,
@@ -17,3 +21,149 @@ impl Default for HealthResponse {
 pub async fn health_check() -> Json<HealthResponse> {
     Json(HealthResponse::default())
 }
+
+/// Estado de cada backend de configuracion.
+#[derive(Debug, Serialize)]
+pub struct SourcesHealthResponse {
+    /// `UP` si el origen de configuracion puede servir peticiones.
+    pub status: String,
+    /// Estado de cada backend, en orden de precedencia.
+    pub sources: Vec<SourceHealth>,
+}
+
+/// Estado de un backend.
+#[derive(Debug, Serialize)]
+pub struct SourceHealth {
+    /// Nombre del backend.
+    pub name: String,
+    /// `UP` o `DOWN`.
+    pub status: String,
+    /// Motivo si esta `DOWN`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn status(up: bool) -> String {
+    if up { "UP" } else { "DOWN" }.to_string()
+}
+
+/// GET /health/sources
+/// Comprueba cada backend; responde 503 si el origen no puede servir
+/// peticiones.
+pub async fn sources_health(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<SourcesHealthResponse>) {
+    let source = state.config_source();
+    let up = source.health_check().await.is_ok();
+    let sources = source
+        .backend_health()
+        .await
+        .into_iter()
+        .map(|(name, result)| SourceHealth {
+            name,
+            status: status(result.is_ok()),
+            error: result.err().map(|e| e.to_string()),
+        })
+        .collect();
+
+    let code = if up {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        code,
+        Json(SourcesHealthResponse {
+            status: status(up),
+            sources,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+    use vortex_git::{ConfigQuery, ConfigResult, ConfigSource, ConfigSourceError};
+    use vortex_sources::CompositeConfigSource;
+
+    /// A backend that is either reachable or not.
+    struct Probe(bool);
+
+    #[async_trait::async_trait]
+    impl ConfigSource for Probe {
+        async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+            Err(ConfigSourceError::ApplicationNotFound(
+                query.application().to_string(),
+            ))
+        }
+
+        async fn health_check(&self) -> Result<(), ConfigSourceError> {
+            if self.0 {
+                Ok(())
+            } else {
+                Err(ConfigSourceError::unavailable("connection refused"))
+            }
+        }
+
+        fn name(&self) -> &str {
+            "probe"
+        }
+    }
+
+    async fn send(state: AppState) -> (StatusCode, serde_json::Value) {
+        let handle = metrics_exporter_prometheus::PrometheusBuilder::new()
+            .build_recorder()
+            .handle();
+        let response = crate::server::create_router_with_state(state, handle)
+            .oneshot(Request::get("/health/sources").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_single_source() {
+        let (status, body) = send(AppState::without_cache(Arc::new(Probe(true)))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            serde_json::json!({"status": "UP", "sources": [{"name": "probe", "status": "UP"}]})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_composite_sources() {
+        let backends = CompositeConfigSource::new()
+            .with_backend("git", 10, Arc::new(Probe(true)))
+            .with_backend("sql", 100, Arc::new(Probe(false)));
+
+        let (status, body) = send(AppState::from_backends(backends, None)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["sources"][0]["name"], "sql");
+        assert_eq!(body["sources"][0]["status"], "DOWN");
+        assert!(
+            body["sources"][0]["error"]
+                .as_str()
+                .unwrap()
+                .contains("connection refused")
+        );
+        assert_eq!(body["sources"][1]["status"], "UP");
+
+        let backends = CompositeConfigSource::new()
+            .with_fail_fast(true)
+            .with_backend("git", 10, Arc::new(Probe(true)))
+            .with_backend("sql", 100, Arc::new(Probe(false)));
+        let (status, body) = send(AppState::from_backends(backends, None)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "DOWN");
+    }
+}
@@ -1,5 +1,8 @@
 pub mod config;
+pub mod diff;
+pub mod encrypt;
 pub mod health;
 pub mod invalidate;
 pub mod metrics;
 pub mod response;
+pub mod store;
@@ -1,10 +1,17 @@
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use vortex_git::vortex_core::diff::ConfigDiff;
+use vortex_git::vortex_core::validation::ValidationReport;
+use vortex_git::vortex_core::{ConfigMap, ConfigValue};
+use vortex_git::{ConfigResult, ConfigWarning};
 
 /// Response compatible con Spring Cloud Config Server.
 ///
 /// Este struct mapea exactamente al formato JSON que retorna
-/// Spring Cloud Config para mantener compatibilidad.
+/// Spring Cloud Config para mantener compatibilidad. Los serializadores de
+/// formatos reciben el mismo arbol como documento (ver [`to_document`]).
+///
+/// [`to_document`]: ConfigResponse::to_document
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigResponse {
@@ -25,6 +32,19 @@ pub struct ConfigResponse {
 
     /// Lista de property sources en orden de precedencia
     pub property_sources: Vec<PropertySourceResponse>,
+
+    /// Resultado de validar contra el JSON Schema de la aplicacion (modo `annotate`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation: Option<ValidationReport>,
+
+    /// Advertencias de la resolucion (p.ej. claves duplicadas)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<ConfigWarning>,
+
+    /// Si falta parte de la configuracion (p.ej. fallo un backend del
+    /// composite); el cache la guarda menos tiempo
+    #[serde(skip)]
+    pub partial: bool,
 }
 
 /// Representa un archivo de configuracion individual.
@@ -33,8 +53,44 @@ pub struct PropertySourceResponse {
     /// Nombre/path del archivo de configuracion
     pub name: String,
 
-    /// Propiedades como mapa clave-valor
-    pub source: HashMap<String, serde_json::Value>,
+    /// Propiedades como mapa clave-valor, ordenadas por clave para que la
+    /// respuesta sea estable entre peticiones
+    pub source: BTreeMap<String, serde_json::Value>,
+
+    /// Archivo incluido (`$ref`, `!include`, `@include`) del que viene cada
+    /// clave, para las claves que no estan definidas en el propio archivo
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub origins: BTreeMap<String, String>,
+}
+
+/// Diferencias de configuracion entre dos labels.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffResponse {
+    /// Nombre de la aplicacion
+    pub name: String,
+
+    /// Lista de profiles comparados
+    pub profiles: Vec<String>,
+
+    /// Lado de origen de la comparacion
+    pub from: DiffSide,
+
+    /// Lado de destino de la comparacion
+    pub to: DiffSide,
+
+    /// Cambios sobre la configuracion mergeada, de `from` a `to`
+    pub changes: ConfigDiff,
+}
+
+/// Label y version de un lado del diff.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffSide {
+    /// Label solicitado
+    pub label: String,
+
+    /// Version del commit (para Git backend)
+    pub version: Option<String>,
 }
 
 impl ConfigResponse {
@@ -47,6 +103,179 @@ impl ConfigResponse {
             version: None,
             state: None,
             property_sources: Vec::new(),
+            validation: None,
+            warnings: Vec::new(),
+            partial: false,
+        }
+    }
+
+    /// Convierte el resultado de un backend en la respuesta del servidor.
+    pub fn from_result(result: &ConfigResult) -> Self {
+        Self {
+            name: result.name().to_string(),
+            profiles: result.profiles().to_vec(),
+            label: Some(result.label().to_string()),
+            version: result.version().map(String::from),
+            state: result.state().map(String::from),
+            property_sources: result
+                .property_sources()
+                .iter()
+                .map(|ps| PropertySourceResponse {
+                    name: ps.name.clone(),
+                    source: ps
+                        .config
+                        .as_inner()
+                        .iter()
+                        .map(|(k, v)| (k.clone(), config_value_to_json(v)))
+                        .collect(),
+                    origins: ps.origins.clone(),
+                })
+                .collect(),
+            validation: None,
+            warnings: result.warnings().to_vec(),
+            partial: result.is_partial(),
         }
     }
+
+    /// Construye el documento de la respuesta: el mismo arbol (y orden de
+    /// campos) que su JSON, con cada valor de configuracion en su tipo
+    /// original, sin pasar por texto.
+    pub fn to_document(&self) -> ConfigMap {
+        let mut document = ConfigMap::new();
+        document.insert("name", self.name.as_str());
+        document.insert("profiles", self.profiles.clone());
+        document.insert("label", optional(&self.label));
+        document.insert("version", optional(&self.version));
+        document.insert("state", optional(&self.state));
+        document.insert(
+            "propertySources",
+            ConfigValue::Array(
+                self.property_sources
+                    .iter()
+                    .map(PropertySourceResponse::to_value)
+                    .collect(),
+            ),
+        );
+        if let Some(report) = &self.validation {
+            document.insert("validation", validation_value(report));
+        }
+        if !self.warnings.is_empty() {
+            document.insert(
+                "warnings",
+                ConfigValue::Array(self.warnings.iter().map(warning_value).collect()),
+            );
+        }
+        document
+    }
+}
+
+impl PropertySourceResponse {
+    /// Property source como objeto del documento de la respuesta.
+    fn to_value(&self) -> ConfigValue {
+        let source = self
+            .source
+            .iter()
+            .map(|(k, v)| (k.clone(), json_to_config_value(v)))
+            .collect();
+
+        let mut fields = vec![
+            ("name", self.name.as_str().into()),
+            ("source", ConfigValue::Object(source)),
+        ];
+        if !self.origins.is_empty() {
+            let origins = self
+                .origins
+                .iter()
+                .map(|(k, v)| (k.clone(), v.as_str().into()))
+                .collect();
+            fields.push(("origins", ConfigValue::Object(origins)));
+        }
+        object(fields)
+    }
+}
+
+/// Objeto del documento con los campos en el orden dado.
+fn object(fields: Vec<(&str, ConfigValue)>) -> ConfigValue {
+    ConfigValue::Object(
+        fields
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    )
+}
+
+fn optional(value: &Option<String>) -> ConfigValue {
+    value
+        .as_deref()
+        .map_or(ConfigValue::Null, ConfigValue::from)
+}
+
+fn validation_value(report: &ValidationReport) -> ConfigValue {
+    let violations = report
+        .violations()
+        .iter()
+        .map(|violation| {
+            object(vec![
+                ("path", violation.path.to_string().into()),
+                ("message", violation.message.as_str().into()),
+            ])
+        })
+        .collect();
+
+    object(vec![
+        ("valid", report.is_valid().into()),
+        ("violations", ConfigValue::Array(violations)),
+    ])
+}
+
+fn warning_value(warning: &ConfigWarning) -> ConfigValue {
+    let mut fields = vec![("source", warning.source.as_str().into())];
+    if let Some(line) = warning.line {
+        fields.push(("line", (line as i64).into()));
+    }
+    fields.push(("message", warning.message.as_str().into()));
+    object(fields)
+}
+
+/// Convierte un ConfigValue a serde_json::Value.
+fn config_value_to_json(value: &ConfigValue) -> serde_json::Value {
+    match value {
+        ConfigValue::Null => serde_json::Value::Null,
+        ConfigValue::Bool(b) => serde_json::Value::Bool(*b),
+        ConfigValue::Integer(i) => serde_json::Value::Number((*i).into()),
+        ConfigValue::Float(f) => serde_json::Number::from_f64(f.into_inner())
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        ConfigValue::String(s) | ConfigValue::DateTime(s) => serde_json::Value::String(s.clone()),
+        ConfigValue::Array(arr) => {
+            serde_json::Value::Array(arr.iter().map(config_value_to_json).collect())
+        },
+        ConfigValue::Object(obj) => serde_json::Value::Object(
+            obj.iter()
+                .map(|(k, v)| (k.clone(), config_value_to_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Convierte un serde_json::Value a ConfigValue (inversa de
+/// [`config_value_to_json`]).
+fn json_to_config_value(value: &serde_json::Value) -> ConfigValue {
+    match value {
+        serde_json::Value::Null => ConfigValue::Null,
+        serde_json::Value::Bool(b) => ConfigValue::Bool(*b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => ConfigValue::Integer(i),
+            None => n.as_f64().map_or(ConfigValue::Null, ConfigValue::from),
+        },
+        serde_json::Value::String(s) => ConfigValue::String(s.clone()),
+        serde_json::Value::Array(arr) => {
+            ConfigValue::Array(arr.iter().map(json_to_config_value).collect())
+        },
+        serde_json::Value::Object(obj) => ConfigValue::Object(
+            obj.iter()
+                .map(|(k, v)| (k.clone(), json_to_config_value(v)))
+                .collect(),
+        ),
+    }
 }
@@ -0,0 +1,547 @@
+//! Escritura de configuracion en backends que la admiten (SQL, Redis y
+//! nativo).
+//!
+//! - `PUT /{app}/{profile}/{label}` reemplaza el documento con el objeto
+//!   JSON del body.
+//! - `PATCH /{app}/{profile}/{label}` aplica un JSON Merge Patch (RFC 7396):
+//!   las claves con `null` se borran y los objetos se combinan.
+//! - `DELETE /{app}/{profile}/{label}/{key}` borra una clave y todo lo que
+//!   cuelga de ella.
+//!
+//! Las claves del primer nivel del body pueden ser rutas con puntos
+//! (`{"db.pool.size": 25}`), igual que en los archivos `.properties`.
+//!
+//! Cada respuesta lleva el documento escrito y su version en el header
+//! `ETag`. Con `If-Match` la escritura solo se aplica si el documento sigue
+//! en esa version (`412 Precondition Failed` si no); un `PATCH` con `{}`
+//! devuelve el documento sin cambiarlo. Tras escribir se invalida la cache
+//! de la aplicacion, o toda si es `application`.
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, header},
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+use tracing::instrument;
+use vortex_git::vortex_core::merge::deep_merge;
+use vortex_git::vortex_core::{ConfigMap, ConfigPath, ConfigValue};
+use vortex_git::{ConfigSourceError, ConfigStore, DocumentId, StoredProperties};
+
+use crate::error::AppError;
+use crate::extractors::auth::WriteAccess;
+use crate::extractors::path::{AppProfileLabelKeyPath, AppProfileLabelPath};
+use crate::handlers::config::validate_label;
+use crate::state::AppState;
+
+/// Aplicacion cuyas propiedades aplican a todas las demas.
+const SHARED_APPLICATION: &str = "application";
+
+/// Documento escrito, tal como queda en el backend.
+#[derive(Debug, Serialize)]
+pub struct DocumentResponse {
+    /// Nombre de la aplicacion
+    pub name: String,
+    /// Profile del documento
+    pub profile: String,
+    /// Label del documento
+    pub label: String,
+    /// Version del documento, la misma del header `ETag`
+    pub version: String,
+    /// Propiedades del documento
+    pub properties: ConfigMap,
+}
+
+/// PUT /{app}/{profile}/{label}
+/// Reemplaza el documento.
+#[instrument(skip_all, fields(app = %path.app, profile = %path.profile, label = %path.label))]
+pub async fn put_properties(
+    State(state): State<AppState>,
+    _access: WriteAccess,
+    Path(path): Path<AppProfileLabelPath>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, AppError> {
+    let id = document_id(&path)?;
+    let store = store(&state)?;
+    let properties = parse_body(&body)?;
+    let expected = if_match(&headers);
+
+    let stored = store
+        .put_properties(&id, &properties, expected.as_deref())
+        .await
+        .map_err(|e| store_error(e, expected.is_some()))?;
+
+    written(&state, id, stored).await
+}
+
+/// PATCH /{app}/{profile}/{label}
+/// Aplica un JSON Merge Patch al documento.
+#[instrument(skip_all, fields(app = %path.app, profile = %path.profile, label = %path.label))]
+pub async fn patch_properties(
+    State(state): State<AppState>,
+    _access: WriteAccess,
+    Path(path): Path<AppProfileLabelPath>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, AppError> {
+    let id = document_id(&path)?;
+    let store = store(&state)?;
+    let patch = parse_body(&body)?;
+    let expected = if_match(&headers);
+
+    let current = store
+        .get_properties(&id)
+        .await
+        .map_err(|e| store_error(e, false))?;
+    if let Some(expected) = &expected
+        && *expected != current.version
+    {
+        return Err(AppError::PreconditionFailed(format!(
+            "{} changed: expected version {}, found {}",
+            id, expected, current.version
+        )));
+    }
+
+    let mut properties = ConfigValue::Object(current.properties.as_inner().clone());
+    merge_patch(
+        &mut properties,
+        &ConfigValue::Object(patch.as_inner().clone()),
+    );
+    let properties = match properties {
+        ConfigValue::Object(map) => ConfigMap::from_inner(map),
+        _ => ConfigMap::new(),
+    };
+    if properties == current.properties {
+        return Ok(document(id, current));
+    }
+
+    // La version leida protege de escrituras entre la lectura y esta.
+    let stored = store
+        .put_properties(&id, &properties, Some(&current.version))
+        .await
+        .map_err(|e| store_error(e, expected.is_some()))?;
+
+    written(&state, id, stored).await
+}
+
+/// DELETE /{app}/{profile}/{label}/{key}
+/// Borra una clave del documento.
+#[instrument(skip_all, fields(app = %path.app, profile = %path.profile, label = %path.label))]
+pub async fn delete_key(
+    State(state): State<AppState>,
+    _access: WriteAccess,
+    Path(path): Path<AppProfileLabelKeyPath>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let (path, key) = path.into_parts();
+    let id = document_id(&path)?;
+    let store = store(&state)?;
+    if ConfigPath::parse(&key).is_err() {
+        return Err(AppError::BadRequest(format!("Invalid key: {}", key)));
+    }
+    let expected = if_match(&headers);
+
+    let stored = store
+        .delete_key(&id, &key, expected.as_deref())
+        .await
+        .map_err(|e| store_error(e, expected.is_some()))?;
+
+    written(&state, id, stored).await
+}
+
+/// Valida la ruta y construye el identificador del documento.
+fn document_id(path: &AppProfileLabelPath) -> Result<DocumentId, AppError> {
+    path.validate().map_err(AppError::BadRequest)?;
+    if path.profile.contains(',') {
+        return Err(AppError::BadRequest(
+            "Writes take a single profile".to_string(),
+        ));
+    }
+    let label = path.sanitized_label();
+    validate_label(&label)?;
+    Ok(DocumentId::new(&path.app, path.profile.trim(), label))
+}
+
+/// Retorna el backend de escritura, o `405` si el backend es de solo lectura.
+fn store(state: &AppState) -> Result<&dyn ConfigStore, AppError> {
+    let source = state.config_source();
+    source.store().ok_or_else(|| {
+        AppError::MethodNotAllowed(format!(
+            "The {} backend does not accept writes",
+            source.name()
+        ))
+    })
+}
+
+/// Parsea el body: un objeto JSON cuyas claves de primer nivel pueden ser
+/// rutas con puntos.
+fn parse_body(body: &[u8]) -> Result<ConfigMap, AppError> {
+    let text = std::str::from_utf8(body)
+        .map_err(|_| AppError::BadRequest("The body must be UTF-8".to_string()))?;
+    let flat = ConfigMap::from_json(text)
+        .map_err(|e| AppError::BadRequest(format!("The body must be a JSON object: {}", e)))?;
+
+    let mut properties = ConfigMap::new();
+    for (key, value) in flat.as_inner() {
+        let path = ConfigPath::parse(key)
+            .map_err(|e| AppError::BadRequest(format!("Invalid key {}: {}", key, e)))?;
+        let mut nested = ConfigMap::new();
+        nested
+            .set_path(&path, value.clone())
+            .map_err(|e| AppError::BadRequest(format!("Invalid key {}: {}", key, e)))?;
+        deep_merge(&mut properties, &nested);
+    }
+    Ok(properties)
+}
+
+/// Aplica un JSON Merge Patch (RFC 7396, seccion 2).
+fn merge_patch(target: &mut ConfigValue, patch: &ConfigValue) {
+    let ConfigValue::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !matches!(target, ConfigValue::Object(_)) {
+        *target = ConfigValue::Object(Default::default());
+    }
+    let ConfigValue::Object(target) = target else {
+        return;
+    };
+    for (key, value) in patch {
+        if matches!(value, ConfigValue::Null) {
+            target.shift_remove(key);
+        } else {
+            merge_patch(
+                target.entry(key.clone()).or_insert(ConfigValue::Null),
+                value,
+            );
+        }
+    }
+}
+
+/// Lee la version de `If-Match`; `*` acepta cualquiera.
+fn if_match(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(header::IF_MATCH)?.to_str().ok()?.trim();
+    let value = value.strip_prefix("W/").unwrap_or(value);
+    let version = value.trim_matches('"');
+    (version != "*" && !version.is_empty()).then(|| version.to_string())
+}
+
+/// Traduce un error del backend. Un conflicto de version es `412` si el
+/// cliente envio `If-Match`, y `409` si no.
+fn store_error(e: ConfigSourceError, conditional: bool) -> AppError {
+    match e {
+        ConfigSourceError::VersionConflict { .. } if conditional => {
+            AppError::PreconditionFailed(e.to_string())
+        },
+        ConfigSourceError::VersionConflict { .. }
+        | ConfigSourceError::ReadOnly(_)
+        | ConfigSourceError::Parse { .. } => AppError::Conflict(e.to_string()),
+        ConfigSourceError::KeyNotFound(key) => AppError::KeyNotFound(key),
+        ConfigSourceError::LabelNotFound(_) | ConfigSourceError::InvalidConfig(_) => {
+            AppError::BadRequest(e.to_string())
+        },
+        e => AppError::Internal(e.to_string()),
+    }
+}
+
+/// Invalida la cache tras una escritura y responde con el documento.
+async fn written(
+    state: &AppState,
+    id: DocumentId,
+    stored: StoredProperties,
+) -> Result<Response, AppError> {
+    if let Some(cache) = state.cache() {
+        if id.application == SHARED_APPLICATION {
+            cache.invalidate_all();
+        } else {
+            cache.invalidate_by_app(&id.application).await;
+        }
+    }
+    tracing::info!(version = %stored.version, "Configuration written");
+    Ok(document(id, stored))
+}
+
+fn document(id: DocumentId, stored: StoredProperties) -> Response {
+    let etag = HeaderValue::from_str(&format!("\"{}\"", stored.version)).ok();
+    let body = Json(DocumentResponse {
+        name: id.application,
+        profile: id.profile,
+        label: id.label,
+        version: stored.version,
+        properties: stored.properties,
+    });
+    match etag {
+        Some(etag) => ([(header::ETAG, etag)], body).into_response(),
+        None => body.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::{Method, Request, StatusCode};
+    use http_body_util::BodyExt;
+    use serde_json::Value;
+    use tower::ServiceExt;
+    use vortex_git::{ConfigQuery, ConfigResult, ConfigSource};
+    use vortex_sources::FilesystemBackend;
+
+    use crate::cache::{CacheConfig, ConfigCache};
+
+    const TOKEN: &str = "s3cret";
+
+    fn router(source: Arc<dyn ConfigSource>, tokens: &[&str]) -> Router {
+        let cache = ConfigCache::new(CacheConfig {
+            ttl_seconds: 300,
+            partial_ttl_seconds: 10,
+            max_capacity: 100,
+            tti_seconds: None,
+        });
+        let state = AppState::new(source, Some(cache))
+            .with_write_tokens(tokens.iter().map(|t| t.to_string()).collect());
+        let handle = metrics_exporter_prometheus::PrometheusBuilder::new()
+            .build_recorder()
+            .handle();
+        crate::server::create_router_with_state(state, handle)
+    }
+
+    async fn send(
+        router: &Router,
+        method: Method,
+        uri: &str,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) -> (StatusCode, HeaderMap, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let response = router
+            .clone()
+            .oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (
+            status,
+            headers,
+            serde_json::from_slice(&body).unwrap_or(Value::Null),
+        )
+    }
+
+    fn native() -> (tempfile::TempDir, Arc<dyn ConfigSource>) {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("payments-prod.yml"), "db:\n  pool: 10\n").unwrap();
+        let source = Arc::new(FilesystemBackend::new(dir.path()));
+        (dir, source)
+    }
+
+    const AUTHORIZED: (&str, &str) = ("authorization", "Bearer s3cret");
+
+    #[tokio::test]
+    async fn test_writes_require_a_token() {
+        let (_dir, source) = native();
+
+        let disabled = router(Arc::clone(&source), &[]);
+        let (status, _, _) = send(
+            &disabled,
+            Method::PUT,
+            "/payments/prod/main",
+            &[AUTHORIZED],
+            "{}",
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let router = router(source, &["other", TOKEN]);
+        let (status, headers, body) =
+            send(&router, Method::PUT, "/payments/prod/main", &[], "{}").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(headers[header::WWW_AUTHENTICATE], "Bearer");
+        assert_eq!(body["error"], "Unauthorized");
+
+        let (status, _, _) = send(
+            &router,
+            Method::DELETE,
+            "/payments/prod/main/db.pool",
+            &[("authorization", "Bearer wrong")],
+            "",
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_put_patch_and_delete() {
+        let (dir, source) = native();
+        let router = router(source, &[TOKEN]);
+
+        // Cached before the write, served fresh after it.
+        let (_, _, config) = send(&router, Method::GET, "/payments/prod", &[], "").await;
+        assert_eq!(config["propertySources"][0]["source"]["db"]["pool"], 10);
+
+        let (status, headers, body) = send(
+            &router,
+            Method::PUT,
+            "/payments/prod/main",
+            &[AUTHORIZED],
+            r#"{"db.pool": 20, "db": {"hosts": ["db-1"]}, "owner": "team-a"}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let version = body["version"].as_str().unwrap().to_string();
+        assert_eq!(headers[header::ETAG], format!("\"{}\"", version));
+        assert_eq!(body["name"], "payments");
+        assert_eq!(body["properties"]["db"]["pool"], 20);
+        assert_eq!(body["properties"]["db"]["hosts"][0], "db-1");
+        assert!(dir.path().join("payments-prod.yml").is_file());
+
+        let (_, _, config) = send(&router, Method::GET, "/payments/prod", &[], "").await;
+        assert_eq!(config["propertySources"][0]["source"]["db"]["pool"], 20);
+
+        // A stale If-Match changes nothing.
+        let (status, _, body) = send(
+            &router,
+            Method::PATCH,
+            "/payments/prod/main",
+            &[AUTHORIZED, ("if-match", "\"0000000000000000\"")],
+            r#"{"owner": null}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+        assert_eq!(body["error"], "Precondition Failed");
+
+        let if_match = format!("\"{}\"", version);
+        let (status, _, body) = send(
+            &router,
+            Method::PATCH,
+            "/payments/prod/main",
+            &[AUTHORIZED, ("if-match", &if_match)],
+            r#"{"owner": null, "db": {"pool": 25}}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["properties"].get("owner").is_none());
+        assert_eq!(body["properties"]["db"]["pool"], 25);
+        assert_eq!(body["properties"]["db"]["hosts"][0], "db-1");
+        let version = body["version"].as_str().unwrap().to_string();
+
+        // An empty patch reads the document.
+        let (status, headers, _) = send(
+            &router,
+            Method::PATCH,
+            "/payments/prod/main",
+            &[AUTHORIZED],
+            "{}",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::ETAG], format!("\"{}\"", version));
+
+        let (status, _, body) = send(
+            &router,
+            Method::DELETE,
+            "/payments/prod/main/db.hosts",
+            &[AUTHORIZED],
+            "",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["properties"]["db"].get("hosts").is_none());
+
+        let (status, _, _) = send(
+            &router,
+            Method::DELETE,
+            "/payments/prod/main/db.hosts",
+            &[AUTHORIZED],
+            "",
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_writes() {
+        let (_dir, source) = native();
+        let router = router(source, &[TOKEN]);
+
+        for (uri, body) in [
+            ("/payments/prod,dev/main", "{}"),
+            ("/payments/prod/main", "[1, 2]"),
+            ("/payments/prod/main", r#"{"db..pool": 1}"#),
+            ("/payments/prod/release-3.0", "{}"),
+        ] {
+            let (status, _, _) = send(&router, Method::PUT, uri, &[AUTHORIZED], body).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{} {}", uri, body);
+        }
+    }
+
+    /// Source without write support.
+    struct ReadOnly;
+
+    #[async_trait::async_trait]
+    impl ConfigSource for ReadOnly {
+        async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+            Ok(ConfigResult::new(
+                query.application(),
+                query.profiles().to_vec(),
+                "main",
+            ))
+        }
+
+        async fn health_check(&self) -> Result<(), ConfigSourceError> {
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "read-only"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_only_backend() {
+        let router = router(Arc::new(ReadOnly), &[TOKEN]);
+        let (status, _, body) = send(
+            &router,
+            Method::PUT,
+            "/payments/prod/main",
+            &[AUTHORIZED],
+            "{}",
+        )
+        .await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            body["message"],
+            "The read-only backend does not accept writes"
+        );
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let mut target = ConfigValue::Object(
+            ConfigMap::from_json(r#"{"a": "b", "c": {"d": "e", "f": "g"}}"#)
+                .unwrap()
+                .as_inner()
+                .clone(),
+        );
+        let patch = ConfigValue::Object(
+            ConfigMap::from_json(r#"{"a": "z", "c": {"f": null}, "h": [1]}"#)
+                .unwrap()
+                .as_inner()
+                .clone(),
+        );
+        merge_patch(&mut target, &patch);
+        let expected = ConfigMap::from_json(r#"{"a": "z", "c": {"d": "e"}, "h": [1]}"#).unwrap();
+        assert_eq!(target, ConfigValue::Object(expected.as_inner().clone()));
+    }
+}
@@ -1,12 +1,16 @@
+pub mod backends;
 pub mod cache;
+pub mod crypto;
 pub mod error;
 pub mod extractors;
 pub mod handlers;
 pub mod metrics;
 pub mod middleware;
 pub mod response;
+pub mod secrets;
 pub mod server;
 pub mod state;
+pub mod validation;
 
 pub use cache::{CacheConfig, CacheError, CacheKey, ConfigCache};
 pub use handlers::health::HealthResponse;
@@ -15,3 +19,4 @@ pub use metrics::CacheMetrics;
 pub use middleware::{LoggingLayer, REQUEST_ID_HEADER, RequestIdLayer};
 pub use server::{create_router, create_router_with_state, run_server, run_server_with_state};
 pub use state::AppState;
+pub use validation::{ValidationMode, ValidationPolicy};
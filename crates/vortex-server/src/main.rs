@@ -1,12 +1,21 @@
 //! Vortex Config Server binary.
 
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::sync::Arc;
 
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
-use vortex_git::{GitBackend, GitBackendConfig};
-use vortex_server::metrics::{cache, http, init_metrics};
-use vortex_server::{AppState, CacheConfig, ConfigCache, run_server_with_state};
+use vortex_git::vortex_core::format::FormatRegistry;
+use vortex_git::vortex_core::format::yaml::TaggedYamlFormat;
+use vortex_git::{ConfigSource, DuplicateKeyPolicy};
+use vortex_server::backends::{
+    BackendContext, BackendSettings, OverrideSettings, ServerConfigFile,
+};
+use vortex_server::crypto::KeyRing;
+use vortex_server::metrics::{cache, http, init_metrics, validation};
+use vortex_server::secrets::resolver_from_env;
+use vortex_server::{
+    AppState, CacheConfig, ConfigCache, ValidationMode, ValidationPolicy, run_server_with_state,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -27,60 +36,98 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .parse()
         .expect("Invalid address");
 
-    // Get Git configuration from environment
-    let git_uri = std::env::var("GIT_URI").expect("GIT_URI environment variable is required");
-    let git_local_path =
-        std::env::var("GIT_LOCAL_PATH").unwrap_or_else(|_| "/var/lib/vortex/repos".to_string());
-    let git_default_label =
-        std::env::var("GIT_DEFAULT_LABEL").unwrap_or_else(|_| "main".to_string());
-
-    // Build Git backend configuration
-    let mut config_builder = GitBackendConfig::builder()
-        .uri(&git_uri)
-        .local_path(PathBuf::from(&git_local_path))
-        .default_label(&git_default_label);
-
-    // Add search paths if configured
-    if let Ok(search_paths) = std::env::var("GIT_SEARCH_PATHS") {
-        let paths: Vec<String> = search_paths
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .collect();
-        config_builder = config_builder.search_paths(paths);
-    }
-
-    // Add authentication if configured
-    if let (Ok(username), Ok(password)) =
-        (std::env::var("GIT_USERNAME"), std::env::var("GIT_PASSWORD"))
-    {
-        config_builder = config_builder.basic_auth(username, password);
-    }
-
-    let git_config = config_builder
-        .build()
-        .expect("Failed to build Git configuration");
-
     tracing::info!(
         "Starting Vortex Config Server v{}",
         env!("CARGO_PKG_VERSION")
     );
-    tracing::info!("Git repository: {}", git_uri);
-    tracing::info!("Local path: {}", git_local_path);
-    tracing::info!("Default label: {}", git_default_label);
 
-    // Initialize Git backend (clones repository if needed)
-    tracing::info!("Initializing Git backend...");
-    let backend = GitBackend::new(git_config)
-        .await
-        .expect("Failed to initialize Git backend");
+    // Configure YAML tag handlers (custom tags are rejected by default)
+    let mut formats = FormatRegistry::builtin();
+    let yaml_tags: Vec<String> = std::env::var("VORTEX_YAML_TAGS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if !yaml_tags.is_empty() {
+        let yaml = TaggedYamlFormat::builtin(&yaml_tags)
+            .expect("VORTEX_YAML_TAGS must be a list of built-in tag handlers (env)");
+        let entry = formats
+            .by_name("yaml")
+            .cloned()
+            .expect("YAML is a built-in format")
+            .parser(yaml);
+        formats.register(entry);
+        tracing::info!("YAML tag handlers: {}", yaml_tags.join(", "));
+    }
+    let formats = Arc::new(formats);
+
+    // Configure duplicate key handling
+    let duplicate_keys = std::env::var("VORTEX_DUPLICATE_KEYS")
+        .ok()
+        .map(|s| s.parse::<DuplicateKeyPolicy>())
+        .transpose()
+        .expect("VORTEX_DUPLICATE_KEYS must be ignore, warn or error")
+        .unwrap_or_default();
 
-    tracing::info!("Git backend initialized successfully");
+    // Configure decryption of SOPS-encrypted files
+    let sops_age_key_file = std::env::var("SOPS_AGE_KEY_FILE").ok();
+    if let Some(path) = &sops_age_key_file {
+        tracing::info!("SOPS files are decrypted with the age identity in {}", path);
+    }
+
+    // Initialize the configuration backend
+    let context = BackendContext {
+        formats: Arc::clone(&formats),
+        duplicate_keys,
+        sops_age_key_file,
+    };
+    let backend_kind = std::env::var("VORTEX_BACKEND").unwrap_or_else(|_| "git".to_string());
+    let (config_source, mut changes): (Arc<dyn ConfigSource>, Vec<_>) =
+        if backend_kind == "composite" {
+            let path = std::env::var("VORTEX_CONFIG_FILE")
+                .expect("VORTEX_CONFIG_FILE is required with VORTEX_BACKEND=composite");
+            let settings = ServerConfigFile::from_file(&path)
+                .expect("VORTEX_CONFIG_FILE must be a valid server configuration file")
+                .composite
+                .unwrap_or_else(|| panic!("{} must declare a composite section", path));
+
+            tracing::info!("Initializing composite backend from {}...", path);
+            let (composite, changes) = settings
+                .build(&context)
+                .await
+                .expect("Failed to initialize the composite backend");
+            tracing::info!(
+                "Composite backend initialized with {} backends (fail_fast={})",
+                composite.backends().len(),
+                composite.is_fail_fast()
+            );
+            (Arc::new(composite), changes)
+        } else {
+            let settings = BackendSettings::from_env(&backend_kind)
+                .expect("Backend environment variables must be valid");
+            let backend = settings
+                .build(&backend_kind, &context)
+                .await
+                .expect("Failed to initialize the configuration backend");
+            tracing::info!("{} backend initialized successfully", backend_kind);
+            (backend.source, backend.changes.into_iter().collect())
+        };
+
+    // Put emergency overrides from VORTEX_OVERRIDE_* variables or
+    // VORTEX_OVERRIDES_DIR on top of the backend
+    let overridden = OverrideSettings::from_env()
+        .apply(config_source)
+        .expect("VORTEX_OVERRIDES_DIR must be a readable directory");
+    let config_source = overridden.source;
+    changes.extend(overridden.changes);
 
     // Initialize metrics system
     tracing::info!("Initializing metrics system...");
     let prometheus_handle = init_metrics();
     cache::register_cache_metrics();
     http::register_http_metrics();
+    validation::register_validation_metrics();
     tracing::info!("Metrics system initialized");
 
     // Configure cache
@@ -95,19 +142,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .and_then(|s| s.parse::<u64>().ok())
             .unwrap_or(300);
 
+        let partial_ttl_seconds = std::env::var("VORTEX_CACHE_PARTIAL_TTL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(10);
+
         let max_capacity = std::env::var("VORTEX_CACHE_MAX_CAPACITY")
             .ok()
             .and_then(|s| s.parse::<u64>().ok())
             .unwrap_or(10_000);
 
         tracing::info!(
-            "Cache enabled: TTL={}s, max_capacity={}",
+            "Cache enabled: TTL={}s, partial TTL={}s, max_capacity={}",
             ttl_seconds,
+            partial_ttl_seconds,
             max_capacity
         );
 
         Some(ConfigCache::new(CacheConfig {
             ttl_seconds,
+            partial_ttl_seconds,
             max_capacity,
             tti_seconds: None,
         }))
@@ -116,8 +170,103 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         None
     };
 
+    // Configure schema validation
+    let validation_mode = std::env::var("VORTEX_VALIDATION_MODE")
+        .ok()
+        .map(|s| s.parse::<ValidationMode>())
+        .transpose()
+        .expect("VORTEX_VALIDATION_MODE must be off, annotate or reject")
+        .unwrap_or_default();
+
+    let validation_policy = ValidationPolicy::new(validation_mode)
+        .with_overrides(&std::env::var("VORTEX_VALIDATION_APPS").unwrap_or_default())
+        .expect("VORTEX_VALIDATION_APPS must be a list of app=mode pairs");
+
+    tracing::info!("Schema validation mode: {:?}", validation_mode);
+
+    // Configure keys for {cipher} values
+    let keys = KeyRing::from_env()
+        .expect("ENCRYPT_KEY, ENCRYPT_KEY_DIR and the ENCRYPT_* options must be valid");
+    if keys.is_empty() {
+        tracing::info!("Encryption disabled: no keys installed");
+    } else {
+        tracing::info!("Encryption keys: {}", keys.key_ids().join(", "));
+    }
+
+    // Configure the bearer tokens allowed to use /encrypt and /decrypt
+    let encrypt_tokens: Vec<String> = std::env::var("VORTEX_ENCRYPT_TOKENS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let decrypt_enabled = std::env::var("VORTEX_DECRYPT_ENABLED")
+        .unwrap_or_else(|_| "false".to_string())
+        .parse::<bool>()
+        .unwrap_or(false);
+    if encrypt_tokens.is_empty() {
+        tracing::info!("Encryption endpoints disabled: no encrypt tokens configured");
+    } else {
+        tracing::info!(
+            "Encryption endpoints enabled with {} tokens, decrypt {}",
+            encrypt_tokens.len(),
+            if decrypt_enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        );
+    }
+
+    // Configure providers for ${secret:...} references
+    let secrets = resolver_from_env()
+        .expect("VORTEX_SECRET_PROVIDERS must list file, env or vault, with VORTEX_SECRET_ENV_PREFIX for env and VAULT_ADDR and VAULT_TOKEN for vault");
+    if secrets.is_empty() {
+        tracing::info!("Secret references disabled: no providers configured");
+    } else {
+        tracing::info!("Secret providers: {}", secrets.provider_names().join(", "));
+    }
+
+    // Configure the bearer tokens allowed to write configuration
+    let write_tokens: Vec<String> = std::env::var("VORTEX_WRITE_TOKENS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    match (write_tokens.is_empty(), config_source.store().is_some()) {
+        (true, _) => tracing::info!("Writes disabled: no write tokens configured"),
+        (false, true) => tracing::info!("Writes enabled with {} tokens", write_tokens.len()),
+        (false, false) => tracing::warn!(
+            "VORTEX_WRITE_TOKENS is set but the {} backend does not accept writes",
+            config_source.name()
+        ),
+    }
+
+    // Changes to native files, S3 objects, Consul keys, Redis hashes, SQL
+    // rows written through the API or override files are visible right
+    // away; drop cached responses
+    if let Some(cache) = &cache {
+        for mut changes in changes {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                while changes.changed().await.is_ok() {
+                    tracing::info!("Configuration files changed, invalidating cache");
+                    cache.invalidate_all();
+                }
+            });
+        }
+    }
+
     // Create application state
-    let state = AppState::from_git_backend(backend, cache);
+    let state = AppState::new(config_source, cache)
+        .with_validation_policy(validation_policy)
+        .with_format_registry(formats)
+        .with_key_ring(keys)
+        .with_secret_resolver(secrets)
+        .with_write_tokens(write_tokens)
+        .with_encrypt_tokens(encrypt_tokens)
+        .with_decrypt_enabled(decrypt_enabled);
 
     // Run server
     run_server_with_state(addr, state, prometheus_handle).await?;
@@ -3,6 +3,7 @@
 pub mod cache;
 pub mod http;
 pub mod setup;
+pub mod validation;
 
 pub use cache::CacheMetrics;
 pub use setup::init_metrics;
@@ -0,0 +1,51 @@
+//! Validation metrics recording.
+
+use metrics::counter;
+use vortex_git::vortex_core::validation::ValidationReport;
+
+/// Registra las metricas de validacion.
+/// Llamar una vez al inicio para registrar las metricas.
+pub fn register_validation_metrics() {
+    metrics::describe_counter!(
+        "vortex_config_validations_total",
+        "Total number of schema validations of resolved configuration"
+    );
+    metrics::describe_counter!(
+        "vortex_config_validation_violations_total",
+        "Total number of schema violations found"
+    );
+}
+
+/// Registra el resultado de validar la configuracion de una aplicacion.
+pub fn record_validation(application: &str, report: &ValidationReport) {
+    let result = if report.is_valid() {
+        "valid"
+    } else {
+        "invalid"
+    };
+
+    counter!(
+        "vortex_config_validations_total",
+        "application" => application.to_string(),
+        "result" => result
+    )
+    .increment(1);
+
+    if !report.is_valid() {
+        counter!(
+            "vortex_config_validation_violations_total",
+            "application" => application.to_string()
+        )
+        .increment(report.violations().len() as u64);
+    }
+}
+
+/// Registra una validacion que no pudo ejecutarse (p.ej. schema invalido).
+pub fn record_validation_error(application: &str) {
+    counter!(
+        "vortex_config_validations_total",
+        "application" => application.to_string(),
+        "result" => "error"
+    )
+    .increment(1);
+}
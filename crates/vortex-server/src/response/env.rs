@@ -0,0 +1,70 @@
+use vortex_git::vortex_core::ConfigMap;
+use vortex_git::vortex_core::format::FormatSerializer;
+use vortex_git::vortex_core::format::env::EnvFormat;
+use vortex_git::vortex_core::merge::deep_merge;
+
+use super::property_sources;
+
+/// Renderer dotenv (`KEY=value`) para la respuesta de Spring Cloud Config.
+///
+/// A diferencia de properties, los property sources se fusionan en un unico
+/// mapa (respetando la precedencia) porque un `env_file` no admite secciones.
+pub struct EnvResponse;
+
+impl FormatSerializer for EnvResponse {
+    fn serialize(&self, document: &ConfigMap) -> vortex_git::vortex_core::Result<String> {
+        EnvFormat.serialize(&merge_sources(document))
+    }
+}
+
+/// Fusiona los property sources del documento, de menor a mayor precedencia.
+fn merge_sources(document: &ConfigMap) -> ConfigMap {
+    let mut merged = ConfigMap::new();
+
+    for (_, source) in property_sources(document).rev() {
+        let map = ConfigMap::from_inner(source.map(|(k, v)| (k.clone(), v.clone())).collect());
+        deep_merge(&mut merged, &map);
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::response::{ConfigResponse, PropertySourceResponse};
+
+    fn source(name: &str, json: serde_json::Value) -> PropertySourceResponse {
+        PropertySourceResponse {
+            name: name.to_string(),
+            source: json
+                .as_object()
+                .unwrap()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            origins: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_highest_precedence_source_wins() {
+        let mut config = ConfigResponse::empty("myapp", vec!["prod".to_string()]);
+        // First source has the highest precedence.
+        config.property_sources.push(source(
+            "myapp-prod.yml",
+            serde_json::json!({"server": {"port": 9090}}),
+        ));
+        config.property_sources.push(source(
+            "application.yml",
+            serde_json::json!({"server": {"port": 8080, "host": "0.0.0.0"}}),
+        ));
+
+        let merged = merge_sources(&config.to_document());
+        let body = EnvFormat.serialize(&merged).unwrap();
+
+        assert!(body.contains("SERVER_PORT=9090\n"));
+        assert!(body.contains("SERVER_HOST=0.0.0.0\n"));
+        assert!(!body.contains("8080"));
+    }
+}
@@ -1,15 +1,19 @@
 //! Modulo de serializacion de respuestas.
 //!
-//! Proporciona funciones para serializar ConfigResponse a diferentes formatos:
-//! - JSON (por defecto)
-//! - YAML
-//! - Properties (.properties de Java)
+//! Las respuestas se serializan con la entrada del registro de formatos
+//! negociada. JSON, YAML y TOML usan los serializadores integrados sobre el
+//! documento de la respuesta; properties y dotenv registran renderers propios
+//! de Spring Cloud Config (ver [`response_formats`]).
 
-pub mod json;
+pub mod env;
 pub mod properties;
-pub mod yaml;
 
-use axum::response::{IntoResponse, Response};
+use axum::{
+    http::{HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use vortex_git::vortex_core::format::{ConfigFormat, FormatRegistry, FormatSerializer};
+use vortex_git::vortex_core::{ConfigMap, ConfigValue};
 
 use crate::extractors::accept::OutputFormat;
 use crate::handlers::response::ConfigResponse;
@@ -18,7 +22,7 @@ use crate::handlers::response::ConfigResponse;
 #[derive(Debug)]
 pub enum SerializeError {
     Json(serde_json::Error),
-    Yaml(serde_yaml::Error),
+    Format(vortex_git::vortex_core::VortexError),
 }
 
 impl From<serde_json::Error> for SerializeError {
@@ -27,9 +31,9 @@ impl From<serde_json::Error> for SerializeError {
     }
 }
 
-impl From<serde_yaml::Error> for SerializeError {
-    fn from(err: serde_yaml::Error) -> Self {
-        SerializeError::Yaml(err)
+impl From<vortex_git::vortex_core::VortexError> for SerializeError {
+    fn from(err: vortex_git::vortex_core::VortexError) -> Self {
+        SerializeError::Format(err)
     }
 }
 
@@ -37,21 +41,287 @@ impl IntoResponse for SerializeError {
     fn into_response(self) -> Response {
         let message = match self {
             SerializeError::Json(e) => format!("JSON serialization error: {}", e),
-            SerializeError::Yaml(e) => format!("YAML serialization error: {}", e),
+            SerializeError::Format(e) => format!("Serialization error: {}", e),
         };
 
         (axum::http::StatusCode::INTERNAL_SERVER_ERROR, message).into_response()
     }
 }
 
+/// Header con el numero de violaciones de schema de la configuracion.
+pub const CONFIG_VIOLATIONS_HEADER: &str = "x-config-violations";
+
+/// Header con el numero de advertencias de la resolucion (claves duplicadas, ...).
+pub const CONFIG_WARNINGS_HEADER: &str = "x-config-warnings";
+
+/// Registro de formatos de respuesta a partir de un registro de archivos.
+///
+/// Copia las entradas de `formats` y reemplaza los serializadores de
+/// properties y dotenv por los renderers de Spring Cloud Config, que
+/// escriben los property sources en lugar del documento completo. Las
+/// entradas que no esten en `formats` no se agregan.
+pub fn response_formats(formats: &FormatRegistry) -> FormatRegistry {
+    let mut responses = formats.clone();
+    register(
+        &mut responses,
+        ConfigFormat::Properties,
+        properties::PropertiesResponse,
+    );
+    register(&mut responses, ConfigFormat::Env, env::EnvResponse);
+    responses
+}
+
+fn register(
+    formats: &mut FormatRegistry,
+    format: ConfigFormat,
+    renderer: impl FormatSerializer + 'static,
+) {
+    if let Some(entry) = formats.by_name(format.name()).cloned() {
+        formats.register(entry.serializer(renderer));
+    }
+}
+
 /// Convierte ConfigResponse al formato especificado.
+///
+/// El cuerpo lo produce el serializador de la entrada del formato (ver
+/// [`to_body`]). La respuesta incluye `Vary: Accept` porque depende de la
+/// negociacion, y `X-Config-Violations` si la configuracion fue validada
+/// contra su schema, y `X-Config-Warnings` si hubo advertencias, para que los
+/// formatos sin campos `validation`/`warnings` tambien lo reflejen.
 pub fn to_format(
     config: &ConfigResponse,
-    format: OutputFormat,
+    format: &OutputFormat,
 ) -> Result<Response, SerializeError> {
-    match format {
-        OutputFormat::Json => json::to_response(config),
-        OutputFormat::Yaml => yaml::to_response(config),
-        OutputFormat::Properties => properties::to_response(config),
+    let body = to_body(config, format)?;
+    let mut response = (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, format.content_type())],
+        body,
+    )
+        .into_response();
+
+    response
+        .headers_mut()
+        .insert(header::VARY, HeaderValue::from_static("accept"));
+    if let Some(report) = &config.validation {
+        response.headers_mut().insert(
+            CONFIG_VIOLATIONS_HEADER,
+            HeaderValue::from(report.violations().len()),
+        );
+    }
+    if !config.warnings.is_empty() {
+        response.headers_mut().insert(
+            CONFIG_WARNINGS_HEADER,
+            HeaderValue::from(config.warnings.len()),
+        );
+    }
+    Ok(response)
+}
+
+/// Serializa ConfigResponse al formato especificado, sin headers.
+///
+/// El serializador de la entrada recibe la respuesta como documento (el
+/// mismo arbol que el JSON de Spring Cloud Config, ver
+/// [`ConfigResponse::to_document`]). Produce exactamente el
+/// cuerpo que `to_format` envia, para herramientas que necesitan la misma
+/// salida fuera del servidor (p.ej. `vortex render`).
+pub fn to_body(config: &ConfigResponse, format: &OutputFormat) -> Result<String, SerializeError> {
+    Ok(format.entry().serialize(&config.to_document())?)
+}
+
+/// Nombre y propiedades de cada property source del documento de una
+/// respuesta, en orden de precedencia.
+fn property_sources(
+    document: &ConfigMap,
+) -> impl DoubleEndedIterator<Item = (&str, impl Iterator<Item = (&String, &ConfigValue)>)> {
+    document
+        .get("propertySources")
+        .and_then(ConfigValue::as_array)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|ps| {
+            let ps = ps.as_object()?;
+            let name = ps
+                .get("name")
+                .and_then(ConfigValue::as_str)
+                .unwrap_or_default();
+            let source = ps.get("source").and_then(ConfigValue::as_object)?;
+            Some((name, source.iter()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::response::PropertySourceResponse;
+    use vortex_git::vortex_core::format::FormatEntry;
+
+    fn response() -> ConfigResponse {
+        let mut config = ConfigResponse::empty("myapp", vec!["prod".to_string()]);
+        config.property_sources.push(PropertySourceResponse {
+            name: "git:main:myapp-prod.yml".to_string(),
+            source: [
+                ("port".to_string(), serde_json::json!(8080)),
+                ("host".to_string(), serde_json::json!("localhost")),
+                ("missing".to_string(), serde_json::Value::Null),
+            ]
+            .into_iter()
+            .collect(),
+            origins: Default::default(),
+        });
+        config
+    }
+
+    fn format(name: &str) -> OutputFormat {
+        OutputFormat::from_param(name, &response_formats(&FormatRegistry::builtin())).unwrap()
+    }
+
+    struct KeyListFormat;
+
+    impl FormatSerializer for KeyListFormat {
+        fn serialize(&self, config: &ConfigMap) -> vortex_git::vortex_core::Result<String> {
+            Ok(config
+                .as_inner()
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n"))
+        }
+    }
+
+    #[test]
+    fn test_custom_format_uses_registered_serializer() {
+        let format = OutputFormat::new(
+            FormatEntry::new("keys")
+                .media_types(["text/x-keys"])
+                .serializer(KeyListFormat),
+        );
+        let config = ConfigResponse::empty("myapp", vec!["prod".to_string()]);
+
+        let response = to_format(&config, &format).unwrap();
+
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/x-keys; charset=utf-8"
+        );
+        assert_eq!(response.headers()[header::VARY], "accept");
+    }
+
+    #[test]
+    fn test_json_body_is_the_spring_document() {
+        let config = response();
+
+        let body = to_body(&config, &format("json")).unwrap();
+
+        assert_eq!(body, serde_json::to_string_pretty(&config).unwrap());
+    }
+
+    #[test]
+    fn test_document_matches_the_json_response() {
+        use vortex_git::ConfigWarning;
+        use vortex_git::vortex_core::validation::SchemaValidator;
+
+        let mut config = response();
+        config.label = Some("main".to_string());
+        config.property_sources[0]
+            .origins
+            .insert("port".to_string(), "shared/server.yml".to_string());
+        config
+            .warnings
+            .push(ConfigWarning::new("myapp.yml", "duplicate key 'port'").at_line(3));
+        let schema = SchemaValidator::from_json(r#"{"required": ["server"]}"#).unwrap();
+        config.validation = Some(schema.validate(&ConfigMap::new()));
+
+        let document = config.to_document();
+
+        assert_eq!(
+            document.to_json().unwrap(),
+            serde_json::to_string_pretty(&config).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_values_keep_their_type() {
+        let mut config = ConfigResponse::empty("myapp", vec!["prod".to_string()]);
+        config.property_sources.push(PropertySourceResponse {
+            name: "git:main:myapp.yml".to_string(),
+            source: [
+                ("zip".to_string(), serde_json::json!("007")),
+                ("flag".to_string(), serde_json::json!("true")),
+                ("ratio".to_string(), serde_json::json!(1.0)),
+            ]
+            .into_iter()
+            .collect(),
+            origins: Default::default(),
+        });
+
+        let yaml = to_body(&config, &format("yaml")).unwrap();
+        assert!(yaml.contains("zip: '007'"), "{}", yaml);
+        assert!(yaml.contains("flag: 'true'"), "{}", yaml);
+        assert!(yaml.contains("ratio: 1.0"), "{}", yaml);
+
+        let properties = to_body(&config, &format("properties")).unwrap();
+        assert!(properties.contains("zip=007\n"));
+        assert!(properties.contains("ratio=1.0\n"));
+
+        let env = to_body(&config, &format("env")).unwrap();
+        assert!(env.contains("ZIP=007\n"));
+        assert!(env.contains("FLAG=true\n"));
+    }
+
+    #[test]
+    fn test_toml_body_skips_nulls() {
+        let config = response();
+
+        let body = to_body(&config, &format("toml")).unwrap();
+
+        assert!(body.contains(r#"name = "myapp""#));
+        assert!(body.contains("[[propertySources]]"));
+        assert!(body.contains("port = 8080"));
+        assert!(!body.contains("missing"));
+        assert!(!body.contains("label"));
+
+        let response = to_format(&config, &format("toml")).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/toml");
+    }
+
+    #[test]
+    fn test_spring_renderers_are_registered_entries() {
+        let config = response();
+
+        let properties = to_body(&config, &format("properties")).unwrap();
+        assert!(properties.starts_with("# Application: myapp\n"));
+        assert!(properties.contains("port=8080\n"));
+
+        let env = to_body(&config, &format("env")).unwrap();
+        assert!(env.contains("PORT=8080\n"));
+        assert!(!env.contains("myapp"));
+
+        // El registro de archivos conserva los serializadores integrados.
+        let files = FormatRegistry::builtin();
+        let plain = OutputFormat::from_param("env", &files).unwrap();
+        assert!(to_body(&config, &plain).unwrap().contains("NAME=myapp\n"));
+    }
+
+    #[tokio::test]
+    async fn test_body_matches_response_for_builtin_formats() {
+        use http_body_util::BodyExt;
+
+        let config = response();
+
+        for name in ["json", "yaml", "properties", "toml", "env"] {
+            let format = format(name);
+
+            let response = to_format(&config, &format).unwrap();
+            let sent = response.into_body().collect().await.unwrap().to_bytes();
+
+            assert_eq!(
+                sent,
+                to_body(&config, &format).unwrap().as_bytes(),
+                "{}",
+                name
+            );
+        }
     }
 }
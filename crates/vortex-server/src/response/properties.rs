@@ -1,27 +1,49 @@
-use crate::handlers::response::ConfigResponse;
-use axum::{
-    http::{StatusCode, header},
-    response::{IntoResponse, Response},
-};
+use vortex_git::vortex_core::format::FormatSerializer;
+use vortex_git::vortex_core::{ConfigMap, ConfigValue};
 
-/// Convierte ConfigResponse a formato .properties de Java.
-pub fn to_response(config: &ConfigResponse) -> Result<Response, super::SerializeError> {
+use super::property_sources;
+
+/// Renderer `.properties` de Java para la respuesta de Spring Cloud Config.
+///
+/// Escribe cada property source como una seccion comentada, de menor a
+/// mayor precedencia.
+pub struct PropertiesResponse;
+
+impl FormatSerializer for PropertiesResponse {
+    fn serialize(&self, document: &ConfigMap) -> vortex_git::vortex_core::Result<String> {
+        Ok(to_body(document))
+    }
+}
+
+/// Serializa el documento de la respuesta como .properties de Java.
+fn to_body(document: &ConfigMap) -> String {
+    let text = |key: &str| document.get(key).and_then(ConfigValue::as_str);
     let mut output = String::new();
 
     // Agregar comentario con metadata
-    output.push_str(&format!("# Application: {}\n", config.name));
-    output.push_str(&format!("# Profiles: {}\n", config.profiles.join(",")));
-    if let Some(ref label) = config.label {
+    let profiles = document
+        .get("profiles")
+        .and_then(ConfigValue::as_array)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(ConfigValue::as_str)
+        .collect::<Vec<_>>();
+    output.push_str(&format!(
+        "# Application: {}\n",
+        text("name").unwrap_or_default()
+    ));
+    output.push_str(&format!("# Profiles: {}\n", profiles.join(",")));
+    if let Some(label) = text("label") {
         output.push_str(&format!("# Label: {}\n", label));
     }
     output.push('\n');
 
     // Iterar property sources (en orden inverso para precedencia correcta)
-    for ps in config.property_sources.iter().rev() {
-        output.push_str(&format!("# Source: {}\n", ps.name));
+    for (name, source) in property_sources(document).rev() {
+        output.push_str(&format!("# Source: {}\n", name));
 
-        for (key, value) in &ps.source {
-            let value_str = json_value_to_properties_string(value);
+        for (key, value) in source {
+            let value_str = to_properties_string(value);
             // Escapar caracteres especiales en key
             let escaped_key = escape_properties_key(key);
             output.push_str(&format!("{}={}\n", escaped_key, value_str));
@@ -29,32 +51,24 @@ pub fn to_response(config: &ConfigResponse) -> Result<Response, super::Serialize
         output.push('\n');
     }
 
-    Ok((
-        StatusCode::OK,
-        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
-        output,
-    )
-        .into_response())
+    output
 }
 
-/// Convierte un JSON value a string para .properties.
-fn json_value_to_properties_string(value: &serde_json::Value) -> String {
+/// Convierte un valor de configuracion a string para .properties.
+fn to_properties_string(value: &ConfigValue) -> String {
     match value {
-        serde_json::Value::Null => String::new(),
-        serde_json::Value::Bool(b) => b.to_string(),
-        serde_json::Value::Number(n) => n.to_string(),
-        serde_json::Value::String(s) => escape_properties_value(s),
-        serde_json::Value::Array(arr) => {
+        ConfigValue::Null => String::new(),
+        ConfigValue::String(s) | ConfigValue::DateTime(s) => escape_properties_value(s),
+        ConfigValue::Array(arr) => {
             // Arrays como lista separada por comas
             arr.iter()
-                .map(json_value_to_properties_string)
+                .map(to_properties_string)
                 .collect::<Vec<_>>()
                 .join(",")
         },
-        serde_json::Value::Object(_) => {
-            // Objetos como JSON inline (no ideal, pero funcional)
-            value.to_string()
-        },
+        // Numeros y booleanos con su forma JSON; objetos como JSON inline (no
+        // ideal, pero funcional)
+        _ => serde_json::to_string(value).unwrap_or_default(),
     }
 }
 
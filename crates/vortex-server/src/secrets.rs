@@ -0,0 +1,270 @@
+//! Resolucion de referencias `${secret:...}` al servir la configuracion.
+//!
+//! Las referencias se resuelven sobre la respuesta ya obtenida (y cacheada),
+//! justo antes de enviarla: el [`ConfigCache`](crate::cache::ConfigCache)
+//! solo guarda las referencias y los secretos tienen su propia cache, con su
+//! propio TTL, en el [`SecretResolver`].
+//!
+//! Los proveedores se habilitan explicitamente; sin proveedores las
+//! referencias se sirven tal cual.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+use vortex_git::ConfigWarning;
+use vortex_sources::secrets::{
+    DEFAULT_SECRETS_DIR, EnvSecretProvider, FileSecretProvider, SecretError, SecretRef,
+    SecretResolver, VaultSecretProvider,
+};
+
+use crate::crypto::INVALID_VALUE;
+use crate::handlers::response::ConfigResponse;
+
+/// Construye el resolver a partir de variables de entorno.
+///
+/// - `VORTEX_SECRET_PROVIDERS`: proveedores habilitados (`file,env,vault`).
+/// - `VORTEX_SECRET_FILE_ROOT`: directorio de `file` (por defecto `/run/secrets`).
+/// - `VORTEX_SECRET_ENV_PREFIX`: prefijo obligatorio para `env`.
+/// - `VAULT_ADDR`, `VAULT_TOKEN`, `VAULT_NAMESPACE`: servidor de `vault`.
+/// - `VORTEX_SECRET_CACHE_TTL_SECONDS`: TTL de la cache de secretos.
+pub fn resolver_from_env() -> Result<SecretResolver, SecretError> {
+    let mut resolver = SecretResolver::new();
+
+    let providers = std::env::var("VORTEX_SECRET_PROVIDERS").unwrap_or_default();
+    for name in providers
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        resolver = match name {
+            "file" => {
+                let root = std::env::var("VORTEX_SECRET_FILE_ROOT")
+                    .unwrap_or_else(|_| DEFAULT_SECRETS_DIR.to_string());
+                resolver.with_provider(FileSecretProvider::new(PathBuf::from(root)))
+            },
+            "env" => {
+                // Sin prefijo el proveedor expondria todas las variables del
+                // servidor, incluidas sus credenciales.
+                let prefix = std::env::var("VORTEX_SECRET_ENV_PREFIX").map_err(|_| {
+                    SecretError::Provider {
+                        provider: "env".to_string(),
+                        message: "VORTEX_SECRET_ENV_PREFIX is required".to_string(),
+                    }
+                })?;
+                resolver.with_provider(EnvSecretProvider::new(prefix)?)
+            },
+            "vault" => {
+                let missing = |var: &str| SecretError::Provider {
+                    provider: "vault".to_string(),
+                    message: format!("{} is required", var),
+                };
+                let address = std::env::var("VAULT_ADDR").map_err(|_| missing("VAULT_ADDR"))?;
+                let token = std::env::var("VAULT_TOKEN").map_err(|_| missing("VAULT_TOKEN"))?;
+                let mut vault = VaultSecretProvider::new(address, token)?;
+                if let Ok(namespace) = std::env::var("VAULT_NAMESPACE") {
+                    vault = vault.with_namespace(namespace);
+                }
+                resolver.with_provider(vault)
+            },
+            other => return Err(SecretError::UnknownProvider(other.to_string())),
+        };
+    }
+
+    if let Some(ttl) = std::env::var("VORTEX_SECRET_CACHE_TTL_SECONDS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+    {
+        resolver = resolver.with_cache_ttl(Duration::from_secs(ttl));
+    }
+
+    Ok(resolver)
+}
+
+/// Resuelve las referencias `${secret:...}` de una respuesta.
+///
+/// Retorna la misma respuesta si no hay nada que resolver. Un secreto que no
+/// se puede resolver queda como `<n/a>` y se reporta como advertencia; los
+/// logs y advertencias solo mencionan la referencia, nunca el valor.
+pub async fn resolve_secrets(
+    resolver: &SecretResolver,
+    response: Arc<ConfigResponse>,
+) -> Arc<ConfigResponse> {
+    if resolver.is_empty() {
+        return response;
+    }
+
+    // Primero se juntan los valores distintos con referencias, para
+    // resolverlos sin tener la respuesta prestada entre awaits.
+    let mut values = Vec::new();
+    for ps in &response.property_sources {
+        for value in ps.source.values() {
+            collect_references(value, &mut values);
+        }
+    }
+    if values.is_empty() {
+        return response;
+    }
+
+    let mut resolved: HashMap<String, Result<String, SecretError>> = HashMap::new();
+    for value in values {
+        if resolved.contains_key(&value) {
+            continue;
+        }
+        let result = resolver
+            .resolve_value(&value)
+            .await
+            .map(|v| v.unwrap_or_default());
+        resolved.insert(value, result);
+    }
+
+    let mut response = Arc::unwrap_or_clone(response);
+    let mut warnings = Vec::new();
+    for ps in &mut response.property_sources {
+        for (key, value) in &mut ps.source {
+            replace_references(value, key, &resolved, &ps.name, &mut warnings);
+        }
+    }
+    for warning in &warnings {
+        tracing::warn!(source = %warning.source, "{}", warning.message);
+    }
+    response.warnings.extend(warnings);
+
+    Arc::new(response)
+}
+
+fn collect_references(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::String(s) if !SecretRef::find_all(s).is_empty() => out.push(s.clone()),
+        Value::Array(items) => items.iter().for_each(|v| collect_references(v, out)),
+        Value::Object(map) => map.values().for_each(|v| collect_references(v, out)),
+        _ => {},
+    }
+}
+
+fn replace_references(
+    value: &mut Value,
+    path: &str,
+    resolved: &HashMap<String, Result<String, SecretError>>,
+    source: &str,
+    warnings: &mut Vec<ConfigWarning>,
+) {
+    match value {
+        Value::String(s) => match resolved.get(s.as_str()) {
+            Some(Ok(secret)) => *s = secret.clone(),
+            Some(Err(e)) => {
+                warnings.push(ConfigWarning::new(
+                    source,
+                    format!("cannot resolve secret for '{}': {}", path, e),
+                ));
+                *s = INVALID_VALUE.to_string();
+            },
+            None => {},
+        },
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                replace_references(
+                    item,
+                    &format!("{}[{}]", path, i),
+                    resolved,
+                    source,
+                    warnings,
+                );
+            }
+        },
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                replace_references(
+                    item,
+                    &format!("{}.{}", path, key),
+                    resolved,
+                    source,
+                    warnings,
+                );
+            }
+        },
+        _ => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use vortex_sources::secrets::EnvSecretProvider;
+
+    use crate::handlers::response::PropertySourceResponse;
+
+    fn response(source: Value) -> Arc<ConfigResponse> {
+        let mut response = ConfigResponse::empty("payments", vec!["prod".to_string()]);
+        response.property_sources.push(PropertySourceResponse {
+            name: "payments-prod.yml".to_string(),
+            source: serde_json::from_value(source).unwrap(),
+            origins: Default::default(),
+        });
+        Arc::new(response)
+    }
+
+    #[tokio::test]
+    async fn test_resolves_nested_references() {
+        // SAFETY: the variable name is unique to this test.
+        unsafe { std::env::set_var("VORTEX_SECRETS_TEST_PW", "hunter2") };
+        let resolver = SecretResolver::new()
+            .with_provider(EnvSecretProvider::new("VORTEX_SECRETS_TEST_").unwrap());
+
+        let resolved = resolve_secrets(
+            &resolver,
+            response(serde_json::json!({
+                "db": {
+                    "password": "${secret:env:VORTEX_SECRETS_TEST_PW}",
+                    "url": "jdbc://${db.host}/payments",
+                },
+                "hosts": ["a:${secret:env:VORTEX_SECRETS_TEST_PW}"],
+            })),
+        )
+        .await;
+
+        let source = &resolved.property_sources[0].source;
+        assert_eq!(source["db"]["password"], "hunter2");
+        assert_eq!(source["db"]["url"], "jdbc://${db.host}/payments");
+        assert_eq!(source["hosts"][0], "a:hunter2");
+        assert!(resolved.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_secrets_become_warnings() {
+        let resolver = SecretResolver::new()
+            .with_provider(EnvSecretProvider::new("VORTEX_SECRETS_TEST_").unwrap());
+
+        let resolved = resolve_secrets(
+            &resolver,
+            response(serde_json::json!({
+                "db": {"password": "${secret:vault:kv/payments#db_password}"},
+            })),
+        )
+        .await;
+
+        assert_eq!(
+            resolved.property_sources[0].source["db"]["password"],
+            INVALID_VALUE
+        );
+        assert_eq!(resolved.warnings.len(), 1);
+        assert!(resolved.warnings[0].message.contains("'db.password'"));
+        assert!(
+            resolved.warnings[0]
+                .message
+                .contains("unknown secret provider 'vault'")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_without_providers_references_are_served_as_is() {
+        let original = response(serde_json::json!({"pw": "${secret:env:HOME}"}));
+
+        let served = resolve_secrets(&SecretResolver::new(), Arc::clone(&original)).await;
+
+        assert!(Arc::ptr_eq(&original, &served));
+    }
+}
@@ -2,19 +2,22 @@ use std::net::SocketAddr;
 
 use axum::{
     Router, middleware,
-    routing::{delete, get},
+    routing::{delete, get, post},
 };
 use metrics_exporter_prometheus::PrometheusHandle;
 use tower::ServiceBuilder;
 
 use crate::handlers::{
     config::{get_config, get_config_with_label},
-    health::health_check,
+    diff::get_diff,
+    encrypt::{decrypt, encrypt, encrypt_status},
+    health::{health_check, sources_health},
     invalidate::{
         invalidate_all, invalidate_by_app, invalidate_by_app_profile,
         invalidate_by_app_profile_label,
     },
     metrics::metrics_handler,
+    store::{delete_key, patch_properties, put_properties},
 };
 use crate::middleware::{LoggingLayer, RequestIdLayer};
 use crate::state::AppState;
@@ -33,15 +36,29 @@ pub fn create_router_with_state(state: AppState, prometheus_handle: PrometheusHa
     // Main application router
     let app_router = Router::new()
         .route("/health", get(health_check))
+        .route("/health/sources", get(sources_health))
+        // Diff routes
+        .route("/diff/{app}/{profile}", get(get_diff))
+        // Encryption routes
+        .route("/encrypt", post(encrypt))
+        .route("/encrypt/status", get(encrypt_status))
+        .route("/decrypt", post(decrypt))
         // Config routes
-        .route("/:app/:profile/:label", get(get_config_with_label))
-        .route("/:app/:profile", get(get_config))
+        .route(
+            "/{app}/{profile}/{label}",
+            get(get_config_with_label)
+                .put(put_properties)
+                .patch(patch_properties),
+        )
+        .route("/{app}/{profile}", get(get_config))
+        // Write routes
+        .route("/{app}/{profile}/{label}/{key}", delete(delete_key))
         // Cache invalidation routes
         .route("/cache", delete(invalidate_all))
-        .route("/cache/:app", delete(invalidate_by_app))
-        .route("/cache/:app/:profile", delete(invalidate_by_app_profile))
+        .route("/cache/{app}", delete(invalidate_by_app))
+        .route("/cache/{app}/{profile}", delete(invalidate_by_app_profile))
         .route(
-            "/cache/:app/:profile/:label",
+            "/cache/{app}/{profile}/{label}",
             delete(invalidate_by_app_profile_label),
         )
         .with_state(state);
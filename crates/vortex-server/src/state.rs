@@ -2,9 +2,15 @@
 
 use std::sync::Arc;
 
+use vortex_git::vortex_core::format::FormatRegistry;
 use vortex_git::{ConfigSource, GitBackend};
+use vortex_sources::CompositeConfigSource;
+use vortex_sources::secrets::SecretResolver;
 
 use crate::cache::ConfigCache;
+use crate::crypto::KeyRing;
+use crate::response::response_formats;
+use crate::validation::ValidationPolicy;
 
 /// Application state shared across all handlers.
 #[derive(Clone)]
@@ -13,6 +19,21 @@ pub struct AppState {
     config_source: Arc<dyn ConfigSource>,
     /// Cache layer for configurations.
     cache: Option<ConfigCache>,
+    /// Formats available for content negotiation.
+    formats: Arc<FormatRegistry>,
+    /// Schema validation mode per application.
+    validation: Arc<ValidationPolicy>,
+    /// Keys for `{cipher}` values and the encryption endpoints.
+    encryption: Arc<KeyRing>,
+    /// Providers for `${secret:...}` references, with their own cache.
+    secrets: Arc<SecretResolver>,
+    /// Bearer tokens allowed to write configuration; none disables writes.
+    write_tokens: Arc<Vec<String>>,
+    /// Bearer tokens allowed to use `/encrypt` and `/decrypt`; none
+    /// disables them.
+    encrypt_tokens: Arc<Vec<String>>,
+    /// Whether `POST /decrypt` is served.
+    decrypt_enabled: bool,
 }
 
 impl AppState {
@@ -21,6 +42,13 @@ impl AppState {
         Self {
             config_source,
             cache,
+            formats: Arc::new(response_formats(&FormatRegistry::builtin())),
+            validation: Arc::new(ValidationPolicy::default()),
+            encryption: Arc::new(KeyRing::default()),
+            secrets: Arc::new(SecretResolver::default()),
+            write_tokens: Arc::new(Vec::new()),
+            encrypt_tokens: Arc::new(Vec::new()),
+            decrypt_enabled: false,
         }
     }
 
@@ -29,17 +57,81 @@ impl AppState {
         Self {
             config_source: Arc::new(backend),
             cache,
+            formats: Arc::new(response_formats(&FormatRegistry::builtin())),
+            validation: Arc::new(ValidationPolicy::default()),
+            encryption: Arc::new(KeyRing::default()),
+            secrets: Arc::new(SecretResolver::default()),
+            write_tokens: Arc::new(Vec::new()),
+            encrypt_tokens: Arc::new(Vec::new()),
+            decrypt_enabled: false,
         }
     }
 
+    /// Creates an AppState serving several backends combined by priority.
+    pub fn from_backends(backends: CompositeConfigSource, cache: Option<ConfigCache>) -> Self {
+        Self::new(Arc::new(backends), cache)
+    }
+
     /// Creates an AppState without cache (for testing).
     pub fn without_cache(config_source: Arc<dyn ConfigSource>) -> Self {
         Self {
             config_source,
             cache: None,
+            formats: Arc::new(response_formats(&FormatRegistry::builtin())),
+            validation: Arc::new(ValidationPolicy::default()),
+            encryption: Arc::new(KeyRing::default()),
+            secrets: Arc::new(SecretResolver::default()),
+            write_tokens: Arc::new(Vec::new()),
+            encrypt_tokens: Arc::new(Vec::new()),
+            decrypt_enabled: false,
         }
     }
 
+    /// Replaces the format registry used for content negotiation.
+    ///
+    /// The Spring Cloud Config renderers are registered on top of it (see
+    /// [`response_formats`]).
+    pub fn with_format_registry(mut self, formats: Arc<FormatRegistry>) -> Self {
+        self.formats = Arc::new(response_formats(&formats));
+        self
+    }
+
+    /// Replaces the schema validation policy.
+    pub fn with_validation_policy(mut self, policy: ValidationPolicy) -> Self {
+        self.validation = Arc::new(policy);
+        self
+    }
+
+    /// Replaces the keys used to decrypt `{cipher}` values.
+    pub fn with_key_ring(mut self, keys: KeyRing) -> Self {
+        self.encryption = Arc::new(keys);
+        self
+    }
+
+    /// Replaces the providers used to resolve `${secret:...}` references.
+    pub fn with_secret_resolver(mut self, secrets: SecretResolver) -> Self {
+        self.secrets = Arc::new(secrets);
+        self
+    }
+
+    /// Allows writes to configuration with any of these bearer tokens.
+    pub fn with_write_tokens(mut self, tokens: Vec<String>) -> Self {
+        self.write_tokens = Arc::new(tokens);
+        self
+    }
+
+    /// Allows `/encrypt` and `/decrypt` with any of these bearer tokens.
+    pub fn with_encrypt_tokens(mut self, tokens: Vec<String>) -> Self {
+        self.encrypt_tokens = Arc::new(tokens);
+        self
+    }
+
+    /// Serves `POST /decrypt` (disabled by default).
+    pub fn with_decrypt_enabled(mut self, enabled: bool) -> Self {
+        self.decrypt_enabled = enabled;
+        self
+    }
+
     /// Returns a reference to the config source.
     pub fn config_source(&self) -> &dyn ConfigSource {
         self.config_source.as_ref()
@@ -54,4 +146,39 @@ impl AppState {
     pub fn is_cache_enabled(&self) -> bool {
         self.cache.is_some()
     }
+
+    /// Returns the format registry used for content negotiation.
+    pub fn formats(&self) -> &FormatRegistry {
+        self.formats.as_ref()
+    }
+
+    /// Returns the schema validation policy.
+    pub fn validation_policy(&self) -> &ValidationPolicy {
+        self.validation.as_ref()
+    }
+
+    /// Returns the keys used to decrypt `{cipher}` values.
+    pub fn key_ring(&self) -> &KeyRing {
+        self.encryption.as_ref()
+    }
+
+    /// Returns the providers used to resolve `${secret:...}` references.
+    pub fn secret_resolver(&self) -> &SecretResolver {
+        self.secrets.as_ref()
+    }
+
+    /// Returns the bearer tokens allowed to write configuration.
+    pub fn write_tokens(&self) -> &[String] {
+        self.write_tokens.as_slice()
+    }
+
+    /// Returns the bearer tokens allowed to use `/encrypt` and `/decrypt`.
+    pub fn encrypt_tokens(&self) -> &[String] {
+        self.encrypt_tokens.as_slice()
+    }
+
+    /// Returns whether `POST /decrypt` is served.
+    pub fn is_decrypt_enabled(&self) -> bool {
+        self.decrypt_enabled
+    }
 }
@@ -0,0 +1,113 @@
+//! Politica de validacion de configuracion contra JSON Schema.
+//!
+//! Cada aplicacion puede tener un schema en el repositorio de configuracion
+//! (`schemas/{application}.schema.json`). Cuando existe, la configuracion
+//! mergeada se valida tras resolverla y, segun el modo de la aplicacion:
+//!
+//! - `off`: no se valida.
+//! - `annotate`: se responde normalmente, agregando el reporte de validacion.
+//! - `reject`: una configuracion invalida responde `422 Unprocessable Entity`.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Que hacer con una configuracion que no cumple su schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationMode {
+    /// No validar.
+    Off,
+    /// Validar y anotar la respuesta con las violaciones.
+    #[default]
+    Annotate,
+    /// Validar y rechazar configuraciones invalidas con 422.
+    Reject,
+}
+
+impl FromStr for ValidationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" | "none" | "disabled" => Ok(Self::Off),
+            "annotate" | "warn" => Ok(Self::Annotate),
+            "reject" | "enforce" => Ok(Self::Reject),
+            other => Err(format!(
+                "invalid validation mode '{}': expected off, annotate or reject",
+                other
+            )),
+        }
+    }
+}
+
+/// Modo de validacion por defecto y excepciones por aplicacion.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationPolicy {
+    default_mode: ValidationMode,
+    apps: HashMap<String, ValidationMode>,
+}
+
+impl ValidationPolicy {
+    /// Crea una politica con el modo por defecto dado.
+    pub fn new(default_mode: ValidationMode) -> Self {
+        Self {
+            default_mode,
+            apps: HashMap::new(),
+        }
+    }
+
+    /// Define el modo de una aplicacion.
+    pub fn with_app(mut self, app: impl Into<String>, mode: ValidationMode) -> Self {
+        self.apps.insert(app.into(), mode);
+        self
+    }
+
+    /// Agrega excepciones en formato `app=modo,app2=modo`.
+    pub fn with_overrides(mut self, overrides: &str) -> Result<Self, String> {
+        for entry in overrides
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+        {
+            let (app, mode) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("invalid validation override '{}'", entry))?;
+            self.apps.insert(app.trim().to_string(), mode.parse()?);
+        }
+        Ok(self)
+    }
+
+    /// Retorna el modo aplicable a una aplicacion.
+    pub fn mode_for(&self, app: &str) -> ValidationMode {
+        self.apps.get(app).copied().unwrap_or(self.default_mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_from_str() {
+        assert_eq!("REJECT".parse(), Ok(ValidationMode::Reject));
+        assert_eq!(" annotate ".parse(), Ok(ValidationMode::Annotate));
+        assert_eq!("off".parse(), Ok(ValidationMode::Off));
+        assert!("strict".parse::<ValidationMode>().is_err());
+    }
+
+    #[test]
+    fn test_policy_overrides() {
+        let policy = ValidationPolicy::new(ValidationMode::Annotate)
+            .with_overrides("billing=reject, legacy=off,")
+            .unwrap();
+
+        assert_eq!(policy.mode_for("billing"), ValidationMode::Reject);
+        assert_eq!(policy.mode_for("legacy"), ValidationMode::Off);
+        assert_eq!(policy.mode_for("other"), ValidationMode::Annotate);
+        assert!(
+            ValidationPolicy::default()
+                .with_overrides("billing")
+                .is_err()
+        );
+        assert!(ValidationPolicy::default().with_overrides("a=b").is_err());
+    }
+}
@@ -133,3 +133,43 @@ async fn accept_header_is_case_insensitive() {
 
     response.assert_content_type_contains("yaml");
 }
+
+// === Negotiation ===
+
+#[tokio::test]
+#[ignore = "requires GitBackend - create_router() only has /health endpoint"]
+async fn respects_quality_values() {
+    let response = client()
+        .get_with_accept("/myapp/dev", "text/plain;q=0.5, application/x-yaml")
+        .await;
+
+    response.assert_content_type_contains("yaml");
+}
+
+#[tokio::test]
+#[ignore = "requires GitBackend - create_router() only has /health endpoint"]
+async fn returns_not_acceptable_for_unsupported_type() {
+    let response = client().get_with_accept("/myapp/dev", "image/png").await;
+
+    response.assert_status(StatusCode::NOT_ACCEPTABLE);
+}
+
+#[tokio::test]
+#[ignore = "requires GitBackend - create_router() only has /health endpoint"]
+async fn format_query_overrides_accept() {
+    let response = client()
+        .get_with_accept("/myapp/dev?format=toml", "application/json")
+        .await;
+
+    response
+        .assert_status(StatusCode::OK)
+        .assert_content_type_contains("application/toml");
+}
+
+#[tokio::test]
+#[ignore = "requires GitBackend - create_router() only has /health endpoint"]
+async fn negotiated_response_varies_on_accept() {
+    let response = client().get("/myapp/dev").await;
+
+    response.assert_header("vary", "accept");
+}
@@ -0,0 +1,512 @@
+//! Composite source (Spring's `composite` profile).
+//!
+//! Fans a query out to several backends and combines their property
+//! sources. Each backend has a priority: its property sources get that
+//! [`PropertySource::priority`], and higher priorities take precedence, so a
+//! SQL backend at priority 100 can override a git repository at priority 10.
+//!
+//! A backend that does not have the requested application or label simply
+//! contributes nothing. Other failures either fail the whole request
+//! ([`with_fail_fast`](CompositeConfigSource::with_fail_fast)) or are
+//! skipped and reported as warnings on the result. A result missing a failed
+//! backend is marked [partial](ConfigResult::is_partial), so the server only
+//! caches it briefly instead of serving it for the full cache TTL after the
+//! backend recovers.
+//!
+//! Writes go to the highest-priority backend that accepts them.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::task::JoinSet;
+use tracing::{debug, warn};
+use vortex_core::validation::SchemaValidator;
+use vortex_git::{
+    ConfigQuery, ConfigResult, ConfigSource, ConfigSourceError, ConfigStore, ConfigWarning,
+};
+
+/// Label reported when no backend answered.
+pub const DEFAULT_LABEL: &str = "main";
+
+/// A backend of a [`CompositeConfigSource`].
+#[derive(Clone)]
+pub struct Backend {
+    /// Name used in logs, warnings and health reports.
+    pub name: String,
+    /// Precedence of the backend; higher wins.
+    pub priority: i32,
+    /// The backend itself.
+    pub source: Arc<dyn ConfigSource>,
+}
+
+impl std::fmt::Debug for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Backend")
+            .field("name", &self.name)
+            .field("priority", &self.priority)
+            .field("source", &self.source.name())
+            .finish()
+    }
+}
+
+/// A configuration source combining several backends by priority.
+pub struct CompositeConfigSource {
+    /// Backends, highest priority first.
+    backends: Vec<Backend>,
+    /// Whether one failing backend fails the request.
+    fail_fast: bool,
+    /// Label reported when the request does not name one.
+    default_label: String,
+}
+
+impl CompositeConfigSource {
+    /// Creates a composite with no backends that skips failing ones.
+    pub fn new() -> Self {
+        Self {
+            backends: Vec::new(),
+            fail_fast: false,
+            default_label: DEFAULT_LABEL.to_string(),
+        }
+    }
+
+    /// Adds a backend. Backends with the same priority keep the order they
+    /// were added in, the first taking precedence.
+    pub fn with_backend(
+        mut self,
+        name: impl Into<String>,
+        priority: i32,
+        source: Arc<dyn ConfigSource>,
+    ) -> Self {
+        let index = self
+            .backends
+            .iter()
+            .position(|b| b.priority < priority)
+            .unwrap_or(self.backends.len());
+        self.backends.insert(
+            index,
+            Backend {
+                name: name.into(),
+                priority,
+                source,
+            },
+        );
+        self
+    }
+
+    /// Fails requests when any backend fails, instead of serving what the
+    /// others returned.
+    pub fn with_fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Sets the label reported when the request does not name one.
+    pub fn with_default_label(mut self, label: impl Into<String>) -> Self {
+        self.default_label = label.into();
+        self
+    }
+
+    /// Returns the backends, highest priority first.
+    pub fn backends(&self) -> &[Backend] {
+        &self.backends
+    }
+
+    /// Returns whether one failing backend fails the request.
+    pub fn is_fail_fast(&self) -> bool {
+        self.fail_fast
+    }
+
+    /// Fetches from every backend concurrently, in priority order.
+    async fn fetch_all(&self, query: &ConfigQuery) -> Vec<Result<ConfigResult, ConfigSourceError>> {
+        let mut tasks = JoinSet::new();
+        for (index, backend) in self.backends.iter().enumerate() {
+            let source = Arc::clone(&backend.source);
+            let query = query.clone();
+            tasks.spawn(async move { (index, source.fetch(&query).await) });
+        }
+
+        let mut results: Vec<Option<Result<ConfigResult, ConfigSourceError>>> =
+            (0..self.backends.len()).map(|_| None).collect();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((index, result)) => results[index] = Some(result),
+                Err(e) => warn!("A backend task failed: {}", e),
+            }
+        }
+        results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|| Err(ConfigSourceError::unavailable("backend task failed")))
+            })
+            .collect()
+    }
+}
+
+impl Default for CompositeConfigSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns whether an error only means the backend has nothing to serve.
+fn is_not_found(error: &ConfigSourceError) -> bool {
+    matches!(
+        error,
+        ConfigSourceError::ApplicationNotFound(_)
+            | ConfigSourceError::ProfileNotFound(_)
+            | ConfigSourceError::LabelNotFound(_)
+    )
+}
+
+#[async_trait]
+impl ConfigSource for CompositeConfigSource {
+    async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+        let mut found = Vec::new();
+        let mut warnings = Vec::new();
+        let mut first_error = None;
+
+        for (backend, result) in self.backends.iter().zip(self.fetch_all(query).await) {
+            match result {
+                Ok(result) => found.push((backend, result)),
+                Err(e) if is_not_found(&e) => {
+                    debug!("Backend {} has nothing for {}: {}", backend.name, query, e);
+                    first_error.get_or_insert(e);
+                },
+                Err(e) if self.fail_fast => return Err(e),
+                Err(e) => {
+                    warn!("Backend {} failed for {}: {}", backend.name, query, e);
+                    warnings.push(ConfigWarning::new(&backend.name, e.to_string()));
+                    first_error.get_or_insert(e);
+                },
+            }
+        }
+
+        // Nothing served at all: report why.
+        if found.is_empty() {
+            return Err(first_error
+                .unwrap_or_else(|| ConfigSourceError::unavailable("no backends configured")));
+        }
+
+        let label = found[0].1.label().to_string();
+        let mut combined = ConfigResult::new(query.application(), query.profiles().to_vec(), label);
+
+        let versions: Vec<(&str, &str)> = found
+            .iter()
+            .filter_map(|(backend, result)| Some((backend.name.as_str(), result.version()?)))
+            .collect();
+        match versions.as_slice() {
+            [] => {},
+            [(_, version)] => combined.set_version(*version),
+            _ => combined.set_version(
+                versions
+                    .iter()
+                    .map(|(name, version)| format!("{}@{}", name, version))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+        }
+        if let Some(state) = found.iter().find_map(|(_, result)| result.state()) {
+            combined.set_state(state);
+        }
+        if let Some(schema) = found.iter().find_map(|(_, result)| result.schema()) {
+            combined.set_schema(Arc::clone(schema));
+        }
+
+        if !warnings.is_empty() || found.iter().any(|(_, result)| result.is_partial()) {
+            combined.set_partial();
+        }
+        for (backend, result) in found {
+            combined.add_warnings(result.warnings().iter().cloned());
+            combined.add_property_sources(result.property_sources().iter().cloned().map(
+                |mut source| {
+                    source.priority = backend.priority;
+                    source
+                },
+            ));
+        }
+        combined.add_warnings(warnings);
+
+        Ok(combined)
+    }
+
+    async fn schema(
+        &self,
+        query: &ConfigQuery,
+    ) -> Result<Option<Arc<SchemaValidator>>, ConfigSourceError> {
+        for backend in &self.backends {
+            match backend.source.schema(query).await {
+                Ok(Some(schema)) => return Ok(Some(schema)),
+                Ok(None) => {},
+                Err(e) if is_not_found(&e) => {},
+                Err(e) if self.fail_fast => return Err(e),
+                Err(e) => warn!("Backend {} failed to load the schema: {}", backend.name, e),
+            }
+        }
+        Ok(None)
+    }
+
+    /// With fail-fast, every backend must be healthy; otherwise one is
+    /// enough.
+    async fn health_check(&self) -> Result<(), ConfigSourceError> {
+        let mut errors = Vec::new();
+        for (name, result) in self.backend_health().await {
+            if let Err(e) = result {
+                errors.push(format!("{}: {}", name, e));
+            }
+        }
+
+        let healthy = errors.is_empty() || !self.fail_fast && errors.len() < self.backends.len();
+        if healthy {
+            Ok(())
+        } else {
+            Err(ConfigSourceError::unavailable(errors.join("; ")))
+        }
+    }
+
+    async fn backend_health(&self) -> Vec<(String, Result<(), ConfigSourceError>)> {
+        let mut report = Vec::new();
+        for backend in &self.backends {
+            report.push((backend.name.clone(), backend.source.health_check().await));
+        }
+        report
+    }
+
+    fn name(&self) -> &str {
+        "composite"
+    }
+
+    async fn refresh(&self) -> Result<(), ConfigSourceError> {
+        for backend in self.backends.iter().filter(|b| b.source.supports_refresh()) {
+            if let Err(e) = backend.source.refresh().await {
+                if self.fail_fast {
+                    return Err(e);
+                }
+                warn!("Failed to refresh backend {}: {}", backend.name, e);
+            }
+        }
+        Ok(())
+    }
+
+    fn supports_refresh(&self) -> bool {
+        self.backends.iter().any(|b| b.source.supports_refresh())
+    }
+
+    fn default_label(&self) -> &str {
+        &self.default_label
+    }
+
+    fn store(&self) -> Option<&dyn ConfigStore> {
+        self.backends.iter().find_map(|b| b.source.store())
+    }
+}
+
+impl std::fmt::Debug for CompositeConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompositeConfigSource")
+            .field("backends", &self.backends)
+            .field("fail_fast", &self.fail_fast)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use vortex_core::{ConfigMap, ConfigValue, PropertySource};
+
+    use crate::FilesystemBackend;
+
+    /// Serves one property source, or fails with the given error.
+    struct Fixed {
+        name: &'static str,
+        values: Vec<(&'static str, i64)>,
+        error: Option<fn() -> ConfigSourceError>,
+    }
+
+    impl Fixed {
+        fn serving(name: &'static str, values: Vec<(&'static str, i64)>) -> Arc<dyn ConfigSource> {
+            Arc::new(Self {
+                name,
+                values,
+                error: None,
+            })
+        }
+
+        fn failing(name: &'static str, error: fn() -> ConfigSourceError) -> Arc<dyn ConfigSource> {
+            Arc::new(Self {
+                name,
+                values: Vec::new(),
+                error: Some(error),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl ConfigSource for Fixed {
+        async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+            if let Some(error) = self.error {
+                return Err(error());
+            }
+            let mut config = ConfigMap::new();
+            for (key, value) in &self.values {
+                config.insert(*key, ConfigValue::Integer(*value));
+            }
+            Ok(
+                ConfigResult::new(query.application(), query.profiles().to_vec(), "main")
+                    .with_version(format!("{}-v1", self.name))
+                    .with_property_sources(vec![PropertySource::new(
+                        format!("{}:{}.yml", self.name, query.application()),
+                        config,
+                    )]),
+            )
+        }
+
+        async fn health_check(&self) -> Result<(), ConfigSourceError> {
+            match self.error {
+                Some(error) => Err(error()),
+                None => Ok(()),
+            }
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+    }
+
+    fn unavailable() -> ConfigSourceError {
+        ConfigSourceError::unavailable("connection refused")
+    }
+
+    fn label_not_found() -> ConfigSourceError {
+        ConfigSourceError::LabelNotFound("main".to_string())
+    }
+
+    fn query() -> ConfigQuery {
+        ConfigQuery::new("payments", vec!["prod"])
+    }
+
+    #[tokio::test]
+    async fn test_higher_priority_wins() {
+        let composite = CompositeConfigSource::new()
+            .with_backend(
+                "git",
+                10,
+                Fixed::serving("git", vec![("pool", 10), ("port", 80)]),
+            )
+            .with_backend("sql", 100, Fixed::serving("sql", vec![("pool", 20)]))
+            .with_backend("native", 0, Fixed::serving("native", vec![("debug", 1)]));
+
+        let result = composite.fetch(&query()).await.unwrap();
+
+        let sources: Vec<(&str, i32)> = result
+            .property_sources()
+            .iter()
+            .map(|ps| (ps.name.as_str(), ps.priority))
+            .collect();
+        assert_eq!(
+            sources,
+            [
+                ("sql:payments.yml", 100),
+                ("git:payments.yml", 10),
+                ("native:payments.yml", 0)
+            ]
+        );
+        let merged = result.merged();
+        assert_eq!(merged.get("pool"), Some(&ConfigValue::Integer(20)));
+        assert_eq!(merged.get("port"), Some(&ConfigValue::Integer(80)));
+        assert_eq!(merged.get("debug"), Some(&ConfigValue::Integer(1)));
+        assert_eq!(
+            result.version(),
+            Some("sql@sql-v1,git@git-v1,native@native-v1")
+        );
+        assert!(!result.is_partial());
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_skipped_unless_fail_fast() {
+        let composite = CompositeConfigSource::new()
+            .with_backend("git", 10, Fixed::serving("git", vec![("pool", 10)]))
+            .with_backend("sql", 100, Fixed::failing("sql", unavailable))
+            .with_backend("s3", 50, Fixed::failing("s3", label_not_found));
+
+        let result = composite.fetch(&query()).await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.version(), Some("git-v1"));
+        assert_eq!(result.warnings().len(), 1);
+        assert_eq!(result.warnings()[0].source, "sql");
+        // The failed backend makes the result partial; the missing label
+        // alone would not.
+        assert!(result.is_partial());
+        assert!(composite.health_check().await.is_ok());
+
+        let composite = composite.with_fail_fast(true);
+        assert!(matches!(
+            composite.fetch(&query()).await,
+            Err(ConfigSourceError::SourceUnavailable { .. })
+        ));
+        assert!(composite.health_check().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_nothing_found_reports_the_error() {
+        let composite = CompositeConfigSource::new()
+            .with_backend("git", 10, Fixed::failing("git", label_not_found))
+            .with_backend("sql", 100, Fixed::failing("sql", label_not_found));
+        assert!(matches!(
+            composite.fetch(&query()).await,
+            Err(ConfigSourceError::LabelNotFound(_))
+        ));
+
+        assert!(CompositeConfigSource::new().fetch(&query()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_writes_go_to_the_first_writable_backend() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let composite = CompositeConfigSource::new()
+            .with_backend("git", 100, Fixed::serving("git", vec![("a", 1)]))
+            .with_backend("native", 10, Arc::new(FilesystemBackend::new(dir.path())));
+
+        let store = composite.store().unwrap();
+        let id = vortex_git::DocumentId::new("payments", "prod", "main");
+        let properties = ConfigMap::from_json(r#"{"b": 2}"#).unwrap();
+        store.put_properties(&id, &properties, None).await.unwrap();
+        assert!(dir.path().join("payments-prod.yml").is_file());
+
+        let merged = composite
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap()
+            .merged();
+        assert_eq!(merged.get("b").and_then(|v| v.as_i64()), Some(2));
+
+        let read_only = CompositeConfigSource::new().with_backend(
+            "git",
+            100,
+            Fixed::serving("git", vec![("a", 1)]),
+        );
+        assert!(read_only.store().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_backend_health() {
+        let composite = CompositeConfigSource::new()
+            .with_backend("git", 10, Fixed::serving("git", vec![]))
+            .with_backend("sql", 100, Fixed::failing("sql", unavailable));
+
+        let report = composite.backend_health().await;
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].0, "sql");
+        assert!(report[0].1.is_err());
+        assert_eq!(report[1].0, "git");
+        assert!(report[1].1.is_ok());
+
+        let composite = CompositeConfigSource::new().with_backend(
+            "sql",
+            100,
+            Fixed::failing("sql", unavailable),
+        );
+        assert!(composite.health_check().await.is_err());
+    }
+}
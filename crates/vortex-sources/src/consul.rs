@@ -0,0 +1,764 @@
+//! Consul KV backend (Spring Cloud Consul Config).
+//!
+//! Serves configuration stored as Consul keys, one folder per application
+//! and profile:
+//!
+//! ```text
+//! config/application/server/port           = 8080
+//! config/payments/db/pool                  = 10
+//! config/payments,prod/db/pool             = 20
+//! config/payments,prod/data                = "db:\n  url: jdbc:..."
+//! ```
+//!
+//! Keys below a folder are split on `/` into property paths and values are
+//! read as YAML, so `20` is a number and a mapping nests under its key. The
+//! `data` key holds a whole YAML document merged at the folder's root, as
+//! Spring's `YAML` format does. Folders follow Spring's precedence:
+//! `{app},{profile}`, then `{app}`, then `application,{profile}` and
+//! `application`.
+//!
+//! Consul has no labels: every label serves the same keys. The response
+//! version is Consul's index for the prefix, which changes with any write
+//! below it. [`watch`](ConsulBackend::watch) runs blocking queries on the
+//! prefix and reports writes on [`changes`](ConsulBackend::changes).
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use hyper::{StatusCode, Uri};
+use serde::Deserialize;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use vortex_core::{ConfigMap, ConfigPath, ConfigValue, PropertySource};
+use vortex_git::{ConfigQuery, ConfigResult, ConfigSource, ConfigSourceError};
+
+use crate::http::{DEFAULT_TIMEOUT, HttpClient, HttpResponse, uri_encode};
+
+/// Label reported when the request does not name one.
+pub const DEFAULT_LABEL: &str = "main";
+
+/// Folder holding the configuration, as in Spring.
+pub const DEFAULT_PREFIX: &str = "config";
+
+/// Folder shared by every application.
+pub const DEFAULT_CONTEXT: &str = "application";
+
+/// Separator between application and profile in folder names.
+pub const DEFAULT_PROFILE_SEPARATOR: &str = ",";
+
+/// Key holding a YAML document for its whole folder.
+pub const DEFAULT_DATA_KEY: &str = "data";
+
+/// Pause after a failed blocking query before the next one.
+const WATCH_RETRY: Duration = Duration::from_secs(5);
+
+/// A configuration source backed by the Consul KV store.
+pub struct ConsulBackend {
+    /// Client for the agent.
+    client: ConsulClient,
+    /// Folder holding the configuration, without slashes around it.
+    prefix: String,
+    /// Folder shared by every application.
+    default_context: String,
+    /// Separator between application and profile.
+    profile_separator: String,
+    /// Key holding a YAML document, if any.
+    data_key: Option<String>,
+    /// Label reported when the request does not name one.
+    default_label: String,
+    /// Index of the last read, for change detection.
+    tracker: Arc<ChangeTracker>,
+    /// Blocking queries, when enabled.
+    watcher: Option<JoinHandle<()>>,
+}
+
+impl ConsulBackend {
+    /// Serves configuration from the Consul agent at `address`, e.g.
+    /// `http://127.0.0.1:8500`.
+    pub fn new(address: &str) -> Result<Self, ConfigSourceError> {
+        Ok(Self {
+            client: ConsulClient::new(address)?,
+            prefix: DEFAULT_PREFIX.to_string(),
+            default_context: DEFAULT_CONTEXT.to_string(),
+            profile_separator: DEFAULT_PROFILE_SEPARATOR.to_string(),
+            data_key: Some(DEFAULT_DATA_KEY.to_string()),
+            default_label: DEFAULT_LABEL.to_string(),
+            tracker: Arc::new(ChangeTracker::default()),
+            watcher: None,
+        })
+    }
+
+    /// Reads the keys below `prefix` instead of `config`.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.trim_matches('/').to_string();
+        self
+    }
+
+    /// Sends `token` with every request, for agents with ACLs enabled.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.client.token = Some(token.into());
+        self
+    }
+
+    /// Reads from `datacenter` instead of the agent's own.
+    pub fn with_datacenter(mut self, datacenter: impl Into<String>) -> Self {
+        self.client.datacenter = Some(datacenter.into());
+        self
+    }
+
+    /// Sets the folder shared by every application.
+    pub fn with_default_context(mut self, context: impl Into<String>) -> Self {
+        self.default_context = context.into();
+        self
+    }
+
+    /// Sets the separator between application and profile.
+    pub fn with_profile_separator(mut self, separator: impl Into<String>) -> Self {
+        self.profile_separator = separator.into();
+        self
+    }
+
+    /// Sets the key holding a YAML document for its folder; `None` reads
+    /// every key as a property.
+    pub fn with_data_key(mut self, key: Option<String>) -> Self {
+        self.data_key = key;
+        self
+    }
+
+    /// Sets the label reported when the request does not name one.
+    pub fn with_default_label(mut self, label: impl Into<String>) -> Self {
+        self.default_label = label.into();
+        self
+    }
+
+    /// Watches the prefix with blocking queries of up to `wait` on a
+    /// background task, which stops when the backend is dropped.
+    ///
+    /// Call it last, once the backend is configured. Requires a tokio
+    /// runtime.
+    pub fn watch(mut self, wait: Duration) -> Self {
+        // Consul adds up to wait / 16 of jitter to the wait.
+        let client = self
+            .client
+            .clone()
+            .with_timeout(wait + wait / 16 + DEFAULT_TIMEOUT);
+        let prefix = self.folder();
+        let tracker = Arc::clone(&self.tracker);
+
+        self.watcher = Some(tokio::spawn(async move {
+            loop {
+                let index = tracker.index();
+                match client.list(&prefix, index.map(|index| (index, wait))).await {
+                    Ok(listing) => {
+                        tracker.record(listing.index);
+                        // Without an index, queries would not block.
+                        if listing.index == 0 {
+                            tokio::time::sleep(WATCH_RETRY).await;
+                        }
+                    },
+                    Err(e) => {
+                        warn!("Failed to watch {:?} for changes: {}", client, e);
+                        tokio::time::sleep(WATCH_RETRY).await;
+                    },
+                }
+            }
+        }));
+        self
+    }
+
+    /// Returns a receiver notified whenever the keys below the prefix
+    /// change.
+    ///
+    /// The value is the number of changes seen so far.
+    pub fn changes(&self) -> watch::Receiver<u64> {
+        self.tracker.changes.subscribe()
+    }
+
+    /// Returns the prefix folder, with its trailing slash.
+    fn folder(&self) -> String {
+        if self.prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", self.prefix)
+        }
+    }
+
+    /// Returns the folders to read, lowest precedence first.
+    fn contexts(&self, query: &ConfigQuery) -> Vec<String> {
+        let with_profiles = |name: &str| {
+            let mut contexts = vec![name.to_string()];
+            contexts.extend(
+                query
+                    .profiles()
+                    .iter()
+                    .map(|p| format!("{}{}{}", name, self.profile_separator, p)),
+            );
+            contexts
+        };
+
+        let mut contexts = with_profiles(&self.default_context);
+        if query.application() != self.default_context {
+            contexts.extend(with_profiles(query.application()));
+        }
+        contexts
+    }
+
+    /// Builds the configuration of one folder from its keys.
+    fn read_context(
+        &self,
+        folder: &str,
+        entries: &[KvEntry],
+    ) -> Result<ConfigMap, ConfigSourceError> {
+        let mut config = ConfigMap::new();
+        for entry in entries {
+            let Some(relative) = entry.key.strip_prefix(folder) else {
+                continue;
+            };
+            // Folders themselves have no value.
+            let Some(encoded) = entry.value.as_deref() else {
+                continue;
+            };
+            let bytes = BASE64
+                .decode(encoded)
+                .map_err(|e| ConfigSourceError::parse(&entry.key, e.to_string()))?;
+            let text = String::from_utf8(bytes)
+                .map_err(|e| ConfigSourceError::parse(&entry.key, e.to_string()))?;
+
+            if self.data_key.as_deref() == Some(relative) {
+                let document = ConfigMap::from_yaml(&text)
+                    .map_err(|e| ConfigSourceError::parse(&entry.key, e.to_string()))?;
+                for (key, value) in document.as_inner() {
+                    config.insert(key.clone(), value.clone());
+                }
+                continue;
+            }
+
+            let path: ConfigPath = relative
+                .split('/')
+                .filter(|segment| !segment.is_empty())
+                .fold(ConfigPath::root(), |path, segment| path.key(segment));
+            if path.is_root() {
+                continue;
+            }
+            config
+                .set_path(&path, parse_value(&text))
+                .map_err(|e| ConfigSourceError::parse(&entry.key, e.to_string()))?;
+        }
+        Ok(config)
+    }
+}
+
+/// Reads a value as YAML, falling back to the plain text.
+fn parse_value(text: &str) -> ConfigValue {
+    match serde_yaml::from_str::<ConfigValue>(text) {
+        // Through the YAML format, for merge keys and tag checks.
+        Ok(ConfigValue::Object(_)) => ConfigMap::from_yaml(text)
+            .map(|map| ConfigValue::Object(map.as_inner().clone()))
+            .unwrap_or_else(|_| ConfigValue::String(text.to_string())),
+        Ok(value) => value,
+        Err(_) => ConfigValue::String(text.to_string()),
+    }
+}
+
+#[async_trait]
+impl ConfigSource for ConsulBackend {
+    async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+        let label = query.effective_label(&self.default_label);
+        let prefix = self.folder();
+
+        debug!(
+            "Fetching config for {} from {:?} under {}",
+            query, self.client, prefix
+        );
+
+        let listing = self.client.list(&prefix, None).await?;
+        self.tracker.record(listing.index);
+
+        let mut sources = Vec::new();
+        for context in self.contexts(query) {
+            let folder = format!("{}{}/", prefix, context);
+            let config = self.read_context(&folder, &listing.entries)?;
+            if !config.is_empty() {
+                sources.push(PropertySource::new(format!("consul:{}", folder), config));
+            }
+        }
+        sources.reverse();
+
+        debug!("Resolved {} property sources for {}", sources.len(), query);
+
+        let mut result = ConfigResult::new(query.application(), query.profiles().to_vec(), label);
+        if !sources.is_empty() {
+            result.set_version(listing.index.to_string());
+        }
+        result.add_property_sources(sources);
+        Ok(result)
+    }
+
+    async fn health_check(&self) -> Result<(), ConfigSourceError> {
+        self.client.leader().await
+    }
+
+    fn name(&self) -> &str {
+        "consul"
+    }
+
+    async fn refresh(&self) -> Result<(), ConfigSourceError> {
+        let listing = self.client.list(&self.folder(), None).await?;
+        self.tracker.record(listing.index);
+        Ok(())
+    }
+
+    fn supports_refresh(&self) -> bool {
+        true
+    }
+
+    fn default_label(&self) -> &str {
+        &self.default_label
+    }
+}
+
+impl Drop for ConsulBackend {
+    fn drop(&mut self) {
+        if let Some(watcher) = self.watcher.take() {
+            watcher.abort();
+        }
+    }
+}
+
+impl std::fmt::Debug for ConsulBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConsulBackend")
+            .field("client", &self.client)
+            .field("prefix", &self.prefix)
+            .field("default_label", &self.default_label)
+            .field("watching", &self.watcher.is_some())
+            .finish()
+    }
+}
+
+/// Detects changes by comparing the prefix's index between reads.
+struct ChangeTracker {
+    index: Mutex<Option<u64>>,
+    changes: watch::Sender<u64>,
+}
+
+impl Default for ChangeTracker {
+    fn default() -> Self {
+        Self {
+            index: Mutex::new(None),
+            changes: watch::Sender::new(0),
+        }
+    }
+}
+
+impl ChangeTracker {
+    /// Returns the index of the last read.
+    fn index(&self) -> Option<u64> {
+        *self.index.lock().expect("tracker poisoned")
+    }
+
+    /// Records the index of a read and returns whether it changed. The
+    /// first read only records the index.
+    fn record(&self, index: u64) -> bool {
+        let previous = self.index.lock().expect("tracker poisoned").replace(index);
+        let changed = previous.is_some_and(|previous| previous != index);
+        if changed {
+            debug!("Consul index moved from {:?} to {}", previous, index);
+            self.changes.send_modify(|count| *count += 1);
+        }
+        changed
+    }
+}
+
+/// A key as returned by `/v1/kv`.
+#[derive(Debug, Deserialize)]
+struct KvEntry {
+    #[serde(rename = "Key")]
+    key: String,
+    /// Base64-encoded value; `None` for folders.
+    #[serde(rename = "Value")]
+    value: Option<String>,
+}
+
+/// The keys below a prefix and the index they were read at.
+#[derive(Debug)]
+struct Listing {
+    entries: Vec<KvEntry>,
+    index: u64,
+}
+
+/// Client for the KV and status endpoints of an agent.
+#[derive(Clone)]
+struct ConsulClient {
+    http: HttpClient,
+    /// Scheme and authority of the agent.
+    address: String,
+    token: Option<String>,
+    datacenter: Option<String>,
+}
+
+impl ConsulClient {
+    fn new(address: &str) -> Result<Self, ConfigSourceError> {
+        let invalid = |reason: String| {
+            ConfigSourceError::unavailable(format!(
+                "invalid Consul address {}: {}",
+                address, reason
+            ))
+        };
+        let uri: Uri = address.parse().map_err(|e| invalid(format!("{}", e)))?;
+        let (Some(scheme), Some(authority)) = (uri.scheme_str(), uri.authority()) else {
+            return Err(invalid("expected http(s)://host[:port]".to_string()));
+        };
+
+        Ok(Self {
+            http: HttpClient::new().map_err(ConfigSourceError::unavailable)?,
+            address: format!("{}://{}", scheme, authority),
+            token: None,
+            datacenter: None,
+        })
+    }
+
+    fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.http = self.http.with_timeout(timeout);
+        self
+    }
+
+    /// Reads every key below `prefix`. With `block`, waits until the index
+    /// moves past the given one or the wait elapses.
+    async fn list(
+        &self,
+        prefix: &str,
+        block: Option<(u64, Duration)>,
+    ) -> Result<Listing, ConfigSourceError> {
+        let mut url = format!(
+            "{}/v1/kv/{}?recurse=true",
+            self.address,
+            uri_encode(prefix, false)
+        );
+        if let Some((index, wait)) = block {
+            url.push_str(&format!("&index={}&wait={}s", index, wait.as_secs().max(1)));
+        }
+        let response = self.get(&url).await?;
+
+        let index = response
+            .headers
+            .get("x-consul-index")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or_default();
+        let entries = match response.status {
+            StatusCode::OK => serde_json::from_slice(&response.body)
+                .map_err(|e| ConfigSourceError::parse(prefix, e.to_string()))?,
+            // No keys below the prefix.
+            StatusCode::NOT_FOUND => Vec::new(),
+            status => return Err(error_response(&url, status, &response.body)),
+        };
+        Ok(Listing { entries, index })
+    }
+
+    /// Checks that the cluster has a leader.
+    async fn leader(&self) -> Result<(), ConfigSourceError> {
+        let url = format!("{}/v1/status/leader", self.address);
+        let response = self.get(&url).await?;
+        if response.status != StatusCode::OK {
+            return Err(error_response(&url, response.status, &response.body));
+        }
+        let leader: String = serde_json::from_slice(&response.body)
+            .map_err(|e| ConfigSourceError::parse(&url, e.to_string()))?;
+        if leader.is_empty() {
+            return Err(ConfigSourceError::unavailable(
+                "Consul cluster has no leader",
+            ));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, url: &str) -> Result<HttpResponse, ConfigSourceError> {
+        let url = match &self.datacenter {
+            Some(dc) if url.contains('?') => format!("{}&dc={}", url, uri_encode(dc, true)),
+            Some(dc) => format!("{}?dc={}", url, uri_encode(dc, true)),
+            None => url.to_string(),
+        };
+        let headers: Vec<(&str, &str)> = self
+            .token
+            .as_deref()
+            .map(|token| ("x-consul-token", token))
+            .into_iter()
+            .collect();
+        self.http
+            .get(&url, &headers)
+            .await
+            .map_err(ConfigSourceError::unavailable)
+    }
+}
+
+impl std::fmt::Debug for ConsulClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConsulClient")
+            .field("address", &self.address)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("datacenter", &self.datacenter)
+            .finish()
+    }
+}
+
+fn error_response(url: &str, status: StatusCode, body: &[u8]) -> ConfigSourceError {
+    ConfigSourceError::unavailable(format!(
+        "Consul request {} failed with {}: {}",
+        url,
+        status,
+        String::from_utf8_lossy(body).trim()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use crate::testing::{Request, Response, serve};
+
+    const TOKEN: &str = "test-token";
+
+    /// Keys of the fake agent and the index of the last write.
+    #[derive(Default)]
+    struct Store {
+        keys: BTreeMap<String, Option<String>>,
+        index: u64,
+    }
+
+    impl Store {
+        fn put(&mut self, key: &str, value: &str) {
+            self.keys.insert(key.to_string(), Some(value.to_string()));
+            self.index += 1;
+        }
+    }
+
+    type Shared = Arc<Mutex<Store>>;
+
+    /// Starts a fake Consul agent serving `store` to requests carrying the
+    /// test token, with blocking queries.
+    async fn fake_consul(store: Shared) -> String {
+        serve(move |request| {
+            let store = Arc::clone(&store);
+            async move {
+                if request.header("x-consul-token") == Some(TOKEN) {
+                    respond(&store, &request).await
+                } else {
+                    Response::new(403, "ACL not found").with_header("x-consul-index", "0")
+                }
+            }
+        })
+        .await
+    }
+
+    async fn respond(store: &Shared, request: &Request) -> Response {
+        let path = request.path();
+        if path == "/v1/status/leader" {
+            return Response::new(200, "\"127.0.0.1:8300\"").with_header("x-consul-index", "0");
+        }
+        let Some(prefix) = path.strip_prefix("/v1/kv/") else {
+            return Response::new(404, "").with_header("x-consul-index", "0");
+        };
+
+        // Blocking query: wait for a write past the given index.
+        if let Some(index) = request.param("index").and_then(|i| i.parse::<u64>().ok()) {
+            let wait: u64 = request
+                .param("wait")
+                .and_then(|w| w.trim_end_matches('s').parse().ok())
+                .unwrap_or(300);
+            let deadline = tokio::time::Instant::now() + Duration::from_secs(wait);
+            while store.lock().unwrap().index == index && tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+
+        let store = store.lock().unwrap();
+        let entries: Vec<serde_json::Value> = store
+            .keys
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| {
+                serde_json::json!({
+                    "Key": key,
+                    "Value": value.as_ref().map(|v| BASE64.encode(v)),
+                    "ModifyIndex": store.index,
+                })
+            })
+            .collect();
+        let response = if entries.is_empty() {
+            Response::new(404, "")
+        } else {
+            Response::new(200, serde_json::to_string(&entries).unwrap())
+        };
+        response.with_header("x-consul-index", store.index.to_string())
+    }
+
+    fn default_store() -> Shared {
+        let mut store = Store::default();
+        store.keys.insert("config/".to_string(), None);
+        store.put("config/application/server/port", "8080");
+        store.put("config/application,prod/log/level", "WARN");
+        store.put("config/payments/db/pool", "10");
+        store.put("config/payments/db/url", "jdbc:postgresql://db/payments");
+        store.put("config/payments,prod/db/pool", "20");
+        store.put(
+            "config/payments,prod/data",
+            "features:\n  - fraud-check\n  - refunds\nretry:\n  attempts: 3\n",
+        );
+        store.put(
+            "config/payments,prod/limits",
+            "{daily: 1000, monthly: 20000}",
+        );
+        store.put("other/payments/db/pool", "99");
+        Arc::new(Mutex::new(store))
+    }
+
+    async fn backend(store: &Shared) -> ConsulBackend {
+        ConsulBackend::new(&fake_consul(Arc::clone(store)).await)
+            .unwrap()
+            .with_token(TOKEN)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_maps_folders_to_sources() {
+        let store = default_store();
+        let backend = backend(&store).await;
+
+        let result = backend
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap();
+
+        let names: Vec<&str> = result
+            .property_sources()
+            .iter()
+            .map(|ps| ps.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "consul:config/payments,prod/",
+                "consul:config/payments/",
+                "consul:config/application,prod/",
+                "consul:config/application/"
+            ]
+        );
+
+        let merged = result.merged();
+        assert_eq!(merged.get("db.pool"), Some(&ConfigValue::Integer(20)));
+        assert_eq!(
+            merged.get("db.url").and_then(|v| v.as_str()),
+            Some("jdbc:postgresql://db/payments")
+        );
+        assert_eq!(merged.get("server.port"), Some(&ConfigValue::Integer(8080)));
+        assert_eq!(
+            merged.get("log.level").and_then(|v| v.as_str()),
+            Some("WARN")
+        );
+        assert_eq!(merged.get("retry.attempts"), Some(&ConfigValue::Integer(3)));
+        assert_eq!(
+            merged
+                .get("features")
+                .and_then(|v| v.as_array())
+                .map(<[_]>::len),
+            Some(2)
+        );
+        assert_eq!(
+            merged.get("limits.daily"),
+            Some(&ConfigValue::Integer(1000))
+        );
+        assert_eq!(result.label(), "main");
+        assert_eq!(result.version(), Some("8"));
+
+        let result = backend
+            .fetch(&ConfigQuery::new("billing", vec!["dev"]))
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_options() {
+        let store = default_store();
+        let backend = backend(&store)
+            .await
+            .with_prefix("/other/")
+            .with_profile_separator("-")
+            .with_data_key(None);
+        let result = backend
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.merged().get("db.pool"),
+            Some(&ConfigValue::Integer(99))
+        );
+
+        let backend = self::backend(&store).await.with_data_key(None);
+        let result = backend
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap();
+        let merged = result.merged();
+        assert!(merged.get("retry").is_none());
+        assert_eq!(
+            merged.get("data.retry.attempts"),
+            Some(&ConfigValue::Integer(3))
+        );
+
+        assert_eq!(parse_value("true"), ConfigValue::Bool(true));
+        assert_eq!(
+            parse_value("a: b: c"),
+            ConfigValue::String("a: b: c".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_reports_writes() {
+        let store = default_store();
+        let backend = backend(&store).await;
+        let mut changes = backend.changes();
+
+        // The first refresh records the index.
+        backend.refresh().await.unwrap();
+        backend.refresh().await.unwrap();
+        assert!(!changes.has_changed().unwrap());
+
+        store.lock().unwrap().put("config/payments/db/pool", "15");
+        backend.refresh().await.unwrap();
+        assert_eq!(*changes.borrow_and_update(), 1);
+
+        // Blocking queries do the same in the background.
+        let backend = backend.watch(Duration::from_secs(30));
+        let mut changes = backend.changes();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        store.lock().unwrap().put("config/payments/db/pool", "25");
+        tokio::time::timeout(Duration::from_secs(5), changes.changed())
+            .await
+            .expect("no change detected")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let store = default_store();
+        let backend = backend(&store).await;
+        assert!(backend.health_check().await.is_ok());
+
+        let anonymous = ConsulBackend::new(&fake_consul(store).await).unwrap();
+        let error = anonymous
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("403"), "{}", error);
+        assert!(!format!("{:?}", backend).contains(TOKEN));
+
+        assert!(ConsulBackend::new("not a url").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Minimal HTTP(S) client shared by the network backends.
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{HeaderMap, Method, Request, StatusCode};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use hyper_util::client::legacy::Client;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::rt::TokioExecutor;
+
+/// Default timeout for a whole request, body included.
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A response with its body fully read.
+#[derive(Debug)]
+pub(crate) struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+/// HTTP client that speaks both `http://` and `https://` (native roots).
+#[derive(Clone)]
+pub(crate) struct HttpClient {
+    client: Client<HttpsConnector<HttpConnector>, Full<Bytes>>,
+    timeout: Duration,
+}
+
+impl HttpClient {
+    /// Creates a client that trusts the platform's root certificates.
+    pub fn new() -> Result<Self, String> {
+        let connector = HttpsConnectorBuilder::new()
+            .with_native_roots()
+            .map_err(|e| format!("cannot load root certificates: {}", e))?
+            .https_or_http()
+            .enable_http1()
+            .build();
+
+        Ok(Self {
+            client: Client::builder(TokioExecutor::new()).build(connector),
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Replaces the timeout for a whole request, body included.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends a request and reads the whole response body.
+    pub async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Bytes,
+    ) -> Result<HttpResponse, String> {
+        let mut request = Request::builder().method(method).uri(url);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let request = request
+            .body(Full::new(body))
+            .map_err(|e| format!("invalid request to {}: {}", url, e))?;
+
+        let exchange = async {
+            let response = self
+                .client
+                .request(request)
+                .await
+                .map_err(|e| format!("request to {} failed: {}", url, e))?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response
+                .into_body()
+                .collect()
+                .await
+                .map_err(|e| format!("cannot read response from {}: {}", url, e))?
+                .to_bytes();
+            Ok(HttpResponse {
+                status,
+                headers,
+                body,
+            })
+        };
+
+        tokio::time::timeout(self.timeout, exchange)
+            .await
+            .map_err(|_| format!("request to {} timed out", url))?
+    }
+
+    /// Sends a `GET` request.
+    pub async fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<HttpResponse, String> {
+        self.send(Method::GET, url, headers, Bytes::new()).await
+    }
+}
+
+/// Encodes everything but unreserved characters, and `/` unless
+/// `encode_slash` is false.
+pub(crate) fn uri_encode(s: &str, encode_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            },
+            b'/' if !encode_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
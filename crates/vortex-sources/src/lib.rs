@@ -1,7 +1,49 @@
 //! Vortex Sources - Configuration backends
 //!
-//! This crate will provide implementations for different configuration sources
-//! including Git, S3, and SQL backends.
+//! This crate provides configuration sources beyond the git backend in
+//! `vortex-git`:
+//!
+//! - [`consul`]: keys in the Consul KV store (Spring Cloud Consul Config).
+//! - [`native`]: a plain local directory (Spring's `native` profile).
+//! - [`redis`]: hashes in Redis, with the `redis` feature.
+//! - [`s3`]: objects in an S3-compatible bucket (Spring's `awss3` profile).
+//! - [`sql`]: a `PROPERTIES` table in SQLite or PostgreSQL (Spring's JDBC
+//!   backend).
+//! - [`upstream`]: another Vortex or Spring Cloud Config server.
+//!
+//! [`composite`] combines several of these backends, and the git one, by
+//! priority (Spring's `composite` profile), and [`overrides`] puts
+//! emergency overrides from the environment or a mounted directory on top
+//! of any source.
+//!
+//! It also provides the [`secrets`] providers that resolve `${secret:...}`
+//! references at serve time.
+
+pub mod composite;
+pub mod consul;
+mod http;
+pub mod native;
+pub mod overrides;
+mod pool;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod s3;
+pub mod secrets;
+pub mod sql;
+mod store;
+#[cfg(test)]
+mod testing;
+pub mod upstream;
+
+pub use composite::CompositeConfigSource;
+pub use consul::ConsulBackend;
+pub use native::FilesystemBackend;
+pub use overrides::OverrideSource;
+#[cfg(feature = "redis")]
+pub use redis::RedisBackend;
+pub use s3::S3Backend;
+pub use sql::SqlBackend;
+pub use upstream::HttpUpstreamSource;
 
 /// Returns the crate version.
 pub fn version() -> &'static str {
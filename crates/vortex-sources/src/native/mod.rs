@@ -0,0 +1,613 @@
+//! Native filesystem backend (Spring's `native` profile).
+//!
+//! Serves configuration from a plain directory, without git, using the same
+//! file conventions as the git backend. Labels map to subdirectories:
+//!
+//! ```text
+//! /etc/vortex/config/
+//! ├── application.yml          # default label
+//! ├── payments-prod.yml
+//! └── release-2.4/             # label "release-2.4"
+//!     └── payments-prod.yml
+//! ```
+//!
+//! Files are read on every fetch, so edits are served right away; a
+//! [`DirectoryWatcher`] reports changes so caches in front of the backend
+//! can be invalidated.
+//!
+//! The backend is writable: the document of an application and profile is
+//! `{application}-{profile}.yml` in the label directory, and `{application}.yml`
+//! for the `default` profile. Files are rewritten whole, so comments and
+//! formatting are lost; files in other formats, encrypted with SOPS or
+//! using custom YAML tags are never written.
+
+mod watch;
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::{fs, io};
+
+use async_trait::async_trait;
+use tokio::sync::{Mutex, watch as channel};
+use tracing::{debug, info};
+use vortex_core::format::yaml::YamlFormat;
+use vortex_core::format::{FormatParser, FormatRegistry, FormatSerializer};
+use vortex_core::validation::SchemaValidator;
+use vortex_core::{ConfigMap, ConfigPath, ConfigValue};
+use vortex_git::{
+    ConfigFileResolver, ConfigQuery, ConfigResult, ConfigSource, ConfigSourceError, ConfigStore,
+    DocumentId, DuplicateKeyPolicy, SopsDecryptor, StoredProperties, check_version,
+};
+
+use crate::store::{Write, content_version};
+
+pub use watch::DirectoryWatcher;
+
+/// Label served from the root directory unless a subdirectory claims it.
+pub const DEFAULT_LABEL: &str = "main";
+
+/// Profile whose document is `{application}.yml`.
+const DEFAULT_PROFILE: &str = "default";
+
+/// Extensions of the files writes go to.
+const YAML_EXTENSIONS: [&str; 2] = ["yml", "yaml"];
+
+/// Top-level key of SOPS-encrypted files.
+const SOPS_KEY: &str = "sops";
+
+/// A configuration source backed by a local directory.
+pub struct FilesystemBackend {
+    /// Directory configuration is served from.
+    root: PathBuf,
+    /// Search paths within each label directory.
+    search_paths: Vec<String>,
+    /// Label served from the root directory.
+    default_label: String,
+    /// Formats used to recognize and parse files.
+    formats: Arc<FormatRegistry>,
+    /// How duplicate keys are handled.
+    duplicate_keys: DuplicateKeyPolicy,
+    /// Decrypts SOPS files.
+    sops: Option<Arc<SopsDecryptor>>,
+    /// Change detection, when enabled.
+    watcher: Option<DirectoryWatcher>,
+    /// Held by writes, so that each sees the file as the previous one left
+    /// it.
+    write_lock: Mutex<()>,
+}
+
+impl FilesystemBackend {
+    /// Serves configuration from `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            search_paths: Vec::new(),
+            default_label: DEFAULT_LABEL.to_string(),
+            formats: Arc::new(FormatRegistry::builtin()),
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            sops: None,
+            watcher: None,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Looks for files in these subdirectories of each label directory.
+    pub fn with_search_paths(mut self, search_paths: Vec<String>) -> Self {
+        self.search_paths = search_paths;
+        self
+    }
+
+    /// Sets the label served from the root directory.
+    pub fn with_default_label(mut self, label: impl Into<String>) -> Self {
+        self.default_label = label.into();
+        self
+    }
+
+    /// Uses the given format registry to recognize and parse config files.
+    pub fn with_format_registry(mut self, formats: Arc<FormatRegistry>) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    /// Sets how duplicate keys in resolved files are handled.
+    pub fn with_duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Decrypts SOPS-encrypted files with `sops`.
+    pub fn with_sops(mut self, sops: Arc<SopsDecryptor>) -> Self {
+        self.sops = Some(sops);
+        self
+    }
+
+    /// Starts watching the directory for changes.
+    ///
+    /// See [`changes`](Self::changes).
+    pub fn watch(mut self) -> Result<Self, ConfigSourceError> {
+        self.watcher = Some(DirectoryWatcher::start(&self.root)?);
+        Ok(self)
+    }
+
+    /// Returns the directory configuration is served from.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns a receiver notified whenever a file under the root changes,
+    /// or `None` if the backend is not watching.
+    pub fn changes(&self) -> Option<channel::Receiver<u64>> {
+        self.watcher.as_ref().map(DirectoryWatcher::changes)
+    }
+
+    /// Returns the directory holding a label's files.
+    ///
+    /// A subdirectory named like the label wins; otherwise the default label
+    /// is served from the root.
+    fn label_dir(&self, label: &str) -> Result<PathBuf, ConfigSourceError> {
+        let relative = Path::new(label);
+        let is_plain = relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)));
+        if label.is_empty() || !is_plain {
+            return Err(ConfigSourceError::LabelNotFound(label.to_string()));
+        }
+
+        let dir = self.root.join(relative);
+        if dir.is_dir() {
+            Ok(dir)
+        } else if label == self.default_label {
+            Ok(self.root.clone())
+        } else {
+            Err(ConfigSourceError::LabelNotFound(label.to_string()))
+        }
+    }
+
+    /// Returns the file holding a document: the file the resolver reads for
+    /// it if there is one, `{name}.yml` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// - `ConfigSourceError::ReadOnly` if the file cannot be written back
+    fn document_path(&self, id: &DocumentId) -> Result<PathBuf, ConfigSourceError> {
+        let is_name =
+            |s: &str| !s.is_empty() && !s.starts_with('.') && !s.contains(['/', '\\', '\0']);
+        if !is_name(&id.application) || !is_name(&id.profile) {
+            return Err(ConfigSourceError::InvalidConfig(format!(
+                "invalid document name: {}",
+                id
+            )));
+        }
+        let name = if id.profile == DEFAULT_PROFILE {
+            id.application.clone()
+        } else {
+            format!("{}-{}", id.application, id.profile)
+        };
+
+        let dir = self.label_dir(&id.label)?;
+        let existing = self.formats.parsers().find_map(|format| {
+            format.file_extensions().iter().find_map(|ext| {
+                [
+                    format!("{}.{}", name, ext),
+                    format!("{}.sops.{}", name, ext),
+                ]
+                .into_iter()
+                .find(|file| dir.join(file).exists())
+                .map(|file| (file, ext.as_str()))
+            })
+        });
+        match existing {
+            None => Ok(dir.join(format!("{}.yml", name))),
+            Some((file, _)) if file.contains(".sops.") => Err(ConfigSourceError::ReadOnly(
+                format!("{} is encrypted with SOPS", file),
+            )),
+            Some((file, ext)) if YAML_EXTENSIONS.contains(&ext) => Ok(dir.join(file)),
+            Some((file, _)) => Err(ConfigSourceError::ReadOnly(format!(
+                "{} is not a YAML file",
+                file
+            ))),
+        }
+    }
+
+    /// Applies a change to a document and returns it as stored.
+    async fn write(
+        &self,
+        id: &DocumentId,
+        expected_version: Option<&str>,
+        write: Write<'_>,
+    ) -> Result<StoredProperties, ConfigSourceError> {
+        let _guard = self.write_lock.lock().await;
+        let path = self.document_path(id)?;
+        let current = read_document(&path)?;
+        check_version(id, expected_version, &current.version)?;
+
+        let properties = match write {
+            Write::Put(properties) => properties.clone(),
+            Write::Delete(key) => {
+                let mut properties = current.properties;
+                let mut path = ConfigPath::parse(key)
+                    .map_err(|_| ConfigSourceError::KeyNotFound(key.to_string()))?;
+                if properties.remove_path(&path).is_none() {
+                    return Err(ConfigSourceError::KeyNotFound(key.to_string()));
+                }
+                // Maps left empty go too, as in the flat backends.
+                path.pop();
+                while !path.is_root()
+                    && matches!(properties.get_path(&path), Some(ConfigValue::Object(map)) if map.is_empty())
+                {
+                    properties.remove_path(&path);
+                    path.pop();
+                }
+                properties
+            },
+        };
+
+        if properties.is_empty() {
+            match fs::remove_file(&path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {},
+            }
+        } else {
+            let content = YamlFormat
+                .serialize(&properties)
+                .map_err(|e| ConfigSourceError::InvalidConfig(e.to_string()))?;
+            write_atomically(&path, content.as_bytes())?;
+        }
+
+        let stored = read_document(&path)?;
+        info!("Wrote {} (version {})", path.display(), stored.version);
+        Ok(stored)
+    }
+
+    fn resolver(&self, dir: PathBuf) -> ConfigFileResolver {
+        let resolver = ConfigFileResolver::new(dir, self.search_paths.clone())
+            .with_format_registry(Arc::clone(&self.formats))
+            .with_duplicate_keys(self.duplicate_keys)
+            .with_source_scheme("file");
+
+        match &self.sops {
+            Some(sops) => resolver.with_sops(Arc::clone(sops)),
+            None => resolver,
+        }
+    }
+}
+
+#[async_trait]
+impl ConfigSource for FilesystemBackend {
+    async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+        let label = query.effective_label(&self.default_label);
+        let dir = self.label_dir(label)?;
+
+        debug!("Fetching config for {} from {}", query, dir.display());
+
+        let (sources, warnings) = self.resolver(dir).resolve_with_warnings(query, label)?;
+
+        let mut result = ConfigResult::new(query.application(), query.profiles().to_vec(), label);
+        result.add_property_sources(sources);
+        result.add_warnings(warnings);
+
+        Ok(result)
+    }
+
+    async fn schema(
+        &self,
+        query: &ConfigQuery,
+    ) -> Result<Option<Arc<SchemaValidator>>, ConfigSourceError> {
+        let label = query.effective_label(&self.default_label);
+        let dir = self.label_dir(label)?;
+
+        Ok(self
+            .resolver(dir)
+            .load_schema(query.application())?
+            .map(Arc::new))
+    }
+
+    async fn health_check(&self) -> Result<(), ConfigSourceError> {
+        if self.root.is_dir() {
+            Ok(())
+        } else {
+            Err(ConfigSourceError::unavailable(format!(
+                "{} is not a directory",
+                self.root.display()
+            )))
+        }
+    }
+
+    fn name(&self) -> &str {
+        "native"
+    }
+
+    fn default_label(&self) -> &str {
+        &self.default_label
+    }
+
+    fn store(&self) -> Option<&dyn ConfigStore> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl ConfigStore for FilesystemBackend {
+    async fn get_properties(&self, id: &DocumentId) -> Result<StoredProperties, ConfigSourceError> {
+        read_document(&self.document_path(id)?)
+    }
+
+    async fn put_properties(
+        &self,
+        id: &DocumentId,
+        properties: &ConfigMap,
+        expected_version: Option<&str>,
+    ) -> Result<StoredProperties, ConfigSourceError> {
+        self.write(id, expected_version, Write::Put(properties))
+            .await
+    }
+
+    async fn delete_key(
+        &self,
+        id: &DocumentId,
+        key: &str,
+        expected_version: Option<&str>,
+    ) -> Result<StoredProperties, ConfigSourceError> {
+        self.write(id, expected_version, Write::Delete(key)).await
+    }
+}
+
+/// Reads a document file; a missing file is an empty document.
+fn read_document(path: &Path) -> Result<StoredProperties, ConfigSourceError> {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let text = String::from_utf8_lossy(&content);
+    let properties = if text.trim().is_empty() {
+        ConfigMap::new()
+    } else {
+        YamlFormat
+            .parse(&text)
+            .map_err(|e| ConfigSourceError::parse(path, e.to_string()))?
+    };
+    if properties.as_inner().contains_key(SOPS_KEY) {
+        return Err(ConfigSourceError::ReadOnly(format!(
+            "{} is encrypted with SOPS",
+            path.display()
+        )));
+    }
+    Ok(StoredProperties {
+        properties,
+        version: content_version(&content),
+    })
+}
+
+/// Replaces a file through a temporary file renamed over it, so readers
+/// never see it half written.
+fn write_atomically(path: &Path, content: &[u8]) -> io::Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temporary = path.with_file_name(format!(".{}.tmp", name));
+    fs::write(&temporary, content)?;
+    fs::rename(&temporary, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temporary);
+    })
+}
+
+impl std::fmt::Debug for FilesystemBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FilesystemBackend")
+            .field("root", &self.root)
+            .field("search_paths", &self.search_paths)
+            .field("default_label", &self.default_label)
+            .field("watching", &self.watcher.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::time::Duration;
+
+    use tempfile::TempDir;
+
+    fn create_config_dir() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("application.yml"),
+            "server:\n  port: 8080\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("payments-prod.yml"), "db:\n  pool: 10\n").unwrap();
+        fs::create_dir(dir.path().join("release-2.4")).unwrap();
+        fs::write(
+            dir.path().join("release-2.4").join("payments-prod.yml"),
+            "db:\n  pool: 5\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_fetch_default_label_from_root() {
+        let dir = create_config_dir();
+        let backend = FilesystemBackend::new(dir.path());
+
+        let result = backend
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap();
+
+        assert_eq!(result.label(), "main");
+        assert_eq!(result.len(), 2);
+        assert_eq!(
+            result.property_sources()[0].name,
+            "file:main:payments-prod.yml"
+        );
+        assert_eq!(
+            result.merged().get("db.pool").and_then(|v| v.as_i64()),
+            Some(10)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_labels_map_to_subdirectories() {
+        let dir = create_config_dir();
+        let backend = FilesystemBackend::new(dir.path());
+
+        let query = ConfigQuery::new("payments", vec!["prod"]).with_label_set("release-2.4");
+        let result = backend.fetch(&query).await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result.merged().get("db.pool").and_then(|v| v.as_i64()),
+            Some(5)
+        );
+
+        for label in ["release-3.0", "../etc", "/etc"] {
+            let query = ConfigQuery::new("payments", vec!["prod"]).with_label_set(label);
+            assert!(matches!(
+                backend.fetch(&query).await,
+                Err(ConfigSourceError::LabelNotFound(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_edits_are_served_and_reported() {
+        let dir = create_config_dir();
+        let backend = FilesystemBackend::new(dir.path()).watch().unwrap();
+        let mut changes = backend.changes().unwrap();
+
+        fs::write(dir.path().join("payments-prod.yml"), "db:\n  pool: 20\n").unwrap();
+        tokio::time::timeout(Duration::from_secs(5), changes.changed())
+            .await
+            .expect("no change detected")
+            .unwrap();
+
+        let result = backend
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.merged().get("db.pool").and_then(|v| v.as_i64()),
+            Some(20)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_store_writes_yaml_files() {
+        let dir = create_config_dir();
+        let backend = FilesystemBackend::new(dir.path());
+        let store = backend.store().unwrap();
+        let id = DocumentId::new("payments", "prod", "main");
+
+        let current = store.get_properties(&id).await.unwrap();
+        assert_eq!(
+            current.properties.get("db.pool").and_then(|v| v.as_i64()),
+            Some(10)
+        );
+
+        let properties =
+            ConfigMap::from_json(r#"{"db": {"pool": 25, "hosts": ["db-1"]}, "owner": "team-a"}"#)
+                .unwrap();
+        let written = store
+            .put_properties(&id, &properties, Some(&current.version))
+            .await
+            .unwrap();
+        assert_ne!(written.version, current.version);
+        assert_eq!(written.properties, properties);
+        let merged = backend
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap()
+            .merged();
+        assert_eq!(merged.get("db.pool").and_then(|v| v.as_i64()), Some(25));
+        assert_eq!(
+            merged.get("db.hosts[0]").and_then(|v| v.as_str()),
+            Some("db-1")
+        );
+
+        assert!(matches!(
+            store
+                .put_properties(&id, &properties, Some(&current.version))
+                .await,
+            Err(ConfigSourceError::VersionConflict { .. })
+        ));
+
+        let deleted = store
+            .delete_key(&id, "db.hosts", Some(&written.version))
+            .await
+            .unwrap();
+        assert!(deleted.properties.get("db.hosts").is_none());
+        let deleted = store.delete_key(&id, "db.pool", None).await.unwrap();
+        assert!(deleted.properties.get("db").is_none());
+        assert!(matches!(
+            store.delete_key(&id, "db", None).await,
+            Err(ConfigSourceError::KeyNotFound(_))
+        ));
+
+        // The default profile is the application file; an empty document
+        // removes it.
+        let id = DocumentId::new("payments", "default", "release-2.4");
+        store.put_properties(&id, &properties, None).await.unwrap();
+        let file = dir.path().join("release-2.4").join("payments.yml");
+        assert!(file.is_file());
+        store
+            .put_properties(&id, &ConfigMap::new(), None)
+            .await
+            .unwrap();
+        assert!(!file.exists());
+    }
+
+    #[tokio::test]
+    async fn test_store_refuses_other_files() {
+        let dir = create_config_dir();
+        fs::write(dir.path().join("billing.properties"), "db.pool=5\n").unwrap();
+        fs::write(dir.path().join("billing-prod.sops.yml"), "db: x\n").unwrap();
+        fs::write(
+            dir.path().join("billing-dev.yml"),
+            "db: x\nsops:\n  version: 3.8.1\n",
+        )
+        .unwrap();
+        let backend = FilesystemBackend::new(dir.path());
+        let store = backend.store().unwrap();
+
+        for profile in ["default", "prod", "dev"] {
+            let id = DocumentId::new("billing", profile, "main");
+            assert!(
+                matches!(
+                    store.put_properties(&id, &ConfigMap::new(), None).await,
+                    Err(ConfigSourceError::ReadOnly(_))
+                ),
+                "{}",
+                profile
+            );
+        }
+        for id in [
+            DocumentId::new("../billing", "prod", "main"),
+            DocumentId::new("billing", "prod", "../main"),
+        ] {
+            assert!(store.get_properties(&id).await.is_err(), "{}", id);
+        }
+        assert_eq!(
+            fs::read_to_string(dir.path().join("billing.properties")).unwrap(),
+            "db.pool=5\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        let dir = create_config_dir();
+        assert!(
+            FilesystemBackend::new(dir.path())
+                .health_check()
+                .await
+                .is_ok()
+        );
+        assert!(
+            FilesystemBackend::new(dir.path().join("missing"))
+                .health_check()
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Change detection for a directory tree.
+//!
+//! The tree is watched with [`notify`](notify_debouncer_mini::notify)
+//! (inotify on Linux, FSEvents on macOS, polling where nothing better
+//! exists). Bursts of events (an editor saving, a `git pull` into the
+//! directory) are coalesced into a single change.
+
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{DebounceEventResult, Debouncer, new_debouncer};
+use tokio::sync::watch;
+
+/// How long to wait for more events before reporting a change.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Watches a directory tree and counts the changes made to it.
+///
+/// The watcher stops when dropped.
+pub struct DirectoryWatcher {
+    changes: watch::Receiver<u64>,
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+impl DirectoryWatcher {
+    /// Starts watching `root` and every directory below it.
+    pub fn start(root: impl Into<PathBuf>) -> io::Result<Self> {
+        let root = root.into();
+        if !root.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not a directory", root.display()),
+            ));
+        }
+
+        let (tx, changes) = watch::channel(0u64);
+        let mut debouncer = new_debouncer(DEBOUNCE, {
+            let root = root.clone();
+            move |events: DebounceEventResult| match events {
+                Ok(_) => {
+                    tx.send_modify(|count| *count += 1);
+                    tracing::debug!("Change detected under {}", root.display());
+                },
+                Err(e) => tracing::warn!("Error watching {} for changes: {}", root.display(), e),
+            }
+        })
+        .map_err(io::Error::other)?;
+        debouncer
+            .watcher()
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(io::Error::other)?;
+
+        Ok(Self {
+            changes,
+            _debouncer: debouncer,
+        })
+    }
+
+    /// Returns a receiver that is notified after every change.
+    ///
+    /// The value is the number of changes seen so far.
+    pub fn changes(&self) -> watch::Receiver<u64> {
+        self.changes.clone()
+    }
+}
+
+impl std::fmt::Debug for DirectoryWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DirectoryWatcher")
+            .field("changes", &*self.changes.borrow())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Waits for a change and returns the number of changes seen so far.
+    async fn next_change(changes: &mut watch::Receiver<u64>) -> u64 {
+        tokio::time::timeout(Duration::from_secs(5), changes.changed())
+            .await
+            .expect("no change detected")
+            .unwrap();
+        *changes.borrow_and_update()
+    }
+
+    /// Waits until no change has been reported for a while, so events of
+    /// one step are not taken for those of the next; one operation may be
+    /// reported in more than one batch.
+    async fn settle(changes: &mut watch::Receiver<u64>) -> u64 {
+        while let Ok(changed) = tokio::time::timeout(DEBOUNCE * 5, changes.changed()).await {
+            changed.unwrap();
+        }
+        *changes.borrow_and_update()
+    }
+
+    #[tokio::test]
+    async fn test_detects_changes_in_new_subdirectories() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("application.yml"), "a: 1").unwrap();
+        let watcher = DirectoryWatcher::start(dir.path()).unwrap();
+        let mut changes = watcher.changes();
+
+        std::fs::write(dir.path().join("application.yml"), "a: 2").unwrap();
+        assert!(next_change(&mut changes).await >= 1);
+        let seen = settle(&mut changes).await;
+
+        std::fs::create_dir(dir.path().join("staging")).unwrap();
+        assert!(next_change(&mut changes).await > seen);
+        let seen = settle(&mut changes).await;
+
+        std::fs::write(dir.path().join("staging").join("app.yml"), "a: 3").unwrap();
+        assert!(next_change(&mut changes).await > seen);
+    }
+
+    #[test]
+    fn test_missing_root_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(DirectoryWatcher::start(dir.path().join("missing")).is_err());
+    }
+}
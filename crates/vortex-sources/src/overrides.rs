@@ -0,0 +1,624 @@
+//! Emergency overrides on top of any configuration source.
+//!
+//! [`OverrideSource`] wraps another source and puts the overrides in front
+//! of what it serves, as the highest-priority property sources. Overrides
+//! come from environment variables of the server and from a mounted
+//! directory, so a value can be changed without a commit to the backend.
+//!
+//! Environment variables are named `{prefix}{APP}__{PROFILE}__{KEY}`:
+//!
+//! ```text
+//! VORTEX_OVERRIDE_PAYMENTS__PROD__SERVER_PORT=9090
+//! VORTEX_OVERRIDE_APPLICATION__DEFAULT__LOGGING_LEVEL_ROOT=WARN
+//! ```
+//!
+//! The directory holds one file per key, its contents being the value, in
+//! one subdirectory per application and profile (a Kubernetes ConfigMap
+//! mounted per application and profile has this layout):
+//!
+//! ```text
+//! /etc/vortex/overrides/
+//! ├── payments/
+//! │   └── prod/
+//! │       └── server.port      # 9090
+//! └── application/
+//!     └── default/
+//!         └── LOGGING_LEVEL_ROOT
+//! ```
+//!
+//! The application `application` and the profile `default` match every
+//! application and profile. Application and profile names match ignoring
+//! case, with any character that cannot appear in a variable name written
+//! as `_` (`PAYMENTS_API` matches `payments-api`).
+//!
+//! Keys follow Spring's relaxed binding: a key without lowercase letters is
+//! an environment-style name, so `SERVER_PORT` is `server.port` and
+//! `HOSTS_0` is `hosts[0]`. A key that only differs from one the backend
+//! serves in case, dashes or underscores replaces it:
+//! `SERVER_MAXHTTPHEADERSIZE` overrides `server.max-http-header-size`.
+//!
+//! Variables are read once, when the source is built; the directory is read
+//! on every fetch, and [`watch`](OverrideSource::watch) reports its changes.
+//! Writes go to the wrapped source, under the overrides.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::watch as channel;
+use tracing::{debug, warn};
+use vortex_core::format::env::property_name;
+use vortex_core::format::spring::flatten_config_map;
+use vortex_core::validation::SchemaValidator;
+use vortex_core::{ConfigMap, ConfigValue, PropertySource};
+use vortex_git::{ConfigQuery, ConfigResult, ConfigSource, ConfigSourceError, ConfigStore};
+
+use crate::native::DirectoryWatcher;
+use crate::sql::insert_nested;
+
+/// Default prefix of override variables.
+pub const DEFAULT_PREFIX: &str = "VORTEX_OVERRIDE_";
+
+/// Application whose overrides apply to every application.
+const SHARED_APPLICATION: &str = "application";
+
+/// Profile whose overrides apply to every profile.
+const DEFAULT_PROFILE: &str = "default";
+
+/// Separator between the application, profile and key of a variable.
+const SEPARATOR: &str = "__";
+
+/// Largest override file read; bigger files are skipped.
+const MAX_FILE_SIZE: u64 = 64 * 1024;
+
+/// One overridden key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Override {
+    /// Application, as written in the variable or directory name.
+    pub application: String,
+    /// Profile, as written in the variable or directory name.
+    pub profile: String,
+    /// Property name, after relaxed binding (`server.port`).
+    pub key: String,
+    /// The value.
+    pub value: String,
+}
+
+impl Override {
+    fn new(application: &str, profile: &str, key: &str, value: String) -> Self {
+        Self {
+            application: application.to_string(),
+            profile: profile.to_string(),
+            key: property_name(key),
+            value,
+        }
+    }
+
+    /// Returns whether the override applies to this application and profile
+    /// of a query.
+    fn applies_to(&self, application: &str, profile: &str) -> bool {
+        variable_name(&self.application) == variable_name(application)
+            && variable_name(&self.profile) == variable_name(profile)
+    }
+}
+
+/// A configuration source serving another one with overrides on top.
+pub struct OverrideSource {
+    /// The source being overridden.
+    inner: Arc<dyn ConfigSource>,
+    /// Overrides read from environment variables.
+    variables: Vec<Override>,
+    /// Directory overrides are read from on every fetch.
+    directory: Option<PathBuf>,
+    /// Change detection for the directory, when enabled.
+    watcher: Option<DirectoryWatcher>,
+}
+
+impl OverrideSource {
+    /// Serves `inner` without overrides, until some are added.
+    pub fn new(inner: Arc<dyn ConfigSource>) -> Self {
+        Self {
+            inner,
+            variables: Vec::new(),
+            directory: None,
+            watcher: None,
+        }
+    }
+
+    /// Reads overrides from the environment variables of the process that
+    /// start with `prefix`.
+    pub fn with_environment(self, prefix: &str) -> Self {
+        self.with_variables(prefix, std::env::vars())
+    }
+
+    /// Reads overrides from the given variables that start with `prefix`.
+    ///
+    /// Variables not named `{prefix}{APP}__{PROFILE}__{KEY}` are skipped
+    /// with a warning.
+    pub fn with_variables(
+        mut self,
+        prefix: &str,
+        variables: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        for (name, value) in variables {
+            let Some(rest) = name.strip_prefix(prefix) else {
+                continue;
+            };
+            match rest.split(SEPARATOR).collect::<Vec<_>>()[..] {
+                [application, profile, key]
+                    if !application.is_empty() && !profile.is_empty() && !key.is_empty() =>
+                {
+                    self.variables
+                        .push(Override::new(application, profile, key, value));
+                },
+                _ => warn!(
+                    "Ignoring override variable {}: expected {}APP__PROFILE__KEY",
+                    name, prefix
+                ),
+            }
+        }
+        self
+    }
+
+    /// Reads overrides from `directory`, laid out as
+    /// `{application}/{profile}/{key}`.
+    ///
+    /// A missing directory has no overrides.
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// Starts watching the override directory for changes.
+    ///
+    /// See [`changes`](Self::changes).
+    pub fn watch(mut self) -> Result<Self, ConfigSourceError> {
+        let Some(directory) = &self.directory else {
+            return Err(ConfigSourceError::unavailable(
+                "no override directory to watch",
+            ));
+        };
+        self.watcher = Some(DirectoryWatcher::start(directory)?);
+        Ok(self)
+    }
+
+    /// Returns the overrides read from environment variables.
+    pub fn variables(&self) -> &[Override] {
+        &self.variables
+    }
+
+    /// Returns the override directory, if any.
+    pub fn directory(&self) -> Option<&Path> {
+        self.directory.as_deref()
+    }
+
+    /// Returns a receiver notified whenever a file in the override
+    /// directory changes, or `None` if the source is not watching.
+    pub fn changes(&self) -> Option<channel::Receiver<u64>> {
+        self.watcher.as_ref().map(DirectoryWatcher::changes)
+    }
+
+    /// Returns every override: the variables, then the directory's, which
+    /// take precedence.
+    async fn overrides(&self) -> Vec<Override> {
+        let mut overrides = self.variables.clone();
+        if let Some(directory) = &self.directory {
+            match read_directory(directory).await {
+                Ok(found) => overrides.extend(found),
+                Err(e) => warn!(
+                    "Cannot read override directory {}: {}",
+                    directory.display(),
+                    e
+                ),
+            }
+        }
+        overrides
+    }
+
+    /// Builds the override property sources for a query, highest precedence
+    /// first.
+    ///
+    /// Precedence follows the backends: the application's own overrides
+    /// beat shared ones, and later profiles beat earlier ones.
+    fn property_sources(
+        query: &ConfigQuery,
+        overrides: &[Override],
+        served: &ConfigResult,
+    ) -> Vec<PropertySource> {
+        if overrides.is_empty() {
+            return Vec::new();
+        }
+
+        let mut applications = vec![SHARED_APPLICATION];
+        if query.application() != SHARED_APPLICATION {
+            applications.push(query.application());
+        }
+        let mut profiles = vec![DEFAULT_PROFILE];
+        profiles.extend(
+            query
+                .profiles()
+                .iter()
+                .map(String::as_str)
+                .filter(|p| *p != DEFAULT_PROFILE),
+        );
+
+        let known = served_keys(served);
+
+        // Lowest precedence first, reversed at the end like the backends.
+        let mut sources = Vec::new();
+        for application in &applications {
+            for profile in &profiles {
+                let mut config = ConfigMap::new();
+                for entry in overrides
+                    .iter()
+                    .filter(|o| o.applies_to(application, profile))
+                {
+                    let key = known
+                        .get(&canonical_name(&entry.key))
+                        .map_or(entry.key.as_str(), String::as_str);
+                    insert_nested(&mut config, key, ConfigValue::String(entry.value.clone()));
+                }
+                if !config.is_empty() {
+                    let mut source = PropertySource::new(
+                        format!("overrides:{}-{}", application, profile),
+                        config,
+                    );
+                    source.priority = i32::MAX;
+                    sources.push(source);
+                }
+            }
+        }
+        sources.reverse();
+        sources
+    }
+}
+
+/// Returns the keys a result serves, by their relaxed form.
+fn served_keys(served: &ConfigResult) -> HashMap<String, String> {
+    let mut keys = HashMap::new();
+    for source in served.property_sources() {
+        for key in flatten_config_map(&source.config).into_keys() {
+            keys.entry(canonical_name(&key)).or_insert(key);
+        }
+    }
+    keys
+}
+
+/// Reads the overrides in `{directory}/{application}/{profile}/{key}`.
+///
+/// Hidden entries are skipped, which leaves out the `..data` links of
+/// Kubernetes volumes.
+async fn read_directory(directory: &Path) -> std::io::Result<Vec<Override>> {
+    let mut overrides = Vec::new();
+    for (application, application_dir) in subdirectories(directory).await? {
+        for (profile, profile_dir) in subdirectories(&application_dir).await? {
+            let mut entries = tokio::fs::read_dir(&profile_dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let Some(key) = visible_name(&entry.file_name()) else {
+                    continue;
+                };
+                let path = entry.path();
+                let metadata = tokio::fs::metadata(&path).await?;
+                if !metadata.is_file() {
+                    continue;
+                }
+                if metadata.len() > MAX_FILE_SIZE {
+                    warn!("Ignoring override file {}: too large", path.display());
+                    continue;
+                }
+                match tokio::fs::read_to_string(&path).await {
+                    Ok(value) => {
+                        let value = value.strip_suffix('\n').unwrap_or(&value).to_string();
+                        overrides.push(Override::new(&application, &profile, &key, value));
+                    },
+                    Err(e) => warn!("Ignoring override file {}: {}", path.display(), e),
+                }
+            }
+        }
+    }
+    Ok(overrides)
+}
+
+/// Lists the visible subdirectories of a directory, by name.
+///
+/// A missing directory has none.
+async fn subdirectories(directory: &Path) -> std::io::Result<Vec<(String, PathBuf)>> {
+    let mut entries = match tokio::fs::read_dir(directory).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut found = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let Some(name) = visible_name(&entry.file_name()) else {
+            continue;
+        };
+        let path = entry.path();
+        if tokio::fs::metadata(&path).await?.is_dir() {
+            found.push((name, path));
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+fn visible_name(name: &std::ffi::OsStr) -> Option<String> {
+    name.to_str()
+        .filter(|name| !name.starts_with('.'))
+        .map(str::to_string)
+}
+
+/// Writes a name the way it appears in a variable name: uppercase, with
+/// `_` for anything but letters and digits.
+fn variable_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Reduces a property name to its relaxed form: lowercase, without dashes
+/// or underscores.
+fn canonical_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[async_trait]
+impl ConfigSource for OverrideSource {
+    async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+        let result = self.inner.fetch(query).await?;
+
+        let overrides = self.overrides().await;
+        let mut sources = Self::property_sources(query, &overrides, &result);
+        if sources.is_empty() {
+            return Ok(result);
+        }
+
+        debug!(
+            "Applying {} override property sources to {}",
+            sources.len(),
+            query
+        );
+        sources.extend(result.property_sources().iter().cloned());
+        Ok(result.with_property_sources(sources))
+    }
+
+    async fn schema(
+        &self,
+        query: &ConfigQuery,
+    ) -> Result<Option<Arc<SchemaValidator>>, ConfigSourceError> {
+        self.inner.schema(query).await
+    }
+
+    async fn health_check(&self) -> Result<(), ConfigSourceError> {
+        self.inner.health_check().await
+    }
+
+    async fn backend_health(&self) -> Vec<(String, Result<(), ConfigSourceError>)> {
+        self.inner.backend_health().await
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn refresh(&self) -> Result<(), ConfigSourceError> {
+        self.inner.refresh().await
+    }
+
+    fn supports_refresh(&self) -> bool {
+        self.inner.supports_refresh()
+    }
+
+    fn default_label(&self) -> &str {
+        self.inner.default_label()
+    }
+
+    fn store(&self) -> Option<&dyn ConfigStore> {
+        self.inner.store()
+    }
+}
+
+impl std::fmt::Debug for OverrideSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OverrideSource")
+            .field("inner", &self.inner.name())
+            .field("variables", &self.variables.len())
+            .field("directory", &self.directory)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serves one property source with the given values.
+    struct Fixed(Vec<(&'static str, i64)>);
+
+    #[async_trait]
+    impl ConfigSource for Fixed {
+        async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+            let mut config = ConfigMap::new();
+            for (key, value) in &self.0 {
+                insert_nested(&mut config, key, ConfigValue::Integer(*value));
+            }
+            let mut result =
+                ConfigResult::new(query.application(), query.profiles().to_vec(), "main");
+            result.add_property_source(PropertySource::new("fixed", config));
+            Ok(result)
+        }
+
+        async fn health_check(&self) -> Result<(), ConfigSourceError> {
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "fixed"
+        }
+    }
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(variable_name("payments-api"), "PAYMENTS_API");
+        assert_eq!(
+            canonical_name("server.max-http-header-size"),
+            canonical_name("server.maxHttpHeaderSize")
+        );
+    }
+
+    #[test]
+    fn test_parse_variables() {
+        let source = OverrideSource::new(Arc::new(Fixed(vec![]))).with_variables(
+            DEFAULT_PREFIX,
+            vars(&[
+                ("VORTEX_OVERRIDE_PAYMENTS__PROD__SERVER_PORT", "9090"),
+                ("VORTEX_OVERRIDE_PAYMENTS__SERVER_PORT", "1"),
+                ("VORTEX_OVERRIDE_A__B__C__D", "1"),
+                ("VORTEX_OVERRIDES_DIR", "/tmp"),
+                ("PATH", "/bin"),
+            ]),
+        );
+
+        assert_eq!(
+            source.variables(),
+            [Override {
+                application: "PAYMENTS".into(),
+                profile: "PROD".into(),
+                key: "server.port".into(),
+                value: "9090".into(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_variables_override_the_backend() {
+        let inner = Fixed(vec![
+            ("server.port", 8080),
+            ("server.max-http-header-size", 8),
+        ]);
+        let source = OverrideSource::new(Arc::new(inner)).with_variables(
+            DEFAULT_PREFIX,
+            vars(&[
+                ("VORTEX_OVERRIDE_PAYMENTS_API__PROD__SERVER_PORT", "9090"),
+                ("VORTEX_OVERRIDE_PAYMENTS_API__DEFAULT__SERVER_PORT", "9000"),
+                (
+                    "VORTEX_OVERRIDE_APPLICATION__DEFAULT__SERVER_MAXHTTPHEADERSIZE",
+                    "16",
+                ),
+                ("VORTEX_OVERRIDE_APPLICATION__DEV__SERVER_PORT", "1"),
+                ("VORTEX_OVERRIDE_ORDERS__DEFAULT__SERVER_PORT", "2"),
+            ]),
+        );
+
+        let result = source
+            .fetch(&ConfigQuery::new("payments-api", vec!["prod"]))
+            .await
+            .unwrap();
+        let names: Vec<&str> = result
+            .property_sources()
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "overrides:payments-api-prod",
+                "overrides:payments-api-default",
+                "overrides:application-default",
+                "fixed"
+            ]
+        );
+
+        let merged = result.merged();
+        assert_eq!(merged.get("server.port").unwrap().as_str(), Some("9090"));
+        assert_eq!(
+            merged.get("server.max-http-header-size").unwrap().as_str(),
+            Some("16")
+        );
+
+        // Other applications only get the shared overrides.
+        let result = source
+            .fetch(&ConfigQuery::new("billing", vec!["dev"]))
+            .await
+            .unwrap();
+        let names: Vec<&str> = result
+            .property_sources()
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "overrides:application-dev",
+                "overrides:application-default",
+                "fixed"
+            ]
+        );
+        assert_eq!(
+            result.merged().get("server.port").unwrap().as_str(),
+            Some("1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_directory_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let prod = dir.path().join("payments/prod");
+        std::fs::create_dir_all(&prod).unwrap();
+        std::fs::write(prod.join("server.port"), "9191\n").unwrap();
+        std::fs::write(prod.join("FEATURE_CHECKOUT"), "off").unwrap();
+        std::fs::create_dir_all(prod.join("..data")).unwrap();
+        std::fs::write(prod.join(".hidden"), "x").unwrap();
+
+        let source = OverrideSource::new(Arc::new(Fixed(vec![("server.port", 8080)])))
+            .with_variables(
+                DEFAULT_PREFIX,
+                vars(&[("VORTEX_OVERRIDE_PAYMENTS__PROD__SERVER_PORT", "9090")]),
+            )
+            .with_directory(dir.path())
+            .watch()
+            .unwrap();
+        let mut changes = source.changes().unwrap();
+
+        let query = ConfigQuery::new("payments", vec!["prod"]);
+        let result = source.fetch(&query).await.unwrap();
+        assert_eq!(result.len(), 2);
+        let merged = result.merged();
+        assert_eq!(merged.get("server.port").unwrap().as_str(), Some("9191"));
+        assert_eq!(
+            merged.get("feature.checkout").unwrap().as_str(),
+            Some("off")
+        );
+        assert!(merged.get(".hidden").is_none());
+
+        // Removing the file is seen on the next fetch, and reported.
+        std::fs::remove_file(prod.join("server.port")).unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), changes.changed())
+            .await
+            .unwrap()
+            .unwrap();
+        let merged = source.fetch(&query).await.unwrap().merged();
+        assert_eq!(merged.get("server.port").unwrap().as_str(), Some("9090"));
+
+        // A missing directory has no overrides.
+        let source = OverrideSource::new(Arc::new(Fixed(vec![("server.port", 8080)])))
+            .with_directory(dir.path().join("missing"));
+        assert_eq!(source.fetch(&query).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,151 @@
+//! A small connection pool, used by the SQLite client.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Opens connections for a [`Pool`].
+#[async_trait]
+pub(crate) trait Connect: Send + Sync + 'static {
+    /// The connection type.
+    type Connection: Send + 'static;
+
+    /// Why a connection could not be opened.
+    type Error;
+
+    /// Opens a new connection.
+    async fn connect(&self) -> Result<Self::Connection, Self::Error>;
+}
+
+/// Hands out up to `size` connections, opened on first use and reused after.
+pub(crate) struct Pool<M: Connect> {
+    inner: Arc<Inner<M>>,
+}
+
+struct Inner<M: Connect> {
+    manager: M,
+    idle: Mutex<Vec<M::Connection>>,
+    permits: Arc<Semaphore>,
+}
+
+impl<M: Connect> Pool<M> {
+    /// Creates a pool of at most `size` connections.
+    pub fn new(manager: M, size: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                manager,
+                idle: Mutex::new(Vec::new()),
+                permits: Arc::new(Semaphore::new(size)),
+            }),
+        }
+    }
+
+    /// Waits for a free connection, opening one if none is idle.
+    pub async fn get(&self) -> Result<Pooled<M>, M::Error> {
+        let permit = Arc::clone(&self.inner.permits)
+            .acquire_owned()
+            .await
+            .expect("the pool's semaphore is never closed");
+
+        let idle = self.inner.idle.lock().expect("pool poisoned").pop();
+        let connection = match idle {
+            Some(connection) => connection,
+            None => self.inner.manager.connect().await?,
+        };
+
+        Ok(Pooled {
+            pool: Arc::clone(&self.inner),
+            connection: Some(connection),
+            _permit: permit,
+        })
+    }
+}
+
+/// A connection borrowed from a [`Pool`], returned to it on drop.
+pub(crate) struct Pooled<M: Connect> {
+    pool: Arc<Inner<M>>,
+    connection: Option<M::Connection>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl<M: Connect> Pooled<M> {
+    /// Closes the connection instead of returning it, e.g. after an I/O
+    /// error left it in an unknown state.
+    #[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+    pub fn discard(mut self) {
+        self.connection = None;
+    }
+}
+
+impl<M: Connect> Deref for Pooled<M> {
+    type Target = M::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        self.connection.as_ref().expect("connection taken")
+    }
+}
+
+impl<M: Connect> DerefMut for Pooled<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.connection.as_mut().expect("connection taken")
+    }
+}
+
+impl<M: Connect> Drop for Pooled<M> {
+    fn drop(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            self.pool
+                .idle
+                .lock()
+                .expect("pool poisoned")
+                .push(connection);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Connections are numbers, counting the connections opened.
+    struct Counter(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl Connect for Counter {
+        type Connection = usize;
+        type Error = ();
+
+        async fn connect(&self) -> Result<usize, ()> {
+            Ok(self.0.fetch_add(1, Ordering::SeqCst))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reuses_and_limits_connections() {
+        let opened = Arc::new(AtomicUsize::new(0));
+        let pool = Pool::new(Counter(Arc::clone(&opened)), 2);
+
+        let first = pool.get().await.unwrap();
+        let second = pool.get().await.unwrap();
+        assert_eq!((*first, *second), (0, 1));
+
+        // A third caller waits for a connection to come back.
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), pool.get())
+                .await
+                .is_err()
+        );
+        drop(first);
+        assert_eq!(*pool.get().await.unwrap(), 0);
+
+        // A discarded connection is replaced by a new one.
+        second.discard();
+        let _both = (pool.get().await.unwrap(), pool.get().await.unwrap());
+        assert_eq!(opened.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,987 @@
+//! Redis backend.
+//!
+//! Properties are fields of hashes named `{application}:{profile}:{label}`:
+//!
+//! ```text
+//! HSET application:default:main server.port 8080
+//! HSET payments:prod:main db.pool.size 20 db.hosts[0] db-1
+//! ```
+//!
+//! As in the SQL backend, the `default` profile is always included and
+//! `application` hashes apply to every application, so a request for
+//! `payments/prod` yields, highest precedence first: `payments:prod:main`,
+//! `payments:default:main`, `application:prod:main`,
+//! `application:default:main`. Fields are dotted paths nested like
+//! `.properties` files; values are strings.
+//!
+//! [`watch`](RedisBackend::watch) subscribes to keyspace notifications for
+//! the hashes (enabled on the server with `notify-keyspace-events Kgh`) and
+//! to a channel writers can publish to, and reports both on
+//! [`changes`](RedisBackend::changes).
+//!
+//! The backend is writable: documents are written whole in a
+//! `WATCH`/`MULTI` transaction, with string values, and the hash name is
+//! then published on the channel so that other servers watching it
+//! refresh too.
+//!
+//! Commands share one multiplexed connection, which reconnects on its own
+//! when it is lost. URLs are `redis://[[user]:password@]host[:port][/db]`;
+//! `rediss://` connects over TLS, trusting the platform's root
+//! certificates.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use redis::Client;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig, MultiplexedConnection};
+use thiserror::Error;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use vortex_core::{ConfigMap, ConfigValue, PropertySource};
+use vortex_git::{
+    ConfigQuery, ConfigResult, ConfigSource, ConfigSourceError, ConfigStore, DocumentId,
+    StoredProperties, check_version,
+};
+
+use crate::sql::{insert_nested, redact_url};
+use crate::store::{Write, covers, flat_version, flatten, scalar_text};
+
+/// Label used when the request does not name one.
+pub const DEFAULT_LABEL: &str = "main";
+
+/// Channel writers can publish to after changing the hashes.
+pub const DEFAULT_CHANNEL: &str = "vortex:changes";
+
+/// Application whose hashes apply to every application.
+const SHARED_APPLICATION: &str = "application";
+
+/// Profile whose hashes apply to every profile.
+const DEFAULT_PROFILE: &str = "default";
+
+/// Pause after a lost subscription before subscribing again.
+const WATCH_RETRY: Duration = Duration::from_secs(5);
+
+/// Attempts at a write whose hash keeps changing under it.
+const WRITE_ATTEMPTS: usize = 5;
+
+/// Errors talking to Redis.
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum RedisError {
+    /// The Redis URL cannot be used.
+    #[error("invalid Redis URL: {0}")]
+    InvalidUrl(String),
+
+    /// The server cannot be reached or rejected the connection.
+    #[error("cannot connect to Redis: {0}")]
+    Connection(String),
+
+    /// A command failed.
+    #[error("Redis command failed: {0}")]
+    Command(String),
+}
+
+impl From<redis::RedisError> for RedisError {
+    fn from(e: redis::RedisError) -> Self {
+        if e.is_io_error() || e.is_connection_dropped() || e.is_connection_refusal() {
+            RedisError::Connection(e.to_string())
+        } else {
+            RedisError::Command(e.to_string())
+        }
+    }
+}
+
+impl From<RedisError> for ConfigSourceError {
+    fn from(e: RedisError) -> Self {
+        ConfigSourceError::unavailable(e.to_string())
+    }
+}
+
+/// A configuration source backed by Redis hashes.
+pub struct RedisBackend {
+    /// The URL, without its password.
+    url: String,
+    /// Opens the subscription and transaction connections.
+    client: Client,
+    /// Connection for commands.
+    manager: ConnectionManager,
+    /// Database selected by the URL.
+    database: i64,
+    /// Prepended to every hash name.
+    key_prefix: String,
+    /// Label used when the request does not name one.
+    default_label: String,
+    /// Channel writers can publish to.
+    channel: String,
+    /// Number of changes seen so far.
+    changes: Arc<watch::Sender<u64>>,
+    /// Subscription, when enabled.
+    watcher: Option<JoinHandle<()>>,
+}
+
+impl RedisBackend {
+    /// Connects to the server at `url`.
+    pub async fn connect(url: &str) -> Result<Self, RedisError> {
+        let client = Client::open(url)
+            .map_err(|e| RedisError::InvalidUrl(format!("{}: {}", redact_url(url), e)))?;
+        let database = client.get_connection_info().redis_settings().db();
+
+        // Connect now so bad credentials fail at startup. A lost connection
+        // is opened again by the next command, which fails instead of
+        // waiting while the server is down.
+        let manager = client
+            .get_connection_manager_with_config(
+                ConnectionManagerConfig::new().set_number_of_retries(0),
+            )
+            .await
+            .map_err(|e| RedisError::Connection(e.to_string()))?;
+
+        Ok(Self {
+            url: redact_url(url),
+            client,
+            manager,
+            database,
+            key_prefix: String::new(),
+            default_label: DEFAULT_LABEL.to_string(),
+            channel: DEFAULT_CHANNEL.to_string(),
+            changes: Arc::new(watch::Sender::new(0)),
+            watcher: None,
+        })
+    }
+
+    /// Prepends `prefix` to every hash name, e.g. `config:`.
+    pub fn with_key_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.key_prefix = prefix.into();
+        self
+    }
+
+    /// Sets the label used when the request does not name one.
+    pub fn with_default_label(mut self, label: impl Into<String>) -> Self {
+        self.default_label = label.into();
+        self
+    }
+
+    /// Listens on `channel` instead of `vortex:changes`.
+    pub fn with_channel(mut self, channel: impl Into<String>) -> Self {
+        self.channel = channel.into();
+        self
+    }
+
+    /// Subscribes to changes on a dedicated connection, on a background task
+    /// which stops when the backend is dropped.
+    ///
+    /// Call it last, once the backend is configured. Requires a tokio
+    /// runtime.
+    pub fn watch(mut self) -> Self {
+        let client = self.client.clone();
+        let pattern = format!(
+            "__keyspace@{}__:{}*",
+            self.database,
+            glob_escape(&self.key_prefix)
+        );
+        let channel = self.channel.clone();
+        let changes = Arc::clone(&self.changes);
+        let url = self.url.clone();
+
+        self.watcher = Some(tokio::spawn(async move {
+            let mut resubscribing = false;
+            loop {
+                let Err(e) = subscribe(&client, &pattern, &channel, &changes, resubscribing).await;
+                warn!("Lost the Redis subscription on {}: {}", url, e);
+                resubscribing = true;
+                tokio::time::sleep(WATCH_RETRY).await;
+            }
+        }));
+        self
+    }
+
+    /// Returns a receiver notified whenever a hash below the prefix changes
+    /// or a message arrives on the channel.
+    ///
+    /// The value is the number of changes seen so far.
+    pub fn changes(&self) -> watch::Receiver<u64> {
+        self.changes.subscribe()
+    }
+
+    /// Returns the server URL, without its password.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the name of the hash holding a document.
+    fn document_key(&self, id: &DocumentId) -> String {
+        format!(
+            "{}{}:{}:{}",
+            self.key_prefix, id.application, id.profile, id.label
+        )
+    }
+
+    /// Applies a change to a hash and returns it as stored.
+    ///
+    /// The hash is watched while it is read and its version checked, so a
+    /// change made in between aborts the transaction; it is then tried
+    /// again, and fails on the version if one is expected. Once written,
+    /// the hash name is published on the channel for the other servers.
+    async fn write(
+        &self,
+        id: &DocumentId,
+        expected_version: Option<&str>,
+        write: Write<'_>,
+    ) -> Result<StoredProperties, ConfigSourceError> {
+        let key = self.document_key(id);
+        // WATCH applies to the whole connection, so the transaction gets
+        // one of its own, closed when the write is done.
+        let mut connection = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| RedisError::Connection(e.to_string()))?;
+        for _ in 0..WRITE_ATTEMPTS {
+            let Some(fields) = self
+                .try_write(&mut connection, id, &key, expected_version, &write)
+                .await?
+            else {
+                debug!("{} changed while being written, retrying", key);
+                continue;
+            };
+
+            let published = redis::cmd("PUBLISH")
+                .arg(&self.channel)
+                .arg(&key)
+                .exec_async(&mut self.manager.clone())
+                .await;
+            if let Err(e) = published {
+                warn!("Cannot publish the change to {}: {}", key, e);
+            }
+            let stored = stored(fields);
+            info!("Wrote {} (version {})", key, stored.version);
+            self.changes.send_modify(|count| *count += 1);
+            return Ok(stored);
+        }
+        Err(RedisError::Command(format!("{} kept changing while being written", key)).into())
+    }
+
+    /// Runs one attempt of [`write`](Self::write). Returns the fields
+    /// written, or `None` if the hash changed before the transaction ran.
+    async fn try_write(
+        &self,
+        connection: &mut MultiplexedConnection,
+        id: &DocumentId,
+        key: &str,
+        expected_version: Option<&str>,
+        write: &Write<'_>,
+    ) -> Result<Option<Vec<(String, String)>>, ConfigSourceError> {
+        redis::cmd("WATCH")
+            .arg(key)
+            .exec_async(connection)
+            .await
+            .map_err(RedisError::from)?;
+        let mut fields: Vec<(String, String)> = redis::cmd("HGETALL")
+            .arg(key)
+            .query_async(connection)
+            .await
+            .map_err(RedisError::from)?;
+        check_version(
+            id,
+            expected_version,
+            &flat_version(fields.iter().map(|(f, v)| (f.as_str(), v.clone()))),
+        )?;
+
+        match write {
+            Write::Put(properties) => {
+                fields = flatten(properties)
+                    .into_iter()
+                    .filter_map(|(field, value)| Some((field, scalar_text(&value)?)))
+                    .collect();
+            },
+            Write::Delete(deleted) => {
+                let before = fields.len();
+                fields.retain(|(field, _)| !covers(deleted, field));
+                if fields.len() == before {
+                    return Err(ConfigSourceError::KeyNotFound(deleted.to_string()));
+                }
+            },
+        }
+
+        let mut transaction = redis::pipe();
+        transaction.atomic().cmd("DEL").arg(key).ignore();
+        if !fields.is_empty() {
+            transaction.cmd("HSET").arg(key).arg(&fields).ignore();
+        }
+        // EXEC replies nil when a watched key changed.
+        let executed: Option<()> = transaction
+            .query_async(connection)
+            .await
+            .map_err(RedisError::from)?;
+        Ok(executed.map(|()| fields))
+    }
+
+    /// Reads the fields and values of hashes, in one round trip.
+    async fn read_fields(&self, keys: &[String]) -> Result<Vec<Vec<(String, String)>>, RedisError> {
+        let mut pipeline = redis::pipe();
+        for key in keys {
+            pipeline.cmd("HGETALL").arg(key);
+        }
+        Ok(pipeline.query_async(&mut self.manager.clone()).await?)
+    }
+}
+
+/// Subscribes to the keyspace notifications and the channel, and counts the
+/// messages until the connection fails.
+async fn subscribe(
+    client: &Client,
+    pattern: &str,
+    channel: &str,
+    changes: &watch::Sender<u64>,
+    resubscribing: bool,
+) -> Result<Infallible, RedisError> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.psubscribe(pattern).await?;
+    pubsub.subscribe(channel).await?;
+    debug!("Subscribed to {} and {}", pattern, channel);
+
+    // Writes made while the subscription was down went unnoticed.
+    if resubscribing {
+        changes.send_modify(|count| *count += 1);
+    }
+
+    let mut messages = pubsub.on_message();
+    while let Some(message) = messages.next().await {
+        debug!("Redis change notification: {}", message.get_channel_name());
+        changes.send_modify(|count| *count += 1);
+    }
+    Err(RedisError::Connection(
+        "the server closed the connection".to_string(),
+    ))
+}
+
+/// Escapes the glob characters of a `PSUBSCRIBE` pattern.
+fn glob_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Nests the fields of a hash like a parsed file.
+fn nest(fields: Vec<(String, String)>) -> ConfigMap {
+    let mut config = ConfigMap::new();
+    for (field, value) in fields {
+        insert_nested(&mut config, &field, ConfigValue::String(value));
+    }
+    config
+}
+
+/// Returns a hash as stored, with its version: a digest of its fields and
+/// values.
+fn stored(fields: Vec<(String, String)>) -> StoredProperties {
+    let version = flat_version(fields.iter().map(|(f, v)| (f.as_str(), v.clone())));
+    StoredProperties {
+        properties: nest(fields),
+        version,
+    }
+}
+
+#[async_trait]
+impl ConfigSource for RedisBackend {
+    async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+        let label = query.effective_label(&self.default_label);
+
+        let mut applications = vec![query.application()];
+        if query.application() != SHARED_APPLICATION {
+            applications.insert(0, SHARED_APPLICATION);
+        }
+        let mut profiles = vec![DEFAULT_PROFILE];
+        profiles.extend(
+            query
+                .profiles()
+                .iter()
+                .map(String::as_str)
+                .filter(|p| *p != DEFAULT_PROFILE),
+        );
+
+        // Lowest precedence first, reversed at the end like the file resolver.
+        let keys: Vec<String> = applications
+            .iter()
+            .flat_map(|application| {
+                profiles.iter().map(move |profile| {
+                    format!("{}{}:{}:{}", self.key_prefix, application, profile, label)
+                })
+            })
+            .collect();
+        let hashes = self.read_fields(&keys).await?;
+
+        let mut sources = Vec::new();
+        for (key, fields) in keys.iter().zip(hashes) {
+            if !fields.is_empty() {
+                sources.push(PropertySource::new(format!("redis:{}", key), nest(fields)));
+            }
+        }
+        sources.reverse();
+
+        debug!("Resolved {} property sources for {}", sources.len(), query);
+
+        let mut result = ConfigResult::new(query.application(), query.profiles().to_vec(), label);
+        result.add_property_sources(sources);
+        Ok(result)
+    }
+
+    async fn health_check(&self) -> Result<(), ConfigSourceError> {
+        redis::cmd("PING")
+            .exec_async(&mut self.manager.clone())
+            .await
+            .map_err(RedisError::from)?;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "redis"
+    }
+
+    fn default_label(&self) -> &str {
+        &self.default_label
+    }
+
+    fn store(&self) -> Option<&dyn ConfigStore> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl ConfigStore for RedisBackend {
+    async fn get_properties(&self, id: &DocumentId) -> Result<StoredProperties, ConfigSourceError> {
+        let key = self.document_key(id);
+        let mut hashes = self.read_fields(&[key]).await?;
+        Ok(stored(hashes.pop().unwrap_or_default()))
+    }
+
+    async fn put_properties(
+        &self,
+        id: &DocumentId,
+        properties: &ConfigMap,
+        expected_version: Option<&str>,
+    ) -> Result<StoredProperties, ConfigSourceError> {
+        self.write(id, expected_version, Write::Put(properties))
+            .await
+    }
+
+    async fn delete_key(
+        &self,
+        id: &DocumentId,
+        key: &str,
+        expected_version: Option<&str>,
+    ) -> Result<StoredProperties, ConfigSourceError> {
+        self.write(id, expected_version, Write::Delete(key)).await
+    }
+}
+
+impl Drop for RedisBackend {
+    fn drop(&mut self) {
+        if let Some(watcher) = self.watcher.take() {
+            watcher.abort();
+        }
+    }
+}
+
+impl std::fmt::Debug for RedisBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisBackend")
+            .field("url", &self.url)
+            .field("key_prefix", &self.key_prefix)
+            .field("default_label", &self.default_label)
+            .field("channel", &self.channel)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::broadcast;
+
+    /// The fields of a hash, in insertion order.
+    type Fields = Vec<(String, String)>;
+
+    /// Hashes by name.
+    type Hashes = Arc<Mutex<HashMap<String, Fields>>>;
+
+    /// A Redis server holding `hashes` in memory. Commands sent on
+    /// `publish` reach every subscribed client as keyspace notifications.
+    struct FakeRedis {
+        address: String,
+        hashes: Hashes,
+        publish: broadcast::Sender<String>,
+    }
+
+    impl FakeRedis {
+        async fn start(hashes: &[(&str, &[(&str, &str)])]) -> Self {
+            let hashes: Hashes = Arc::new(Mutex::new(
+                hashes
+                    .iter()
+                    .map(|(key, fields)| {
+                        let fields = fields
+                            .iter()
+                            .map(|(f, v)| (f.to_string(), v.to_string()))
+                            .collect();
+                        (key.to_string(), fields)
+                    })
+                    .collect(),
+            ));
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+            let (publish, _) = broadcast::channel(16);
+
+            let sender = publish.clone();
+            let served = Arc::clone(&hashes);
+            tokio::spawn(async move {
+                loop {
+                    let (stream, _) = listener.accept().await.unwrap();
+                    tokio::spawn(serve(stream, Arc::clone(&served), sender.clone()));
+                }
+            });
+            Self {
+                address,
+                hashes,
+                publish,
+            }
+        }
+
+        fn url(&self) -> String {
+            format!("redis://:s3cret@{}/2", self.address)
+        }
+
+        fn hash(&self, key: &str) -> Option<Fields> {
+            self.hashes.lock().unwrap().get(key).cloned()
+        }
+    }
+
+    fn bulk(s: &str) -> String {
+        format!("${}\r\n{}\r\n", s.len(), s)
+    }
+
+    /// Runs a command on the hashes and returns its reply.
+    fn apply(hashes: &Hashes, args: &[&str]) -> String {
+        let mut hashes = hashes.lock().unwrap();
+        match args {
+            ["HGETALL", "payments:broken:main"] => {
+                "-WRONGTYPE Operation against a key holding the wrong kind of value\r\n".to_string()
+            },
+            ["HGETALL", key] => {
+                let fields = hashes.get(*key).cloned().unwrap_or_default();
+                let mut reply = format!("*{}\r\n", fields.len() * 2);
+                for (field, value) in fields {
+                    reply.push_str(&bulk(&field));
+                    reply.push_str(&bulk(&value));
+                }
+                reply
+            },
+            ["DEL", key] => format!(":{}\r\n", hashes.remove(*key).map_or(0, |_| 1)),
+            ["HSET", key, pairs @ ..] if !pairs.is_empty() && pairs.len() % 2 == 0 => {
+                let fields = hashes.entry(key.to_string()).or_default();
+                for pair in pairs.chunks(2) {
+                    fields.retain(|(f, _)| f != pair[0]);
+                    fields.push((pair[0].to_string(), pair[1].to_string()));
+                }
+                format!(":{}\r\n", pairs.len() / 2)
+            },
+            ["PUBLISH", _, _] => ":0\r\n".to_string(),
+            _ => format!("-ERR unexpected {:?}\r\n", args),
+        }
+    }
+
+    async fn serve(stream: TcpStream, hashes: Hashes, publish: broadcast::Sender<String>) {
+        let mut stream = BufReader::new(stream);
+        let mut authenticated = false;
+        let mut subscribed = false;
+        // Watched hashes as they were, and commands queued by MULTI.
+        let mut watched: Vec<(String, Option<Fields>)> = Vec::new();
+        let mut queued: Option<Vec<Vec<String>>> = None;
+        while let Some(command) = read_command(&mut stream).await {
+            let args: Vec<&str> = command.iter().map(String::as_str).collect();
+            let reply = match args.as_slice() {
+                ["AUTH", "s3cret"] => {
+                    authenticated = true;
+                    "+OK\r\n".to_string()
+                },
+                ["AUTH", _] => "-WRONGPASS invalid password\r\n".to_string(),
+                _ if !authenticated => "-NOAUTH Authentication required.\r\n".to_string(),
+                ["SELECT", "2"] => "+OK\r\n".to_string(),
+                ["PING"] => "+PONG\r\n".to_string(),
+                ["WATCH", key] => {
+                    let hash = hashes.lock().unwrap().get(*key).cloned();
+                    watched.push((key.to_string(), hash));
+                    "+OK\r\n".to_string()
+                },
+                ["UNWATCH"] => {
+                    watched.clear();
+                    "+OK\r\n".to_string()
+                },
+                ["MULTI"] => {
+                    queued = Some(Vec::new());
+                    "+OK\r\n".to_string()
+                },
+                ["EXEC"] => {
+                    let commands = queued.take().unwrap_or_default();
+                    let unchanged = std::mem::take(&mut watched)
+                        .into_iter()
+                        .all(|(key, hash)| hashes.lock().unwrap().get(&key).cloned() == hash);
+                    if unchanged {
+                        let mut reply = format!("*{}\r\n", commands.len());
+                        for command in commands {
+                            let args: Vec<&str> = command.iter().map(String::as_str).collect();
+                            reply.push_str(&apply(&hashes, &args));
+                        }
+                        reply
+                    } else {
+                        "*-1\r\n".to_string()
+                    }
+                },
+                _ if queued.is_some() => {
+                    queued.as_mut().unwrap().push(command.clone());
+                    "+QUEUED\r\n".to_string()
+                },
+                [kind @ ("SUBSCRIBE" | "PSUBSCRIBE"), name] => {
+                    subscribed = true;
+                    let kind = kind.to_lowercase();
+                    format!("*3\r\n{}{}:1\r\n", bulk(&kind), bulk(name))
+                },
+                _ => apply(&hashes, &args),
+            };
+            stream.write_all(reply.as_bytes()).await.unwrap();
+            if subscribed && args[0] == "SUBSCRIBE" {
+                break;
+            }
+        }
+        if !subscribed {
+            return;
+        }
+
+        let mut published = publish.subscribe();
+        while let Ok(key) = published.recv().await {
+            let pattern = "__keyspace@2__:*";
+            let channel = format!("__keyspace@2__:{}", key);
+            let message = format!(
+                "*4\r\n$8\r\npmessage\r\n${}\r\n{}\r\n${}\r\n{}\r\n$4\r\nhset\r\n",
+                pattern.len(),
+                pattern,
+                channel.len(),
+                channel
+            );
+            if stream.write_all(message.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Reads a command sent as an array of bulk strings.
+    async fn read_command(stream: &mut BufReader<TcpStream>) -> Option<Vec<String>> {
+        async fn line(stream: &mut BufReader<TcpStream>) -> Option<String> {
+            let mut line = String::new();
+            stream.read_line(&mut line).await.ok().filter(|n| *n > 0)?;
+            Some(line.trim_end().to_string())
+        }
+
+        let count: usize = line(stream).await?.strip_prefix('*')?.parse().ok()?;
+        let mut args = Vec::with_capacity(count);
+        for _ in 0..count {
+            let len: usize = line(stream).await?.strip_prefix('$')?.parse().ok()?;
+            let mut data = vec![0; len + 2];
+            stream.read_exact(&mut data).await.ok()?;
+            data.truncate(len);
+            args.push(String::from_utf8(data).ok()?);
+        }
+        Some(args)
+    }
+
+    async fn backend() -> (FakeRedis, RedisBackend) {
+        let server = FakeRedis::start(&[
+            ("application:default:main", &[("server.port", "8080")]),
+            ("application:prod:main", &[("logging.level", "WARN")]),
+            ("payments:default:main", &[("db.pool.size", "10")]),
+            (
+                "payments:prod:main",
+                &[("db.pool.size", "20"), ("db.hosts[0]", "db-1")],
+            ),
+            ("payments:prod:v2", &[("db.pool.size", "30")]),
+        ])
+        .await;
+        let backend = RedisBackend::connect(&server.url()).await.unwrap();
+        (server, backend)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_orders_sources_like_spring() {
+        let (_server, backend) = backend().await;
+        assert!(backend.health_check().await.is_ok());
+
+        let result = backend
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap();
+        let names: Vec<&str> = result
+            .property_sources()
+            .iter()
+            .map(|ps| ps.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "redis:payments:prod:main",
+                "redis:payments:default:main",
+                "redis:application:prod:main",
+                "redis:application:default:main"
+            ]
+        );
+
+        let merged = result.merged();
+        assert_eq!(
+            merged.get("db.pool.size").and_then(|v| v.as_str()),
+            Some("20")
+        );
+        assert_eq!(
+            merged.get("db.hosts[0]").and_then(|v| v.as_str()),
+            Some("db-1")
+        );
+        assert_eq!(
+            merged.get("server.port").and_then(|v| v.as_str()),
+            Some("8080")
+        );
+
+        let query = ConfigQuery::new("payments", vec!["prod"]).with_label_set("v2");
+        let result = backend.fetch(&query).await.unwrap();
+        assert_eq!(result.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_key_prefix_and_errors() {
+        let (server, backend) = backend().await;
+
+        let result = backend
+            .fetch(&ConfigQuery::new("payments", vec!["broken"]))
+            .await;
+        assert!(result.unwrap_err().to_string().contains("WRONGTYPE"));
+
+        let backend = backend.with_key_prefix("other:");
+        let result = backend
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap();
+        assert!(result.property_sources().is_empty());
+
+        let url = format!("redis://:wrong@{}", server.address);
+        assert!(matches!(
+            RedisBackend::connect(&url).await,
+            Err(RedisError::Connection(e)) if e.contains("authentication failed")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_store_writes_hashes() {
+        let (server, backend) = backend().await;
+        let store = backend.store().unwrap();
+        let mut changes = backend.changes();
+        let id = DocumentId::new("payments", "prod", "main");
+
+        let current = store.get_properties(&id).await.unwrap();
+        assert_eq!(
+            current
+                .properties
+                .get("db.hosts[0]")
+                .and_then(|v| v.as_str()),
+            Some("db-1")
+        );
+
+        let properties = ConfigMap::from_json(
+            r#"{"db": {"pool": {"size": 25}, "ssl": true, "ssl-mode": null},
+                "owner": "team-a"}"#,
+        )
+        .unwrap();
+        let written = store
+            .put_properties(&id, &properties, Some(&current.version))
+            .await
+            .unwrap();
+        assert_ne!(written.version, current.version);
+        assert_eq!(
+            server.hash("payments:prod:main").unwrap(),
+            [
+                ("db.pool.size".to_string(), "25".to_string()),
+                ("db.ssl".to_string(), "true".to_string()),
+                ("owner".to_string(), "team-a".to_string()),
+            ]
+        );
+        assert!(changes.has_changed().unwrap());
+        changes.mark_unchanged();
+        assert_eq!(
+            store.get_properties(&id).await.unwrap().version,
+            written.version
+        );
+
+        // A stale version changes nothing.
+        let error = store
+            .put_properties(&id, &ConfigMap::new(), Some(&current.version))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ConfigSourceError::VersionConflict { .. }));
+        assert!(!changes.has_changed().unwrap());
+
+        let deleted = store
+            .delete_key(&id, "db", Some(&written.version))
+            .await
+            .unwrap();
+        assert_eq!(
+            server.hash("payments:prod:main").unwrap(),
+            [("owner".to_string(), "team-a".to_string())]
+        );
+        assert_eq!(
+            deleted.version,
+            store.get_properties(&id).await.unwrap().version
+        );
+        assert!(matches!(
+            store.delete_key(&id, "db", None).await,
+            Err(ConfigSourceError::KeyNotFound(_))
+        ));
+
+        // The connection is still usable after a failed write.
+        store
+            .put_properties(&id, &ConfigMap::new(), None)
+            .await
+            .unwrap();
+        assert!(server.hash("payments:prod:main").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_watch_reports_notifications() {
+        let (server, backend) = backend().await;
+        let backend = backend.watch();
+        let mut changes = backend.changes();
+
+        // Wait for the subscription before publishing.
+        while server.publish.receiver_count() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        server
+            .publish
+            .send("payments:prod:main".to_string())
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), changes.changed())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(*changes.borrow(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_urls() {
+        for url in [
+            "http://localhost",
+            "redis://",
+            "redis://localhost:port",
+            "redis://localhost/db",
+        ] {
+            assert!(
+                matches!(
+                    RedisBackend::connect(url).await,
+                    Err(RedisError::InvalidUrl(_))
+                ),
+                "{}",
+                url
+            );
+        }
+        assert_eq!(glob_escape("config:*[a]"), "config:\\*\\[a\\]");
+    }
+
+    /// Runs against the server at `VORTEX_TEST_REDIS_URL`.
+    #[tokio::test]
+    #[ignore = "requires Redis"]
+    async fn test_against_server() {
+        let url = std::env::var("VORTEX_TEST_REDIS_URL")
+            .expect("VORTEX_TEST_REDIS_URL must point to a Redis server");
+        let backend = RedisBackend::connect(&url)
+            .await
+            .unwrap()
+            .with_key_prefix("vortex-test:")
+            .watch();
+        let mut changes = backend.changes();
+
+        let mut connection = backend.manager.clone();
+        for command in [
+            vec!["CONFIG", "SET", "notify-keyspace-events", "Kgh"],
+            vec!["DEL", "vortex-test:payments:default:main"],
+            vec![
+                "HSET",
+                "vortex-test:application:default:main",
+                "server.port",
+                "8080",
+            ],
+        ] {
+            redis::cmd(command[0])
+                .arg(&command[1..])
+                .exec_async(&mut connection)
+                .await
+                .unwrap();
+        }
+
+        // The subscription may not be up yet; keep writing until it is.
+        let notified = async {
+            loop {
+                redis::cmd("HSET")
+                    .arg(&["vortex-test:payments:default:main", "db.pool", "10"])
+                    .exec_async(&mut connection)
+                    .await
+                    .unwrap();
+                if tokio::time::timeout(Duration::from_millis(200), changes.changed())
+                    .await
+                    .is_ok()
+                {
+                    break;
+                }
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(10), notified)
+            .await
+            .unwrap();
+
+        let result = backend
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap();
+        let merged = result.merged();
+        assert_eq!(merged.get("db.pool").and_then(|v| v.as_str()), Some("10"));
+        assert_eq!(
+            merged.get("server.port").and_then(|v| v.as_str()),
+            Some("8080")
+        );
+
+        let id = DocumentId::new("payments", "default", "main");
+        let current = backend.get_properties(&id).await.unwrap();
+        let properties = ConfigMap::from_json(r#"{"db": {"pool": 15}}"#).unwrap();
+        let written = backend
+            .put_properties(&id, &properties, Some(&current.version))
+            .await
+            .unwrap();
+        assert!(matches!(
+            backend
+                .put_properties(&id, &properties, Some(&current.version))
+                .await,
+            Err(ConfigSourceError::VersionConflict { .. })
+        ));
+        let deleted = backend
+            .delete_key(&id, "db.pool", Some(&written.version))
+            .await
+            .unwrap();
+        assert!(deleted.properties.is_empty());
+    }
+}
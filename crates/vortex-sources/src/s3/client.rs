@@ -0,0 +1,301 @@
+//! The few S3 calls the backend makes: `ListObjectsV2` and `GetObject`.
+
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use hyper::{StatusCode, Uri};
+use vortex_git::ConfigSourceError;
+
+use super::sign::{self, Credentials};
+use crate::http::HttpClient;
+
+/// An object in a listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ObjectInfo {
+    pub key: String,
+    pub etag: String,
+}
+
+/// A downloaded object.
+#[derive(Debug)]
+pub(crate) struct Object {
+    pub body: Bytes,
+    pub etag: Option<String>,
+    /// Version id, when the bucket is versioned.
+    pub version_id: Option<String>,
+}
+
+/// Client for one bucket.
+#[derive(Clone)]
+pub(crate) struct S3Client {
+    http: HttpClient,
+    /// `http` or `https`.
+    scheme: String,
+    /// Host and port of the endpoint.
+    host: String,
+    bucket: String,
+    region: String,
+    credentials: Option<Credentials>,
+    /// Whether the bucket goes in the path (MinIO) or the host name (AWS).
+    path_style: bool,
+}
+
+impl S3Client {
+    /// Creates an anonymous, virtual-hosted client for `bucket`.
+    pub fn new(endpoint: &str, bucket: String) -> Result<Self, ConfigSourceError> {
+        let invalid = |reason: String| {
+            ConfigSourceError::unavailable(format!("invalid S3 endpoint {}: {}", endpoint, reason))
+        };
+        let uri: Uri = endpoint.parse().map_err(|e| invalid(format!("{}", e)))?;
+        let (Some(scheme), Some(authority)) = (uri.scheme_str(), uri.authority()) else {
+            return Err(invalid("expected http(s)://host[:port]".to_string()));
+        };
+
+        Ok(Self {
+            http: HttpClient::new().map_err(ConfigSourceError::unavailable)?,
+            scheme: scheme.to_string(),
+            host: authority.to_string(),
+            bucket,
+            region: super::DEFAULT_REGION.to_string(),
+            credentials: None,
+            path_style: false,
+        })
+    }
+
+    pub fn set_region(&mut self, region: String) {
+        self.region = region;
+    }
+
+    pub fn set_credentials(&mut self, credentials: Credentials) {
+        self.credentials = Some(credentials);
+    }
+
+    pub fn set_path_style(&mut self, path_style: bool) {
+        self.path_style = path_style;
+    }
+
+    /// Checks that the bucket can be listed, reading at most one key.
+    pub async fn probe(&self, prefix: &str) -> Result<(), ConfigSourceError> {
+        let query = [("list-type", "2"), ("max-keys", "1"), ("prefix", prefix)];
+        let response = self.request("", &query).await?;
+        if response.status == StatusCode::OK {
+            Ok(())
+        } else {
+            Err(error_response(
+                &self.bucket,
+                prefix,
+                response.status,
+                &response.body,
+            ))
+        }
+    }
+
+    /// Lists every object under `prefix`, following continuation tokens.
+    pub async fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>, ConfigSourceError> {
+        let mut objects = Vec::new();
+        let mut token = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+            if let Some(token) = token.as_deref() {
+                query.push(("continuation-token", token));
+            }
+            let response = self.request("", &query).await?;
+            if response.status != StatusCode::OK {
+                return Err(error_response(
+                    &self.bucket,
+                    prefix,
+                    response.status,
+                    &response.body,
+                ));
+            }
+
+            let xml = String::from_utf8_lossy(&response.body);
+            let (page, next) = parse_listing(&xml);
+            objects.extend(page);
+            match next {
+                Some(next) => token = Some(next),
+                None => return Ok(objects),
+            }
+        }
+    }
+
+    /// Downloads an object, or returns `None` if it does not exist.
+    pub async fn get(&self, key: &str) -> Result<Option<Object>, ConfigSourceError> {
+        let response = self.request(key, &[]).await?;
+        match response.status {
+            StatusCode::OK => {
+                let header = |name: &str| {
+                    response
+                        .headers
+                        .get(name)
+                        .and_then(|v| v.to_str().ok())
+                        .map(|v| v.trim_matches('"').to_string())
+                };
+                Ok(Some(Object {
+                    etag: header("etag"),
+                    version_id: header("x-amz-version-id").filter(|v| v != "null"),
+                    body: response.body,
+                }))
+            },
+            StatusCode::NOT_FOUND => Ok(None),
+            status => Err(error_response(&self.bucket, key, status, &response.body)),
+        }
+    }
+
+    /// Sends a signed `GET` for `key` (empty for the bucket itself).
+    async fn request(
+        &self,
+        key: &str,
+        query: &[(&str, &str)],
+    ) -> Result<crate::http::HttpResponse, ConfigSourceError> {
+        let (host, path) = if self.path_style {
+            let path = if key.is_empty() {
+                format!("/{}", self.bucket)
+            } else {
+                format!("/{}/{}", self.bucket, key)
+            };
+            (self.host.clone(), path)
+        } else {
+            (
+                format!("{}.{}", self.bucket, self.host),
+                format!("/{}", key),
+            )
+        };
+        let path = crate::http::uri_encode(&path, false);
+        let url = match sign::canonical_query(query) {
+            query if query.is_empty() => format!("{}://{}{}", self.scheme, host, path),
+            query => format!("{}://{}{}?{}", self.scheme, host, path, query),
+        };
+
+        let headers = match &self.credentials {
+            Some(credentials) => {
+                sign::sign(credentials, &self.region, &url, &[], SystemTime::now()).map_err(
+                    |e| ConfigSourceError::unavailable(format!("cannot sign the request: {}", e)),
+                )?
+            },
+            None => Vec::new(),
+        };
+        let headers: Vec<(&str, &str)> = headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+
+        self.http
+            .get(&url, &headers)
+            .await
+            .map_err(ConfigSourceError::unavailable)
+    }
+}
+
+impl std::fmt::Debug for S3Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Client")
+            .field("endpoint", &format!("{}://{}", self.scheme, self.host))
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("credentials", &self.credentials)
+            .field("path_style", &self.path_style)
+            .finish()
+    }
+}
+
+/// Turns an S3 error document into a source error.
+fn error_response(bucket: &str, key: &str, status: StatusCode, body: &[u8]) -> ConfigSourceError {
+    let xml = String::from_utf8_lossy(body);
+    let code = element(&xml, "Code").unwrap_or_default();
+    let message = element(&xml, "Message").unwrap_or_default();
+    ConfigSourceError::unavailable(format!(
+        "S3 request for s3://{}/{} failed with {}: {} {}",
+        bucket, key, status, code, message
+    ))
+}
+
+/// Reads the objects and continuation token of a `ListObjectsV2` response.
+fn parse_listing(xml: &str) -> (Vec<ObjectInfo>, Option<String>) {
+    let objects = xml
+        .split("<Contents>")
+        .skip(1)
+        .filter_map(|contents| {
+            let contents = contents.split("</Contents>").next()?;
+            Some(ObjectInfo {
+                key: element(contents, "Key")?,
+                etag: element(contents, "ETag")
+                    .unwrap_or_default()
+                    .trim_matches('"')
+                    .to_string(),
+            })
+        })
+        .collect();
+
+    let next = (element(xml, "IsTruncated").as_deref() == Some("true"))
+        .then(|| element(xml, "NextContinuationToken"))
+        .flatten();
+    (objects, next)
+}
+
+/// Returns the unescaped text of the first `<name>` element.
+fn element(xml: &str, name: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", name))? + name.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", name))?;
+    Some(unescape(&xml[start..end]))
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_listing() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+              <Name>config</Name><IsTruncated>true</IsTruncated>
+              <Contents><Key>main/payments-prod.yml</Key><ETag>&quot;abc&quot;</ETag></Contents>
+              <Contents><Key>main/a&amp;b.yml</Key><ETag>"def"</ETag></Contents>
+              <NextContinuationToken>token/1</NextContinuationToken>
+            </ListBucketResult>"#;
+
+        let (objects, next) = parse_listing(xml);
+        assert_eq!(
+            objects,
+            [
+                ObjectInfo {
+                    key: "main/payments-prod.yml".to_string(),
+                    etag: "abc".to_string()
+                },
+                ObjectInfo {
+                    key: "main/a&b.yml".to_string(),
+                    etag: "def".to_string()
+                },
+            ]
+        );
+        assert_eq!(next.as_deref(), Some("token/1"));
+
+        let (objects, next) =
+            parse_listing("<ListBucketResult><IsTruncated>false</IsTruncated></ListBucketResult>");
+        assert!(objects.is_empty());
+        assert!(next.is_none());
+    }
+
+    #[test]
+    fn test_error_response() {
+        let error = error_response(
+            "config",
+            "main/",
+            StatusCode::FORBIDDEN,
+            b"<Error><Code>AccessDenied</Code><Message>Access Denied</Message></Error>",
+        );
+        assert!(
+            error
+                .to_string()
+                .contains("403 Forbidden: AccessDenied Access Denied")
+        );
+    }
+}
@@ -0,0 +1,608 @@
+//! S3 backend (Spring's `awss3` profile).
+//!
+//! Serves configuration files stored as objects in an S3-compatible bucket,
+//! one key prefix per label:
+//!
+//! ```text
+//! s3://config-bucket/
+//! ├── main/application.yml
+//! ├── main/payments-prod.yml
+//! └── release-2.4/payments-prod.yml
+//! ```
+//!
+//! Files follow the same naming and precedence as the git backend. The
+//! response version is a digest of the version ids of the objects served,
+//! or of their ETags when the bucket is not versioned, so it changes
+//! whenever one of them does.
+//!
+//! [`refresh`](ConfigSource::refresh) compares the ETags of every object
+//! with the previous listing and reports changes on
+//! [`changes`](S3Backend::changes); [`poll`](S3Backend::poll) does so
+//! periodically. Path-style addressing (`http://minio:9000/bucket/key`)
+//! works with MinIO and other S3-compatible servers.
+
+mod client;
+mod sign;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use vortex_core::PropertySource;
+use vortex_core::format::FormatRegistry;
+use vortex_core::validation::SchemaValidator;
+use vortex_git::{ConfigQuery, ConfigResult, ConfigSource, ConfigSourceError};
+
+use client::S3Client;
+pub use sign::Credentials;
+
+/// Label used when the request does not name one.
+pub const DEFAULT_LABEL: &str = "main";
+
+/// Region used to sign requests unless another one is set.
+pub const DEFAULT_REGION: &str = "us-east-1";
+
+/// Directory of JSON Schemas within each label, as in the git backend.
+const SCHEMAS_DIR: &str = "schemas";
+
+/// A configuration source backed by an S3 bucket.
+pub struct S3Backend {
+    /// Client for the bucket.
+    client: S3Client,
+    /// Key prefix in front of the labels, empty or ending in `/`.
+    prefix: String,
+    /// Label used when the request does not name one.
+    default_label: String,
+    /// Formats used to recognize and parse objects.
+    formats: Arc<FormatRegistry>,
+    /// ETags of the last listing, for change detection.
+    tracker: Arc<ChangeTracker>,
+    /// Periodic refresh, when enabled.
+    poller: Option<JoinHandle<()>>,
+}
+
+impl S3Backend {
+    /// Serves configuration from `bucket` at `endpoint`, e.g.
+    /// `https://s3.eu-west-1.amazonaws.com` or `http://localhost:9000`.
+    pub fn new(endpoint: &str, bucket: impl Into<String>) -> Result<Self, ConfigSourceError> {
+        Ok(Self {
+            client: S3Client::new(endpoint, bucket.into())?,
+            prefix: String::new(),
+            default_label: DEFAULT_LABEL.to_string(),
+            formats: Arc::new(FormatRegistry::builtin()),
+            tracker: Arc::new(ChangeTracker::default()),
+            poller: None,
+        })
+    }
+
+    /// Sets the region requests are signed for.
+    pub fn with_region(mut self, region: impl Into<String>) -> Self {
+        self.client.set_region(region.into());
+        self
+    }
+
+    /// Signs requests with `credentials`; without them requests are
+    /// anonymous, which works for public buckets only.
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.client.set_credentials(credentials);
+        self
+    }
+
+    /// Puts the bucket in the path instead of the host name, as MinIO and
+    /// most S3-compatible servers expect.
+    pub fn with_path_style(mut self, path_style: bool) -> Self {
+        self.client.set_path_style(path_style);
+        self
+    }
+
+    /// Serves the labels found under `prefix` instead of the bucket root.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        let prefix = prefix.trim_matches('/');
+        self.prefix = if prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", prefix)
+        };
+        self
+    }
+
+    /// Sets the label used when the request does not name one.
+    pub fn with_default_label(mut self, label: impl Into<String>) -> Self {
+        self.default_label = label.into();
+        self
+    }
+
+    /// Uses the given format registry to recognize and parse objects.
+    pub fn with_format_registry(mut self, formats: Arc<FormatRegistry>) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    /// Refreshes every `interval` on a background task, which stops when the
+    /// backend is dropped.
+    ///
+    /// Call it last, once the backend is configured. Requires a tokio
+    /// runtime.
+    pub fn poll(mut self, interval: Duration) -> Self {
+        let client = self.client.clone();
+        let prefix = self.prefix.clone();
+        let tracker = Arc::clone(&self.tracker);
+
+        self.poller = Some(tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            loop {
+                ticks.tick().await;
+                if let Err(e) = tracker.check(&client, &prefix).await {
+                    warn!("Failed to poll {:?} for changes: {}", client, e);
+                }
+            }
+        }));
+        self
+    }
+
+    /// Returns a receiver notified whenever a refresh finds changed objects.
+    ///
+    /// The value is the number of changes seen so far.
+    pub fn changes(&self) -> watch::Receiver<u64> {
+        self.tracker.changes.subscribe()
+    }
+
+    /// Returns the key prefix of a label's objects.
+    fn label_prefix(&self, label: &str) -> Result<String, ConfigSourceError> {
+        let is_plain = label
+            .split('/')
+            .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+        if !is_plain {
+            return Err(ConfigSourceError::LabelNotFound(label.to_string()));
+        }
+        Ok(format!("{}{}/", self.prefix, label))
+    }
+
+    /// Reads the first object named `{name}.{ext}` in any registered format.
+    ///
+    /// Returns the property source and the object's version.
+    async fn read(
+        &self,
+        dir: &str,
+        name: &str,
+        label: &str,
+        listing: &HashMap<String, String>,
+    ) -> Result<Option<(PropertySource, String)>, ConfigSourceError> {
+        for format in self.formats.parsers() {
+            for ext in format.file_extensions() {
+                let key = format!("{}{}.{}", dir, name, ext);
+                let Some(etag) = listing.get(&key) else {
+                    continue;
+                };
+                // Deleted since the listing.
+                let Some(object) = self.client.get(&key).await? else {
+                    continue;
+                };
+
+                debug!("Reading config object: {}", key);
+
+                let content = String::from_utf8(object.body.to_vec())
+                    .map_err(|e| ConfigSourceError::parse(&key, e.to_string()))?;
+                let config = format
+                    .parse(&content)
+                    .map_err(|e| ConfigSourceError::parse(&key, e.to_string()))?;
+                let version = object
+                    .version_id
+                    .or(object.etag)
+                    .unwrap_or_else(|| etag.clone());
+
+                let source_name = format!("s3:{}:{}", label, &key[dir.len()..]);
+                return Ok(Some((PropertySource::new(source_name, config), version)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl ConfigSource for S3Backend {
+    async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+        let label = query.effective_label(&self.default_label);
+        let dir = self.label_prefix(label)?;
+
+        debug!(
+            "Fetching config for {} from {:?} under {}",
+            query, self.client, dir
+        );
+
+        let listing: HashMap<String, String> = self
+            .client
+            .list(&dir)
+            .await?
+            .into_iter()
+            .map(|object| (object.key, object.etag))
+            .collect();
+        if listing.is_empty() {
+            return Err(ConfigSourceError::LabelNotFound(label.to_string()));
+        }
+
+        // Lowest precedence first, like the file resolver.
+        let mut names = vec!["application".to_string()];
+        names.extend(
+            query
+                .profiles()
+                .iter()
+                .map(|p| format!("application-{}", p)),
+        );
+        names.push(query.application().to_string());
+        names.extend(
+            query
+                .profiles()
+                .iter()
+                .map(|p| format!("{}-{}", query.application(), p)),
+        );
+
+        let mut sources = Vec::new();
+        let mut versions = Sha256::new();
+        for name in &names {
+            if let Some((source, version)) = self.read(&dir, name, label, &listing).await? {
+                versions.update(format!("{}={}\n", source.name, version));
+                sources.push(source);
+            }
+        }
+        sources.reverse();
+
+        debug!("Resolved {} property sources for {}", sources.len(), query);
+
+        let mut result = ConfigResult::new(query.application(), query.profiles().to_vec(), label);
+        if !sources.is_empty() {
+            result.set_version(sign::hex(&versions.finalize()[..20]));
+        }
+        result.add_property_sources(sources);
+        Ok(result)
+    }
+
+    async fn schema(
+        &self,
+        query: &ConfigQuery,
+    ) -> Result<Option<Arc<SchemaValidator>>, ConfigSourceError> {
+        let label = query.effective_label(&self.default_label);
+        let key = format!(
+            "{}{}/{}.schema.json",
+            self.label_prefix(label)?,
+            SCHEMAS_DIR,
+            query.application()
+        );
+
+        let Some(object) = self.client.get(&key).await? else {
+            return Ok(None);
+        };
+        let content = String::from_utf8_lossy(&object.body);
+        SchemaValidator::from_json(&content)
+            .map(|schema| Some(Arc::new(schema)))
+            .map_err(|e| ConfigSourceError::parse(&key, e.to_string()))
+    }
+
+    async fn health_check(&self) -> Result<(), ConfigSourceError> {
+        self.client.probe(&self.prefix).await
+    }
+
+    fn name(&self) -> &str {
+        "s3"
+    }
+
+    async fn refresh(&self) -> Result<(), ConfigSourceError> {
+        self.tracker.check(&self.client, &self.prefix).await?;
+        Ok(())
+    }
+
+    fn supports_refresh(&self) -> bool {
+        true
+    }
+
+    fn default_label(&self) -> &str {
+        &self.default_label
+    }
+}
+
+impl Drop for S3Backend {
+    fn drop(&mut self) {
+        if let Some(poller) = self.poller.take() {
+            poller.abort();
+        }
+    }
+}
+
+impl std::fmt::Debug for S3Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Backend")
+            .field("client", &self.client)
+            .field("prefix", &self.prefix)
+            .field("default_label", &self.default_label)
+            .field("polling", &self.poller.is_some())
+            .finish()
+    }
+}
+
+/// Detects changes by comparing ETags between listings.
+struct ChangeTracker {
+    etags: Mutex<Option<BTreeMap<String, String>>>,
+    changes: watch::Sender<u64>,
+}
+
+impl Default for ChangeTracker {
+    fn default() -> Self {
+        Self {
+            etags: Mutex::new(None),
+            changes: watch::Sender::new(0),
+        }
+    }
+}
+
+impl ChangeTracker {
+    /// Lists the objects under `prefix` and returns whether any changed
+    /// since the last check. The first check only records the listing.
+    async fn check(&self, client: &S3Client, prefix: &str) -> Result<bool, ConfigSourceError> {
+        let etags: BTreeMap<String, String> = client
+            .list(prefix)
+            .await?
+            .into_iter()
+            .map(|object| (object.key, object.etag))
+            .collect();
+
+        let previous = self
+            .etags
+            .lock()
+            .expect("tracker poisoned")
+            .replace(etags.clone());
+        let changed = previous.is_some_and(|previous| previous != etags);
+        if changed {
+            debug!("Objects changed under {:?} {}", client, prefix);
+            self.changes.send_modify(|count| *count += 1);
+        }
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use crate::testing::{Response, serve};
+
+    const BUCKET: &str = "config";
+
+    /// Objects of the fake bucket: key to body and version.
+    type Objects = Arc<Mutex<BTreeMap<String, (String, u32)>>>;
+
+    /// Starts a fake S3 server with path-style addressing that serves
+    /// `objects` to signed requests, two keys per listing page.
+    async fn fake_s3(objects: Objects) -> String {
+        serve(move |request| {
+            let signed = request.header("authorization").is_some_and(|value| {
+                value
+                    .to_lowercase()
+                    .starts_with("aws4-hmac-sha256 credential=test-key/")
+            });
+            let response = if signed {
+                respond(&objects.lock().unwrap(), &request.target)
+            } else {
+                Response::new(403, "<Error><Code>AccessDenied</Code></Error>")
+            };
+            async move { response }
+        })
+        .await
+    }
+
+    fn respond(objects: &BTreeMap<String, (String, u32)>, target: &str) -> Response {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let params: HashMap<&str, String> = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key, decode(value)))
+            .collect();
+        let etag = |body: &str| sign::hex(&Sha256::digest(body)[..8]);
+
+        if path == format!("/{}", BUCKET) {
+            let prefix = params.get("prefix").cloned().unwrap_or_default();
+            let after = params
+                .get("continuation-token")
+                .cloned()
+                .unwrap_or_default();
+            let keys: Vec<&String> = objects
+                .keys()
+                .filter(|key| key.starts_with(&prefix) && **key > after)
+                .collect();
+            let page = &keys[..keys.len().min(2)];
+            let mut xml = format!(
+                "<ListBucketResult><IsTruncated>{}</IsTruncated>",
+                keys.len() > 2
+            );
+            for key in page {
+                xml.push_str(&format!(
+                    "<Contents><Key>{}</Key><ETag>&quot;{}&quot;</ETag></Contents>",
+                    key,
+                    etag(&objects[*key].0)
+                ));
+            }
+            if keys.len() > 2 {
+                xml.push_str(&format!(
+                    "<NextContinuationToken>{}</NextContinuationToken>",
+                    page[1]
+                ));
+            }
+            xml.push_str("</ListBucketResult>");
+            return Response::new(200, xml);
+        }
+
+        let key = decode(path.trim_start_matches(&format!("/{}/", BUCKET)));
+        match objects.get(&key) {
+            Some((body, version)) => Response::new(200, body.clone())
+                .with_header("etag", format!("\"{}\"", etag(body)))
+                .with_header("x-amz-version-id", format!("v{}", version)),
+            None => Response::new(404, "<Error><Code>NoSuchKey</Code></Error>"),
+        }
+    }
+
+    fn decode(s: &str) -> String {
+        let mut out = Vec::new();
+        let mut bytes = s.bytes();
+        while let Some(b) = bytes.next() {
+            if b == b'%' {
+                let hex: String = bytes.by_ref().take(2).map(char::from).collect();
+                out.push(u8::from_str_radix(&hex, 16).unwrap());
+            } else {
+                out.push(b);
+            }
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    fn bucket(files: &[(&str, &str)]) -> Objects {
+        Arc::new(Mutex::new(
+            files
+                .iter()
+                .map(|(key, body)| (key.to_string(), (body.to_string(), 1)))
+                .collect(),
+        ))
+    }
+
+    async fn backend(objects: &Objects) -> S3Backend {
+        S3Backend::new(&fake_s3(Arc::clone(objects)).await, BUCKET)
+            .unwrap()
+            .with_path_style(true)
+            .with_prefix("/vortex/")
+            .with_credentials(Credentials::new("test-key", "test-secret"))
+    }
+
+    fn default_bucket() -> Objects {
+        bucket(&[
+            ("vortex/main/application.yml", "server:\n  port: 8080\n"),
+            (
+                "vortex/main/application-prod.properties",
+                "log.level=WARN\n",
+            ),
+            ("vortex/main/payments-prod.yml", "db:\n  pool: 10\n"),
+            (
+                "vortex/main/schemas/payments.schema.json",
+                r#"{"type": "object"}"#,
+            ),
+            ("vortex/release-2.4/payments-prod.yml", "db:\n  pool: 5\n"),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_fetch_orders_objects_like_files() {
+        let objects = default_bucket();
+        let backend = backend(&objects).await;
+
+        let result = backend
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap();
+
+        let names: Vec<&str> = result
+            .property_sources()
+            .iter()
+            .map(|ps| ps.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "s3:main:payments-prod.yml",
+                "s3:main:application-prod.properties",
+                "s3:main:application.yml"
+            ]
+        );
+        let merged = result.merged();
+        assert_eq!(merged.get("db.pool").and_then(|v| v.as_i64()), Some(10));
+        assert_eq!(
+            merged.get("log.level").and_then(|v| v.as_str()),
+            Some("WARN")
+        );
+        assert_eq!(result.version().map(str::len), Some(40));
+
+        let query = ConfigQuery::new("payments", vec!["prod"]).with_label_set("release-2.4");
+        let result = backend.fetch(&query).await.unwrap();
+        assert_eq!(result.len(), 1);
+
+        for label in ["release-3.0", "../main", "main/.."] {
+            let query = ConfigQuery::new("payments", vec!["prod"]).with_label_set(label);
+            assert!(matches!(
+                backend.fetch(&query).await,
+                Err(ConfigSourceError::LabelNotFound(_))
+            ));
+        }
+
+        let query = ConfigQuery::new("payments", vec!["prod"]);
+        assert!(backend.schema(&query).await.unwrap().is_some());
+        let query = ConfigQuery::new("billing", vec!["prod"]);
+        assert!(backend.schema(&query).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_version_follows_object_versions() {
+        let objects = default_bucket();
+        let backend = backend(&objects).await;
+        let query = ConfigQuery::new("payments", vec!["prod"]);
+
+        let first = backend.fetch(&query).await.unwrap();
+        let again = backend.fetch(&query).await.unwrap();
+        assert_eq!(first.version(), again.version());
+
+        objects.lock().unwrap().insert(
+            "vortex/main/payments-prod.yml".to_string(),
+            ("db:\n  pool: 20\n".to_string(), 2),
+        );
+        let changed = backend.fetch(&query).await.unwrap();
+        assert_ne!(first.version(), changed.version());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reports_changed_etags() {
+        let objects = default_bucket();
+        let backend = backend(&objects).await;
+        let mut changes = backend.changes();
+
+        // The first refresh records the listing.
+        backend.refresh().await.unwrap();
+        backend.refresh().await.unwrap();
+        assert!(!changes.has_changed().unwrap());
+
+        objects.lock().unwrap().insert(
+            "vortex/main/payments-prod.yml".to_string(),
+            ("db:\n  pool: 20\n".to_string(), 2),
+        );
+        backend.refresh().await.unwrap();
+        assert_eq!(*changes.borrow_and_update(), 1);
+
+        // Polling does the same in the background.
+        let backend = backend.poll(Duration::from_millis(20));
+        let mut changes = backend.changes();
+        objects
+            .lock()
+            .unwrap()
+            .remove("vortex/main/application.yml");
+        tokio::time::timeout(Duration::from_secs(5), changes.changed())
+            .await
+            .expect("no change detected")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let objects = default_bucket();
+        let backend = backend(&objects).await;
+        assert!(backend.health_check().await.is_ok());
+
+        let anonymous = S3Backend::new(&fake_s3(objects).await, BUCKET)
+            .unwrap()
+            .with_path_style(true);
+        let error = anonymous.health_check().await.unwrap_err();
+        assert!(error.to_string().contains("AccessDenied"), "{}", error);
+
+        assert!(S3Backend::new("not a url", BUCKET).is_err());
+    }
+}
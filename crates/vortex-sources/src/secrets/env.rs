@@ -0,0 +1,102 @@
+//! Secrets from the server's environment variables.
+
+use async_trait::async_trait;
+
+use super::{SecretError, SecretProvider, SecretRef, SecretString};
+
+/// Resolves `${secret:env:<VAR>}` from the server's environment.
+///
+/// Only variables whose name starts with the provider's prefix are served,
+/// so clients cannot read the server's own credentials.
+#[derive(Debug, Clone)]
+pub struct EnvSecretProvider {
+    prefix: String,
+}
+
+impl EnvSecretProvider {
+    /// Creates a provider serving the variables that start with `prefix`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `prefix` is empty, which would expose every variable.
+    pub fn new(prefix: impl Into<String>) -> Result<Self, SecretError> {
+        let prefix = prefix.into();
+        if prefix.trim().is_empty() {
+            return Err(SecretError::Provider {
+                provider: "env".to_string(),
+                message: "a variable prefix is required".to_string(),
+            });
+        }
+        Ok(Self { prefix })
+    }
+}
+
+#[async_trait]
+impl SecretProvider for EnvSecretProvider {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    async fn resolve(&self, reference: &SecretRef) -> Result<SecretString, SecretError> {
+        if reference.key.is_some() {
+            return Err(SecretError::InvalidReference(reference.to_string()));
+        }
+        if !reference.path.starts_with(self.prefix.as_str()) {
+            return Err(SecretError::Denied {
+                reference: reference.to_string(),
+                reason: format!("only variables starting with '{}' are served", self.prefix),
+            });
+        }
+
+        std::env::var(&reference.path)
+            .map(SecretString::new)
+            .map_err(|_| SecretError::NotFound(reference.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(body: &str) -> SecretRef {
+        SecretRef::parse(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reads_variables_within_prefix() {
+        // SAFETY: the variable names are unique to this test.
+        unsafe {
+            std::env::set_var("VORTEX_TEST_SECRET_DB", "hunter2");
+            std::env::set_var("OTHER_TEST_SECRET_DB", "nope");
+        }
+        let provider = EnvSecretProvider::new("VORTEX_TEST_").unwrap();
+
+        let value = provider
+            .resolve(&reference("env:VORTEX_TEST_SECRET_DB"))
+            .await
+            .unwrap();
+        assert_eq!(value.expose(), "hunter2");
+
+        assert!(matches!(
+            provider
+                .resolve(&reference("env:OTHER_TEST_SECRET_DB"))
+                .await,
+            Err(SecretError::Denied { .. })
+        ));
+        assert!(matches!(
+            provider
+                .resolve(&reference("env:VORTEX_TEST_MISSING"))
+                .await,
+            Err(SecretError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_prefix_is_required() {
+        assert!(matches!(
+            EnvSecretProvider::new(""),
+            Err(SecretError::Provider { .. })
+        ));
+        assert!(EnvSecretProvider::new("  ").is_err());
+    }
+}
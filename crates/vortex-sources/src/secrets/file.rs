@@ -0,0 +1,131 @@
+//! Secrets from files, such as Docker or Kubernetes mounted secrets.
+
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+use super::{SecretError, SecretProvider, SecretRef, SecretString};
+
+/// Directory mounted secrets live in by default.
+pub const DEFAULT_SECRETS_DIR: &str = "/run/secrets";
+
+/// Resolves `${secret:file:<path>}` to the contents of a file.
+///
+/// Only files under the root directory are served, so a configuration file
+/// cannot read arbitrary files from the server. Relative paths are relative
+/// to the root. A single trailing newline is dropped, as `echo` and most
+/// secret mounts add one.
+#[derive(Debug, Clone)]
+pub struct FileSecretProvider {
+    root: PathBuf,
+}
+
+impl FileSecretProvider {
+    /// Serves files under `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Returns the directory files are served from.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl Default for FileSecretProvider {
+    fn default() -> Self {
+        Self::new(DEFAULT_SECRETS_DIR)
+    }
+}
+
+#[async_trait]
+impl SecretProvider for FileSecretProvider {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn resolve(&self, reference: &SecretRef) -> Result<SecretString, SecretError> {
+        if reference.key.is_some() {
+            return Err(SecretError::InvalidReference(reference.to_string()));
+        }
+        let not_found = |e: std::io::Error| match e.kind() {
+            ErrorKind::NotFound => SecretError::NotFound(reference.to_string()),
+            _ => SecretError::Provider {
+                provider: self.name().to_string(),
+                message: format!("cannot read {}: {}", reference.path, e),
+            },
+        };
+
+        // Symlinks and `..` are resolved before checking the root, so neither
+        // can escape it.
+        let root = tokio::fs::canonicalize(&self.root)
+            .await
+            .map_err(not_found)?;
+        let path = tokio::fs::canonicalize(root.join(&reference.path))
+            .await
+            .map_err(not_found)?;
+        if !path.starts_with(&root) {
+            return Err(SecretError::Denied {
+                reference: reference.to_string(),
+                reason: format!("only files under {} are served", self.root.display()),
+            });
+        }
+
+        let mut contents = tokio::fs::read_to_string(&path).await.map_err(not_found)?;
+        if contents.ends_with('\n') {
+            contents.pop();
+            if contents.ends_with('\r') {
+                contents.pop();
+            }
+        }
+        Ok(SecretString::new(contents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference(body: &str) -> SecretRef {
+        SecretRef::parse(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reads_files_under_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("db"), "hunter2\n").unwrap();
+        let provider = FileSecretProvider::new(dir.path());
+
+        let relative = provider.resolve(&reference("file:db")).await.unwrap();
+        assert_eq!(relative.expose(), "hunter2");
+
+        let absolute = format!("file:{}", dir.path().join("db").display());
+        let absolute = provider.resolve(&reference(&absolute)).await.unwrap();
+        assert_eq!(absolute.expose(), "hunter2");
+
+        assert!(matches!(
+            provider.resolve(&reference("file:missing")).await,
+            Err(SecretError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_files_outside_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("secrets");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(dir.path().join("private"), "nope").unwrap();
+        let provider = FileSecretProvider::new(&root);
+
+        for body in [
+            "file:../private".to_string(),
+            format!("file:{}", dir.path().join("private").display()),
+        ] {
+            assert!(matches!(
+                provider.resolve(&reference(&body)).await,
+                Err(SecretError::Denied { .. })
+            ));
+        }
+    }
+}
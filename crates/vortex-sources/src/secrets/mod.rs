@@ -0,0 +1,228 @@
+//! Secret references resolved at serve time.
+//!
+//! Instead of committing secrets, configuration values reference them:
+//!
+//! ```yaml
+//! db:
+//!   password: ${secret:vault:kv/payments#db_password}
+//!   url: jdbc:postgresql://${secret:env:DB_HOST}/payments
+//! tls:
+//!   key: ${secret:file:/run/secrets/tls.key}
+//! ```
+//!
+//! A reference is `${secret:<provider>:<path>[#<key>]}`. The provider named
+//! in the reference fetches the value through a [`SecretProvider`];
+//! [`SecretResolver`] holds the registered providers and caches what they
+//! return. Resolved values are carried as [`SecretString`], which never
+//! prints its contents, so they cannot end up in logs by accident.
+//!
+//! # Example
+//!
+//! ```
+//! use vortex_sources::secrets::{EnvSecretProvider, SecretResolver};
+//!
+//! # let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//! # rt.block_on(async {
+//! // SAFETY: the example is single threaded.
+//! unsafe { std::env::set_var("DOC_DB_USER", "payments") };
+//!
+//! let resolver = SecretResolver::new().with_provider(EnvSecretProvider::new("DOC_").unwrap());
+//! let value = resolver.resolve_value("user=${secret:env:DOC_DB_USER}").await.unwrap();
+//! assert_eq!(value.as_deref(), Some("user=payments"));
+//! # });
+//! ```
+
+mod env;
+mod file;
+mod resolver;
+mod vault;
+
+use std::fmt;
+
+use async_trait::async_trait;
+use thiserror::Error;
+use vortex_core::placeholder;
+
+pub use env::EnvSecretProvider;
+pub use file::{DEFAULT_SECRETS_DIR, FileSecretProvider};
+pub use resolver::{DEFAULT_CACHE_TTL, SecretResolver};
+pub use vault::VaultSecretProvider;
+
+/// Placeholder key that marks a secret reference: `${secret:...}`.
+pub const SECRET_KEY: &str = "secret";
+
+/// Errors resolving a secret reference.
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum SecretError {
+    /// The reference is not `<provider>:<path>[#<key>]`.
+    #[error("invalid secret reference '{0}'")]
+    InvalidReference(String),
+
+    /// No provider is registered under the name.
+    #[error("unknown secret provider '{0}'")]
+    UnknownProvider(String),
+
+    /// The provider has no secret at the reference.
+    #[error("secret '{0}' not found")]
+    NotFound(String),
+
+    /// The provider refuses to serve the reference.
+    #[error("secret '{reference}' is not allowed: {reason}")]
+    Denied {
+        /// The reference, without its value.
+        reference: String,
+        /// Why it was refused.
+        reason: String,
+    },
+
+    /// The provider failed to fetch the secret.
+    #[error("secret provider '{provider}' failed: {message}")]
+    Provider {
+        /// Name of the provider.
+        provider: String,
+        /// What went wrong. Never contains secret values.
+        message: String,
+    },
+}
+
+/// A reference to a secret: `<provider>:<path>[#<key>]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SecretRef {
+    /// Name of the provider, e.g. `vault`.
+    pub provider: String,
+    /// Provider specific location, e.g. `kv/payments`.
+    pub path: String,
+    /// Field within the secret, e.g. `db_password`.
+    pub key: Option<String>,
+}
+
+impl SecretRef {
+    /// Parses `<provider>:<path>[#<key>]`, the body after `secret:`.
+    pub fn parse(reference: &str) -> Result<Self, SecretError> {
+        let invalid = || SecretError::InvalidReference(reference.to_string());
+
+        let (provider, location) = reference.split_once(':').ok_or_else(invalid)?;
+        let (path, key) = match location.rsplit_once('#') {
+            Some((path, key)) => (path, Some(key)),
+            None => (location, None),
+        };
+
+        if provider.is_empty() || path.is_empty() || key.is_some_and(str::is_empty) {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            provider: provider.to_string(),
+            path: path.to_string(),
+            key: key.map(String::from),
+        })
+    }
+
+    /// Finds the `${secret:...}` references in a string value, with the byte
+    /// range each one spans.
+    ///
+    /// Other placeholders, including a plain `${secret}` property reference,
+    /// are left alone; they are resolved by the clients.
+    pub fn find_all(value: &str) -> Vec<(std::ops::Range<usize>, Result<Self, SecretError>)> {
+        placeholder::parse(value)
+            .into_iter()
+            .filter(|p| p.key == SECRET_KEY)
+            .filter_map(|p| Some((p.span, Self::parse(p.default.as_deref()?))))
+            .collect()
+    }
+}
+
+impl fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", SECRET_KEY, self.provider, self.path)?;
+        if let Some(key) = &self.key {
+            write!(f, "#{}", key)?;
+        }
+        Ok(())
+    }
+}
+
+/// A resolved secret value.
+///
+/// `Debug` and `Display` print `***`; call [`expose`](Self::expose) to read
+/// the value where it is actually needed.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    /// Wraps a secret value.
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// Returns the secret value.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString(***)")
+    }
+}
+
+impl fmt::Display for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+/// Fetches secrets from one kind of store.
+#[async_trait]
+pub trait SecretProvider: Send + Sync {
+    /// Name used in references, e.g. `vault` in `${secret:vault:...}`.
+    fn name(&self) -> &str;
+
+    /// Fetches the secret a reference points to.
+    async fn resolve(&self, reference: &SecretRef) -> Result<SecretString, SecretError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reference() {
+        let reference = SecretRef::parse("vault:kv/payments#db_password").unwrap();
+        assert_eq!(reference.provider, "vault");
+        assert_eq!(reference.path, "kv/payments");
+        assert_eq!(reference.key.as_deref(), Some("db_password"));
+        assert_eq!(
+            reference.to_string(),
+            "secret:vault:kv/payments#db_password"
+        );
+
+        let reference = SecretRef::parse("file:/run/secrets/db").unwrap();
+        assert_eq!(reference.path, "/run/secrets/db");
+        assert_eq!(reference.key, None);
+
+        for invalid in ["vault", ":path", "env:", "vault:kv/app#"] {
+            assert!(SecretRef::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_find_all_skips_other_placeholders() {
+        let value = "${db.user}:${secret:env:DB_PASSWORD}@${secret}/${secret:bad}";
+        let found = SecretRef::find_all(value);
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(&value[found[0].0.clone()], "${secret:env:DB_PASSWORD}");
+        assert_eq!(found[0].1.as_ref().unwrap().path, "DB_PASSWORD");
+        assert!(found[1].1.is_err());
+    }
+
+    #[test]
+    fn test_secret_string_is_redacted() {
+        let secret = SecretString::new("hunter2");
+        assert_eq!(format!("{:?}", secret), "SecretString(***)");
+        assert_eq!(secret.to_string(), "***");
+        assert_eq!(secret.expose(), "hunter2");
+    }
+}
@@ -0,0 +1,215 @@
+//! Provider registry and secret cache.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{SecretError, SecretProvider, SecretRef, SecretString};
+
+/// How long resolved secrets are reused by default.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Resolves `${secret:...}` references with the registered providers.
+///
+/// Resolved secrets are cached by reference for the cache TTL, independently
+/// of any configuration cache, so a rotated secret is picked up without
+/// reloading configuration and configuration caches never hold secrets.
+/// Failed lookups are not cached.
+pub struct SecretResolver {
+    providers: HashMap<String, Arc<dyn SecretProvider>>,
+    cache: Mutex<HashMap<SecretRef, (SecretString, Instant)>>,
+    ttl: Duration,
+}
+
+impl SecretResolver {
+    /// Creates a resolver without providers.
+    pub fn new() -> Self {
+        Self {
+            providers: HashMap::new(),
+            cache: Mutex::new(HashMap::new()),
+            ttl: DEFAULT_CACHE_TTL,
+        }
+    }
+
+    /// Registers a provider under its [`name`](SecretProvider::name),
+    /// replacing any provider with the same name.
+    pub fn with_provider(mut self, provider: impl SecretProvider + 'static) -> Self {
+        self.providers
+            .insert(provider.name().to_string(), Arc::new(provider));
+        self
+    }
+
+    /// Sets how long resolved secrets are reused. Zero disables the cache.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Returns `true` if no provider is registered.
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Returns the names of the registered providers, sorted.
+    pub fn provider_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.providers.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Drops every cached secret.
+    pub fn clear_cache(&self) {
+        self.cache.lock().expect("secret cache poisoned").clear();
+    }
+
+    /// Resolves a single reference, from the cache when possible.
+    pub async fn resolve(&self, reference: &SecretRef) -> Result<SecretString, SecretError> {
+        if let Some(secret) = self.cached(reference) {
+            return Ok(secret);
+        }
+
+        let provider = self
+            .providers
+            .get(&reference.provider)
+            .ok_or_else(|| SecretError::UnknownProvider(reference.provider.clone()))?;
+        tracing::debug!(reference = %reference, "Resolving secret");
+        let secret = provider.resolve(reference).await?;
+
+        if !self.ttl.is_zero() {
+            self.cache
+                .lock()
+                .expect("secret cache poisoned")
+                .insert(reference.clone(), (secret.clone(), Instant::now()));
+        }
+        Ok(secret)
+    }
+
+    /// Replaces every `${secret:...}` reference in a string value.
+    ///
+    /// Returns `None` if the value has no references, so callers can leave it
+    /// untouched. Fails on the first reference that cannot be resolved.
+    pub async fn resolve_value(&self, value: &str) -> Result<Option<String>, SecretError> {
+        let references = SecretRef::find_all(value);
+        if references.is_empty() {
+            return Ok(None);
+        }
+
+        let mut resolved = String::with_capacity(value.len());
+        let mut last = 0;
+        for (span, reference) in references {
+            let secret = self.resolve(&reference?).await?;
+            resolved.push_str(&value[last..span.start]);
+            resolved.push_str(secret.expose());
+            last = span.end;
+        }
+        resolved.push_str(&value[last..]);
+        Ok(Some(resolved))
+    }
+
+    fn cached(&self, reference: &SecretRef) -> Option<SecretString> {
+        let mut cache = self.cache.lock().expect("secret cache poisoned");
+        match cache.get(reference) {
+            Some((secret, at)) if at.elapsed() < self.ttl => Some(secret.clone()),
+            Some(_) => {
+                cache.remove(reference);
+                None
+            },
+            None => None,
+        }
+    }
+}
+
+impl Default for SecretResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for SecretResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretResolver")
+            .field("providers", &self.provider_names())
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use async_trait::async_trait;
+
+    /// Provider that counts lookups and returns `<path>-<count>`.
+    #[derive(Default)]
+    struct Counting(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl SecretProvider for Counting {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        async fn resolve(&self, reference: &SecretRef) -> Result<SecretString, SecretError> {
+            if reference.path == "missing" {
+                return Err(SecretError::NotFound(reference.to_string()));
+            }
+            let count = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(SecretString::new(format!("{}-{}", reference.path, count)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_value_substitutes_references() {
+        let resolver = SecretResolver::new().with_provider(Counting::default());
+
+        let value = resolver
+            .resolve_value("${user}:${secret:counting:pw}@${secret:counting:pw}/db")
+            .await
+            .unwrap();
+        assert_eq!(value.as_deref(), Some("${user}:pw-1@pw-1/db"));
+
+        assert_eq!(resolver.resolve_value("plain ${user}").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_cache_expires() {
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let resolver = SecretResolver::new()
+            .with_provider(Counting(Arc::clone(&lookups)))
+            .with_cache_ttl(Duration::from_millis(50));
+        let reference = SecretRef::parse("counting:pw").unwrap();
+
+        resolver.resolve(&reference).await.unwrap();
+        resolver.resolve(&reference).await.unwrap();
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let secret = resolver.resolve(&reference).await.unwrap();
+        assert_eq!(secret.expose(), "pw-2");
+
+        resolver.clear_cache();
+        resolver.resolve(&reference).await.unwrap();
+        assert_eq!(lookups.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let resolver = SecretResolver::new().with_provider(Counting::default());
+
+        assert_eq!(
+            resolver.resolve_value("${secret:vault:kv/app#pw}").await,
+            Err(SecretError::UnknownProvider("vault".to_string()))
+        );
+        assert!(matches!(
+            resolver.resolve_value("${secret:counting:missing}").await,
+            Err(SecretError::NotFound(_))
+        ));
+        assert!(matches!(
+            resolver.resolve_value("${secret:nonsense}").await,
+            Err(SecretError::InvalidReference(_))
+        ));
+    }
+}
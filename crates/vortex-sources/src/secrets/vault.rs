@@ -0,0 +1,209 @@
+//! Secrets from the HashiCorp Vault KV version 2 engine.
+
+use async_trait::async_trait;
+use hyper::StatusCode;
+use serde_json::Value;
+
+use super::{SecretError, SecretProvider, SecretRef, SecretString};
+use crate::http::HttpClient;
+
+/// Resolves `${secret:vault:<mount>/<path>#<key>}` from a KV v2 engine.
+///
+/// `kv/payments#db_password` reads field `db_password` of secret `payments`
+/// in the engine mounted at `kv`, i.e. `GET /v1/kv/data/payments`. The key
+/// may be left out when the secret has a single field.
+#[derive(Clone)]
+pub struct VaultSecretProvider {
+    address: String,
+    token: String,
+    namespace: Option<String>,
+    client: HttpClient,
+}
+
+impl VaultSecretProvider {
+    /// Creates a provider for the Vault server at `address`, authenticated
+    /// with `token`.
+    pub fn new(address: impl Into<String>, token: impl Into<String>) -> Result<Self, SecretError> {
+        let client = HttpClient::new().map_err(|message| SecretError::Provider {
+            provider: "vault".to_string(),
+            message,
+        })?;
+
+        Ok(Self {
+            address: address.into().trim_end_matches('/').to_string(),
+            token: token.into(),
+            namespace: None,
+            client,
+        })
+    }
+
+    /// Sends requests to a Vault Enterprise namespace.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Returns the address of the Vault server.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    fn error(&self, message: impl Into<String>) -> SecretError {
+        SecretError::Provider {
+            provider: self.name().to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Debug for VaultSecretProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultSecretProvider")
+            .field("address", &self.address)
+            .field("namespace", &self.namespace)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl SecretProvider for VaultSecretProvider {
+    fn name(&self) -> &str {
+        "vault"
+    }
+
+    async fn resolve(&self, reference: &SecretRef) -> Result<SecretString, SecretError> {
+        let (mount, path) = reference
+            .path
+            .trim_matches('/')
+            .split_once('/')
+            .ok_or_else(|| SecretError::InvalidReference(reference.to_string()))?;
+        let url = format!("{}/v1/{}/data/{}", self.address, mount, path);
+
+        let mut headers = vec![("X-Vault-Token", self.token.as_str())];
+        if let Some(namespace) = &self.namespace {
+            headers.push(("X-Vault-Namespace", namespace.as_str()));
+        }
+
+        let response = self
+            .client
+            .get(&url, &headers)
+            .await
+            .map_err(|e| self.error(e))?;
+        match response.status {
+            StatusCode::OK => {},
+            StatusCode::NOT_FOUND => return Err(SecretError::NotFound(reference.to_string())),
+            StatusCode::FORBIDDEN => {
+                return Err(SecretError::Denied {
+                    reference: reference.to_string(),
+                    reason: "permission denied by Vault".to_string(),
+                });
+            },
+            status => return Err(self.error(format!("GET {} returned {}", url, status))),
+        }
+
+        let body: Value = serde_json::from_slice(&response.body)
+            .map_err(|e| self.error(format!("invalid response from {}: {}", url, e)))?;
+        let Some(Value::Object(data)) = body.pointer("/data/data") else {
+            return Err(self.error(format!("{} is not a KV v2 secret", url)));
+        };
+
+        let value = match &reference.key {
+            Some(key) => data.get(key),
+            None if data.len() == 1 => data.values().next(),
+            None => {
+                return Err(self.error(format!(
+                    "secret {} has {} fields; name one with '#<key>'",
+                    reference.path,
+                    data.len()
+                )));
+            },
+        };
+
+        match value {
+            Some(Value::String(s)) => Ok(SecretString::new(s.clone())),
+            Some(Value::Null) | None => Err(SecretError::NotFound(reference.to_string())),
+            Some(other) => Ok(SecretString::new(other.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::{Response, serve};
+
+    const TOKEN: &str = "s.test-token";
+
+    /// Starts a Vault mock that serves `kv/payments` to requests carrying
+    /// [`TOKEN`], and returns its address.
+    async fn mock_vault() -> String {
+        serve(|request| async move {
+            if request.header("x-vault-token") != Some(TOKEN) {
+                Response::new(403, r#"{"errors":["permission denied"]}"#)
+            } else if request.method == "GET" && request.target == "/v1/kv/data/payments" {
+                Response::new(
+                    200,
+                    r#"{"data":{"data":{"db_password":"hunter2","port":5432},
+                    "metadata":{"version":3}}}"#,
+                )
+            } else {
+                Response::new(404, r#"{"errors":[]}"#)
+            }
+            .with_header("content-type", "application/json")
+        })
+        .await
+    }
+
+    fn reference(body: &str) -> SecretRef {
+        SecretRef::parse(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reads_kv_v2_fields() {
+        let provider = VaultSecretProvider::new(mock_vault().await, TOKEN).unwrap();
+
+        let password = provider
+            .resolve(&reference("vault:kv/payments#db_password"))
+            .await
+            .unwrap();
+        assert_eq!(password.expose(), "hunter2");
+
+        let port = provider
+            .resolve(&reference("vault:kv/payments#port"))
+            .await
+            .unwrap();
+        assert_eq!(port.expose(), "5432");
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let address = mock_vault().await;
+        let provider = VaultSecretProvider::new(&address, TOKEN).unwrap();
+
+        assert!(matches!(
+            provider.resolve(&reference("vault:kv/orders#x")).await,
+            Err(SecretError::NotFound(_))
+        ));
+        assert!(matches!(
+            provider.resolve(&reference("vault:kv/payments#user")).await,
+            Err(SecretError::NotFound(_))
+        ));
+        assert!(matches!(
+            provider.resolve(&reference("vault:kv/payments")).await,
+            Err(SecretError::Provider { .. })
+        ));
+        assert!(matches!(
+            provider.resolve(&reference("vault:payments#x")).await,
+            Err(SecretError::InvalidReference(_))
+        ));
+
+        let anonymous = VaultSecretProvider::new(&address, "wrong").unwrap();
+        assert!(matches!(
+            anonymous
+                .resolve(&reference("vault:kv/payments#db_password"))
+                .await,
+            Err(SecretError::Denied { .. })
+        ));
+    }
+}
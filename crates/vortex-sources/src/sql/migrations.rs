@@ -0,0 +1,148 @@
+//! Schema migrations for the `PROPERTIES` table.
+//!
+//! Applied versions are recorded in `vortex_schema_migrations`, so running
+//! the migrations again only applies the new ones.
+
+use super::{Dialect, SqlDatabase, SqlError, SqlValue};
+
+/// Table recording the applied migrations.
+const MIGRATIONS_TABLE: &str = "vortex_schema_migrations";
+
+/// A versioned schema change.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// Version, applied in increasing order.
+    pub version: u32,
+    /// Short description.
+    pub name: &'static str,
+    /// Statements for SQLite.
+    sqlite: &'static str,
+    /// Statements for PostgreSQL.
+    postgres: &'static str,
+}
+
+impl Migration {
+    /// Returns the statements for `dialect`.
+    pub fn sql(&self, dialect: Dialect) -> &'static str {
+        match dialect {
+            Dialect::Sqlite => self.sqlite,
+            Dialect::Postgres => self.postgres,
+        }
+    }
+}
+
+/// All migrations, oldest first.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    name: "create properties",
+    // No declared type on VALUE: SQLite keeps each value's own type, so
+    // integers and booleans come back typed.
+    sqlite: "CREATE TABLE IF NOT EXISTS PROPERTIES (
+        ID INTEGER PRIMARY KEY AUTOINCREMENT,
+        APPLICATION TEXT NOT NULL,
+        PROFILE TEXT NOT NULL DEFAULT 'default',
+        LABEL TEXT NOT NULL DEFAULT 'main',
+        KEY TEXT NOT NULL,
+        VALUE,
+        UNIQUE (APPLICATION, PROFILE, LABEL, KEY)
+    )",
+    postgres: "CREATE TABLE IF NOT EXISTS PROPERTIES (
+        ID BIGSERIAL PRIMARY KEY,
+        APPLICATION TEXT NOT NULL,
+        PROFILE TEXT NOT NULL DEFAULT 'default',
+        LABEL TEXT NOT NULL DEFAULT 'main',
+        KEY TEXT NOT NULL,
+        VALUE TEXT,
+        UNIQUE (APPLICATION, PROFILE, LABEL, KEY)
+    )",
+}];
+
+/// Applies the pending migrations and returns their versions.
+pub(crate) async fn run(db: &dyn SqlDatabase) -> Result<Vec<u32>, SqlError> {
+    db.execute(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (VERSION INTEGER PRIMARY KEY, NAME TEXT NOT NULL)",
+        MIGRATIONS_TABLE
+    ))
+    .await?;
+
+    let applied = db
+        .query(
+            &format!("SELECT MAX(VERSION) FROM {}", MIGRATIONS_TABLE),
+            &[],
+        )
+        .await?;
+    let current = match applied.first().and_then(|row| row.first()) {
+        Some(SqlValue::Integer(version)) => *version,
+        Some(SqlValue::Text(version)) => version.parse().unwrap_or(0),
+        _ => 0,
+    };
+
+    let mut versions = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| i64::from(m.version) > current) {
+        tracing::info!(
+            "Applying migration {} ({})",
+            migration.version,
+            migration.name
+        );
+        let result = db
+            .execute(&format!(
+                "BEGIN; {}; INSERT INTO {} (VERSION, NAME) VALUES ({}, '{}'); COMMIT",
+                migration.sql(db.dialect()),
+                MIGRATIONS_TABLE,
+                migration.version,
+                migration.name
+            ))
+            .await;
+        if let Err(e) = result {
+            // SQLite stops at the failing statement with the transaction open.
+            let _ = db.execute("ROLLBACK").await;
+            return Err(SqlError::Query(format!(
+                "migration {} failed: {}",
+                migration.version, e
+            )));
+        }
+        versions.push(migration.version);
+    }
+    Ok(versions)
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::sql::SqliteDatabase;
+
+    #[test]
+    fn test_versions_increase() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version < w[1].version));
+        assert!(MIGRATIONS.iter().all(|m| !m.name.contains('\'')));
+    }
+
+    #[tokio::test]
+    async fn test_runs_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite:{}", dir.path().join("config.db").display());
+        let db = SqliteDatabase::open(&url, 1).unwrap();
+
+        let all: Vec<u32> = MIGRATIONS.iter().map(|m| m.version).collect();
+        assert_eq!(run(&db).await.unwrap(), all);
+        assert!(run(&db).await.unwrap().is_empty());
+
+        db.execute(
+            "INSERT INTO PROPERTIES (APPLICATION, KEY, VALUE) VALUES ('payments', 'a', TRUE)",
+        )
+        .await
+        .unwrap();
+        let rows = db
+            .query("SELECT PROFILE, LABEL, VALUE FROM PROPERTIES", &[])
+            .await
+            .unwrap();
+        assert_eq!(
+            rows,
+            [vec![
+                SqlValue::Text("default".to_string()),
+                SqlValue::Text("main".to_string()),
+                SqlValue::Integer(1),
+            ]]
+        );
+    }
+}
@@ -0,0 +1,307 @@
+//! SQLite through `rusqlite`, with SQLite bundled into the binary.
+//!
+//! Statements run on tokio's blocking threads; each connection is used by
+//! one thread at a time.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags, params_from_iter};
+
+use super::{Dialect, SqlDatabase, SqlError, SqlTransaction, SqlValue};
+use crate::pool::{Connect, Pool, Pooled};
+
+/// How long a connection waits for a lock held by another one.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A SQLite database file, with a pool of connections.
+pub struct SqliteDatabase {
+    path: PathBuf,
+    pool: Pool<Opener>,
+}
+
+impl SqliteDatabase {
+    /// Opens `sqlite:<path>` (also `sqlite://<path>`), creating the file if
+    /// needed.
+    pub fn open(url: &str, pool_size: usize) -> Result<Self, SqlError> {
+        let path = url
+            .strip_prefix("sqlite:")
+            .map(|rest| rest.strip_prefix("//").unwrap_or(rest))
+            .filter(|path| !path.is_empty())
+            .ok_or_else(|| SqlError::InvalidUrl(url.to_string()))?;
+        let path = PathBuf::from(path);
+
+        // Open one connection now so a bad path fails at startup.
+        let opener = Opener(path.clone());
+        drop(opener.open()?);
+
+        Ok(Self {
+            path,
+            pool: Pool::new(opener, pool_size),
+        })
+    }
+
+    /// Returns the path of the database file.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Runs `f` with a pooled connection on a blocking thread.
+    async fn with_connection<T, F>(&self, f: F) -> Result<T, SqlError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, SqlError> + Send + 'static,
+    {
+        let connection = self.pool.get().await?;
+        tokio::task::spawn_blocking(move || f(&connection))
+            .await
+            .map_err(|e| SqlError::Query(format!("SQLite task failed: {}", e)))?
+    }
+}
+
+#[async_trait]
+impl SqlDatabase for SqliteDatabase {
+    fn dialect(&self) -> Dialect {
+        Dialect::Sqlite
+    }
+
+    async fn query(&self, sql: &str, params: &[&str]) -> Result<Vec<Vec<SqlValue>>, SqlError> {
+        let sql = sql.to_string();
+        let params: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+        self.with_connection(move |connection| query(connection, &sql, &params))
+            .await
+    }
+
+    async fn execute(&self, sql: &str) -> Result<(), SqlError> {
+        let sql = sql.to_string();
+        self.with_connection(move |connection| execute(connection, &sql))
+            .await
+    }
+
+    async fn begin(&self) -> Result<Box<dyn SqlTransaction>, SqlError> {
+        let mut transaction = Transaction {
+            connection: Some(self.pool.get().await?),
+        };
+        transaction
+            .run(|connection| execute(connection, "BEGIN IMMEDIATE"))
+            .await?;
+        Ok(Box::new(transaction))
+    }
+}
+
+/// A transaction holding a pooled connection.
+struct Transaction {
+    connection: Option<Pooled<Opener>>,
+}
+
+impl Transaction {
+    /// Runs `f` with the connection on a blocking thread.
+    async fn run<T, F>(&mut self, f: F) -> Result<T, SqlError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T, SqlError> + Send + 'static,
+    {
+        let connection = self
+            .connection
+            .take()
+            .ok_or_else(|| SqlError::Query("the transaction is over".to_string()))?;
+        let (connection, result) = tokio::task::spawn_blocking(move || {
+            let result = f(&connection);
+            (connection, result)
+        })
+        .await
+        .map_err(|e| SqlError::Query(format!("SQLite task failed: {}", e)))?;
+        self.connection = Some(connection);
+        result
+    }
+}
+
+#[async_trait]
+impl SqlTransaction for Transaction {
+    async fn query(&mut self, sql: &str, params: &[&str]) -> Result<Vec<Vec<SqlValue>>, SqlError> {
+        let sql = sql.to_string();
+        let params: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+        self.run(move |connection| query(connection, &sql, &params))
+            .await
+    }
+
+    async fn commit(mut self: Box<Self>) -> Result<(), SqlError> {
+        self.run(|connection| execute(connection, "COMMIT")).await?;
+        // Committed: the connection can go back to the pool.
+        drop(self.connection.take());
+        Ok(())
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            connection.discard();
+        }
+    }
+}
+
+impl std::fmt::Debug for SqliteDatabase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SqliteDatabase")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+/// Opens connections to one database file.
+struct Opener(PathBuf);
+
+impl Opener {
+    fn open(&self) -> Result<Connection, SqlError> {
+        let connection = Connection::open_with_flags(
+            &self.0,
+            OpenFlags::SQLITE_OPEN_READ_WRITE
+                | OpenFlags::SQLITE_OPEN_CREATE
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(|e| SqlError::Connection(format!("{}: {}", self.0.display(), e)))?;
+        connection
+            .busy_timeout(BUSY_TIMEOUT)
+            .map_err(|e| SqlError::Connection(format!("{}: {}", self.0.display(), e)))?;
+        Ok(connection)
+    }
+}
+
+#[async_trait]
+impl Connect for Opener {
+    type Connection = Connection;
+    type Error = SqlError;
+
+    async fn connect(&self) -> Result<Connection, SqlError> {
+        self.open()
+    }
+}
+
+/// Runs one or more statements without parameters.
+fn execute(connection: &Connection, sql: &str) -> Result<(), SqlError> {
+    connection
+        .execute_batch(sql)
+        .map_err(|e| SqlError::Query(e.to_string()))
+}
+
+/// Runs a query with text parameters and returns its rows.
+fn query(
+    connection: &Connection,
+    sql: &str,
+    params: &[String],
+) -> Result<Vec<Vec<SqlValue>>, SqlError> {
+    let error = |e: rusqlite::Error| SqlError::Query(e.to_string());
+    let mut statement = connection.prepare(sql).map_err(error)?;
+    let expected = statement.parameter_count();
+    if expected != params.len() {
+        return Err(SqlError::Query(format!(
+            "the query takes {} parameters, {} given",
+            expected,
+            params.len()
+        )));
+    }
+
+    let columns = statement.column_count();
+    let mut rows = statement.query(params_from_iter(params)).map_err(error)?;
+    let mut values = Vec::new();
+    while let Some(row) = rows.next().map_err(error)? {
+        let row = (0..columns)
+            .map(|i| {
+                Ok(match row.get_ref(i)? {
+                    ValueRef::Null => SqlValue::Null,
+                    ValueRef::Integer(n) => SqlValue::Integer(n),
+                    ValueRef::Real(n) => SqlValue::Float(n),
+                    ValueRef::Text(text) => SqlValue::Text(String::from_utf8_lossy(text).into()),
+                    ValueRef::Blob(blob) => SqlValue::Blob(blob.to_vec()),
+                })
+            })
+            .collect::<Result<_, rusqlite::Error>>()
+            .map_err(error)?;
+        values.push(row);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_typed_columns_and_parameters() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}", dir.path().join("t.db").display());
+        let db = SqliteDatabase::open(&url, 2).unwrap();
+
+        db.execute("CREATE TABLE t (k TEXT, v); INSERT INTO t VALUES ('a', 1), ('b', 'x''y'), ('c', 2.5), ('d', NULL), ('e', X'6869')")
+            .await
+            .unwrap();
+
+        let rows = db
+            .query("SELECT k, v FROM t WHERE k <> ? ORDER BY k", &["e"])
+            .await
+            .unwrap();
+        let values: Vec<SqlValue> = rows.into_iter().map(|mut r| r.remove(1)).collect();
+        assert_eq!(
+            values,
+            [
+                SqlValue::Integer(1),
+                SqlValue::Text("x'y".to_string()),
+                SqlValue::Float(2.5),
+                SqlValue::Null,
+            ]
+        );
+
+        let blob = db
+            .query("SELECT v FROM t WHERE k = ?", &["e"])
+            .await
+            .unwrap();
+        assert_eq!(blob[0][0], SqlValue::Blob(b"hi".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_transactions() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = SqliteDatabase::open(&format!("sqlite:{}/t.db", dir.path().display()), 2).unwrap();
+        db.execute("CREATE TABLE t (k TEXT)").await.unwrap();
+
+        let mut transaction = db.begin().await.unwrap();
+        transaction
+            .query("INSERT INTO t VALUES (?)", &["a"])
+            .await
+            .unwrap();
+        transaction.commit().await.unwrap();
+
+        // Dropped without a commit: rolled back.
+        let mut transaction = db.begin().await.unwrap();
+        transaction
+            .query("INSERT INTO t VALUES (?)", &["b"])
+            .await
+            .unwrap();
+        drop(transaction);
+
+        let rows = db.query("SELECT k FROM t", &[]).await.unwrap();
+        assert_eq!(rows, [vec![SqlValue::Text("a".to_string())]]);
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = SqliteDatabase::open(&format!("sqlite:{}/t.db", dir.path().display()), 1).unwrap();
+
+        let error = db.query("SELECT * FROM missing", &[]).await.unwrap_err();
+        assert!(error.to_string().contains("no such table"), "{}", error);
+
+        let error = db.query("SELECT ?", &[]).await.unwrap_err();
+        assert!(
+            error.to_string().contains("1 parameters, 0 given"),
+            "{}",
+            error
+        );
+
+        assert!(SqliteDatabase::open("sqlite:", 1).is_err());
+        assert!(SqliteDatabase::open("sqlite:/nonexistent/dir/t.db", 1).is_err());
+    }
+}
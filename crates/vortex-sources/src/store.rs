@@ -0,0 +1,147 @@
+//! Helpers shared by the writable backends.
+//!
+//! Versions are digests of what is stored, so a document edited behind the
+//! server's back gets a new version too.
+
+use sha2::{Digest, Sha256};
+use vortex_core::{ConfigMap, ConfigPath, ConfigValue};
+
+/// Hex digits of the digest kept as the version.
+const VERSION_LEN: usize = 16;
+
+/// A change to one document.
+pub(crate) enum Write<'a> {
+    /// Replace the whole document.
+    Put(&'a ConfigMap),
+    /// Delete a key and the keys nested below it.
+    Delete(&'a str),
+}
+
+/// Returns the version of a stored document from its contents.
+pub(crate) fn content_version(content: &[u8]) -> String {
+    let digest = Sha256::digest(content);
+    digest
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>()[..VERSION_LEN]
+        .to_string()
+}
+
+/// Returns the version of a document stored as flat keys and values.
+///
+/// The pairs are hashed sorted, so the order they were read in does not
+/// matter.
+pub(crate) fn flat_version<'a>(pairs: impl IntoIterator<Item = (&'a str, String)>) -> String {
+    let mut pairs: Vec<(&str, String)> = pairs.into_iter().collect();
+    pairs.sort();
+    let mut content = Vec::new();
+    for (key, value) in pairs {
+        content.extend_from_slice(key.as_bytes());
+        content.push(0);
+        content.extend_from_slice(value.as_bytes());
+        content.push(b'\n');
+    }
+    content_version(&content)
+}
+
+/// Flattens a map into dotted keys, with list items as indexes
+/// (`db.hosts[0]`), as the flat backends store them.
+///
+/// Empty maps and lists have no keys.
+pub(crate) fn flatten(config: &ConfigMap) -> Vec<(String, ConfigValue)> {
+    let mut flat = Vec::new();
+    let mut path = ConfigPath::root();
+    for (key, value) in config.as_inner() {
+        path.push_key(key.as_str());
+        flatten_value(&mut path, value, &mut flat);
+        path.pop();
+    }
+    flat
+}
+
+fn flatten_value(
+    path: &mut ConfigPath,
+    value: &ConfigValue,
+    flat: &mut Vec<(String, ConfigValue)>,
+) {
+    match value {
+        ConfigValue::Object(map) => {
+            for (key, value) in map {
+                path.push_key(key.as_str());
+                flatten_value(path, value, flat);
+                path.pop();
+            }
+        },
+        ConfigValue::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                path.push_index(index);
+                flatten_value(path, item, flat);
+                path.pop();
+            }
+        },
+        leaf => flat.push((path.to_string(), leaf.clone())),
+    }
+}
+
+/// Returns a scalar as the text a flat backend stores.
+pub(crate) fn scalar_text(value: &ConfigValue) -> Option<String> {
+    match value {
+        ConfigValue::Null => None,
+        ConfigValue::Bool(b) => Some(b.to_string()),
+        ConfigValue::Integer(i) => Some(i.to_string()),
+        ConfigValue::Float(f) => Some(f.to_string()),
+        ConfigValue::String(s) => Some(s.clone()),
+        other => serde_json::to_string(other).ok(),
+    }
+}
+
+/// Returns whether deleting `key` deletes the stored key `stored`: the key
+/// itself or anything nested below it.
+pub(crate) fn covers(key: &str, stored: &str) -> bool {
+    match stored.strip_prefix(key) {
+        Some(rest) => rest.is_empty() || rest.starts_with('.') || rest.starts_with('['),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flatten() {
+        let config = ConfigMap::from_json(
+            r#"{"db": {"hosts": ["a", "b"], "pool": {"size": 20}, "ssl": null},
+                "logging": {"level": {"com.acme": "DEBUG"}}, "empty": {}}"#,
+        )
+        .unwrap();
+        let keys: Vec<String> = flatten(&config).into_iter().map(|(k, _)| k).collect();
+        assert_eq!(
+            keys,
+            [
+                "db.hosts[0]",
+                "db.hosts[1]",
+                "db.pool.size",
+                "db.ssl",
+                "logging.level[com.acme]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_versions_and_keys() {
+        let version = flat_version([("b", "2".to_string()), ("a", "1".to_string())]);
+        assert_eq!(version.len(), VERSION_LEN);
+        assert_eq!(
+            version,
+            flat_version([("a", "1".to_string()), ("b", "2".to_string())])
+        );
+        assert_ne!(version, flat_version([("a", "1".to_string())]));
+
+        assert!(covers("db", "db"));
+        assert!(covers("db", "db.pool.size"));
+        assert!(covers("db.hosts", "db.hosts[0]"));
+        assert!(!covers("db", "dba"));
+        assert!(!covers("db.pool", "db"));
+    }
+}
@@ -0,0 +1,118 @@
+//! A fake HTTP server for the tests of the HTTP backends.
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::HeaderMap;
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+
+/// A request received by the fake server.
+#[derive(Debug, Clone)]
+pub(crate) struct Request {
+    /// Method, e.g. `GET`.
+    pub method: String,
+    /// Path and query, as sent.
+    pub target: String,
+    /// Request headers.
+    pub headers: HeaderMap,
+}
+
+impl Request {
+    /// Returns the path, without the query.
+    pub(crate) fn path(&self) -> &str {
+        self.target
+            .split_once('?')
+            .map_or(self.target.as_str(), |(path, _)| path)
+    }
+
+    /// Returns the raw value of a query parameter.
+    pub(crate) fn param(&self, name: &str) -> Option<&str> {
+        let (_, query) = self.target.split_once('?')?;
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+    }
+
+    /// Returns a header value.
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+}
+
+/// A response of the fake server.
+#[derive(Debug, Clone)]
+pub(crate) struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Response {
+    /// Creates a response with a status code and a body.
+    pub(crate) fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Adds a header.
+    pub(crate) fn with_header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+}
+
+/// Starts a server answering every request with `handler`, and returns its
+/// address (`http://127.0.0.1:port`).
+pub(crate) async fn serve<F, Fut>(handler: F) -> String
+where
+    F: Fn(Request) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Response> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let handler = Arc::new(handler);
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let handler = Arc::clone(&handler);
+            let service = service_fn(move |request: hyper::Request<Incoming>| {
+                let handler = Arc::clone(&handler);
+                async move {
+                    let (parts, _) = request.into_parts();
+                    let request = Request {
+                        method: parts.method.to_string(),
+                        target: parts
+                            .uri
+                            .path_and_query()
+                            .map_or_else(|| "/".to_string(), ToString::to_string),
+                        headers: parts.headers,
+                    };
+
+                    let reply = handler(request).await;
+                    let mut response = hyper::Response::builder().status(reply.status);
+                    for (name, value) in reply.headers {
+                        response = response.header(name, value);
+                    }
+                    Ok::<_, Infallible>(response.body(Full::new(Bytes::from(reply.body))).unwrap())
+                }
+            });
+            tokio::spawn(async move {
+                let _ = http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    });
+
+    address
+}
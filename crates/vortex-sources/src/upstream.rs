@@ -0,0 +1,630 @@
+//! Upstream backend: another config server (server chaining).
+//!
+//! Serves the configuration of another Vortex or Spring Cloud Config server
+//! through its `/{application}/{profiles}/{label}` endpoint, so an edge
+//! region can run a caching Vortex in front of a central one:
+//!
+//! ```text
+//! clients ──> edge Vortex (cache) ──> central Vortex ──> git
+//! ```
+//!
+//! Property sources keep the upstream's names, and the response its version,
+//! state and warnings. Spring's flat keys (`db.hosts[0]`) are nested like
+//! `.properties` files; Vortex's nested values are kept as they are.
+//!
+//! Requests failing with a connection error, a `5xx` or a `429` are retried
+//! with exponential backoff. Each response is kept with its `ETag`: the next
+//! request for the same configuration sends `If-None-Match`, and a `304`
+//! reuses the kept copy. When the upstream still fails after the retries,
+//! the last good copy is served with a warning (stale-on-error).
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use hyper::{StatusCode, Uri};
+use serde::Deserialize;
+use tracing::{debug, warn};
+use vortex_core::{ConfigMap, PropertySource};
+use vortex_git::{ConfigQuery, ConfigResult, ConfigSource, ConfigSourceError, ConfigWarning};
+
+use crate::http::{HttpClient, HttpResponse, uri_encode};
+use crate::sql::insert_nested;
+
+/// Label used when the request does not name one.
+pub const DEFAULT_LABEL: &str = "main";
+
+/// Retries after a failed request.
+pub const DEFAULT_RETRIES: u32 = 2;
+
+/// Pause before the first retry; each retry doubles it.
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(200);
+
+/// Health endpoint of a Vortex server.
+pub const DEFAULT_HEALTH_PATH: &str = "/health";
+
+/// A configuration source backed by another config server.
+pub struct HttpUpstreamSource {
+    /// Client for the upstream.
+    client: HttpClient,
+    /// URL of the upstream, without a trailing slash.
+    base_url: String,
+    /// Headers sent with every request, authorization included.
+    headers: Vec<(String, String)>,
+    /// Label used when the request does not name one.
+    default_label: String,
+    /// Retries after a failed request.
+    retries: u32,
+    /// Pause before the first retry.
+    retry_backoff: Duration,
+    /// Serve the last good copy when the upstream fails.
+    stale_on_error: bool,
+    /// Oldest copy served when the upstream fails.
+    max_stale: Option<Duration>,
+    /// Path probed by the health check.
+    health_path: String,
+    /// Last good response per request URL.
+    responses: Mutex<HashMap<String, Cached>>,
+}
+
+/// A good response, kept for conditional requests and stale-on-error.
+struct Cached {
+    etag: Option<String>,
+    result: ConfigResult,
+    fetched: Instant,
+}
+
+impl HttpUpstreamSource {
+    /// Serves configuration from the server at `base_url`, e.g.
+    /// `https://config.internal:8888`.
+    pub fn new(base_url: &str) -> Result<Self, ConfigSourceError> {
+        let invalid = |reason: String| {
+            ConfigSourceError::unavailable(format!("invalid upstream URL {}: {}", base_url, reason))
+        };
+        let uri: Uri = base_url.parse().map_err(|e| invalid(format!("{}", e)))?;
+        if !matches!(uri.scheme_str(), Some("http" | "https")) || uri.authority().is_none() {
+            return Err(invalid("expected http(s)://host[:port][/path]".to_string()));
+        }
+
+        Ok(Self {
+            client: HttpClient::new().map_err(ConfigSourceError::unavailable)?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            headers: Vec::new(),
+            default_label: DEFAULT_LABEL.to_string(),
+            retries: DEFAULT_RETRIES,
+            retry_backoff: DEFAULT_RETRY_BACKOFF,
+            stale_on_error: true,
+            max_stale: None,
+            health_path: DEFAULT_HEALTH_PATH.to_string(),
+            responses: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Authenticates with HTTP basic authentication.
+    pub fn with_basic_auth(self, username: &str, password: &str) -> Self {
+        let credentials = BASE64.encode(format!("{}:{}", username, password));
+        self.with_header("authorization", format!("Basic {}", credentials))
+    }
+
+    /// Authenticates with a bearer token.
+    pub fn with_bearer_token(self, token: &str) -> Self {
+        self.with_header("authorization", format!("Bearer {}", token))
+    }
+
+    /// Sends `name: value` with every request, replacing any header of the
+    /// same name.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into().to_ascii_lowercase();
+        self.headers.retain(|(existing, _)| *existing != name);
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    /// Replaces the timeout of each attempt, body included.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.with_timeout(timeout);
+        self
+    }
+
+    /// Retries a failed request up to `retries` times, waiting `backoff`
+    /// before the first retry and twice as long before each next one.
+    pub fn with_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Serves the last good copy when the upstream fails (the default).
+    pub fn with_stale_on_error(mut self, enabled: bool) -> Self {
+        self.stale_on_error = enabled;
+        self
+    }
+
+    /// Fails instead of serving copies older than `age`.
+    pub fn with_max_stale(mut self, age: Duration) -> Self {
+        self.max_stale = Some(age);
+        self
+    }
+
+    /// Probes `path` instead of `/health`, e.g. `/actuator/health` for
+    /// Spring.
+    pub fn with_health_path(mut self, path: impl Into<String>) -> Self {
+        self.health_path = path.into();
+        self
+    }
+
+    /// Sets the label used when the request does not name one.
+    pub fn with_default_label(mut self, label: impl Into<String>) -> Self {
+        self.default_label = label.into();
+        self
+    }
+
+    /// Returns the URL of the upstream.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Returns the URL serving `query` with `label`.
+    fn url(&self, query: &ConfigQuery, label: &str) -> String {
+        let profiles = match query.profiles() {
+            [] => "default".to_string(),
+            profiles => profiles
+                .iter()
+                .map(|p| uri_encode(p, true))
+                .collect::<Vec<_>>()
+                .join(","),
+        };
+        format!(
+            "{}/{}/{}/{}",
+            self.base_url,
+            uri_encode(query.application(), true),
+            profiles,
+            uri_encode(label, true)
+        )
+    }
+
+    /// Sends a `GET`, retrying connection errors, `5xx` and `429`.
+    async fn get(&self, url: &str, etag: Option<&str>) -> Result<HttpResponse, ConfigSourceError> {
+        let mut headers: Vec<(&str, &str)> = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        headers.push(("accept", "application/json"));
+        if let Some(etag) = etag {
+            headers.push(("if-none-match", etag));
+        }
+
+        let mut attempt = 0;
+        loop {
+            let error = match self.client.get(url, &headers).await {
+                Ok(response)
+                    if response.status.is_server_error()
+                        || response.status == StatusCode::TOO_MANY_REQUESTS =>
+                {
+                    format!("{} responded {}", url, response.status)
+                },
+                Ok(response) => return Ok(response),
+                Err(e) => e,
+            };
+            if attempt >= self.retries {
+                return Err(ConfigSourceError::unavailable(error));
+            }
+            let delay = self.retry_backoff.saturating_mul(1 << attempt.min(16));
+            debug!("Retrying in {:?}: {}", delay, error);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Fetches `url`, revalidating the kept copy if there is one.
+    async fn fetch_url(
+        &self,
+        url: &str,
+        query: &ConfigQuery,
+        label: &str,
+    ) -> Result<ConfigResult, ConfigSourceError> {
+        let etag = self
+            .responses
+            .lock()
+            .expect("responses poisoned")
+            .get(url)
+            .and_then(|cached| cached.etag.clone());
+        let response = self.get(url, etag.as_deref()).await?;
+
+        match response.status {
+            StatusCode::NOT_MODIFIED => {
+                let mut responses = self.responses.lock().expect("responses poisoned");
+                let cached = responses.get_mut(url).ok_or_else(|| {
+                    ConfigSourceError::unavailable(format!(
+                        "{} responded 304 to a new request",
+                        url
+                    ))
+                })?;
+                debug!("{} not modified", url);
+                cached.fetched = Instant::now();
+                Ok(cached.result.clone())
+            },
+            status if status.is_success() => {
+                let result = to_result(url, query, label, &response.body)?;
+                let etag = response
+                    .headers
+                    .get("etag")
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                self.responses.lock().expect("responses poisoned").insert(
+                    url.to_string(),
+                    Cached {
+                        etag,
+                        result: result.clone(),
+                        fetched: Instant::now(),
+                    },
+                );
+                Ok(result)
+            },
+            StatusCode::NOT_FOUND => Err(ConfigSourceError::LabelNotFound(label.to_string())),
+            status => Err(ConfigSourceError::unavailable(format!(
+                "{} responded {}: {}",
+                url,
+                status,
+                String::from_utf8_lossy(&response.body).trim()
+            ))),
+        }
+    }
+
+    /// Serves the kept copy of `url` in place of `error`, if allowed.
+    fn stale(
+        &self,
+        url: &str,
+        error: ConfigSourceError,
+    ) -> Result<ConfigResult, ConfigSourceError> {
+        if !self.stale_on_error {
+            return Err(error);
+        }
+        let responses = self.responses.lock().expect("responses poisoned");
+        let Some(cached) = responses.get(url) else {
+            return Err(error);
+        };
+        let age = cached.fetched.elapsed();
+        if self.max_stale.is_some_and(|max| age > max) {
+            return Err(error);
+        }
+
+        warn!(
+            "Serving a copy of {} from {}s ago: {}",
+            url,
+            age.as_secs(),
+            error
+        );
+        let mut result = cached.result.clone();
+        result.add_warnings(vec![ConfigWarning::new(
+            "upstream",
+            format!(
+                "upstream unavailable, serving a copy from {}s ago: {}",
+                age.as_secs(),
+                error
+            ),
+        )]);
+        Ok(result)
+    }
+}
+
+/// A response of the `/{application}/{profiles}/{label}` endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpstreamResponse {
+    label: Option<String>,
+    version: Option<String>,
+    state: Option<String>,
+    #[serde(default)]
+    property_sources: Vec<UpstreamPropertySource>,
+    /// Only sent by Vortex.
+    #[serde(default)]
+    warnings: Vec<ConfigWarning>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpstreamPropertySource {
+    name: String,
+    #[serde(default)]
+    source: ConfigMap,
+}
+
+/// Converts a response body back into a result.
+fn to_result(
+    url: &str,
+    query: &ConfigQuery,
+    label: &str,
+    body: &[u8],
+) -> Result<ConfigResult, ConfigSourceError> {
+    let response: UpstreamResponse =
+        serde_json::from_slice(body).map_err(|e| ConfigSourceError::parse(url, e.to_string()))?;
+
+    let sources: Vec<PropertySource> = response
+        .property_sources
+        .into_iter()
+        .map(|ps| {
+            let mut config = ConfigMap::new();
+            for (key, value) in ps.source.as_inner() {
+                insert_nested(&mut config, key, value.clone());
+            }
+            PropertySource::new(ps.name, config)
+        })
+        .collect();
+
+    let mut result = ConfigResult::new(
+        query.application(),
+        query.profiles().to_vec(),
+        response.label.unwrap_or_else(|| label.to_string()),
+    );
+    if let Some(version) = response.version {
+        result.set_version(version);
+    }
+    if let Some(state) = response.state {
+        result.set_state(state);
+    }
+    result.add_property_sources(sources);
+    result.add_warnings(response.warnings);
+    Ok(result)
+}
+
+#[async_trait]
+impl ConfigSource for HttpUpstreamSource {
+    async fn fetch(&self, query: &ConfigQuery) -> Result<ConfigResult, ConfigSourceError> {
+        let label = query.effective_label(&self.default_label);
+        let url = self.url(query, label);
+        debug!("Fetching config for {} from {}", query, url);
+
+        match self.fetch_url(&url, query, label).await {
+            Ok(result) => Ok(result),
+            Err(e @ ConfigSourceError::LabelNotFound(_)) => Err(e),
+            Err(e) => self.stale(&url, e),
+        }
+    }
+
+    async fn health_check(&self) -> Result<(), ConfigSourceError> {
+        let url = format!("{}{}", self.base_url, self.health_path);
+        let headers: Vec<(&str, &str)> = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        let response = self
+            .client
+            .get(&url, &headers)
+            .await
+            .map_err(ConfigSourceError::unavailable)?;
+        if !response.status.is_success() {
+            return Err(ConfigSourceError::unavailable(format!(
+                "{} responded {}",
+                url, response.status
+            )));
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "upstream"
+    }
+
+    fn default_label(&self) -> &str {
+        &self.default_label
+    }
+}
+
+impl std::fmt::Debug for HttpUpstreamSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpUpstreamSource")
+            .field("base_url", &self.base_url)
+            .field("default_label", &self.default_label)
+            .field("retries", &self.retries)
+            .field("stale_on_error", &self.stale_on_error)
+            .field("max_stale", &self.max_stale)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+
+    use vortex_core::ConfigValue;
+
+    use crate::testing::{Request, Response, serve};
+
+    /// Requests seen by the fake upstream, and the responses it has left.
+    #[derive(Default)]
+    struct Script {
+        requests: Vec<Request>,
+        responses: VecDeque<Response>,
+    }
+
+    type Shared = Arc<Mutex<Script>>;
+
+    /// Starts a fake upstream answering with the scripted responses in turn,
+    /// and `503` once they run out.
+    async fn fake_upstream(script: Shared) -> String {
+        serve(move |request| {
+            let response = {
+                let mut script = script.lock().unwrap();
+                script.requests.push(request);
+                script
+                    .responses
+                    .pop_front()
+                    .unwrap_or_else(|| Response::new(503, ""))
+            };
+            async move { response }
+        })
+        .await
+    }
+
+    /// A Vortex response, nested, with a duplicate-key warning.
+    fn vortex_body() -> String {
+        serde_json::json!({
+            "name": "payments",
+            "profiles": ["prod"],
+            "label": "main",
+            "version": "abc123",
+            "state": null,
+            "propertySources": [
+                {"name": "git:main:payments-prod.yml", "source": {"db": {"pool": 20}}},
+                {"name": "git:main:application.yml", "source": {"server": {"port": 8080}, "db": {"pool": 5}}}
+            ],
+            "warnings": [{"source": "git:main:payments-prod.yml", "message": "duplicate key db.pool"}]
+        })
+        .to_string()
+    }
+
+    async fn source(responses: Vec<Response>) -> (Shared, HttpUpstreamSource) {
+        let script = Arc::new(Mutex::new(Script {
+            requests: Vec::new(),
+            responses: responses.into(),
+        }));
+        let source = HttpUpstreamSource::new(&fake_upstream(Arc::clone(&script)).await)
+            .unwrap()
+            .with_retries(1, Duration::from_millis(1))
+            .with_bearer_token("s3cret");
+        (script, source)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_converts_responses() {
+        let spring = serde_json::json!({
+            "name": "payments",
+            "profiles": ["prod", "eu"],
+            "label": null,
+            "propertySources": [
+                {"name": "payments-prod.properties", "source": {"db.hosts[0]": "db-1", "db.pool": 20}}
+            ]
+        })
+        .to_string();
+        let (script, source) = source(vec![
+            Response::new(200, vortex_body()),
+            Response::new(200, spring),
+        ])
+        .await;
+
+        let result = source
+            .fetch(&ConfigQuery::new("payments", vec!["prod"]))
+            .await
+            .unwrap();
+        assert_eq!(result.version(), Some("abc123"));
+        assert_eq!(result.len(), 2);
+        assert_eq!(
+            result.property_sources()[0].name,
+            "git:main:payments-prod.yml"
+        );
+        assert_eq!(result.warnings().len(), 1);
+        let merged = result.merged();
+        assert_eq!(merged.get("db.pool"), Some(&ConfigValue::Integer(20)));
+        assert_eq!(merged.get("server.port"), Some(&ConfigValue::Integer(8080)));
+
+        let query = ConfigQuery::new("payments", vec!["prod", "eu"]).with_label_set("release/2.4");
+        let result = source.fetch(&query).await.unwrap();
+        assert_eq!(result.label(), "release/2.4");
+        let merged = result.merged();
+        assert_eq!(
+            merged.get("db.hosts[0]").and_then(|v| v.as_str()),
+            Some("db-1")
+        );
+        assert_eq!(merged.get("db.pool"), Some(&ConfigValue::Integer(20)));
+
+        let requests = &script.lock().unwrap().requests;
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].target, "/payments/prod/main");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer s3cret"));
+        assert_eq!(requests[0].header("accept"), Some("application/json"));
+        assert_eq!(requests[1].target, "/payments/prod,eu/release%2F2.4");
+    }
+
+    #[tokio::test]
+    async fn test_etag_and_retries() {
+        let (script, source) = source(vec![
+            Response::new(200, vortex_body()).with_header("etag", "\"v1\""),
+            Response::new(502, ""),
+            Response::new(304, "").with_header("etag", "\"v1\""),
+        ])
+        .await;
+        let query = ConfigQuery::new("payments", vec!["prod"]);
+
+        source.fetch(&query).await.unwrap();
+        let result = source.fetch(&query).await.unwrap();
+        assert_eq!(result.version(), Some("abc123"));
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.warnings().len(), 1);
+
+        let requests = &script.lock().unwrap().requests;
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].header("if-none-match"), None);
+        assert_eq!(requests[1].header("if-none-match"), Some("\"v1\""));
+        assert_eq!(requests[2].header("if-none-match"), Some("\"v1\""));
+    }
+
+    #[tokio::test]
+    async fn test_stale_on_error() {
+        let (_script, source) = source(vec![
+            Response::new(200, vortex_body()),
+            Response::new(404, ""),
+        ])
+        .await;
+        let query = ConfigQuery::new("payments", vec!["prod"]);
+        source.fetch(&query).await.unwrap();
+
+        // A missing label is an answer, not an outage.
+        assert!(matches!(
+            source.fetch(&query).await,
+            Err(ConfigSourceError::LabelNotFound(_))
+        ));
+
+        // The upstream is down from now on.
+        let result = source.fetch(&query).await.unwrap();
+        assert_eq!(result.version(), Some("abc123"));
+        let warning = result.warnings().last().unwrap();
+        assert_eq!(warning.source, "upstream");
+        assert!(warning.message.contains("503"));
+
+        assert!(
+            source
+                .fetch(&ConfigQuery::new("orders", vec!["prod"]))
+                .await
+                .is_err()
+        );
+
+        let source = source.with_stale_on_error(false);
+        assert!(source.fetch(&query).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_health_check_and_errors() {
+        let (script, source) = source(vec![
+            Response::new(200, "{\"status\":\"UP\"}"),
+            Response::new(401, "bad token"),
+            Response::new(200, "not json"),
+        ])
+        .await;
+        let source = source.with_stale_on_error(false);
+        assert!(source.health_check().await.is_ok());
+
+        let query = ConfigQuery::new("payments", vec!["prod"]);
+        let error = source.fetch(&query).await.unwrap_err().to_string();
+        assert!(
+            error.contains("401") && error.contains("bad token"),
+            "{}",
+            error
+        );
+        assert!(matches!(
+            source.fetch(&query).await,
+            Err(ConfigSourceError::Parse { .. })
+        ));
+        assert!(source.health_check().await.is_err());
+
+        assert_eq!(script.lock().unwrap().requests[0].target, "/health");
+        assert!(HttpUpstreamSource::new("config.internal:8888").is_err());
+    }
+}